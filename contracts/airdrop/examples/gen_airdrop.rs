@@ -0,0 +1,134 @@
+//! Generates the merkle root and the claim proofs of an airdrop stage
+//!
+//! cargo run --example gen_airdrop -- accounts.csv > airdrop.json
+//!
+//! Each line of the csv file is an `address,amount` pair, an optional
+//! `address,amount` header line is skipped. The tree is hashed with the
+//! functions the contract verifies the claims with.
+
+use std::env::args;
+use std::fs::read_to_string;
+
+use anchor_airdrop::contract::{hash_pair, leaf_hash};
+use cosmwasm_std::Uint128;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct AirdropOutput {
+    merkle_root: String,
+    total_amount: Uint128,
+    claims: Vec<ClaimOutput>,
+}
+
+#[derive(Serialize)]
+struct ClaimOutput {
+    address: String,
+    amount: Uint128,
+    proof: Vec<String>,
+}
+
+fn main() {
+    let path = args()
+        .nth(1)
+        .expect("Usage: gen_airdrop <address,amount csv file>");
+    let csv = read_to_string(&path).expect("Failed to read the csv file");
+    let accounts = parse_accounts(&csv);
+    if accounts.is_empty() {
+        panic!("No accounts in the csv file");
+    }
+
+    let leaves: Vec<[u8; 32]> = accounts
+        .iter()
+        .map(|(address, amount)| leaf_hash(address, *amount))
+        .collect();
+    let layers = build_layers(leaves);
+    let root = layers.last().unwrap()[0];
+
+    let mut total_amount = Uint128::zero();
+    let mut claims: Vec<ClaimOutput> = vec![];
+    for (index, (address, amount)) in accounts.into_iter().enumerate() {
+        let proof = build_proof(&layers, index);
+
+        // the proof must verify the same way the contract verifies a claim
+        let hash = proof
+            .iter()
+            .fold(leaf_hash(&address, amount), |hash, p| hash_pair(hash, *p));
+        assert_eq!(hash, root, "Proof verification failed for {}", address);
+
+        total_amount += amount;
+        claims.push(ClaimOutput {
+            address,
+            amount,
+            proof: proof.iter().map(hex::encode).collect(),
+        });
+    }
+
+    let output = AirdropOutput {
+        merkle_root: hex::encode(root),
+        total_amount,
+        claims,
+    };
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+}
+
+fn parse_accounts(csv: &str) -> Vec<(String, Uint128)> {
+    let mut accounts: Vec<(String, Uint128)> = vec![];
+    for (line_number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (line_number == 0 && line.starts_with("address")) {
+            continue;
+        }
+
+        let columns: Vec<&str> = line.split(',').map(|column| column.trim()).collect();
+        if columns.len() != 2 {
+            panic!("Line {}: expected address,amount", line_number + 1);
+        }
+
+        let address = columns[0].to_string();
+        let amount: u128 = columns[1]
+            .parse()
+            .unwrap_or_else(|_| panic!("Line {}: invalid amount", line_number + 1));
+        if accounts.iter().any(|(other, _)| *other == address) {
+            panic!("Line {}: duplicate address {}", line_number + 1, address);
+        }
+
+        accounts.push((address, Uint128(amount)));
+    }
+
+    accounts
+}
+
+// Layers of the tree from the leaves to the root, a node without
+// a sibling is carried up to the next layer unhashed
+fn build_layers(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut layers = vec![leaves];
+    while layers.last().unwrap().len() > 1 {
+        let next = layers
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        layers.push(next);
+    }
+
+    layers
+}
+
+fn build_proof(layers: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof: Vec<[u8; 32]> = vec![];
+    for layer in layers[..layers.len() - 1].iter() {
+        let sibling = index ^ 1;
+        if sibling < layer.len() {
+            proof.push(layer[sibling]);
+        }
+
+        index /= 2;
+    }
+
+    proof
+}
@@ -1,18 +1,24 @@
 use cosmwasm_std::{
-    log, to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr,
-    InitResponse, InitResult, MigrateResponse, MigrateResult, Querier, StdError, StdResult,
-    Storage, Uint128, WasmMsg,
+    log, to_binary, Api, Binary, BlockInfo, CanonicalAddr, CosmosMsg, Decimal, Env, Extern,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, MigrateResponse,
+    MigrateResult, Querier, QueryRequest, StdError, StdResult, Storage, Uint128, WasmMsg,
+    WasmQuery,
 };
 
 use crate::state::{
-    read_claimed, read_config, read_latest_stage, read_merkle_root, store_claimed, store_config,
-    store_latest_stage, store_merkle_root, Config,
+    read_claimed, read_claimed_amount, read_config, read_latest_stage, read_merkle_root,
+    read_stage_info, store_claimed, store_claimed_amount, store_config, store_latest_stage,
+    store_merkle_root, store_stage_info, Config, StageInfo, StakingRequirement, StakingSnapshot,
 };
 
 use anchor_token::airdrop::{
     ConfigResponse, HandleMsg, InitMsg, IsClaimedResponse, LatestStageResponse, MerkleRootResponse,
-    MigrateMsg, QueryMsg,
+    MigrateMsg, QueryMsg, StageInfoResponse, StakingRequirement as StakingRequirementInfo,
+    StakingSnapshot as StakingSnapshotInfo,
 };
+use anchor_token::distributor::HandleMsg as DistributorHandleMsg;
+use anchor_token::gov::{QueryMsg as GovQueryMsg, StakerResponse, StakingSnapshotResponse};
+use anchor_token::querier::query_token_balance;
 
 use cw20::Cw20HandleMsg;
 use hex;
@@ -29,6 +35,15 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         &Config {
             owner: deps.api.canonical_address(&msg.owner)?,
             anchor_token: deps.api.canonical_address(&msg.anchor_token)?,
+            claim_cap: msg.claim_cap,
+            distributor: match msg.distributor {
+                Some(distributor) => Some(deps.api.canonical_address(&distributor)?),
+                None => None,
+            },
+            reclaim_gov_contract: match msg.reclaim_gov_contract {
+                Some(gov_contract) => Some(deps.api.canonical_address(&gov_contract)?),
+                None => None,
+            },
         },
     )?;
 
@@ -44,15 +59,42 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> HandleResult {
     match msg {
-        HandleMsg::UpdateConfig { owner } => update_config(deps, env, owner),
-        HandleMsg::RegisterMerkleRoot { merkle_root } => {
-            register_merkle_root(deps, env, merkle_root)
-        }
+        HandleMsg::UpdateConfig {
+            owner,
+            reclaim_gov_contract,
+        } => update_config(deps, env, owner, reclaim_gov_contract),
+        HandleMsg::RegisterMerkleRoot {
+            merkle_root,
+            total_amount,
+            expiry,
+            staking_requirement,
+        } => register_merkle_root(
+            deps,
+            env,
+            merkle_root,
+            total_amount,
+            expiry,
+            staking_requirement,
+        ),
         HandleMsg::Claim {
             stage,
             amount,
             proof,
-        } => claim(deps, env, stage, amount, proof),
+            splits,
+        } => claim(deps, env, stage, amount, proof, splits),
+        HandleMsg::RolloverUnclaimed {
+            from_stage,
+            to_stage_root,
+            expiry,
+        } => rollover_unclaimed(deps, env, from_stage, to_stage_root, expiry),
+        HandleMsg::RegisterStakingSnapshot {
+            gov_contract,
+            height,
+            total_amount,
+            expiry,
+        } => register_staking_snapshot(deps, env, gov_contract, height, total_amount, expiry),
+        HandleMsg::ClaimStakingSnapshot { stage } => claim_staking_snapshot(deps, env, stage),
+        HandleMsg::ReclaimUnclaimed { stage } => reclaim_unclaimed(deps, env, stage),
     }
 }
 
@@ -60,6 +102,7 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     owner: Option<HumanAddr>,
+    reclaim_gov_contract: Option<HumanAddr>,
 ) -> StdResult<HandleResponse> {
     let mut config: Config = read_config(&deps.storage)?;
     if deps.api.canonical_address(&env.message.sender)? != config.owner {
@@ -70,6 +113,10 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
         config.owner = deps.api.canonical_address(&owner)?;
     }
 
+    if let Some(reclaim_gov_contract) = reclaim_gov_contract {
+        config.reclaim_gov_contract = Some(deps.api.canonical_address(&reclaim_gov_contract)?);
+    }
+
     store_config(&mut deps.storage, &config)?;
     Ok(HandleResponse {
         messages: vec![],
@@ -82,23 +129,31 @@ pub fn register_merkle_root<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     merkle_root: String,
+    total_amount: Uint128,
+    expiry: Option<u64>,
+    staking_requirement: Option<StakingRequirementInfo>,
 ) -> StdResult<HandleResponse> {
     let config: Config = read_config(&deps.storage)?;
     if deps.api.canonical_address(&env.message.sender)? != config.owner {
         return Err(StdError::unauthorized());
     }
 
-    let mut root_buf: [u8; 32] = [0; 32];
-    match hex::decode_to_slice(merkle_root.to_string(), &mut root_buf) {
-        Ok(()) => {}
-        _ => return Err(StdError::generic_err("Invalid hex encoded merkle root")),
-    }
-
-    let latest_stage: u8 = read_latest_stage(&deps.storage)?;
-    let stage = latest_stage + 1;
+    let staking_requirement = match staking_requirement {
+        Some(requirement) => Some(StakingRequirement {
+            gov_contract: deps.api.canonical_address(&requirement.gov_contract)?,
+            min_staked: requirement.min_staked,
+        }),
+        None => None,
+    };
 
-    store_merkle_root(&mut deps.storage, stage, merkle_root.to_string())?;
-    store_latest_stage(&mut deps.storage, stage)?;
+    let stage = store_new_stage(
+        &mut deps.storage,
+        &merkle_root,
+        Some(total_amount),
+        expiry,
+        staking_requirement,
+    )?;
+    assert_stages_funded(deps, &config, &env)?;
 
     Ok(HandleResponse {
         messages: vec![],
@@ -111,29 +166,296 @@ pub fn register_merkle_root<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Closes an expired stage and moves its unclaimed remainder into a new stage.
+/// The new stage total is exactly what the closed stage has left, so the
+/// total registered amount never grows beyond what was funded. The new stage
+/// keeps the staking requirement of the closed one.
+pub fn rollover_unclaimed<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    from_stage: u8,
+    to_stage_root: String,
+    expiry: Option<u64>,
+) -> StdResult<HandleResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    // make sure the stage exists
+    read_merkle_root(&deps.storage, from_stage)?;
+
+    let mut from_stage_info: StageInfo = read_stage_info(&deps.storage, from_stage)?;
+    if from_stage_info.rolled_over_to.is_some() {
+        return Err(StdError::generic_err("Stage has already been rolled over"));
+    }
+
+    if from_stage_info.reclaimed {
+        return Err(StdError::generic_err("Stage has already been reclaimed"));
+    }
+
+    if !from_stage_info.claim_expiration().is_expired(&env.block) {
+        return Err(StdError::generic_err("Stage has not expired"));
+    }
+
+    let total_amount = match from_stage_info.total_amount {
+        Some(total_amount) => total_amount,
+        None => {
+            return Err(StdError::generic_err(
+                "Stage total amount is not registered",
+            ))
+        }
+    };
+
+    let unclaimed_amount = (total_amount - from_stage_info.claimed_amount)?;
+    if unclaimed_amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to rollover"));
+    }
+
+    let stage = store_new_stage(
+        &mut deps.storage,
+        &to_stage_root,
+        Some(unclaimed_amount),
+        expiry,
+        from_stage_info.staking_requirement.clone(),
+    )?;
+
+    from_stage_info.rolled_over_to = Some(stage);
+    store_stage_info(&mut deps.storage, from_stage, &from_stage_info)?;
+    assert_stages_funded(deps, &config, &env)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "rollover_unclaimed"),
+            log("from_stage", from_stage),
+            log("stage", stage),
+            log("merkle_root", to_stage_root),
+            log("amount", unclaimed_amount),
+        ],
+        data: None,
+    })
+}
+
+/// Closes an expired stage and transfers its unclaimed remainder out of the
+/// airdrop balance. With the reclaim gov contract set, the tokens are sent to
+/// gov, where they are shared by the stakers the same way as the collected fees.
+pub fn reclaim_unclaimed<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    stage: u8,
+) -> StdResult<HandleResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    // claims paid by the distributor never left it
+    if config.distributor.is_some() {
+        return Err(StdError::generic_err("Claims are paid by the distributor"));
+    }
+
+    let mut stage_info: StageInfo = read_stage_info(&deps.storage, stage)?;
+    if stage_info.rolled_over_to.is_some() {
+        return Err(StdError::generic_err("Stage has already been rolled over"));
+    }
+
+    if stage_info.reclaimed {
+        return Err(StdError::generic_err("Stage has already been reclaimed"));
+    }
+
+    if !stage_info.claim_expiration().is_expired(&env.block) {
+        return Err(StdError::generic_err("Stage has not expired"));
+    }
+
+    let total_amount = match stage_info.total_amount {
+        Some(total_amount) => total_amount,
+        None => {
+            return Err(StdError::generic_err(
+                "Stage total amount is not registered",
+            ))
+        }
+    };
+
+    let unclaimed_amount = (total_amount - stage_info.claimed_amount)?;
+    if unclaimed_amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to reclaim"));
+    }
+
+    stage_info.reclaimed = true;
+    store_stage_info(&mut deps.storage, stage, &stage_info)?;
+
+    let recipient = deps.api.human_address(
+        config
+            .reclaim_gov_contract
+            .as_ref()
+            .unwrap_or(&config.owner),
+    )?;
+    Ok(HandleResponse {
+        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: recipient.clone(),
+                amount: unclaimed_amount,
+            })?,
+        })],
+        log: vec![
+            log("action", "reclaim_unclaimed"),
+            log("stage", stage),
+            log("recipient", recipient),
+            log("amount", unclaimed_amount),
+        ],
+        data: None,
+    })
+}
+
+/// Registers a stage whose claims are read from the gov staking snapshot;
+/// each staker receives the stage total in proportion to their share
+pub fn register_staking_snapshot<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    gov_contract: HumanAddr,
+    height: u64,
+    total_amount: Uint128,
+    expiry: Option<u64>,
+) -> StdResult<HandleResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.owner {
+        return Err(StdError::unauthorized());
+    }
+
+    // shares can still change within the current block
+    if height >= env.block.height {
+        return Err(StdError::generic_err("Snapshot height must be in the past"));
+    }
+
+    let stage = store_next_stage(
+        &mut deps.storage,
+        &StageInfo {
+            total_amount: Some(total_amount),
+            claimed_amount: Uint128::zero(),
+            expiry,
+            rolled_over_to: None,
+            reclaimed: false,
+            staking_snapshot: Some(StakingSnapshot {
+                gov_contract: deps.api.canonical_address(&gov_contract)?,
+                height,
+            }),
+            staking_requirement: None,
+        },
+    )?;
+    assert_stages_funded(deps, &config, &env)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "register_staking_snapshot"),
+            log("stage", stage),
+            log("gov_contract", gov_contract),
+            log("height", height),
+            log("total_amount", total_amount),
+        ],
+        data: None,
+    })
+}
+
+// The unclaimed totals of the open stages must be covered by the ANC balance,
+// so every registered stage can be claimed in full; stages registered without
+// a total are not counted. Claims paid by the distributor are bound by its
+// spend limit instead.
+fn assert_stages_funded<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    env: &Env,
+) -> StdResult<()> {
+    if config.distributor.is_some() {
+        return Ok(());
+    }
+
+    let mut outstanding_amount = Uint128::zero();
+    for stage in 1..=read_latest_stage(&deps.storage)? {
+        let stage_info: StageInfo = read_stage_info(&deps.storage, stage)?;
+        if stage_info.rolled_over_to.is_some() || stage_info.reclaimed {
+            continue;
+        }
+
+        if let Some(total_amount) = stage_info.total_amount {
+            outstanding_amount += (total_amount - stage_info.claimed_amount)?;
+        }
+    }
+
+    let balance: Uint128 = query_token_balance(
+        deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &env.contract.address,
+    )?
+    .into();
+    if outstanding_amount > balance {
+        return Err(StdError::generic_err(format!(
+            "Stages are not funded, {} more tokens are required",
+            (outstanding_amount - balance)?
+        )));
+    }
+
+    Ok(())
+}
+
+fn store_new_stage<S: Storage>(
+    storage: &mut S,
+    merkle_root: &str,
+    total_amount: Option<Uint128>,
+    expiry: Option<u64>,
+    staking_requirement: Option<StakingRequirement>,
+) -> StdResult<u8> {
+    let mut root_buf: [u8; 32] = [0; 32];
+    match hex::decode_to_slice(merkle_root.to_string(), &mut root_buf) {
+        Ok(()) => {}
+        _ => return Err(StdError::generic_err("Invalid hex encoded merkle root")),
+    }
+
+    let stage = store_next_stage(
+        storage,
+        &StageInfo {
+            total_amount,
+            claimed_amount: Uint128::zero(),
+            expiry,
+            rolled_over_to: None,
+            reclaimed: false,
+            staking_snapshot: None,
+            staking_requirement,
+        },
+    )?;
+    store_merkle_root(storage, stage, merkle_root.to_string())?;
+
+    Ok(stage)
+}
+
+fn store_next_stage<S: Storage>(storage: &mut S, stage_info: &StageInfo) -> StdResult<u8> {
+    let latest_stage: u8 = read_latest_stage(storage)?;
+    let stage = latest_stage + 1;
+
+    store_stage_info(storage, stage, stage_info)?;
+    store_latest_stage(storage, stage)?;
+
+    Ok(stage)
+}
+
 pub fn claim<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     stage: u8,
     amount: Uint128,
     proof: Vec<String>,
+    splits: Option<Vec<(HumanAddr, Decimal)>>,
 ) -> StdResult<HandleResponse> {
     let config: Config = read_config(&deps.storage)?;
     let merkle_root: String = read_merkle_root(&deps.storage, stage)?;
 
     let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let stage_info = load_claimable_stage(&deps.storage, &user_raw, stage, &env.block)?;
 
-    // If user claimed target stage, return err
-    if read_claimed(&deps.storage, &user_raw, stage)? {
-        return Err(StdError::generic_err("Already claimed"));
-    }
-
-    let user_input: String = env.message.sender.to_string() + &amount.to_string();
-    let mut hash: [u8; 32] = sha3::Keccak256::digest(user_input.as_bytes())
-        .as_slice()
-        .try_into()
-        .expect("Wrong length");
-
+    let mut hash: [u8; 32] = leaf_hash(env.message.sender.as_str(), amount);
     for p in proof {
         let mut proof_buf: [u8; 32] = [0; 32];
         match hex::decode_to_slice(p, &mut proof_buf) {
@@ -141,17 +463,7 @@ pub fn claim<S: Storage, A: Api, Q: Querier>(
             _ => return Err(StdError::generic_err("Invalid hex encoded proof")),
         }
 
-        hash = if bytes_cmp(hash, proof_buf) == std::cmp::Ordering::Less {
-            sha3::Keccak256::digest(&[hash, proof_buf].concat())
-                .as_slice()
-                .try_into()
-                .expect("Wrong length")
-        } else {
-            sha3::Keccak256::digest(&[proof_buf, hash].concat())
-                .as_slice()
-                .try_into()
-                .expect("Wrong length")
-        };
+        hash = hash_pair(hash, proof_buf);
     }
 
     let mut root_buf: [u8; 32] = [0; 32];
@@ -160,28 +472,259 @@ pub fn claim<S: Storage, A: Api, Q: Querier>(
         return Err(StdError::generic_err("Verification is failed"));
     }
 
+    if let Some(requirement) = &stage_info.staking_requirement {
+        assert_staking_requirement(deps, requirement, &env.message.sender)?;
+    }
+
+    let recipients = split_claim(&env.message.sender, amount, splits)?;
+    transfer_claim(
+        deps, env, config, user_raw, stage, stage_info, amount, recipients,
+    )
+}
+
+// Divides the claim amount among the split recipients, the rounding
+// remainder goes to the last one; the claimer receives all without splits
+fn split_claim(
+    claimer: &HumanAddr,
+    amount: Uint128,
+    splits: Option<Vec<(HumanAddr, Decimal)>>,
+) -> StdResult<Vec<(HumanAddr, Uint128)>> {
+    let splits = match splits {
+        Some(splits) => splits,
+        None => return Ok(vec![(claimer.clone(), amount)]),
+    };
+
+    if splits.is_empty() {
+        return Err(StdError::generic_err("Splits must not be empty"));
+    }
+
+    let mut total_ratio = Decimal::zero();
+    for (_, ratio) in splits.iter() {
+        total_ratio = total_ratio + *ratio;
+    }
+
+    if total_ratio != Decimal::one() {
+        return Err(StdError::generic_err("Split ratios must sum to 1"));
+    }
+
+    let mut remaining = amount;
+    let last = splits.len() - 1;
+    let mut recipients: Vec<(HumanAddr, Uint128)> = vec![];
+    for (i, (recipient, ratio)) in splits.into_iter().enumerate() {
+        let split_amount = if i == last { remaining } else { amount * ratio };
+        remaining = (remaining - split_amount)?;
+        recipients.push((recipient, split_amount));
+    }
+
+    Ok(recipients)
+}
+
+/// Claims the share of the sender in a staking snapshot stage
+pub fn claim_staking_snapshot<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    stage: u8,
+) -> StdResult<HandleResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let user_raw = deps.api.canonical_address(&env.message.sender)?;
+    let stage_info = load_claimable_stage(&deps.storage, &user_raw, stage, &env.block)?;
+
+    let snapshot = match &stage_info.staking_snapshot {
+        Some(snapshot) => snapshot,
+        None => {
+            return Err(StdError::generic_err(
+                "Stage is not a staking snapshot stage",
+            ))
+        }
+    };
+
+    let res: StakingSnapshotResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: deps.api.human_address(&snapshot.gov_contract)?,
+            msg: to_binary(&GovQueryMsg::StakingSnapshot {
+                address: env.message.sender.clone(),
+                height: snapshot.height,
+            })?,
+        }))?;
+
+    if res.share.is_zero() {
+        return Err(StdError::generic_err(
+            "Nothing staked at the snapshot height",
+        ));
+    }
+
+    let amount = stage_info
+        .total_amount
+        .unwrap_or_default()
+        .multiply_ratio(res.share, res.total_share);
+
+    let recipients = vec![(env.message.sender.clone(), amount)];
+    transfer_claim(
+        deps, env, config, user_raw, stage, stage_info, amount, recipients,
+    )
+}
+
+// The staked balance is read from gov at claim time, so the claimant must
+// still be staking when claiming
+fn assert_staking_requirement<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    requirement: &StakingRequirement,
+    claimer: &HumanAddr,
+) -> StdResult<()> {
+    let res: StakerResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: deps.api.human_address(&requirement.gov_contract)?,
+        msg: to_binary(&GovQueryMsg::Staker {
+            address: claimer.clone(),
+            token_balance: None,
+        })?,
+    }))?;
+
+    if res.balance < requirement.min_staked {
+        return Err(StdError::generic_err(format!(
+            "Claim requires at least {} staked in gov",
+            requirement.min_staked
+        )));
+    }
+
+    Ok(())
+}
+
+fn load_claimable_stage<S: Storage>(
+    storage: &S,
+    user_raw: &CanonicalAddr,
+    stage: u8,
+    block: &BlockInfo,
+) -> StdResult<StageInfo> {
+    // If user claimed target stage, return err
+    if read_claimed(storage, user_raw, stage)? {
+        return Err(StdError::generic_err("Already claimed"));
+    }
+
+    let stage_info: StageInfo = read_stage_info(storage, stage)?;
+    if stage_info.rolled_over_to.is_some() {
+        return Err(StdError::generic_err("Stage has been rolled over"));
+    }
+
+    if stage_info.claim_expiration().is_expired(block) {
+        return Err(StdError::generic_err("Stage has expired"));
+    }
+
+    Ok(stage_info)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn transfer_claim<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    config: Config,
+    user_raw: CanonicalAddr,
+    stage: u8,
+    mut stage_info: StageInfo,
+    amount: Uint128,
+    recipients: Vec<(HumanAddr, Uint128)>,
+) -> StdResult<HandleResponse> {
+    stage_info.claimed_amount += amount;
+    match stage_info.total_amount {
+        Some(total_amount) if stage_info.claimed_amount > total_amount => {
+            return Err(StdError::generic_err("Claim amount exceeds stage total"));
+        }
+        // the stage total is the only bound on what is pulled from the distributor
+        None if config.distributor.is_some() => {
+            return Err(StdError::generic_err(
+                "Stage total amount is not registered",
+            ));
+        }
+        _ => {}
+    }
+
+    // the cap bounds the cumulative amount claimed by an address over all stages
+    let claimed_amount = read_claimed_amount(&deps.storage, &user_raw)? + amount;
+    if let Some(claim_cap) = config.claim_cap {
+        if claimed_amount > claim_cap {
+            return Err(StdError::generic_err("Claim amount exceeds address cap"));
+        }
+    }
+
     // Update claim index to the current stage
     store_claimed(&mut deps.storage, &user_raw, stage)?;
+    store_claimed_amount(&mut deps.storage, &user_raw, claimed_amount)?;
+    store_stage_info(&mut deps.storage, stage, &stage_info)?;
+
+    let anchor_token = deps.api.human_address(&config.anchor_token)?;
+    let distributor = match config.distributor {
+        Some(distributor) => Some(deps.api.human_address(&distributor)?),
+        None => None,
+    };
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut logs = vec![
+        log("action", "claim"),
+        log("stage", stage),
+        log("address", env.message.sender.as_str()),
+        log("amount", amount),
+    ];
+
+    let split = recipients.len() > 1 || recipients[0].0 != env.message.sender;
+    for (recipient, recipient_amount) in recipients {
+        if recipient_amount.is_zero() {
+            continue;
+        }
+
+        if split {
+            logs.push(log("recipient", recipient.as_str()));
+            logs.push(log("recipient_amount", recipient_amount));
+        }
+
+        // pull mode lets the distributor pay the recipient out of its spend limit
+        messages.push(match &distributor {
+            Some(distributor) => CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: distributor.clone(),
+                send: vec![],
+                msg: to_binary(&DistributorHandleMsg::Spend {
+                    recipient,
+                    amount: recipient_amount,
+                })?,
+            }),
+            None => CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: anchor_token.clone(),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient,
+                    amount: recipient_amount,
+                })?,
+            }),
+        });
+    }
 
     Ok(HandleResponse {
-        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.human_address(&config.anchor_token)?,
-            send: vec![],
-            msg: to_binary(&Cw20HandleMsg::Transfer {
-                recipient: env.message.sender.clone(),
-                amount,
-            })?,
-        })],
-        log: vec![
-            log("action", "claim"),
-            log("stage", stage),
-            log("address", env.message.sender),
-            log("amount", amount),
-        ],
+        messages,
+        log: logs,
         data: None,
     })
 }
 
+/// Merkle tree leaf of the claim of `amount` by `address`
+pub fn leaf_hash(address: &str, amount: Uint128) -> [u8; 32] {
+    let user_input: String = address.to_string() + &amount.to_string();
+    sha3::Keccak256::digest(user_input.as_bytes())
+        .as_slice()
+        .try_into()
+        .expect("Wrong length")
+}
+
+/// Merkle tree parent node of two nodes, which are hashed in ascending order
+pub fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let pair = if bytes_cmp(a, b) == std::cmp::Ordering::Less {
+        [a, b].concat()
+    } else {
+        [b, a].concat()
+    };
+
+    sha3::Keccak256::digest(&pair)
+        .as_slice()
+        .try_into()
+        .expect("Wrong length")
+}
+
 fn bytes_cmp(a: [u8; 32], b: [u8; 32]) -> std::cmp::Ordering {
     let mut i = 0;
     while i < 32 {
@@ -208,6 +751,7 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
         QueryMsg::IsClaimed { stage, address } => {
             to_binary(&query_is_claimed(deps, stage, address)?)
         }
+        QueryMsg::StageInfo { stage } => to_binary(&query_stage_info(deps, stage)?),
     }
 }
 
@@ -218,6 +762,15 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
     let resp = ConfigResponse {
         owner: deps.api.human_address(&state.owner)?,
         anchor_token: deps.api.human_address(&state.anchor_token)?,
+        claim_cap: state.claim_cap,
+        distributor: match state.distributor {
+            Some(distributor) => Some(deps.api.human_address(&distributor)?),
+            None => None,
+        },
+        reclaim_gov_contract: match state.reclaim_gov_contract {
+            Some(gov_contract) => Some(deps.api.human_address(&gov_contract)?),
+            None => None,
+        },
     };
 
     Ok(resp)
@@ -258,6 +811,37 @@ pub fn query_is_claimed<S: Storage, A: Api, Q: Querier>(
     Ok(resp)
 }
 
+pub fn query_stage_info<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    stage: u8,
+) -> StdResult<StageInfoResponse> {
+    let stage_info = read_stage_info(&deps.storage, stage)?;
+    let resp = StageInfoResponse {
+        stage,
+        total_amount: stage_info.total_amount,
+        claimed_amount: stage_info.claimed_amount,
+        expiry: stage_info.expiry,
+        rolled_over_to: stage_info.rolled_over_to,
+        reclaimed: stage_info.reclaimed,
+        staking_snapshot: match stage_info.staking_snapshot {
+            Some(snapshot) => Some(StakingSnapshotInfo {
+                gov_contract: deps.api.human_address(&snapshot.gov_contract)?,
+                height: snapshot.height,
+            }),
+            None => None,
+        },
+        staking_requirement: match stage_info.staking_requirement {
+            Some(requirement) => Some(StakingRequirementInfo {
+                gov_contract: deps.api.human_address(&requirement.gov_contract)?,
+                min_staked: requirement.min_staked,
+            }),
+            None => None,
+        },
+    };
+
+    Ok(resp)
+}
+
 pub fn migrate<S: Storage, A: Api, Q: Querier>(
     _deps: &mut Extern<S, A, Q>,
     _env: Env,
@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+use anchor_token::common::Expiration;
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
 static KEY_CONFIG: &[u8] = b"config";
@@ -9,11 +10,22 @@ static KEY_LATEST_STAGE: &[u8] = b"latest_stage";
 
 static PREFIX_MERKLE_ROOT: &[u8] = b"merkle_root";
 static PREFIX_CLAIM_INDEX: &[u8] = b"claim_index";
+static PREFIX_STAGE_INFO: &[u8] = b"stage_info";
+static PREFIX_CLAIMED_AMOUNT: &[u8] = b"claimed_amount";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub anchor_token: CanonicalAddr,
+    // no cap for the configs stored before it
+    #[serde(default)]
+    pub claim_cap: Option<Uint128>,
+    // claims are paid from the airdrop balance for the configs stored before it
+    #[serde(default)]
+    pub distributor: Option<CanonicalAddr>,
+    // reclaims are sent to the owner for the configs stored before it
+    #[serde(default)]
+    pub reclaim_gov_contract: Option<CanonicalAddr>,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
@@ -24,6 +36,42 @@ pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct StageInfo {
+    pub total_amount: Option<Uint128>,
+    pub claimed_amount: Uint128,
+    pub expiry: Option<u64>,
+    pub rolled_over_to: Option<u8>,
+    // set once the unclaimed remainder of the expired stage is reclaimed
+    #[serde(default)]
+    pub reclaimed: bool,
+    // set for stages claimed by the gov staking snapshot instead of a merkle proof
+    pub staking_snapshot: Option<StakingSnapshot>,
+    // set for stages restricted to claimants staking in gov
+    pub staking_requirement: Option<StakingRequirement>,
+}
+
+impl StageInfo {
+    /// Claims are accepted up to and including the expiry height
+    pub fn claim_expiration(&self) -> Expiration {
+        self.expiry.map_or(Expiration::Never {}, |expiry| {
+            Expiration::AtHeight(expiry + 1)
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingSnapshot {
+    pub gov_contract: CanonicalAddr,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingRequirement {
+    pub gov_contract: CanonicalAddr,
+    pub min_staked: Uint128,
+}
+
 pub fn store_latest_stage<S: Storage>(storage: &mut S, stage: u8) -> StdResult<()> {
     singleton(storage, KEY_LATEST_STAGE).save(&stage)
 }
@@ -47,6 +95,23 @@ pub fn read_merkle_root<S: Storage>(storage: &S, stage: u8) -> StdResult<String>
     claim_index_bucket.load(&[stage])
 }
 
+pub fn store_stage_info<S: Storage>(
+    storage: &mut S,
+    stage: u8,
+    stage_info: &StageInfo,
+) -> StdResult<()> {
+    let mut stage_info_bucket: Bucket<S, StageInfo> = Bucket::new(PREFIX_STAGE_INFO, storage);
+    stage_info_bucket.save(&[stage], stage_info)
+}
+
+/// Stages registered before stage info was tracked have no entry and
+/// load as the default (no total, no expiry)
+pub fn read_stage_info<S: Storage>(storage: &S, stage: u8) -> StdResult<StageInfo> {
+    let stage_info_bucket: ReadonlyBucket<S, StageInfo> =
+        ReadonlyBucket::new(PREFIX_STAGE_INFO, storage);
+    Ok(stage_info_bucket.may_load(&[stage])?.unwrap_or_default())
+}
+
 pub fn store_claimed<S: Storage>(
     storage: &mut S,
     user: &CanonicalAddr,
@@ -66,3 +131,21 @@ pub fn read_claimed<S: Storage>(storage: &S, user: &CanonicalAddr, stage: u8) ->
         None => Ok(false),
     }
 }
+
+pub fn store_claimed_amount<S: Storage>(
+    storage: &mut S,
+    user: &CanonicalAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut claimed_amount_bucket: Bucket<S, Uint128> = Bucket::new(PREFIX_CLAIMED_AMOUNT, storage);
+    claimed_amount_bucket.save(user.as_slice(), &amount)
+}
+
+/// Total amount the user has claimed across all stages
+pub fn read_claimed_amount<S: Storage>(storage: &S, user: &CanonicalAddr) -> StdResult<Uint128> {
+    let claimed_amount_bucket: ReadonlyBucket<S, Uint128> =
+        ReadonlyBucket::new(PREFIX_CLAIMED_AMOUNT, storage);
+    Ok(claimed_amount_bucket
+        .may_load(user.as_slice())?
+        .unwrap_or_default())
+}
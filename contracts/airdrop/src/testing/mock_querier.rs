@@ -0,0 +1,195 @@
+use anchor_token::gov::{QueryMsg, StakerResponse, StakingSnapshotResponse};
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Api, CanonicalAddr, Coin, Empty, Extern, HumanAddr,
+    Querier, QuerierResult, QueryRequest, SystemError, Uint128, WasmQuery,
+};
+use cosmwasm_storage::to_length_prefixed;
+use std::collections::HashMap;
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    canonical_length: usize,
+    contract_balance: &[Coin],
+) -> Extern<MockStorage, MockApi, WasmMockQuerier> {
+    let contract_addr = HumanAddr::from(MOCK_CONTRACT_ADDR);
+    let custom_querier: WasmMockQuerier = WasmMockQuerier::new(
+        MockQuerier::new(&[(&contract_addr, contract_balance)]),
+        canonical_length,
+    );
+
+    Extern {
+        storage: MockStorage::default(),
+        api: MockApi::new(canonical_length),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    staking_snapshot_querier: StakingSnapshotQuerier,
+    staker_querier: StakerQuerier,
+    token_querier: TokenQuerier,
+    canonical_length: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct StakingSnapshotQuerier {
+    // staker shares and the total share by gov contract and height
+    snapshots: HashMap<(HumanAddr, u64), (HashMap<HumanAddr, Uint128>, Uint128)>,
+}
+
+#[derive(Clone, Default)]
+pub struct StakerQuerier {
+    // staked balances by gov contract and staker
+    balances: HashMap<(HumanAddr, HumanAddr), Uint128>,
+}
+
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    // token balances by token contract and holder
+    balances: HashMap<(HumanAddr, HumanAddr), Uint128>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        // MockQuerier doesn't support Custom, so we ignore it completely here
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
+                let prefix_balance = to_length_prefixed(b"balance").to_vec();
+                if !key.as_slice().starts_with(&prefix_balance) {
+                    panic!("DO NOT ENTER HERE")
+                }
+
+                let address_raw = CanonicalAddr::from(&key.as_slice()[prefix_balance.len()..]);
+                let address = match MockApi::new(self.canonical_length).human_address(&address_raw)
+                {
+                    Ok(v) => v,
+                    Err(e) => {
+                        return Err(SystemError::InvalidRequest {
+                            error: format!("Parsing query request: {}", e),
+                            request: key.as_slice().into(),
+                        })
+                    }
+                };
+
+                let balance = self
+                    .token_querier
+                    .balances
+                    .get(&(contract_addr.clone(), address))
+                    .cloned()
+                    .unwrap_or_default();
+                Ok(to_binary(&to_binary(&balance).unwrap()))
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    QueryMsg::StakingSnapshot { address, height } => {
+                        let (shares, total_share) = match self
+                            .staking_snapshot_querier
+                            .snapshots
+                            .get(&(contract_addr.clone(), height))
+                        {
+                            Some(v) => v,
+                            None => {
+                                return Err(SystemError::InvalidRequest {
+                                    error: "No staking snapshot exists".to_string(),
+                                    request: msg.as_slice().into(),
+                                })
+                            }
+                        };
+
+                        Ok(to_binary(&StakingSnapshotResponse {
+                            share: shares.get(&address).cloned().unwrap_or_default(),
+                            total_share: *total_share,
+                        }))
+                    }
+                    QueryMsg::Staker { address, .. } => Ok(to_binary(&StakerResponse {
+                        balance: self
+                            .staker_querier
+                            .balances
+                            .get(&(contract_addr.clone(), address))
+                            .cloned()
+                            .unwrap_or_default(),
+                        share: Uint128::zero(),
+                        locked_balance: vec![],
+                        vesting_locked_balance: Uint128::zero(),
+                        deposit_locked_share: Uint128::zero(),
+                    })),
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>, canonical_length: usize) -> Self {
+        WasmMockQuerier {
+            base,
+            staking_snapshot_querier: StakingSnapshotQuerier::default(),
+            staker_querier: StakerQuerier::default(),
+            token_querier: TokenQuerier::default(),
+            canonical_length,
+        }
+    }
+
+    // configure the token balances of the holders
+    pub fn with_token_balances(
+        &mut self,
+        token_contract: &HumanAddr,
+        balances: &[(&HumanAddr, &Uint128)],
+    ) {
+        for (holder, balance) in balances.iter() {
+            self.token_querier
+                .balances
+                .insert((token_contract.clone(), HumanAddr::from(holder)), **balance);
+        }
+    }
+
+    // configure the staker shares of the gov contract at the height
+    pub fn with_staking_snapshot(
+        &mut self,
+        gov_contract: &HumanAddr,
+        height: u64,
+        shares: &[(&HumanAddr, &Uint128)],
+        total_share: Uint128,
+    ) {
+        let shares = shares
+            .iter()
+            .map(|(addr, share)| (HumanAddr::from(addr), **share))
+            .collect();
+        self.staking_snapshot_querier
+            .snapshots
+            .insert((gov_contract.clone(), height), (shares, total_share));
+    }
+
+    // configure the staked balances of the gov contract
+    pub fn with_staker_balances(
+        &mut self,
+        gov_contract: &HumanAddr,
+        balances: &[(&HumanAddr, &Uint128)],
+    ) {
+        for (staker, balance) in balances.iter() {
+            self.staker_querier
+                .balances
+                .insert((gov_contract.clone(), HumanAddr::from(staker)), **balance);
+        }
+    }
+}
@@ -1 +1,2 @@
+mod mock_querier;
 mod tests;
\ No newline at end of file
@@ -1,11 +1,19 @@
-use crate::contract::{handle, init, query};
+use crate::contract::{handle, init, migrate, query};
+use crate::state::{store_latest_stage, store_merkle_root};
+use crate::testing::mock_querier::mock_dependencies;
 use anchor_token::airdrop::{
     ConfigResponse, HandleMsg, InitMsg, IsClaimedResponse, LatestStageResponse, MerkleRootResponse,
-    QueryMsg,
+    MigrateMsg, QueryMsg, StageInfoResponse, StakingRequirement, StakingSnapshot,
 };
-use cosmwasm_std::testing::{mock_dependencies, mock_env};
-use cosmwasm_std::{from_binary, log, to_binary, CosmosMsg, HumanAddr, StdError, Uint128, WasmMsg};
+use anchor_token::distributor::HandleMsg as DistributorHandleMsg;
+use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, log, to_binary, Api, CanonicalAddr, CosmosMsg, Decimal, HumanAddr, StdError,
+    Uint128, WasmMsg,
+};
+use cosmwasm_storage::{bucket, singleton};
 use cw20::Cw20HandleMsg;
+use serde::{Deserialize, Serialize};
 
 #[test]
 fn proper_initialization() {
@@ -14,6 +22,9 @@ fn proper_initialization() {
     let msg = InitMsg {
         owner: HumanAddr("owner0000".to_string()),
         anchor_token: HumanAddr("anchor0000".to_string()),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -26,6 +37,8 @@ fn proper_initialization() {
     let config: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("owner0000", config.owner.as_str());
     assert_eq!("anchor0000", config.anchor_token.as_str());
+    assert_eq!(None, config.claim_cap);
+    assert_eq!(None, config.distributor);
 
     let res = query(&deps, QueryMsg::LatestStage {}).unwrap();
     let latest_stage: LatestStageResponse = from_binary(&res).unwrap();
@@ -39,6 +52,9 @@ fn update_config() {
     let msg = InitMsg {
         owner: HumanAddr::from("owner0000"),
         anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -48,6 +64,7 @@ fn update_config() {
     let env = mock_env("owner0000", &[]);
     let msg = HandleMsg::UpdateConfig {
         owner: Some(HumanAddr("owner0001".to_string())),
+        reclaim_gov_contract: None,
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -60,7 +77,10 @@ fn update_config() {
 
     // Unauthorzied err
     let env = mock_env("owner0000", &[]);
-    let msg = HandleMsg::UpdateConfig { owner: None };
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        reclaim_gov_contract: None,
+    };
 
     let res = handle(&mut deps, env, msg);
     match res {
@@ -76,6 +96,9 @@ fn register_merkle_root() {
     let msg = InitMsg {
         owner: HumanAddr::from("owner0000"),
         anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -85,8 +108,18 @@ fn register_merkle_root() {
     let env = mock_env("owner0000", &[]);
     let msg = HandleMsg::RegisterMerkleRoot {
         merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        total_amount: Uint128::from(100000000u128),
+        expiry: None,
+        staking_requirement: None,
     };
 
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
     let res = handle(&mut deps, env, msg).unwrap();
     assert_eq!(
         res.log,
@@ -116,6 +149,23 @@ fn register_merkle_root() {
         "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
         merkle_root.merkle_root
     );
+
+    // stage totals cannot exceed the airdrop balance
+    let env = mock_env("owner0000", &[]);
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        total_amount: Uint128::from(1000000000u128),
+        expiry: None,
+        staking_requirement: None,
+    };
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Stages are not funded, 100000000 more tokens are required"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
@@ -125,21 +175,38 @@ fn claim() {
     let msg = InitMsg {
         owner: HumanAddr::from("owner0000"),
         anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
     };
 
     let env = mock_env("addr0000", &[]);
     let _res = init(&mut deps, env, msg).unwrap();
 
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
+
     // Register merkle roots
     let env = mock_env("owner0000", &[]);
     let msg = HandleMsg::RegisterMerkleRoot {
         merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::from(100000000u128),
+        expiry: None,
+        staking_requirement: None,
     };
     let _res = handle(&mut deps, env, msg).unwrap();
 
     let env = mock_env("owner0000", &[]);
     let msg = HandleMsg::RegisterMerkleRoot {
         merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        total_amount: Uint128::from(100000000u128),
+        expiry: None,
+        staking_requirement: None,
     };
     let _res = handle(&mut deps, env, msg).unwrap();
 
@@ -152,6 +219,7 @@ fn claim() {
             "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
             "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
         ],
+        splits: None,
     };
 
     let env = mock_env(
@@ -214,6 +282,7 @@ fn claim() {
             "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
             "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
         ],
+        splits: None,
     };
 
     let env = mock_env(
@@ -244,3 +313,874 @@ fn claim() {
         ]
     );
 }
+
+#[test]
+fn claim_splits() {
+    let mut deps = mock_dependencies(44, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
+
+    let env = mock_env("owner0000", &[]);
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::from(100000000u128),
+        expiry: None,
+        staking_requirement: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let claim_msg = |splits: Vec<(HumanAddr, Decimal)>| HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        splits: Some(splits),
+    };
+
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    let msg = claim_msg(vec![
+        (HumanAddr::from("sub0000"), Decimal::percent(50)),
+        (HumanAddr::from("sub0001"), Decimal::percent(40)),
+    ]);
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Split ratios must sum to 1"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = claim_msg(vec![
+        (HumanAddr::from("sub0000"), Decimal::percent(30)),
+        (HumanAddr::from("sub0001"), Decimal::percent(70)),
+    ]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("anchor0000"),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("sub0000"),
+                    amount: Uint128::from(300000u128),
+                })
+                .unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("anchor0000"),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("sub0001"),
+                    amount: Uint128::from(700001u128),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "claim"),
+            log("stage", "1"),
+            log("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            log("amount", "1000001"),
+            log("recipient", "sub0000"),
+            log("recipient_amount", "300000"),
+            log("recipient", "sub0001"),
+            log("recipient_amount", "700001"),
+        ]
+    );
+}
+
+#[test]
+fn claim_cap() {
+    let mut deps = mock_dependencies(44, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: Some(Uint128::from(2500000u128)),
+        distributor: None,
+        reclaim_gov_contract: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
+
+    // Register merkle roots
+    let env = mock_env("owner0000", &[]);
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::from(100000000u128),
+        expiry: None,
+        staking_requirement: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("owner0000", &[]);
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        total_amount: Uint128::from(100000000u128),
+        expiry: None,
+        staking_requirement: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        splits: None,
+    };
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the second stage allocation would bring the total over the cap
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(2000001u128),
+        stage: 2u8,
+        proof: vec![
+            "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+            "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+            "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+            "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+        ],
+        splits: None,
+    };
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Claim amount exceeds address cap")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    assert_eq!(
+        false,
+        from_binary::<IsClaimedResponse>(
+            &query(
+                &deps,
+                QueryMsg::IsClaimed {
+                    stage: 2,
+                    address: HumanAddr::from("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+                }
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .is_claimed
+    );
+}
+
+#[test]
+fn claim_from_distributor() {
+    let mut deps = mock_dependencies(44, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: Some(HumanAddr::from("distributor0000")),
+        reclaim_gov_contract: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(HumanAddr::from("distributor0000")), config.distributor);
+
+    // stage registered before the totals cannot be claimed from the distributor
+    store_merkle_root(
+        &mut deps.storage,
+        1,
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+    )
+    .unwrap();
+    store_latest_stage(&mut deps.storage, 1).unwrap();
+
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        splits: None,
+    };
+
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Stage total amount is not registered")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env("owner0000", &[]);
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::from(5000000u128),
+        expiry: None,
+        staking_requirement: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 2u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        splits: None,
+    };
+
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("distributor0000"),
+            send: vec![],
+            msg: to_binary(&DistributorHandleMsg::Spend {
+                recipient: HumanAddr::from("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+                amount: Uint128::from(1000001u128),
+            })
+            .unwrap(),
+        })]
+    );
+
+    let res = query(&deps, QueryMsg::StageInfo { stage: 2 }).unwrap();
+    let stage_info: StageInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(Uint128::from(1000001u128), stage_info.claimed_amount);
+}
+
+#[test]
+fn rollover_unclaimed() {
+    let mut deps = mock_dependencies(44, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
+
+    let env = mock_env("owner0000", &[]);
+    let expiry = env.block.height + 100;
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::from(5000000u128),
+        expiry: Some(expiry),
+        staking_requirement: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        splits: None,
+    };
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::RolloverUnclaimed {
+        from_stage: 1u8,
+        to_stage_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37"
+            .to_string(),
+        expiry: None,
+    };
+
+    // Unauthorized err
+    let env = mock_env("addr0000", &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // stage is still claimable
+    let env = mock_env("owner0000", &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Stage has not expired"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env("owner0000", &[]);
+    env.block.height = expiry + 1;
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "rollover_unclaimed"),
+            log("from_stage", "1"),
+            log("stage", "2"),
+            log(
+                "merkle_root",
+                "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37"
+            ),
+            log("amount", "3999999"),
+        ]
+    );
+
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Stage has already been rolled over")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(&deps, QueryMsg::StageInfo { stage: 1 }).unwrap();
+    let stage_info: StageInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stage_info,
+        StageInfoResponse {
+            stage: 1,
+            total_amount: Some(Uint128::from(5000000u128)),
+            claimed_amount: Uint128::from(1000001u128),
+            expiry: Some(expiry),
+            rolled_over_to: Some(2),
+            reclaimed: false,
+            staking_snapshot: None,
+            staking_requirement: None,
+        }
+    );
+
+    let res = query(&deps, QueryMsg::StageInfo { stage: 2 }).unwrap();
+    let stage_info: StageInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stage_info,
+        StageInfoResponse {
+            stage: 2,
+            total_amount: Some(Uint128::from(3999999u128)),
+            claimed_amount: Uint128::zero(),
+            expiry: None,
+            rolled_over_to: None,
+            reclaimed: false,
+            staking_snapshot: None,
+            staking_requirement: None,
+        }
+    );
+
+    // closed stage can no longer be claimed
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 1u8,
+        proof: vec![],
+        splits: None,
+    };
+    let env = mock_env("addr0000", &[]);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Stage has been rolled over"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // claim from the new stage
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(2000001u128),
+        stage: 2u8,
+        proof: vec![
+            "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+            "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+            "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+            "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+        ],
+        splits: None,
+    };
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::StageInfo { stage: 2 }).unwrap();
+    let stage_info: StageInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(stage_info.claimed_amount, Uint128::from(2000001u128));
+}
+
+#[test]
+fn reclaim_unclaimed() {
+    let mut deps = mock_dependencies(44, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: Some(HumanAddr::from("gov0000")),
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
+
+    let env = mock_env("owner0000", &[]);
+    let expiry = env.block.height + 100;
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::from(5000000u128),
+        expiry: Some(expiry),
+        staking_requirement: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        splits: None,
+    };
+    let env = mock_env(
+        "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        &[],
+    );
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::ReclaimUnclaimed { stage: 1u8 };
+
+    // Unauthorized err
+    let env = mock_env("addr0000", &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // stage is still claimable
+    let env = mock_env("owner0000", &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Stage has not expired"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the unclaimed amount is deposited to gov as a staking reward
+    let mut env = mock_env("owner0000", &[]);
+    env.block.height = expiry + 1;
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor0000"),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("gov0000"),
+                amount: Uint128::from(3999999u128),
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "reclaim_unclaimed"),
+            log("stage", "1"),
+            log("recipient", "gov0000"),
+            log("amount", "3999999"),
+        ]
+    );
+
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Stage has already been reclaimed")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::RolloverUnclaimed {
+        from_stage: 1u8,
+        to_stage_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37"
+            .to_string(),
+        expiry: None,
+    };
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Stage has already been reclaimed")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(&deps, QueryMsg::StageInfo { stage: 1 }).unwrap();
+    let stage_info: StageInfoResponse = from_binary(&res).unwrap();
+    assert!(stage_info.reclaimed);
+}
+
+#[test]
+fn staking_snapshot() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
+
+    deps.querier.with_staking_snapshot(
+        &HumanAddr::from("gov0000"),
+        100,
+        &[
+            (&HumanAddr::from("staker0000"), &Uint128::from(300u128)),
+            (&HumanAddr::from("staker0001"), &Uint128::from(100u128)),
+        ],
+        Uint128::from(400u128),
+    );
+
+    let msg = HandleMsg::RegisterStakingSnapshot {
+        gov_contract: HumanAddr::from("gov0000"),
+        height: 100,
+        total_amount: Uint128::from(1000u128),
+        expiry: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let mut env = mock_env("owner0000", &[]);
+    env.block.height = 100;
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Snapshot height must be in the past")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.height = 101;
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "register_staking_snapshot"),
+            log("stage", "1"),
+            log("gov_contract", "gov0000"),
+            log("height", "100"),
+            log("total_amount", "1000"),
+        ]
+    );
+
+    let msg = HandleMsg::ClaimStakingSnapshot { stage: 1 };
+    let env = mock_env("staker0000", &[]);
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor0000"),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("staker0000"),
+                amount: Uint128::from(750u128),
+            })
+            .unwrap(),
+        })]
+    );
+
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Already claimed"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env("staker0002", &[]);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Nothing staked at the snapshot height")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(&deps, QueryMsg::StageInfo { stage: 1 }).unwrap();
+    let stage_info: StageInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stage_info,
+        StageInfoResponse {
+            stage: 1,
+            total_amount: Some(Uint128::from(1000u128)),
+            claimed_amount: Uint128::from(750u128),
+            expiry: None,
+            rolled_over_to: None,
+            reclaimed: false,
+            staking_snapshot: Some(StakingSnapshot {
+                gov_contract: HumanAddr::from("gov0000"),
+                height: 100,
+            }),
+            staking_requirement: None,
+        }
+    );
+}
+
+#[test]
+fn claim_staking_requirement() {
+    let mut deps = mock_dependencies(44, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr::from("anchor0000"),
+        claim_cap: None,
+        distributor: None,
+        reclaim_gov_contract: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(
+        &HumanAddr::from("anchor0000"),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000000000u128),
+        )],
+    );
+
+    let env = mock_env("owner0000", &[]);
+    let msg = HandleMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        total_amount: Uint128::from(100000000u128),
+        expiry: None,
+        staking_requirement: Some(StakingRequirement {
+            gov_contract: HumanAddr::from("gov0000"),
+            min_staked: Uint128::from(100u128),
+        }),
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::StageInfo { stage: 1 }).unwrap();
+    let stage_info: StageInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stage_info.staking_requirement,
+        Some(StakingRequirement {
+            gov_contract: HumanAddr::from("gov0000"),
+            min_staked: Uint128::from(100u128),
+        })
+    );
+
+    let msg = HandleMsg::Claim {
+        amount: Uint128::from(1000001u128),
+        stage: 1u8,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        splits: None,
+    };
+
+    let claimer = HumanAddr::from("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8");
+    let env = mock_env(claimer.clone(), &[]);
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Claim requires at least 100 staked in gov")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // staked in a different gov contract does not count
+    deps.querier
+        .with_staker_balances(&HumanAddr::from("gov0001"), &[(&claimer, &Uint128(100))]);
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Claim requires at least 100 staked in gov")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_staker_balances(&HumanAddr::from("gov0000"), &[(&claimer, &Uint128(99))]);
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Claim requires at least 100 staked in gov")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_staker_balances(&HumanAddr::from("gov0000"), &[(&claimer, &Uint128(100))]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor0000"),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: claimer,
+                amount: Uint128::from(1000001u128),
+            })
+            .unwrap(),
+        })]
+    );
+}
+
+#[test]
+fn migrate_legacy_config() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    // config and stage info stored before the claim cap, the distributor
+    // and the reclaims
+    #[derive(Serialize, Deserialize)]
+    struct LegacyConfig {
+        owner: CanonicalAddr,
+        anchor_token: CanonicalAddr,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LegacyStageInfo {
+        total_amount: Option<Uint128>,
+        claimed_amount: Uint128,
+        expiry: Option<u64>,
+        rolled_over_to: Option<u8>,
+    }
+
+    singleton(&mut deps.storage, b"config")
+        .save(&LegacyConfig {
+            owner: deps
+                .api
+                .canonical_address(&HumanAddr::from("owner0000"))
+                .unwrap(),
+            anchor_token: deps
+                .api
+                .canonical_address(&HumanAddr::from("anchor0000"))
+                .unwrap(),
+        })
+        .unwrap();
+    bucket(b"stage_info", &mut deps.storage)
+        .save(
+            &[1u8],
+            &LegacyStageInfo {
+                total_amount: Some(Uint128(5000u128)),
+                claimed_amount: Uint128(1000u128),
+                expiry: None,
+                rolled_over_to: None,
+            },
+        )
+        .unwrap();
+
+    let env = mock_env("addr0000", &[]);
+    let _res = migrate(&mut deps, env, MigrateMsg {}).unwrap();
+
+    let config: ConfigResponse = from_binary(&query(&deps, QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: HumanAddr::from("owner0000"),
+            anchor_token: HumanAddr::from("anchor0000"),
+            claim_cap: None,
+            distributor: None,
+            reclaim_gov_contract: None,
+        }
+    );
+
+    let stage_info: StageInfoResponse =
+        from_binary(&query(&deps, QueryMsg::StageInfo { stage: 1 }).unwrap()).unwrap();
+    assert_eq!(
+        stage_info,
+        StageInfoResponse {
+            stage: 1,
+            total_amount: Some(Uint128(5000u128)),
+            claimed_amount: Uint128(1000u128),
+            expiry: None,
+            rolled_over_to: None,
+            reclaimed: false,
+            staking_snapshot: None,
+            staking_requirement: None,
+        }
+    );
+}
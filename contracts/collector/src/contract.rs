@@ -1,22 +1,34 @@
 use cosmwasm_std::{
     log, to_binary, Api, Binary, Coin, CosmosMsg, Decimal, Env, Extern, HandleResponse,
-    HandleResult, InitResponse, MigrateResponse, MigrateResult, Querier, StdError, StdResult,
-    Storage, WasmMsg,
+    HandleResult, HumanAddr, InitResponse, LogAttribute, MigrateResponse, MigrateResult, Querier,
+    StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 
-use crate::state::{read_config, store_config, Config};
+use crate::router::{router_simulate, router_swap_msg};
+use crate::state::{
+    read_collectable_asset, read_collectable_assets, read_config, read_contract_addr,
+    remove_collectable_asset, store_collectable_asset, store_config, store_contract_addr,
+    CollectableAsset, Config, RouterRaw,
+};
 
-use anchor_token::collector::{ConfigResponse, HandleMsg, InitMsg, MigrateMsg, QueryMsg};
+use anchor_token::collector::{
+    CollectableAssetResponse, CollectableAssetsResponse, ConfigResponse, HandleMsg, InitMsg,
+    MigrateMsg, QueryMsg, Router, SweepWindow, SweepableAssetResponse, SweepableResponse,
+};
 use cw20::Cw20HandleMsg;
-use terraswap::asset::{Asset, AssetInfo, PairInfo};
-use terraswap::pair::HandleMsg as TerraswapHandleMsg;
-use terraswap::querier::{query_balance, query_pair_info, query_token_balance};
+use terraswap::asset::{Asset, AssetInfo, AssetInfoRaw, PairInfo};
+use terraswap::pair::{Cw20HookMsg as TerraswapCw20HookMsg, HandleMsg as TerraswapHandleMsg};
+use terraswap::querier::{query_balance, query_pair_info, query_token_balance, simulate};
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
+    store_contract_addr(
+        &mut deps.storage,
+        &deps.api.canonical_address(&env.contract.address)?,
+    )?;
     store_config(
         &mut deps.storage,
         &Config {
@@ -25,6 +37,11 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
             anchor_token: deps.api.canonical_address(&msg.anchor_token)?,
             distributor_contract: deps.api.canonical_address(&msg.distributor_contract)?,
             reward_factor: msg.reward_factor,
+            sweep_windows: validate_sweep_windows(msg.sweep_windows)?,
+            router: match msg.router {
+                Some(router) => Some(router_to_raw(&deps.api, router)?),
+                None => None,
+            },
         },
     )?;
 
@@ -37,8 +54,27 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
     match msg {
-        HandleMsg::UpdateConfig { reward_factor } => update_config(deps, env, reward_factor),
+        HandleMsg::UpdateConfig {
+            reward_factor,
+            sweep_windows,
+            router,
+        } => update_config(deps, env, reward_factor, sweep_windows, router),
+        HandleMsg::RegisterAsset {
+            asset_info,
+            decimals,
+            pair_contract,
+            min_sweep_amount,
+        } => register_asset(
+            deps,
+            env,
+            asset_info,
+            decimals,
+            pair_contract,
+            min_sweep_amount,
+        ),
+        HandleMsg::DeregisterAsset { asset_info } => deregister_asset(deps, env, asset_info),
         HandleMsg::Sweep { denom } => sweep(deps, env, denom),
+        HandleMsg::SweepAll {} => sweep_all(deps, env),
         HandleMsg::Distribute {} => distribute(deps, env),
     }
 }
@@ -46,6 +82,8 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     reward_factor: Option<Decimal>,
+    sweep_windows: Option<Vec<SweepWindow>>,
+    router: Option<Router>,
 ) -> HandleResult {
     let mut config: Config = read_config(&deps.storage)?;
     if deps.api.canonical_address(&env.message.sender)? != config.gov_contract {
@@ -56,78 +94,325 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
         config.reward_factor = reward_factor;
     }
 
+    if let Some(sweep_windows) = sweep_windows {
+        config.sweep_windows = validate_sweep_windows(sweep_windows)?;
+    }
+
+    if let Some(router) = router {
+        config.router = Some(router_to_raw(&deps.api, router)?);
+    }
+
     store_config(&mut deps.storage, &config)?;
     Ok(HandleResponse::default())
 }
+
+pub fn register_asset<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset_info: AssetInfo,
+    decimals: u8,
+    pair_contract: Option<HumanAddr>,
+    min_sweep_amount: Option<Uint128>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.gov_contract {
+        return Err(StdError::unauthorized());
+    }
+
+    if let AssetInfo::Token { contract_addr } = &asset_info {
+        if deps.api.canonical_address(contract_addr)? == config.anchor_token {
+            return Err(StdError::generic_err("Cannot register ANC token"));
+        }
+    }
+
+    let pair_contract = if let Some(pair_contract) = pair_contract {
+        Some(deps.api.canonical_address(&pair_contract)?)
+    } else {
+        None
+    };
+
+    let asset_info_raw = asset_info.to_raw(&deps)?;
+    store_collectable_asset(
+        &mut deps.storage,
+        &CollectableAsset {
+            asset_info: asset_info_raw,
+            decimals,
+            pair_contract,
+            min_sweep_amount: min_sweep_amount.unwrap_or_default(),
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "register_asset"),
+            log("asset_info", asset_info),
+            log("decimals", decimals),
+        ],
+        data: None,
+    })
+}
+
+pub fn deregister_asset<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    asset_info: AssetInfo,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if deps.api.canonical_address(&env.message.sender)? != config.gov_contract {
+        return Err(StdError::unauthorized());
+    }
+
+    let asset_info_raw = asset_info.to_raw(&deps)?;
+    if read_collectable_asset(&deps.storage, &asset_info_raw)?.is_none() {
+        return Err(StdError::generic_err("Asset is not registered"));
+    }
+
+    remove_collectable_asset(&mut deps.storage, &asset_info_raw);
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "deregister_asset"),
+            log("asset_info", asset_info),
+        ],
+        data: None,
+    })
+}
+
 /// Sweep
 /// Anyone can execute sweep function to swap
-/// asset token => ANC token and distribute
+/// registered asset token => ANC token and distribute
 /// result ANC token to gov contract
 pub fn sweep<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     denom: String,
 ) -> HandleResult {
-    let config: Config = read_config(&deps.storage)?;
-    let anchor_token = deps.api.human_address(&config.anchor_token)?;
-    let terraswap_factory_raw = deps.api.human_address(&config.terraswap_factory)?;
+    assert_sweep_window(&deps.storage, &env)?;
+
+    let asset_info = AssetInfoRaw::NativeToken { denom };
+    let asset = match read_collectable_asset(&deps.storage, &asset_info)? {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Asset is not registered")),
+    };
+
+    let (_, amount) = load_sweep_amount(
+        deps,
+        &env.contract.address,
+        &asset.asset_info.to_normal(&deps)?,
+    )?;
+    if amount < asset.min_sweep_amount {
+        return Err(StdError::generic_err(
+            "Sweep amount is below the minimum sweep amount",
+        ));
+    }
+
+    let (mut messages, swap_log) = swap_asset(deps, &env, &asset)?;
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address,
+        msg: to_binary(&HandleMsg::Distribute {})?,
+        send: vec![],
+    }));
+
+    let mut log_items = vec![log("action", "sweep")];
+    log_items.extend(swap_log);
+
+    Ok(HandleResponse {
+        messages,
+        log: log_items,
+        data: None,
+    })
+}
+
+/// SweepAll
+/// Anyone can execute sweep all function to swap
+/// the balances of every registered asset => ANC token
+/// and distribute result ANC token to gov contract
+pub fn sweep_all<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    assert_sweep_window(&deps.storage, &env)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut log_items = vec![log("action", "sweep_all")];
+    for asset in read_collectable_assets(&deps.storage)? {
+        let (swap_messages, swap_log) = swap_asset(deps, &env, &asset)?;
+        messages.extend(swap_messages);
+        log_items.extend(swap_log);
+    }
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address,
+        msg: to_binary(&HandleMsg::Distribute {})?,
+        send: vec![],
+    }));
 
+    Ok(HandleResponse {
+        messages,
+        log: log_items,
+        data: None,
+    })
+}
+
+fn router_to_raw<A: Api>(api: &A, router: Router) -> StdResult<RouterRaw> {
+    Ok(RouterRaw {
+        router_type: router.router_type,
+        contract_addr: api.canonical_address(&router.contract_addr)?,
+    })
+}
+
+fn validate_sweep_windows(sweep_windows: Vec<SweepWindow>) -> StdResult<Vec<SweepWindow>> {
+    for window in sweep_windows.iter() {
+        let valid = match window {
+            SweepWindow::Periodic { period, duration } => *duration > 0 && duration <= period,
+            SweepWindow::Range { start, end } => start <= end,
+        };
+
+        if !valid {
+            return Err(StdError::generic_err("Invalid sweep window"));
+        }
+    }
+
+    Ok(sweep_windows)
+}
+
+// Sweeps are only allowed within one of the configured windows,
+// so they can't be timed to the moment of the largest price impact
+fn assert_sweep_window<S: Storage>(storage: &S, env: &Env) -> StdResult<()> {
+    let config: Config = read_config(storage)?;
+    if !is_sweep_window_open(&config, env.block.height) {
+        return Err(StdError::generic_err("Sweep window is closed"));
+    }
+
+    Ok(())
+}
+
+fn is_sweep_window_open(config: &Config, height: u64) -> bool {
+    config.sweep_windows.is_empty()
+        || config
+            .sweep_windows
+            .iter()
+            .any(|window| window.is_open(height))
+}
+
+// Balance of the asset held by the collector and the amount of it
+// a sweep swaps, which is net of tax for native tokens
+fn load_sweep_amount<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    collector: &HumanAddr,
+    asset_info: &AssetInfo,
+) -> StdResult<(Uint128, Uint128)> {
+    match asset_info.clone() {
+        AssetInfo::NativeToken { denom } => {
+            let balance = query_balance(&deps, collector, denom)?;
+            let swap_asset = Asset {
+                info: asset_info.clone(),
+                amount: balance,
+            };
+
+            // deduct tax first
+            Ok((balance, (swap_asset.deduct_tax(&deps)?).amount))
+        }
+        AssetInfo::Token { contract_addr } => {
+            let balance = query_token_balance(&deps, &contract_addr, collector)?;
+            Ok((balance, balance))
+        }
+    }
+}
+
+// Pair swapping the asset to ANC token, the registered one
+// or else the one of the terraswap factory
+fn load_pair_contract<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    asset: &CollectableAsset,
+    asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+) -> StdResult<HumanAddr> {
+    if let Some(pair_contract) = &asset.pair_contract {
+        return deps.api.human_address(pair_contract);
+    }
+
+    let terraswap_factory_raw = deps.api.human_address(&config.terraswap_factory)?;
     let pair_info: PairInfo = query_pair_info(
         &deps,
         &terraswap_factory_raw,
-        &[
-            AssetInfo::NativeToken {
-                denom: denom.to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: anchor_token.clone(),
-            },
-        ],
+        &[asset_info.clone(), ask_asset_info.clone()],
     )?;
 
-    let amount = query_balance(&deps, &env.contract.address, denom.to_string())?;
-    let swap_asset = Asset {
-        info: AssetInfo::NativeToken {
-            denom: denom.to_string(),
-        },
+    Ok(pair_info.contract_addr)
+}
+
+// Build the messages swapping the whole balance of the asset to ANC token,
+// none when the balance is below the minimum sweep amount
+fn swap_asset<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    asset: &CollectableAsset,
+) -> StdResult<(Vec<CosmosMsg>, Vec<LogAttribute>)> {
+    let config: Config = read_config(&deps.storage)?;
+    let anchor_token = deps.api.human_address(&config.anchor_token)?;
+    let asset_info = asset.asset_info.to_normal(&deps)?;
+
+    let (_, amount) = load_sweep_amount(deps, &env.contract.address, &asset_info)?;
+    if amount.is_zero() || amount < asset.min_sweep_amount {
+        return Ok((vec![], vec![]));
+    }
+
+    let offer_asset = Asset {
+        info: asset_info.clone(),
         amount,
     };
+    let ask_asset_info = AssetInfo::Token {
+        contract_addr: anchor_token,
+    };
 
-    // deduct tax first
-    let amount = (swap_asset.deduct_tax(&deps)?).amount;
-    Ok(HandleResponse {
-        messages: vec![
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: pair_info.contract_addr,
+    let message = if let Some(router) = &config.router {
+        router_swap_msg(
+            &router.router_type,
+            deps.api.human_address(&router.contract_addr)?,
+            offer_asset,
+            ask_asset_info,
+        )?
+    } else {
+        let pair_contract = load_pair_contract(deps, &config, asset, &asset_info, &ask_asset_info)?;
+
+        match asset_info.clone() {
+            AssetInfo::NativeToken { denom } => CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_contract,
                 msg: to_binary(&TerraswapHandleMsg::Swap {
-                    offer_asset: Asset {
-                        amount,
-                        ..swap_asset
-                    },
+                    offer_asset,
                     max_spread: None,
                     belief_price: None,
                     to: None,
                 })?,
-                send: vec![Coin {
-                    denom: denom.to_string(),
-                    amount,
-                }],
+                send: vec![Coin { denom, amount }],
             }),
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: env.contract.address,
-                msg: to_binary(&HandleMsg::Distribute {})?,
+            AssetInfo::Token { contract_addr } => CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg: to_binary(&Cw20HandleMsg::Send {
+                    contract: pair_contract,
+                    amount,
+                    msg: Some(to_binary(&TerraswapCw20HookMsg::Swap {
+                        max_spread: None,
+                        belief_price: None,
+                        to: None,
+                    })?),
+                })?,
                 send: vec![],
             }),
-        ],
-        log: vec![
-            log("action", "sweep"),
-            log(
-                "collected_rewards",
-                format!("{:?}{:?}", amount.to_string(), denom),
-            ),
-        ],
-        data: None,
-    })
+        }
+    };
+
+    Ok((
+        vec![message],
+        vec![log(
+            "collected_rewards",
+            format!("{:?}{:?}", amount.to_string(), asset_info.to_string()),
+        )],
+    ))
 }
 
 // Only contract itself can execute distribute function
@@ -190,6 +475,8 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::CollectableAssets {} => to_binary(&query_collectable_assets(deps)?),
+        QueryMsg::Sweepable { block_height } => to_binary(&query_sweepable(deps, block_height)?),
     }
 }
 
@@ -203,15 +490,109 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
         anchor_token: deps.api.human_address(&state.anchor_token)?,
         distributor_contract: deps.api.human_address(&state.distributor_contract)?,
         reward_factor: state.reward_factor,
+        sweep_windows: state.sweep_windows,
+        router: match state.router {
+            Some(router) => Some(Router {
+                router_type: router.router_type,
+                contract_addr: deps.api.human_address(&router.contract_addr)?,
+            }),
+            None => None,
+        },
     };
 
     Ok(resp)
 }
 
+pub fn query_collectable_assets<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<CollectableAssetsResponse> {
+    let assets: StdResult<Vec<CollectableAssetResponse>> = read_collectable_assets(&deps.storage)?
+        .into_iter()
+        .map(|asset| {
+            Ok(CollectableAssetResponse {
+                asset_info: asset.asset_info.to_normal(&deps)?,
+                decimals: asset.decimals,
+                pair_contract: if let Some(pair_contract) = asset.pair_contract {
+                    Some(deps.api.human_address(&pair_contract)?)
+                } else {
+                    None
+                },
+                min_sweep_amount: asset.min_sweep_amount,
+            })
+        })
+        .collect();
+
+    Ok(CollectableAssetsResponse { assets: assets? })
+}
+
+pub fn query_sweepable<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    block_height: u64,
+) -> StdResult<SweepableResponse> {
+    let config: Config = read_config(&deps.storage)?;
+    let collector = deps
+        .api
+        .human_address(&read_contract_addr(&deps.storage)?)?;
+    let sweep_window_open = is_sweep_window_open(&config, block_height);
+    let ask_asset_info = AssetInfo::Token {
+        contract_addr: deps.api.human_address(&config.anchor_token)?,
+    };
+
+    let mut assets: Vec<SweepableAssetResponse> = vec![];
+    for asset in read_collectable_assets(&deps.storage)? {
+        let asset_info = asset.asset_info.to_normal(&deps)?;
+        let (balance, sweep_amount) = load_sweep_amount(deps, &collector, &asset_info)?;
+
+        let estimated_return = if sweep_amount.is_zero() {
+            Uint128::zero()
+        } else {
+            let offer_asset = Asset {
+                info: asset_info.clone(),
+                amount: sweep_amount,
+            };
+
+            if let Some(router) = &config.router {
+                router_simulate(
+                    deps,
+                    &router.router_type,
+                    deps.api.human_address(&router.contract_addr)?,
+                    offer_asset,
+                    ask_asset_info.clone(),
+                )?
+            } else {
+                let pair_contract =
+                    load_pair_contract(deps, &config, &asset, &asset_info, &ask_asset_info)?;
+                simulate(&deps, &pair_contract, &offer_asset)?.return_amount
+            }
+        };
+
+        assets.push(SweepableAssetResponse {
+            asset_info,
+            balance,
+            sweep_amount,
+            min_sweep_amount: asset.min_sweep_amount,
+            estimated_return,
+            sweepable: sweep_window_open
+                && !sweep_amount.is_zero()
+                && sweep_amount >= asset.min_sweep_amount,
+        });
+    }
+
+    Ok(SweepableResponse {
+        sweep_window_open,
+        assets,
+    })
+}
+
 pub fn migrate<S: Storage, A: Api, Q: Querier>(
-    _deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
     _msg: MigrateMsg,
 ) -> MigrateResult {
+    store_contract_addr(
+        &mut deps.storage,
+        &deps.api.canonical_address(&env.contract.address)?,
+    )?;
+
     Ok(MigrateResponse::default())
 }
@@ -1,4 +1,5 @@
 pub mod contract;
+pub mod router;
 pub mod state;
 
 #[cfg(test)]
@@ -11,7 +11,8 @@ use cosmwasm_storage::to_length_prefixed;
 use std::collections::HashMap;
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
-use terraswap::asset::{AssetInfo, PairInfo};
+use terraswap::asset::{Asset, AssetInfo, PairInfo};
+use terraswap::pair::SimulationResponse;
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -38,6 +39,7 @@ pub struct WasmMockQuerier {
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
     terraswap_factory_querier: TerraswapFactoryQuerier,
+    swap_rate: Decimal,
     canonical_length: usize,
 }
 
@@ -135,6 +137,7 @@ impl Querier for WasmMockQuerier {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Pair { asset_infos: [AssetInfo; 2] },
+    Simulation { offer_asset: Asset },
 }
 
 impl WasmMockQuerier {
@@ -190,6 +193,11 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::Simulation { offer_asset } => Ok(to_binary(&SimulationResponse {
+                    return_amount: offer_asset.amount * self.swap_rate,
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                })),
             },
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
                 let key: &[u8] = key.as_slice();
@@ -255,6 +263,7 @@ impl WasmMockQuerier {
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
             terraswap_factory_querier: TerraswapFactoryQuerier::default(),
+            swap_rate: Decimal::one(),
             canonical_length,
         }
     }
@@ -273,4 +282,9 @@ impl WasmMockQuerier {
     pub fn with_terraswap_pairs(&mut self, pairs: &[(&String, &HumanAddr)]) {
         self.terraswap_factory_querier = TerraswapFactoryQuerier::new(pairs);
     }
+
+    // configure the ANC returned per offer asset by the pair simulation
+    pub fn with_swap_rate(&mut self, rate: Decimal) {
+        self.swap_rate = rate;
+    }
 }
@@ -0,0 +1,156 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use anchor_token::collector::RouterType;
+use cosmwasm_std::{
+    to_binary, Api, Binary, Coin, CosmosMsg, Extern, HumanAddr, Querier, QueryRequest, StdResult,
+    Storage, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::Cw20HandleMsg;
+use terraswap::asset::{Asset, AssetInfo};
+
+/// Terraswap router interface, used as both
+/// the handle message and the cw20 hook message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerraswapRouterMsg {
+    ExecuteSwapOperations {
+        operations: Vec<TerraswapSwapOperation>,
+        minimum_receive: Option<Uint128>,
+        to: Option<HumanAddr>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerraswapSwapOperation {
+    TerraSwap {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+}
+
+/// Astroport router interface, used as both
+/// the handle message and the cw20 hook message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AstroportRouterMsg {
+    ExecuteSwapOperations {
+        operations: Vec<AstroportSwapOperation>,
+        minimum_receive: Option<Uint128>,
+        to: Option<HumanAddr>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AstroportSwapOperation {
+    AstroSwap {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerraswapRouterQueryMsg {
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<TerraswapSwapOperation>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AstroportRouterQueryMsg {
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<AstroportSwapOperation>,
+    },
+}
+
+/// Response of both router simulations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateSwapOperationsResponse {
+    pub amount: Uint128,
+}
+
+// Build the message swapping the offer asset to the ask asset through the router
+pub fn router_swap_msg(
+    router_type: &RouterType,
+    router: HumanAddr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+) -> StdResult<CosmosMsg> {
+    let swap_msg: Binary = match router_type {
+        RouterType::Terraswap => to_binary(&TerraswapRouterMsg::ExecuteSwapOperations {
+            operations: vec![TerraswapSwapOperation::TerraSwap {
+                offer_asset_info: offer_asset.info.clone(),
+                ask_asset_info,
+            }],
+            minimum_receive: None,
+            to: None,
+        })?,
+        RouterType::Astroport => to_binary(&AstroportRouterMsg::ExecuteSwapOperations {
+            operations: vec![AstroportSwapOperation::AstroSwap {
+                offer_asset_info: offer_asset.info.clone(),
+                ask_asset_info,
+            }],
+            minimum_receive: None,
+            to: None,
+        })?,
+    };
+
+    Ok(match offer_asset.info {
+        AssetInfo::NativeToken { denom } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: router,
+            msg: swap_msg,
+            send: vec![Coin {
+                denom,
+                amount: offer_asset.amount,
+            }],
+        }),
+        AssetInfo::Token { contract_addr } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg: to_binary(&Cw20HandleMsg::Send {
+                contract: router,
+                amount: offer_asset.amount,
+                msg: Some(swap_msg),
+            })?,
+            send: vec![],
+        }),
+    })
+}
+
+// Query the amount of the ask asset the router returns for the offer asset
+pub fn router_simulate<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    router_type: &RouterType,
+    router: HumanAddr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+) -> StdResult<Uint128> {
+    let msg: Binary = match router_type {
+        RouterType::Terraswap => to_binary(&TerraswapRouterQueryMsg::SimulateSwapOperations {
+            offer_amount: offer_asset.amount,
+            operations: vec![TerraswapSwapOperation::TerraSwap {
+                offer_asset_info: offer_asset.info,
+                ask_asset_info,
+            }],
+        })?,
+        RouterType::Astroport => to_binary(&AstroportRouterQueryMsg::SimulateSwapOperations {
+            offer_amount: offer_asset.amount,
+            operations: vec![AstroportSwapOperation::AstroSwap {
+                offer_asset_info: offer_asset.info,
+                ask_asset_info,
+            }],
+        })?,
+    };
+
+    let res: SimulateSwapOperationsResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: router,
+            msg,
+        }))?;
+    Ok(res.amount)
+}
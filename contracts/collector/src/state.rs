@@ -1,10 +1,15 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage};
-use cosmwasm_storage::{singleton, singleton_read};
+use anchor_token::collector::{RouterType, SweepWindow};
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdResult, Storage, Uint128};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use terraswap::asset::AssetInfoRaw;
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_CONTRACT_ADDR: &[u8] = b"contract_addr";
+
+static PREFIX_COLLECTABLE_ASSET: &[u8] = b"collectable_asset";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -13,6 +18,15 @@ pub struct Config {
     pub anchor_token: CanonicalAddr,      // anchor token address
     pub distributor_contract: CanonicalAddr,   // distributor contract to sent back rewards
     pub reward_factor: Decimal, // reward distribution rate to gov contract, left rewards sent back to distributor contract
+    #[serde(default)]
+    pub sweep_windows: Vec<SweepWindow>, // block heights at which sweeps are allowed, any if none
+    pub router: Option<RouterRaw>, // DEX router replacing the terraswap pairs
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouterRaw {
+    pub router_type: RouterType,
+    pub contract_addr: CanonicalAddr,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
@@ -22,3 +36,57 @@ pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<(
 pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+// own address, as queries have no env to read it from
+pub fn store_contract_addr<S: Storage>(storage: &mut S, addr: &CanonicalAddr) -> StdResult<()> {
+    singleton(storage, KEY_CONTRACT_ADDR).save(addr)
+}
+
+pub fn read_contract_addr<S: Storage>(storage: &S) -> StdResult<CanonicalAddr> {
+    singleton_read(storage, KEY_CONTRACT_ADDR).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectableAsset {
+    pub asset_info: AssetInfoRaw,
+    pub decimals: u8,
+    pub pair_contract: Option<CanonicalAddr>,
+    #[serde(default)]
+    pub min_sweep_amount: Uint128, // smaller balances are left for a later sweep
+}
+
+pub fn store_collectable_asset<S: Storage>(
+    storage: &mut S,
+    asset: &CollectableAsset,
+) -> StdResult<()> {
+    let mut asset_bucket: Bucket<S, CollectableAsset> =
+        Bucket::new(PREFIX_COLLECTABLE_ASSET, storage);
+    asset_bucket.save(asset.asset_info.as_bytes(), asset)
+}
+
+pub fn remove_collectable_asset<S: Storage>(storage: &mut S, asset_info: &AssetInfoRaw) {
+    let mut asset_bucket: Bucket<S, CollectableAsset> =
+        Bucket::new(PREFIX_COLLECTABLE_ASSET, storage);
+    asset_bucket.remove(asset_info.as_bytes())
+}
+
+pub fn read_collectable_asset<S: Storage>(
+    storage: &S,
+    asset_info: &AssetInfoRaw,
+) -> StdResult<Option<CollectableAsset>> {
+    let asset_bucket: ReadonlyBucket<S, CollectableAsset> =
+        ReadonlyBucket::new(PREFIX_COLLECTABLE_ASSET, storage);
+    asset_bucket.may_load(asset_info.as_bytes())
+}
+
+pub fn read_collectable_assets<S: Storage>(storage: &S) -> StdResult<Vec<CollectableAsset>> {
+    let asset_bucket: ReadonlyBucket<S, CollectableAsset> =
+        ReadonlyBucket::new(PREFIX_COLLECTABLE_ASSET, storage);
+    asset_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
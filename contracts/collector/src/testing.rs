@@ -1,11 +1,24 @@
-use crate::contract::{handle, init, query_config};
+use crate::contract::{
+    handle, init, migrate, query_collectable_assets, query_config, query_sweepable,
+};
 use crate::mock_querier::mock_dependencies;
-use anchor_token::collector::{ConfigResponse, HandleMsg, InitMsg};
+use crate::router::{
+    AstroportRouterMsg, AstroportSwapOperation, TerraswapRouterMsg, TerraswapSwapOperation,
+};
+use anchor_token::collector::{
+    CollectableAssetResponse, CollectableAssetsResponse, ConfigResponse, HandleMsg, InitMsg,
+    MigrateMsg, Router, RouterType, SweepWindow, SweepableAssetResponse, SweepableResponse,
+};
 use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{to_binary, Coin, CosmosMsg, Decimal, HumanAddr, StdError, Uint128, WasmMsg};
+use cosmwasm_std::{
+    log, to_binary, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, HumanAddr, StdError, Uint128,
+    WasmMsg,
+};
+use cosmwasm_storage::singleton;
 use cw20::Cw20HandleMsg;
+use serde::{Deserialize, Serialize};
 use terraswap::asset::{Asset, AssetInfo};
-use terraswap::pair::HandleMsg as TerraswapHandleMsg;
+use terraswap::pair::{Cw20HookMsg as TerraswapCw20HookMsg, HandleMsg as TerraswapHandleMsg};
 
 #[test]
 fn proper_initialization() {
@@ -17,6 +30,8 @@ fn proper_initialization() {
         anchor_token: HumanAddr("tokenANC".to_string()),
         distributor_contract: HumanAddr::from("distributor"),
         reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -39,6 +54,8 @@ fn update_config() {
         anchor_token: HumanAddr("tokenANC".to_string()),
         distributor_contract: HumanAddr::from("distributor"),
         reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -48,6 +65,8 @@ fn update_config() {
     let env = mock_env("gov", &[]);
     let msg = HandleMsg::UpdateConfig {
         reward_factor: Some(Decimal::percent(80)),
+        sweep_windows: None,
+        router: None,
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -61,6 +80,8 @@ fn update_config() {
     let env = mock_env("addr0000", &[]);
     let msg = HandleMsg::UpdateConfig {
         reward_factor: None,
+        sweep_windows: None,
+        router: None,
     };
 
     let res = handle(&mut deps, env, msg);
@@ -94,6 +115,8 @@ fn test_sweep() {
         anchor_token: HumanAddr("tokenANC".to_string()),
         distributor_contract: HumanAddr::from("distributor"),
         reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -103,31 +126,678 @@ fn test_sweep() {
         denom: "uusd".to_string(),
     };
 
+    // tax deduct 100 => 99
+    let expected_messages = vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("pairANC"),
+            msg: to_binary(&TerraswapHandleMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(99u128),
+                },
+                max_spread: None,
+                belief_price: None,
+                to: None,
+            })
+            .unwrap(),
+            send: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(99u128),
+            }],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            msg: to_binary(&HandleMsg::Distribute {}).unwrap(),
+            send: vec![],
+        }),
+    ];
+
+    // unregistered denoms are not swept
+    let env = mock_env("addr0000", &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset is not registered"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env("gov", &[]);
+    let _res = handle(
+        &mut deps,
+        env,
+        HandleMsg::RegisterAsset {
+            asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            decimals: 6,
+            pair_contract: None,
+            min_sweep_amount: None,
+        },
+    )
+    .unwrap();
+
     let env = mock_env("addr0000", &[]);
     let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(res.messages, expected_messages);
+}
 
-    // tax deduct 100 => 99
+#[test]
+fn test_sweep_windows() {
+    let mut deps = mock_dependencies(
+        20,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128(100u128),
+        }],
+    );
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128(1000000u128))],
+    );
+
+    deps.querier
+        .with_terraswap_pairs(&[(&"uusdtokenANC".to_string(), &HumanAddr::from("pairANC"))]);
+
+    let msg = InitMsg {
+        terraswap_factory: HumanAddr("terraswapfactory".to_string()),
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("tokenANC".to_string()),
+        distributor_contract: HumanAddr::from("distributor"),
+        reward_factor: Decimal::percent(90),
+        sweep_windows: vec![SweepWindow::Periodic {
+            period: 100,
+            duration: 10,
+        }],
+        router: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("gov", &[]);
+    let _res = handle(
+        &mut deps,
+        env,
+        HandleMsg::RegisterAsset {
+            asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            decimals: 6,
+            pair_contract: None,
+            min_sweep_amount: None,
+        },
+    )
+    .unwrap();
+
+    let msg = HandleMsg::Sweep {
+        denom: "uusd".to_string(),
+    };
+
+    let mut env = mock_env("addr0000", &[]);
+    env.block.height = 12345;
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Sweep window is closed"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    match handle(&mut deps, env.clone(), HandleMsg::SweepAll {}) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Sweep window is closed"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.height = 12409;
+    let _res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+
+    // a block range opens another window
+    let gov_env = mock_env("gov", &[]);
+    let _res = handle(
+        &mut deps,
+        gov_env.clone(),
+        HandleMsg::UpdateConfig {
+            reward_factor: None,
+            sweep_windows: Some(vec![
+                SweepWindow::Periodic {
+                    period: 100,
+                    duration: 10,
+                },
+                SweepWindow::Range {
+                    start: 12340,
+                    end: 12350,
+                },
+            ]),
+            router: None,
+        },
+    )
+    .unwrap();
+
+    env.block.height = 12345;
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    match handle(
+        &mut deps,
+        gov_env,
+        HandleMsg::UpdateConfig {
+            reward_factor: None,
+            sweep_windows: Some(vec![SweepWindow::Periodic {
+                period: 0,
+                duration: 0,
+            }]),
+            router: None,
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid sweep window"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn test_asset_registry() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        terraswap_factory: HumanAddr("terraswapfactory".to_string()),
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("tokenANC".to_string()),
+        distributor_contract: HumanAddr::from("distributor"),
+        reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: AssetInfo::Token {
+            contract_addr: HumanAddr::from("tokenXYZ"),
+        },
+        decimals: 8,
+        pair_contract: Some(HumanAddr::from("pairXYZ")),
+        min_sweep_amount: None,
+    };
+
+    // Unauthorized err
+    let env = mock_env("addr0000", &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let env = mock_env("gov", &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "register_asset"),
+            log("asset_info", "tokenXYZ"),
+            log("decimals", "8"),
+        ]
+    );
+
+    // ANC is the swap target, not a fee asset
+    let env = mock_env("gov", &[]);
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: AssetInfo::Token {
+            contract_addr: HumanAddr::from("tokenANC"),
+        },
+        decimals: 6,
+        pair_contract: None,
+        min_sweep_amount: None,
+    };
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Cannot register ANC token"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env("gov", &[]);
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        decimals: 6,
+        pair_contract: None,
+        min_sweep_amount: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res: CollectableAssetsResponse = query_collectable_assets(&deps).unwrap();
+    assert_eq!(res.assets.len(), 2);
+    assert!(res.assets.contains(&CollectableAssetResponse {
+        asset_info: AssetInfo::Token {
+            contract_addr: HumanAddr::from("tokenXYZ"),
+        },
+        decimals: 8,
+        pair_contract: Some(HumanAddr::from("pairXYZ")),
+        min_sweep_amount: Uint128::zero(),
+    }));
+
+    let env = mock_env("gov", &[]);
+    let msg = HandleMsg::DeregisterAsset {
+        asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    };
+    let _res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Asset is not registered"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res: CollectableAssetsResponse = query_collectable_assets(&deps).unwrap();
+    assert_eq!(res.assets.len(), 1);
+}
+
+#[test]
+fn test_sweep_all() {
+    let mut deps = mock_dependencies(20, &[]);
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("tokenXYZ"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
+    )]);
+
+    let msg = InitMsg {
+        terraswap_factory: HumanAddr("terraswapfactory".to_string()),
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("tokenANC".to_string()),
+        distributor_contract: HumanAddr::from("distributor"),
+        reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("gov", &[]);
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: AssetInfo::Token {
+            contract_addr: HumanAddr::from("tokenXYZ"),
+        },
+        decimals: 8,
+        pair_contract: Some(HumanAddr::from("pairXYZ")),
+        min_sweep_amount: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, HandleMsg::SweepAll {}).unwrap();
     assert_eq!(
         res.messages,
         vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: HumanAddr::from("pairANC"),
-                msg: to_binary(&TerraswapHandleMsg::Swap {
-                    offer_asset: Asset {
-                        info: AssetInfo::NativeToken {
-                            denom: "uusd".to_string()
-                        },
-                        amount: Uint128::from(99u128),
-                    },
-                    max_spread: None,
-                    belief_price: None,
-                    to: None,
+                contract_addr: HumanAddr::from("tokenXYZ"),
+                msg: to_binary(&Cw20HandleMsg::Send {
+                    contract: HumanAddr::from("pairXYZ"),
+                    amount: Uint128(100u128),
+                    msg: Some(
+                        to_binary(&TerraswapCw20HookMsg::Swap {
+                            max_spread: None,
+                            belief_price: None,
+                            to: None,
+                        })
+                        .unwrap()
+                    ),
                 })
                 .unwrap(),
-                send: vec![Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(99u128),
+                send: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                msg: to_binary(&HandleMsg::Distribute {}).unwrap(),
+                send: vec![],
+            })
+        ]
+    );
+}
+
+#[test]
+fn test_sweep_router() {
+    let mut deps = mock_dependencies(
+        20,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128(100u128),
+        }],
+    );
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("tokenXYZ"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
+    )]);
+
+    let msg = InitMsg {
+        terraswap_factory: HumanAddr("terraswapfactory".to_string()),
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("tokenANC".to_string()),
+        distributor_contract: HumanAddr::from("distributor"),
+        reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: Some(Router {
+            router_type: RouterType::Terraswap,
+            contract_addr: HumanAddr::from("terraswaprouter"),
+        }),
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    // the pair contract is not used when a router is configured
+    let env = mock_env("gov", &[]);
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: AssetInfo::Token {
+            contract_addr: HumanAddr::from("tokenXYZ"),
+        },
+        decimals: 8,
+        pair_contract: Some(HumanAddr::from("pairXYZ")),
+        min_sweep_amount: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, HandleMsg::SweepAll {}).unwrap();
+    assert_eq!(
+        res.messages[0],
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("tokenXYZ"),
+            msg: to_binary(&Cw20HandleMsg::Send {
+                contract: HumanAddr::from("terraswaprouter"),
+                amount: Uint128(100u128),
+                msg: Some(
+                    to_binary(&TerraswapRouterMsg::ExecuteSwapOperations {
+                        operations: vec![TerraswapSwapOperation::TerraSwap {
+                            offer_asset_info: AssetInfo::Token {
+                                contract_addr: HumanAddr::from("tokenXYZ"),
+                            },
+                            ask_asset_info: AssetInfo::Token {
+                                contract_addr: HumanAddr::from("tokenANC"),
+                            },
+                        }],
+                        minimum_receive: None,
+                        to: None,
+                    })
+                    .unwrap()
+                ),
+            })
+            .unwrap(),
+            send: vec![],
+        })
+    );
+
+    // moving to another DEX is a config change
+    let env = mock_env("gov", &[]);
+    let msg = HandleMsg::UpdateConfig {
+        reward_factor: None,
+        sweep_windows: None,
+        router: Some(Router {
+            router_type: RouterType::Astroport,
+            contract_addr: HumanAddr::from("astroportrouter"),
+        }),
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("gov", &[]);
+    let msg = HandleMsg::RegisterAsset {
+        asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        decimals: 6,
+        pair_contract: None,
+        min_sweep_amount: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("addr0000", &[]);
+    let msg = HandleMsg::Sweep {
+        denom: "uusd".to_string(),
+    };
+    let res = handle(&mut deps, env, msg).unwrap();
+
+    // tax deduct 100 => 99
+    assert_eq!(
+        res.messages[0],
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("astroportrouter"),
+            msg: to_binary(&AstroportRouterMsg::ExecuteSwapOperations {
+                operations: vec![AstroportSwapOperation::AstroSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    ask_asset_info: AssetInfo::Token {
+                        contract_addr: HumanAddr::from("tokenANC"),
+                    },
                 }],
+                minimum_receive: None,
+                to: None,
+            })
+            .unwrap(),
+            send: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(99u128),
+            }],
+        })
+    );
+
+    let config: ConfigResponse = query_config(&deps).unwrap();
+    assert_eq!(
+        config.router,
+        Some(Router {
+            router_type: RouterType::Astroport,
+            contract_addr: HumanAddr::from("astroportrouter"),
+        })
+    );
+}
+
+#[test]
+fn test_sweepable() {
+    let mut deps = mock_dependencies(
+        20,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128(100u128),
+        }],
+    );
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("asset0000"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(0u128))],
+    )]);
+    deps.querier
+        .with_terraswap_pairs(&[(&"uusdtokenANC".to_string(), &HumanAddr::from("pairANC"))]);
+    deps.querier.with_swap_rate(Decimal::percent(50));
+
+    let msg = InitMsg {
+        terraswap_factory: HumanAddr("terraswapfactory".to_string()),
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("tokenANC".to_string()),
+        distributor_contract: HumanAddr::from("distributor"),
+        reward_factor: Decimal::percent(90),
+        sweep_windows: vec![SweepWindow::Periodic {
+            period: 100,
+            duration: 10,
+        }],
+        router: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let env = mock_env("gov", &[]);
+    let _res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::RegisterAsset {
+            asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            decimals: 6,
+            pair_contract: None,
+            min_sweep_amount: None,
+        },
+    )
+    .unwrap();
+    let _res = handle(
+        &mut deps,
+        env,
+        HandleMsg::RegisterAsset {
+            asset_info: AssetInfo::Token {
+                contract_addr: HumanAddr::from("asset0000"),
+            },
+            decimals: 6,
+            pair_contract: Some(HumanAddr::from("pair0000")),
+            min_sweep_amount: None,
+        },
+    )
+    .unwrap();
+
+    // tax deduct 100 => 99, swapped at half the price
+    let expected_assets = |sweepable: bool| {
+        vec![
+            SweepableAssetResponse {
+                asset_info: AssetInfo::Token {
+                    contract_addr: HumanAddr::from("asset0000"),
+                },
+                balance: Uint128::zero(),
+                sweep_amount: Uint128::zero(),
+                min_sweep_amount: Uint128::zero(),
+                estimated_return: Uint128::zero(),
+                sweepable: false,
+            },
+            SweepableAssetResponse {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                balance: Uint128(100u128),
+                sweep_amount: Uint128(99u128),
+                min_sweep_amount: Uint128::zero(),
+                estimated_return: Uint128(49u128),
+                sweepable,
+            },
+        ]
+    };
+
+    let res = query_sweepable(&deps, 105).unwrap();
+    assert_eq!(
+        res,
+        SweepableResponse {
+            sweep_window_open: true,
+            assets: expected_assets(true),
+        }
+    );
+
+    // outside of the sweep window
+    let res = query_sweepable(&deps, 150).unwrap();
+    assert_eq!(
+        res,
+        SweepableResponse {
+            sweep_window_open: false,
+            assets: expected_assets(false),
+        }
+    );
+}
+
+#[test]
+fn test_min_sweep_amount() {
+    let mut deps = mock_dependencies(
+        20,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128(100u128),
+        }],
+    );
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("tokenXYZ"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
+    )]);
+    deps.querier
+        .with_terraswap_pairs(&[(&"uusdtokenANC".to_string(), &HumanAddr::from("pairANC"))]);
+    deps.querier.with_swap_rate(Decimal::percent(50));
+
+    let msg = InitMsg {
+        terraswap_factory: HumanAddr("terraswapfactory".to_string()),
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("tokenANC".to_string()),
+        distributor_contract: HumanAddr::from("distributor"),
+        reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: None,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    // tax deduct 100 => 99, below the minimum
+    let env = mock_env("gov", &[]);
+    let _res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::RegisterAsset {
+            asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            decimals: 6,
+            pair_contract: None,
+            min_sweep_amount: Some(Uint128(100u128)),
+        },
+    )
+    .unwrap();
+    let _res = handle(
+        &mut deps,
+        env,
+        HandleMsg::RegisterAsset {
+            asset_info: AssetInfo::Token {
+                contract_addr: HumanAddr::from("tokenXYZ"),
+            },
+            decimals: 8,
+            pair_contract: Some(HumanAddr::from("pairXYZ")),
+            min_sweep_amount: Some(Uint128(100u128)),
+        },
+    )
+    .unwrap();
+
+    let env = mock_env("addr0000", &[]);
+    let msg = HandleMsg::Sweep {
+        denom: "uusd".to_string(),
+    };
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Sweep amount is below the minimum sweep amount")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the assets reaching their minimum are swept
+    let res = handle(&mut deps, env, HandleMsg::SweepAll {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("tokenXYZ"),
+                msg: to_binary(&Cw20HandleMsg::Send {
+                    contract: HumanAddr::from("pairXYZ"),
+                    amount: Uint128(100u128),
+                    msg: Some(
+                        to_binary(&TerraswapCw20HookMsg::Swap {
+                            max_spread: None,
+                            belief_price: None,
+                            to: None,
+                        })
+                        .unwrap()
+                    ),
+                })
+                .unwrap(),
+                send: vec![],
             }),
             CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
@@ -136,6 +806,33 @@ fn test_sweep() {
             })
         ]
     );
+
+    let res = query_sweepable(&deps, 100).unwrap();
+    assert_eq!(
+        res.assets,
+        vec![
+            SweepableAssetResponse {
+                asset_info: AssetInfo::Token {
+                    contract_addr: HumanAddr::from("tokenXYZ"),
+                },
+                balance: Uint128(100u128),
+                sweep_amount: Uint128(100u128),
+                min_sweep_amount: Uint128(100u128),
+                estimated_return: Uint128(50u128),
+                sweepable: true,
+            },
+            SweepableAssetResponse {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                balance: Uint128(100u128),
+                sweep_amount: Uint128(99u128),
+                min_sweep_amount: Uint128(100u128),
+                estimated_return: Uint128(49u128),
+                sweepable: false,
+            },
+        ]
+    );
 }
 
 #[test]
@@ -152,6 +849,8 @@ fn test_distribute() {
         anchor_token: HumanAddr("tokenANC".to_string()),
         distributor_contract: HumanAddr::from("distributor"),
         reward_factor: Decimal::percent(90),
+        sweep_windows: vec![],
+        router: None,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -191,3 +890,44 @@ fn test_distribute() {
         ]
     )
 }
+
+#[test]
+fn test_migrate() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    // config stored before the sweep windows and the router
+    #[derive(Serialize, Deserialize)]
+    struct LegacyConfig {
+        gov_contract: CanonicalAddr,
+        terraswap_factory: CanonicalAddr,
+        anchor_token: CanonicalAddr,
+        distributor_contract: CanonicalAddr,
+        reward_factor: Decimal,
+    }
+
+    singleton(&mut deps.storage, b"config")
+        .save(&LegacyConfig {
+            gov_contract: deps.api.canonical_address(&HumanAddr::from("gov")).unwrap(),
+            terraswap_factory: deps
+                .api
+                .canonical_address(&HumanAddr::from("terraswapfactory"))
+                .unwrap(),
+            anchor_token: deps
+                .api
+                .canonical_address(&HumanAddr::from("tokenANC"))
+                .unwrap(),
+            distributor_contract: deps
+                .api
+                .canonical_address(&HumanAddr::from("distributor"))
+                .unwrap(),
+            reward_factor: Decimal::percent(90),
+        })
+        .unwrap();
+
+    let _res = migrate(&mut deps, mock_env("addr0000", &[]), MigrateMsg {}).unwrap();
+
+    // sweeps stay allowed at any height
+    let config: ConfigResponse = query_config(&deps).unwrap();
+    assert_eq!(config.sweep_windows, vec![]);
+    assert_eq!(config.router, None);
+}
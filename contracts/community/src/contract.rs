@@ -1,14 +1,24 @@
-use crate::state::{read_config, store_config, Config};
+use crate::state::{
+    read_budget, read_budget_spends, read_config, read_donor_info, read_matching_program,
+    read_matching_state, store_budget, store_budget_spend, store_config, store_donor_info,
+    store_matching_program, store_matching_state, Budget, BudgetSpend, Config, MatchingProgram,
+    MatchingState,
+};
 
 use cosmwasm_std::{
-    log, to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HandleResult, HumanAddr,
-    InitResponse, MigrateResponse, MigrateResult, Querier, StdError, StdResult, Storage, Uint128,
-    WasmMsg,
+    from_binary, log, to_binary, Api, Binary, CosmosMsg, Env, Extern, HandleResponse, HandleResult,
+    HumanAddr, InitResponse, MigrateResponse, MigrateResult, Querier, StdError, StdResult, Storage,
+    Uint128, WasmMsg,
 };
 
-use anchor_token::community::{ConfigResponse, HandleMsg, InitMsg, MigrateMsg, QueryMsg};
+use anchor_token::common::Expiration;
+use anchor_token::community::{
+    BudgetResponse, BudgetSpend as BudgetSpendMsg, BudgetSpendsResponse, ConfigResponse,
+    Cw20HookMsg, DonorResponse, HandleMsg, InitMsg, MatchingProgram as MatchingProgramMsg,
+    MatchingProgramResponse, MigrateMsg, QueryMsg,
+};
 
-use cw20::Cw20HandleMsg;
+use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -33,8 +43,44 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
     match msg {
+        HandleMsg::Receive(msg) => receive_cw20(deps, env, msg),
         HandleMsg::UpdateConfig { spend_limit } => update_config(deps, env, spend_limit),
         HandleMsg::Spend { recipient, amount } => spend(deps, env, recipient, amount),
+        HandleMsg::UpdateMatchingProgram { program } => update_matching_program(deps, env, program),
+        HandleMsg::CreateBudget {
+            name,
+            manager,
+            cap,
+            expiry,
+        } => create_budget(deps, env, name, manager, cap, expiry),
+        HandleMsg::SpendBudget {
+            name,
+            recipient,
+            amount,
+        } => spend_budget(deps, env, name, recipient, amount),
+    }
+}
+
+pub fn receive_cw20<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    cw20_msg: Cw20ReceiveMsg,
+) -> HandleResult {
+    if let Some(msg) = cw20_msg.msg {
+        let config: Config = read_config(&deps.storage)?;
+
+        match from_binary(&msg)? {
+            Cw20HookMsg::Donate {} => {
+                // only anchor token contract can execute this message
+                if config.anchor_token != deps.api.canonical_address(&env.message.sender)? {
+                    return Err(StdError::unauthorized());
+                }
+
+                donate(deps, env, cw20_msg.sender, cw20_msg.amount)
+            }
+        }
+    } else {
+        Err(StdError::generic_err("data should be given"))
     }
 }
 
@@ -98,12 +144,259 @@ pub fn spend<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// Donate
+/// Donations stay in the fund unless a matching program runs; then the
+/// donation is forwarded to the program recipient together with the
+/// match from the fund, capped per epoch
+pub fn donate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    donor: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Donation amount must be positive"));
+    }
+
+    let config: Config = read_config(&deps.storage)?;
+    let mut state: MatchingState = read_matching_state(&deps.storage)?;
+    let donor_raw = deps.api.canonical_address(&donor)?;
+    let mut donor_info = read_donor_info(&deps.storage, &donor_raw)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut matched = Uint128::zero();
+    if let Some(program) = read_matching_program(&deps.storage)? {
+        advance_epoch(&mut state, &program, env.block.time);
+
+        let epoch_remaining = (program.epoch_cap - state.epoch_matched).unwrap_or_default();
+        matched = std::cmp::min(amount * program.ratio, epoch_remaining);
+        state.epoch_matched += matched;
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: deps.api.human_address(&program.recipient)?,
+                amount: amount + matched,
+            })?,
+        }));
+    }
+
+    state.total_donated += amount;
+    state.total_matched += matched;
+    donor_info.donated += amount;
+    donor_info.matched += matched;
+    store_matching_state(&mut deps.storage, &state)?;
+    store_donor_info(&mut deps.storage, &donor_raw, &donor_info)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "donate"),
+            log("donor", donor),
+            log("amount", amount),
+            log("matched_amount", matched),
+        ],
+        data: None,
+    })
+}
+
+/// UpdateMatchingProgram
+/// Gov can start, replace or stop the matching program;
+/// a new program starts its first epoch at the current block
+pub fn update_matching_program<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    program: Option<MatchingProgramMsg>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if config.gov_contract != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    let program = match program {
+        Some(program) => {
+            if program.epoch_period == 0 {
+                return Err(StdError::generic_err("Epoch period must be positive"));
+            }
+
+            Some(MatchingProgram {
+                recipient: deps.api.canonical_address(&program.recipient)?,
+                ratio: program.ratio,
+                epoch_cap: program.epoch_cap,
+                epoch_period: program.epoch_period,
+                start_time: env.block.time,
+            })
+        }
+        None => None,
+    };
+
+    let mut state: MatchingState = read_matching_state(&deps.storage)?;
+    state.epoch = 0;
+    state.epoch_matched = Uint128::zero();
+    store_matching_state(&mut deps.storage, &state)?;
+    store_matching_program(&mut deps.storage, &program)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "update_matching_program"),
+            log("active", program.is_some()),
+        ],
+        data: None,
+    })
+}
+
+/// CreateBudget
+/// Gov can allow a manager to spend up to the cap without a poll per
+/// payment; a budget name is used only once, so its spend history stays whole
+pub fn create_budget<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    name: String,
+    manager: HumanAddr,
+    cap: Uint128,
+    expiry: Expiration,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if config.gov_contract != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    if name.is_empty() {
+        return Err(StdError::generic_err("Budget name must not be empty"));
+    }
+
+    if read_budget(&deps.storage, &name)?.is_some() {
+        return Err(StdError::generic_err("Budget name already used"));
+    }
+
+    if cap.is_zero() {
+        return Err(StdError::generic_err("Budget cap must be positive"));
+    }
+
+    if expiry.is_expired(&env.block) {
+        return Err(StdError::generic_err("Budget expiry must be in the future"));
+    }
+
+    store_budget(
+        &mut deps.storage,
+        &name,
+        &Budget {
+            manager: deps.api.canonical_address(&manager)?,
+            cap,
+            spent: Uint128::zero(),
+            expiry,
+            spend_count: 0,
+        },
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "create_budget"),
+            log("name", name),
+            log("manager", manager),
+            log("cap", cap),
+            log("expiry", expiry),
+        ],
+        data: None,
+    })
+}
+
+/// SpendBudget
+/// The budget manager can send `amount` of ANC token to `recipient`
+/// while the budget is not expired and its cap is not exceeded
+pub fn spend_budget<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    name: String,
+    recipient: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let mut budget = match read_budget(&deps.storage, &name)? {
+        Some(budget) => budget,
+        None => return Err(StdError::generic_err("Budget does not exist")),
+    };
+
+    if budget.manager != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    // the unspent rest of an expired budget stays in the fund
+    if budget.expiry.is_expired(&env.block) {
+        return Err(StdError::generic_err("Budget expired"));
+    }
+
+    if config.spend_limit < amount {
+        return Err(StdError::generic_err("Cannot spend more than spend_limit"));
+    }
+
+    let spent = budget.spent + amount;
+    if spent > budget.cap {
+        return Err(StdError::generic_err(
+            "Cannot spend more than the budget cap",
+        ));
+    }
+
+    budget.spent = spent;
+    budget.spend_count += 1;
+    store_budget_spend(
+        &mut deps.storage,
+        &name,
+        budget.spend_count,
+        &BudgetSpend {
+            recipient: deps.api.canonical_address(&recipient)?,
+            amount,
+            height: env.block.height,
+        },
+    )?;
+    store_budget(&mut deps.storage, &name, &budget)?;
+
+    Ok(HandleResponse {
+        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: recipient.clone(),
+                amount,
+            })?,
+        })],
+        log: vec![
+            log("action", "spend_budget"),
+            log("name", name),
+            log("recipient", recipient),
+            log("amount", amount),
+        ],
+        data: None,
+    })
+}
+
+// Moves the matching state to the epoch of the given time,
+// resetting the matched amount when a new epoch began
+fn advance_epoch(state: &mut MatchingState, program: &MatchingProgram, time: u64) {
+    let epoch = (time - program.start_time) / program.epoch_period;
+    if epoch != state.epoch {
+        state.epoch = epoch;
+        state.epoch_matched = Uint128::zero();
+    }
+}
+
 pub fn query<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     msg: QueryMsg,
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::MatchingProgram {} => to_binary(&query_matching_program(deps)?),
+        QueryMsg::Donor { address } => to_binary(&query_donor(deps, address)?),
+        QueryMsg::Budget { name } => to_binary(&query_budget(deps, name)?),
+        QueryMsg::BudgetSpends {
+            name,
+            start_after,
+            limit,
+        } => to_binary(&query_budget_spends(deps, name, start_after, limit)?),
     }
 }
 
@@ -120,6 +413,87 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
     Ok(resp)
 }
 
+pub fn query_matching_program<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<MatchingProgramResponse> {
+    let state: MatchingState = read_matching_state(&deps.storage)?;
+    let program = match read_matching_program(&deps.storage)? {
+        Some(program) => Some(MatchingProgramMsg {
+            recipient: deps.api.human_address(&program.recipient)?,
+            ratio: program.ratio,
+            epoch_cap: program.epoch_cap,
+            epoch_period: program.epoch_period,
+        }),
+        None => None,
+    };
+
+    let epoch_remaining = match &program {
+        Some(program) => (program.epoch_cap - state.epoch_matched).unwrap_or_default(),
+        None => Uint128::zero(),
+    };
+
+    Ok(MatchingProgramResponse {
+        program,
+        epoch: state.epoch,
+        epoch_matched: state.epoch_matched,
+        epoch_remaining,
+        total_donated: state.total_donated,
+        total_matched: state.total_matched,
+    })
+}
+
+pub fn query_donor<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+) -> StdResult<DonorResponse> {
+    let donor_info = read_donor_info(&deps.storage, &deps.api.canonical_address(&address)?)?;
+
+    Ok(DonorResponse {
+        address,
+        donated: donor_info.donated,
+        matched: donor_info.matched,
+    })
+}
+
+pub fn query_budget<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    name: String,
+) -> StdResult<BudgetResponse> {
+    let budget = match read_budget(&deps.storage, &name)? {
+        Some(budget) => budget,
+        None => return Err(StdError::generic_err("Budget does not exist")),
+    };
+
+    Ok(BudgetResponse {
+        name,
+        manager: deps.api.human_address(&budget.manager)?,
+        cap: budget.cap,
+        spent: budget.spent,
+        expiry: budget.expiry,
+    })
+}
+
+pub fn query_budget_spends<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    name: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<BudgetSpendsResponse> {
+    let spends = read_budget_spends(&deps.storage, &name, start_after, limit)?
+        .into_iter()
+        .map(|(id, spend)| {
+            Ok(BudgetSpendMsg {
+                id,
+                recipient: deps.api.human_address(&spend.recipient)?,
+                amount: spend.amount,
+                height: spend.height,
+            })
+        })
+        .collect::<StdResult<Vec<BudgetSpendMsg>>>()?;
+
+    Ok(BudgetSpendsResponse { spends })
+}
+
 pub fn migrate<S: Storage, A: Api, Q: Querier>(
     _deps: &mut Extern<S, A, Q>,
     _env: Env,
@@ -1,10 +1,20 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
-use cosmwasm_storage::{singleton, singleton_read};
+use anchor_token::common::Expiration;
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, ReadonlyStorage, StdResult, Storage, Uint128};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket};
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_MATCHING_PROGRAM: &[u8] = b"matching_program";
+static KEY_MATCHING_STATE: &[u8] = b"matching_state";
+
+static PREFIX_DONOR: &[u8] = b"donor";
+static PREFIX_BUDGET: &[u8] = b"budget";
+static PREFIX_BUDGET_SPEND: &[u8] = b"budget_spend";
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -13,6 +23,45 @@ pub struct Config {
     pub spend_limit: Uint128,        // spend limit per each `spend` request
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MatchingProgram {
+    pub recipient: CanonicalAddr,
+    pub ratio: Decimal,
+    pub epoch_cap: Uint128,
+    pub epoch_period: u64,
+    pub start_time: u64, // epochs are counted from the program start
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MatchingState {
+    pub epoch: u64,
+    pub epoch_matched: Uint128,
+    pub total_donated: Uint128,
+    pub total_matched: Uint128,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DonorInfo {
+    pub donated: Uint128,
+    pub matched: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Budget {
+    pub manager: CanonicalAddr,
+    pub cap: Uint128,
+    pub spent: Uint128,
+    pub expiry: Expiration,
+    pub spend_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BudgetSpend {
+    pub recipient: CanonicalAddr,
+    pub amount: Uint128,
+    pub height: u64,
+}
+
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
     singleton(storage, KEY_CONFIG).save(config)
 }
@@ -20,3 +69,81 @@ pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<(
 pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+pub fn store_matching_program<S: Storage>(
+    storage: &mut S,
+    program: &Option<MatchingProgram>,
+) -> StdResult<()> {
+    singleton(storage, KEY_MATCHING_PROGRAM).save(program)
+}
+
+pub fn read_matching_program<S: Storage>(storage: &S) -> StdResult<Option<MatchingProgram>> {
+    Ok(singleton_read(storage, KEY_MATCHING_PROGRAM)
+        .may_load()?
+        .unwrap_or(None))
+}
+
+pub fn store_matching_state<S: Storage>(storage: &mut S, state: &MatchingState) -> StdResult<()> {
+    singleton(storage, KEY_MATCHING_STATE).save(state)
+}
+
+pub fn read_matching_state<S: Storage>(storage: &S) -> StdResult<MatchingState> {
+    Ok(singleton_read(storage, KEY_MATCHING_STATE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_donor_info<S: Storage>(
+    storage: &mut S,
+    donor: &CanonicalAddr,
+    donor_info: &DonorInfo,
+) -> StdResult<()> {
+    bucket(PREFIX_DONOR, storage).save(donor.as_slice(), donor_info)
+}
+
+pub fn read_donor_info<S: Storage>(storage: &S, donor: &CanonicalAddr) -> StdResult<DonorInfo> {
+    Ok(bucket_read(PREFIX_DONOR, storage)
+        .may_load(donor.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn store_budget<S: Storage>(storage: &mut S, name: &str, budget: &Budget) -> StdResult<()> {
+    bucket(PREFIX_BUDGET, storage).save(name.as_bytes(), budget)
+}
+
+pub fn read_budget<S: Storage>(storage: &S, name: &str) -> StdResult<Option<Budget>> {
+    bucket_read(PREFIX_BUDGET, storage).may_load(name.as_bytes())
+}
+
+pub fn store_budget_spend<S: Storage>(
+    storage: &mut S,
+    name: &str,
+    id: u64,
+    spend: &BudgetSpend,
+) -> StdResult<()> {
+    Bucket::multilevel(&[PREFIX_BUDGET_SPEND, name.as_bytes()], storage)
+        .save(&id.to_be_bytes(), spend)
+}
+
+pub fn read_budget_spends<S: ReadonlyStorage>(
+    storage: &S,
+    name: &str,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, BudgetSpend)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| (id + 1).to_be_bytes().to_vec());
+
+    let spends: ReadonlyBucket<S, BudgetSpend> =
+        ReadonlyBucket::multilevel(&[PREFIX_BUDGET_SPEND, name.as_bytes()], storage);
+    spends
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&k);
+            Ok((u64::from_be_bytes(id), v))
+        })
+        .collect()
+}
@@ -1,9 +1,15 @@
 use crate::contract::{handle, init, query};
 
-use anchor_token::community::{ConfigResponse, HandleMsg, InitMsg, QueryMsg};
+use anchor_token::common::Expiration;
+use anchor_token::community::{
+    BudgetResponse, BudgetSpend, BudgetSpendsResponse, ConfigResponse, Cw20HookMsg, DonorResponse,
+    HandleMsg, InitMsg, MatchingProgram, MatchingProgramResponse, QueryMsg,
+};
 use cosmwasm_std::testing::{mock_dependencies, mock_env};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, HumanAddr, StdError, Uint128, WasmMsg};
-use cw20::Cw20HandleMsg;
+use cosmwasm_std::{
+    from_binary, log, to_binary, CosmosMsg, Decimal, HumanAddr, StdError, Uint128, WasmMsg,
+};
+use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
 
 #[test]
 fn proper_initialization() {
@@ -135,3 +141,244 @@ fn test_spend() {
         })]
     );
 }
+
+#[test]
+fn test_matching_donations() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("anchor".to_string()),
+        spend_limit: Uint128::from(1000000u128),
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let donate_msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("donor0000"),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Donate {}).unwrap()),
+    });
+
+    // only the anchor token can be donated
+    let env = mock_env("other_token", &[]);
+    let res = handle(&mut deps, env, donate_msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // without a program the donation stays in the fund
+    let env = mock_env("anchor", &[]);
+    let res = handle(&mut deps, env, donate_msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let program = MatchingProgram {
+        recipient: HumanAddr::from("campaign0000"),
+        ratio: Decimal::percent(150),
+        epoch_cap: Uint128::from(200u128),
+        epoch_period: 100u64,
+    };
+    let msg = HandleMsg::UpdateMatchingProgram {
+        program: Some(program.clone()),
+    };
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env("gov", &[]);
+    env.block.time = 1000u64;
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let mut env = mock_env("anchor", &[]);
+    env.block.time = 1050u64;
+    let res = handle(&mut deps, env.clone(), donate_msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "donate"),
+            log("donor", "donor0000"),
+            log("amount", "100"),
+            log("matched_amount", "150"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor"),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("campaign0000"),
+                amount: Uint128::from(250u128),
+            })
+            .unwrap(),
+        })]
+    );
+
+    // the epoch cap limits the second match
+    let res = handle(&mut deps, env, donate_msg.clone()).unwrap();
+    assert_eq!(res.log[3], log("matched_amount", "50"));
+
+    let res: MatchingProgramResponse =
+        from_binary(&query(&deps, QueryMsg::MatchingProgram {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        MatchingProgramResponse {
+            program: Some(program),
+            epoch: 0u64,
+            epoch_matched: Uint128::from(200u128),
+            epoch_remaining: Uint128::zero(),
+            total_donated: Uint128::from(300u128),
+            total_matched: Uint128::from(200u128),
+        }
+    );
+
+    // the cap is renewed in the next epoch
+    let mut env = mock_env("anchor", &[]);
+    env.block.time = 1100u64;
+    let res = handle(&mut deps, env, donate_msg).unwrap();
+    assert_eq!(res.log[3], log("matched_amount", "150"));
+
+    let res: DonorResponse = from_binary(
+        &query(
+            &deps,
+            QueryMsg::Donor {
+                address: HumanAddr::from("donor0000"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        DonorResponse {
+            address: HumanAddr::from("donor0000"),
+            donated: Uint128::from(400u128),
+            matched: Uint128::from(350u128),
+        }
+    );
+}
+
+#[test]
+fn test_budget() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("anchor".to_string()),
+        spend_limit: Uint128::from(1000000u128),
+    };
+    let _res = init(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+
+    let msg = HandleMsg::CreateBudget {
+        name: "grants".to_string(),
+        manager: HumanAddr::from("manager0000"),
+        cap: Uint128::from(1500000u128),
+        expiry: Expiration::AtHeight(20000),
+    };
+    match handle(&mut deps, mock_env("manager0000", &[]), msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("gov", &[]), msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "create_budget"),
+            log("name", "grants"),
+            log("manager", "manager0000"),
+            log("cap", "1500000"),
+            log("expiry", "expiration height: 20000"),
+        ]
+    );
+
+    match handle(&mut deps, mock_env("gov", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Budget name already used"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let spend_msg = |amount: u128| HandleMsg::SpendBudget {
+        name: "grants".to_string(),
+        recipient: HumanAddr::from("addr0000"),
+        amount: Uint128::from(amount),
+    };
+    match handle(&mut deps, mock_env("gov", &[]), spend_msg(1000000)) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("manager0000", &[]), spend_msg(1000000)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor"),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("addr0000"),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        })]
+    );
+
+    match handle(&mut deps, mock_env("manager0000", &[]), spend_msg(600000)) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot spend more than the budget cap")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let _res = handle(&mut deps, mock_env("manager0000", &[]), spend_msg(400000)).unwrap();
+
+    let res = query(
+        &deps,
+        QueryMsg::Budget {
+            name: "grants".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<BudgetResponse>(&res).unwrap(),
+        BudgetResponse {
+            name: "grants".to_string(),
+            manager: HumanAddr::from("manager0000"),
+            cap: Uint128::from(1500000u128),
+            spent: Uint128::from(1400000u128),
+            expiry: Expiration::AtHeight(20000),
+        }
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::BudgetSpends {
+            name: "grants".to_string(),
+            start_after: Some(1),
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<BudgetSpendsResponse>(&res).unwrap(),
+        BudgetSpendsResponse {
+            spends: vec![BudgetSpend {
+                id: 2,
+                recipient: HumanAddr::from("addr0000"),
+                amount: Uint128::from(400000u128),
+                height: 12345,
+            }],
+        }
+    );
+
+    // the rest of the budget can't be spent once expired
+    let mut env = mock_env("manager0000", &[]);
+    env.block.height = 20000;
+    match handle(&mut deps, env, spend_msg(100000)) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Budget expired"),
+        _ => panic!("Must return generic error"),
+    }
+}
@@ -1,4 +1,7 @@
-use crate::state::{read_config, store_config, Config};
+use crate::state::{
+    read_config, read_emission_schedule, store_config, store_emission_schedule, Config, Emission,
+    EmissionSchedule,
+};
 
 use cosmwasm_std::{
     log, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Env, Extern, HandleResponse,
@@ -6,7 +9,10 @@ use cosmwasm_std::{
     StdResult, Storage, Uint128, WasmMsg,
 };
 
-use anchor_token::distributor::{ConfigResponse, HandleMsg, InitMsg, MigrateMsg, QueryMsg};
+use anchor_token::distributor::{
+    ConfigResponse, Emission as EmissionMsg, EmissionSchedule as EmissionScheduleMsg,
+    EmissionsResponse, HandleMsg, InitMsg, MigrateMsg, QueryMsg,
+};
 
 use cw20::Cw20HandleMsg;
 
@@ -44,6 +50,8 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
         HandleMsg::Spend { recipient, amount } => spend(deps, env, recipient, amount),
         HandleMsg::AddDistributor { distributor } => add_distributor(deps, env, distributor),
         HandleMsg::RemoveDistributor { distributor } => remove_distributor(deps, env, distributor),
+        HandleMsg::UpdateEmissions { schedule } => update_emissions(deps, env, schedule),
+        HandleMsg::Distribute {} => distribute(deps, env),
     }
 }
 
@@ -183,12 +191,200 @@ pub fn spend<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// UpdateEmissions
+/// Gov can start, replace or stop the emissions paid by Distribute
+pub fn update_emissions<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    schedule: Option<EmissionScheduleMsg>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if config.gov_contract != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    let schedule = match schedule {
+        Some(schedule) => {
+            if schedule.epoch_period == 0 {
+                return Err(StdError::generic_err("Epoch period must be positive"));
+            }
+
+            Some(EmissionSchedule {
+                epoch_period: schedule.epoch_period,
+                emissions: schedule
+                    .emissions
+                    .into_iter()
+                    .map(|emission| {
+                        Ok(Emission {
+                            recipient: deps.api.canonical_address(&emission.recipient)?,
+                            amount: emission.amount,
+                            hook_msg: emission.hook_msg,
+                        })
+                    })
+                    .collect::<StdResult<Vec<Emission>>>()?,
+                start_time: env.block.time,
+                distributed_epochs: 0,
+            })
+        }
+        None => None,
+    };
+
+    if let Some(schedule) = &schedule {
+        if epoch_amount(&schedule.emissions)? > config.spend_limit {
+            return Err(StdError::generic_err(
+                "Epoch emissions exceed the spend limit",
+            ));
+        }
+    }
+
+    // the ended epochs of the replaced schedule are paid before the swap
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut pending_epochs = 0u64;
+    if let Some(old_schedule) = read_emission_schedule(&deps.storage)? {
+        let ended_epochs = (env.block.time - old_schedule.start_time) / old_schedule.epoch_period;
+        pending_epochs = ended_epochs - old_schedule.distributed_epochs;
+        if pending_epochs > 0 {
+            let pending_amount = epoch_amount(&old_schedule.emissions)?
+                .u128()
+                .checked_mul(pending_epochs as u128);
+            if pending_amount.map_or(true, |amount| amount > config.spend_limit.u128()) {
+                return Err(StdError::generic_err(
+                    "Distribute the ended epochs before updating the emissions",
+                ));
+            }
+
+            messages = emission_messages(deps, &config, &old_schedule, pending_epochs)?.0;
+        }
+    }
+
+    store_emission_schedule(&mut deps.storage, &schedule)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "update_emissions"),
+            log("active", schedule.is_some()),
+            log("distributed_epochs", pending_epochs),
+        ],
+        data: None,
+    })
+}
+
+/// Distribute
+/// Anyone can push the emissions of the ended epochs to the recipients;
+/// epochs missed by the crank are paid by the next distributions, as many
+/// per call as the spend limit allows
+pub fn distribute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let mut schedule = match read_emission_schedule(&deps.storage)? {
+        Some(schedule) => schedule,
+        None => return Err(StdError::generic_err("No emission schedule")),
+    };
+
+    let ended_epochs = (env.block.time - schedule.start_time) / schedule.epoch_period;
+    let mut epochs = ended_epochs - schedule.distributed_epochs;
+    if epochs == 0 {
+        return Err(StdError::generic_err(
+            "Nothing to distribute until the epoch ends",
+        ));
+    }
+
+    // the epochs over the spend limit are left for the next distribution
+    let epoch_amount = epoch_amount(&schedule.emissions)?;
+    if !epoch_amount.is_zero() {
+        let max_epochs = config.spend_limit.u128() / epoch_amount.u128();
+        if max_epochs == 0 {
+            return Err(StdError::generic_err(
+                "Epoch emissions exceed the spend limit",
+            ));
+        }
+
+        if (epochs as u128) > max_epochs {
+            epochs = max_epochs as u64;
+        }
+    }
+
+    let (messages, total_amount) = emission_messages(deps, &config, &schedule, epochs)?;
+
+    schedule.distributed_epochs += epochs;
+    store_emission_schedule(&mut deps.storage, &Some(schedule))?;
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "distribute"),
+            log("epochs", epochs),
+            log("amount", total_amount),
+        ],
+        data: None,
+    })
+}
+
+// transfers paying the emissions of `epochs` epochs of the schedule
+fn emission_messages<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    schedule: &EmissionSchedule,
+    epochs: u64,
+) -> StdResult<(Vec<CosmosMsg>, Uint128)> {
+    let anchor_token = deps.api.human_address(&config.anchor_token)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut total_amount = Uint128::zero();
+    for emission in schedule.emissions.iter() {
+        let amount = Uint128(
+            emission
+                .amount
+                .u128()
+                .checked_mul(epochs as u128)
+                .ok_or_else(|| StdError::generic_err("Emission amount overflow"))?,
+        );
+        if amount.is_zero() {
+            continue;
+        }
+
+        let recipient = deps.api.human_address(&emission.recipient)?;
+        let msg = match &emission.hook_msg {
+            Some(hook_msg) => Cw20HandleMsg::Send {
+                contract: recipient,
+                amount,
+                msg: Some(hook_msg.clone()),
+            },
+            None => Cw20HandleMsg::Transfer { recipient, amount },
+        };
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: anchor_token.clone(),
+            send: vec![],
+            msg: to_binary(&msg)?,
+        }));
+        total_amount += amount;
+    }
+
+    Ok((messages, total_amount))
+}
+
+// sum of the emissions paid for a single epoch
+fn epoch_amount(emissions: &[Emission]) -> StdResult<Uint128> {
+    emissions
+        .iter()
+        .try_fold(Uint128::zero(), |total, emission| {
+            total
+                .u128()
+                .checked_add(emission.amount.u128())
+                .map(Uint128)
+                .ok_or_else(|| StdError::generic_err("Emission amount overflow"))
+        })
+}
+
 pub fn query<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     msg: QueryMsg,
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Emissions {} => to_binary(&query_emissions(deps)?),
     }
 }
 
@@ -210,6 +406,42 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
     Ok(resp)
 }
 
+pub fn query_emissions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<EmissionsResponse> {
+    let schedule = match read_emission_schedule(&deps.storage)? {
+        Some(schedule) => schedule,
+        None => {
+            return Ok(EmissionsResponse {
+                schedule: None,
+                distributed_epochs: 0,
+                next_distribution_time: None,
+            })
+        }
+    };
+
+    Ok(EmissionsResponse {
+        schedule: Some(EmissionScheduleMsg {
+            epoch_period: schedule.epoch_period,
+            emissions: schedule
+                .emissions
+                .iter()
+                .map(|emission| {
+                    Ok(EmissionMsg {
+                        recipient: deps.api.human_address(&emission.recipient)?,
+                        amount: emission.amount,
+                        hook_msg: emission.hook_msg.clone(),
+                    })
+                })
+                .collect::<StdResult<Vec<EmissionMsg>>>()?,
+        }),
+        distributed_epochs: schedule.distributed_epochs,
+        next_distribution_time: Some(
+            schedule.start_time + (schedule.distributed_epochs + 1) * schedule.epoch_period,
+        ),
+    })
+}
+
 pub fn migrate<S: Storage, A: Api, Q: Querier>(
     _deps: &mut Extern<S, A, Q>,
     _env: Env,
@@ -1,10 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
+use cosmwasm_std::{Binary, CanonicalAddr, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read};
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_EMISSION_SCHEDULE: &[u8] = b"emission_schedule";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -14,6 +15,21 @@ pub struct Config {
     pub spend_limit: Uint128,          // spend limit per each `spend` request
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionSchedule {
+    pub epoch_period: u64,
+    pub emissions: Vec<Emission>,
+    pub start_time: u64,         // epochs are counted from the schedule start
+    pub distributed_epochs: u64, // epochs paid so far
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Emission {
+    pub recipient: CanonicalAddr,
+    pub amount: Uint128,
+    pub hook_msg: Option<Binary>,
+}
+
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
     singleton(storage, KEY_CONFIG).save(config)
 }
@@ -21,3 +37,16 @@ pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<(
 pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+pub fn store_emission_schedule<S: Storage>(
+    storage: &mut S,
+    schedule: &Option<EmissionSchedule>,
+) -> StdResult<()> {
+    singleton(storage, KEY_EMISSION_SCHEDULE).save(schedule)
+}
+
+pub fn read_emission_schedule<S: Storage>(storage: &S) -> StdResult<Option<EmissionSchedule>> {
+    Ok(singleton_read(storage, KEY_EMISSION_SCHEDULE)
+        .may_load()?
+        .unwrap_or(None))
+}
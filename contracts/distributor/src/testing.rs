@@ -1,8 +1,12 @@
 use crate::contract::{handle, init, query};
 
-use anchor_token::distributor::{ConfigResponse, HandleMsg, InitMsg, QueryMsg};
+use anchor_token::distributor::{
+    ConfigResponse, Emission, EmissionSchedule, EmissionsResponse, HandleMsg, InitMsg, QueryMsg,
+};
 use cosmwasm_std::testing::{mock_dependencies, mock_env};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, HumanAddr, StdError, Uint128, WasmMsg};
+use cosmwasm_std::{
+    from_binary, log, to_binary, CosmosMsg, HumanAddr, StdError, Uint128, WasmMsg,
+};
 use cw20::Cw20HandleMsg;
 
 #[test]
@@ -261,3 +265,265 @@ fn test_spend() {
         })]
     );
 }
+
+#[test]
+fn test_distribute() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("anchor".to_string()),
+        whitelist: vec![],
+        spend_limit: Uint128::from(1000000u128),
+    };
+    let _res = init(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+
+    let schedule = EmissionSchedule {
+        epoch_period: 100,
+        emissions: vec![
+            Emission {
+                recipient: HumanAddr::from("staking0000"),
+                amount: Uint128::from(1000u128),
+                hook_msg: Some(to_binary(&"deposit_reward").unwrap()),
+            },
+            Emission {
+                recipient: HumanAddr::from("addr0000"),
+                amount: Uint128::from(500u128),
+                hook_msg: None,
+            },
+        ],
+    };
+    let msg = HandleMsg::UpdateEmissions {
+        schedule: Some(schedule.clone()),
+    };
+    match handle(&mut deps, mock_env("addr0000", &[]), msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+    let _res = handle(&mut deps, mock_env("gov", &[]), msg).unwrap();
+
+    let mut env = mock_env("addr0001", &[]);
+    env.block.time += 99;
+    match handle(&mut deps, env.clone(), HandleMsg::Distribute {}) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Nothing to distribute until the epoch ends")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // the epochs missed by the crank are paid together
+    env.block.time += 101;
+    let res = handle(&mut deps, env.clone(), HandleMsg::Distribute {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("anchor"),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Send {
+                    contract: HumanAddr::from("staking0000"),
+                    amount: Uint128::from(2000u128),
+                    msg: Some(to_binary(&"deposit_reward").unwrap()),
+                })
+                .unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("anchor"),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("addr0000"),
+                    amount: Uint128::from(1000u128),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+
+    match handle(&mut deps, env.clone(), HandleMsg::Distribute {}) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Nothing to distribute until the epoch ends")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = query(&deps, QueryMsg::Emissions {}).unwrap();
+    assert_eq!(
+        from_binary::<EmissionsResponse>(&res).unwrap(),
+        EmissionsResponse {
+            schedule: Some(schedule),
+            distributed_epochs: 2,
+            next_distribution_time: Some(mock_env("addr0000", &[]).block.time + 300),
+        }
+    );
+
+    // a distribution pays at most the spend limit, the rest is carried forward
+    let msg = HandleMsg::UpdateConfig {
+        spend_limit: Some(Uint128::from(3000u128)),
+    };
+    let _res = handle(&mut deps, mock_env("gov", &[]), msg).unwrap();
+
+    env.block.time += 300;
+    for (epochs, staking_amount, addr_amount) in
+        [(2u64, 2000u128, 1000u128), (1u64, 1000u128, 500u128)].iter()
+    {
+        let res = handle(&mut deps, env.clone(), HandleMsg::Distribute {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: HumanAddr::from("anchor"),
+                    send: vec![],
+                    msg: to_binary(&Cw20HandleMsg::Send {
+                        contract: HumanAddr::from("staking0000"),
+                        amount: Uint128::from(*staking_amount),
+                        msg: Some(to_binary(&"deposit_reward").unwrap()),
+                    })
+                    .unwrap(),
+                }),
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: HumanAddr::from("anchor"),
+                    send: vec![],
+                    msg: to_binary(&Cw20HandleMsg::Transfer {
+                        recipient: HumanAddr::from("addr0000"),
+                        amount: Uint128::from(*addr_amount),
+                    })
+                    .unwrap(),
+                }),
+            ]
+        );
+        assert_eq!(
+            res.log,
+            vec![
+                log("action", "distribute"),
+                log("epochs", epochs),
+                log("amount", staking_amount + addr_amount),
+            ]
+        );
+    }
+
+    match handle(&mut deps, env, HandleMsg::Distribute {}) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Nothing to distribute until the epoch ends")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // an epoch cannot pay more than the spend limit
+    let msg = HandleMsg::UpdateEmissions {
+        schedule: Some(EmissionSchedule {
+            epoch_period: 100,
+            emissions: vec![Emission {
+                recipient: HumanAddr::from("addr0000"),
+                amount: Uint128::from(3001u128),
+                hook_msg: None,
+            }],
+        }),
+    };
+    match handle(&mut deps, mock_env("gov", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Epoch emissions exceed the spend limit")
+        }
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_update_emissions_with_backlog() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        gov_contract: HumanAddr("gov".to_string()),
+        anchor_token: HumanAddr("anchor".to_string()),
+        whitelist: vec![],
+        spend_limit: Uint128::from(1000u128),
+    };
+    let _res = init(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+
+    let schedule = EmissionSchedule {
+        epoch_period: 100,
+        emissions: vec![Emission {
+            recipient: HumanAddr::from("addr0000"),
+            amount: Uint128::from(300u128),
+            hook_msg: None,
+        }],
+    };
+    let msg = HandleMsg::UpdateEmissions {
+        schedule: Some(schedule),
+    };
+    let _res = handle(&mut deps, mock_env("gov", &[]), msg).unwrap();
+
+    let new_schedule = EmissionSchedule {
+        epoch_period: 50,
+        emissions: vec![Emission {
+            recipient: HumanAddr::from("addr0001"),
+            amount: Uint128::from(100u128),
+            hook_msg: None,
+        }],
+    };
+    let msg = HandleMsg::UpdateEmissions {
+        schedule: Some(new_schedule.clone()),
+    };
+
+    // the ended epochs over the spend limit must be distributed first
+    let mut env = mock_env("gov", &[]);
+    env.block.time += 400;
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Distribute the ended epochs before updating the emissions"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    let _res = handle(&mut deps, env.clone(), HandleMsg::Distribute {}).unwrap();
+
+    // the ended epochs of the replaced schedule are paid with the swap
+    env.block.time += 250;
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor"),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("addr0000"),
+                amount: Uint128::from(900u128),
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "update_emissions"),
+            log("active", true),
+            log("distributed_epochs", 3),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Emissions {}).unwrap();
+    assert_eq!(
+        from_binary::<EmissionsResponse>(&res).unwrap(),
+        EmissionsResponse {
+            schedule: Some(new_schedule),
+            distributed_epochs: 0,
+            next_distribution_time: Some(env.block.time + 50),
+        }
+    );
+
+    // stopping the emissions pays the ended epochs as well
+    env.block.time += 120;
+    let res = handle(&mut deps, env, HandleMsg::UpdateEmissions { schedule: None }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor"),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("addr0001"),
+                amount: Uint128::from(200u128),
+            })
+            .unwrap(),
+        })]
+    );
+}
@@ -1,24 +1,59 @@
-use crate::querier::load_token_balance;
-use crate::staking::{query_staker, stake_voting_tokens, withdraw_voting_tokens};
+use crate::deposit::{
+    hold_execution_bounty, hold_partial_refund, load_locked_deposit, lock_deposit,
+    lock_vote_reward, read_deposit_ledger, refund_destination, release_execution_bounty,
+    release_vote_reward, settle_deposit, take_partial_refund,
+};
+use crate::emergency::{
+    approve_emergency_action, assert_not_emergency_paused, emergency_council_normal,
+    emergency_council_raw, query_emergency_action, query_emergency_actions,
+    submit_emergency_action,
+};
+use crate::querier::{load_token_balance, load_token_info};
+use crate::quorum::{
+    adaptive_quorum_value, effective_quorum, record_turnout, validate_adaptive_quorum,
+};
+use crate::staking::{
+    import_stake, lock_deposit_share, migrate_stake, prune_locked_balance,
+    query_migration_snapshot, query_share_accounting, query_staker, query_staking_snapshot,
+    query_withdrawable, release_deposit_share, stake_deposit_refund, stake_poll_deposit,
+    stake_vesting_tokens, stake_voting_tokens, unlock_vesting_tokens, unstake_poll_deposit,
+    withdraw_voting_tokens,
+};
 use crate::state::{
-    bank_read, bank_store, config_read, config_store, poll_indexer_store, poll_read, poll_store,
-    poll_voter_read, poll_voter_store, read_poll_voters, read_polls, state_read, state_store,
-    Config, ExecuteData, Poll, State,
+    bank_read, config_read, config_store, deposit_ledger_store, locked_vote_store, migration_read,
+    operator_read, operator_store, page_limit, param_read, param_store, poll_commit_read,
+    poll_commit_store, poll_event_read, poll_event_store, poll_indexer_store, poll_read,
+    poll_reservation_read, poll_reservation_store, poll_store, poll_voter_read, poll_voter_store,
+    read_bank_raw, read_bank_records, read_config_history, read_params, read_poll_raw,
+    read_poll_records, read_poll_voters, read_polls, read_prunable_polls, read_tally_checkpoints,
+    read_voter_records, remove_poll_data, scheduled_config_update_read,
+    scheduled_config_update_store, sponsor_lock_store, state_read, state_store,
+    store_config_history, store_poll_event, tally_checkpoint_store, voted_read, voted_store,
+    Config, ExecuteData, Poll, PollEscrow, PollReservation, ScheduledConfigUpdate, State,
 };
+use crate::tally::{compute_voters_root, encode_tally};
 
 use cosmwasm_std::{
     from_binary, log, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Decimal, Env, Extern,
-    HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, Querier, StdError,
-    StdResult, Storage, Uint128, WasmMsg,
+    HandleResponse, HandleResult, HumanAddr, InitResponse, InitResult, LogAttribute, Querier,
+    StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
+use sha3::Digest;
 
-use anchor_token::common::OrderBy;
+use anchor_token::common::{Expiration, OrderBy};
 use anchor_token::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, HandleMsg, InitMsg, PollResponse, PollStatus,
-    PollsResponse, QueryMsg, StateResponse, VoteOption, VoterInfo, VotersResponse,
+    ConfigChange, ConfigHistoryResponse, ConfigHistoryResponseItem, ConfigResponse, ConfigUpdate,
+    Cw20HookMsg, DepositLedgerResponse, DepositStatus, ExecuteMsg, ExportRecord, ExportResponse,
+    HandleMsg, InitMsg, OperatorResponse, OverviewResponse, ParamResponse, ParamValue,
+    ParamsResponse, PollDepositResponse, PollEventKind, PollEventsResponse, PollLink, PollResponse,
+    PollStatus, PollTallyResponse, PollsByIdsResponse, PollsResponse, QueryMsg, QuorumPreset,
+    RawStorageResponse, Referendum, RejectedReason, ScheduledConfigUpdateResponse,
+    SimulateExecutionResponse, StateResponse, StatsResponse, TallyCheckpoint,
+    TallyCheckpointsResponse, TokenInfoCache, VoteOption, VoterInfo, VotersResponse,
     VotersResponseItem,
 };
+use anchor_token::math::mul_ratio;
 
 const MIN_TITLE_LENGTH: usize = 4;
 const MAX_TITLE_LENGTH: usize = 64;
@@ -26,6 +61,31 @@ const MIN_DESC_LENGTH: usize = 4;
 const MAX_DESC_LENGTH: usize = 1024;
 const MIN_LINK_LENGTH: usize = 12;
 const MAX_LINK_LENGTH: usize = 128;
+const MAX_LINKS: usize = 4;
+const CONTENT_HASH_LENGTH: usize = 32;
+
+const MAX_PARAM_KEY_LENGTH: usize = 64;
+const DEFAULT_PRUNE_LIMIT: u32 = 30;
+const MAX_PRUNE_LIMIT: u32 = 100;
+const MAX_POLL_IDS: usize = 30;
+const MIN_OPTION_LABELS: usize = 2;
+const MAX_OPTION_LABELS: usize = 8;
+const MAX_LABEL_LENGTH: usize = 32;
+const POLL_RESERVATION_PERIOD: u64 = 1000;
+
+// placeholders of execute msgs resolved at execution time
+const PLACEHOLDER_POLL_ID: &str = "${poll_id}";
+const PLACEHOLDER_CONTRACT_BALANCE: &str = "${contract_balance}";
+
+// hook names accepted by receive_cw20, reported when a hook msg can't be decoded
+const CW20_HOOK_MSGS: [&str; 6] = [
+    "stake_voting_tokens",
+    "stake_vesting_tokens",
+    "stake_voting_tokens_for",
+    "create_poll",
+    "stake_and_vote",
+    "fund_poll_escrow",
+];
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -34,6 +94,9 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
 ) -> InitResult {
     validate_quorum(msg.quorum)?;
     validate_threshold(msg.threshold)?;
+    validate_veto_threshold(msg.veto_threshold)?;
+    validate_partial_refund_ratio(msg.partial_refund_ratio)?;
+    validate_quorum_presets(&msg.quorum_presets)?;
 
     let config = Config {
         anchor_token: CanonicalAddr::default(),
@@ -45,6 +108,41 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         expiration_period: msg.expiration_period,
         proposal_deposit: msg.proposal_deposit,
         snapshot_period: msg.snapshot_period,
+        vesting_contract: None,
+        reveal_period: msg.reveal_period,
+        min_vote_amount: msg.min_vote_amount,
+        quorum_excluded_addresses: msg
+            .quorum_excluded_addresses
+            .iter()
+            .map(|addr| deps.api.canonical_address(addr))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?,
+        tally_checkpoint_interval: msg.tally_checkpoint_interval,
+        poll_retention_period: msg.poll_retention_period,
+        max_poll_extension: msg.max_poll_extension,
+        narrow_pass_margin: msg.narrow_pass_margin,
+        community_contract: None,
+        adaptive_quorum: match msg.adaptive_quorum {
+            Some(adaptive_quorum) => validate_adaptive_quorum(adaptive_quorum)?,
+            None => None,
+        },
+        poll_sponsorship: msg
+            .poll_sponsorship
+            .filter(|sponsorship| sponsorship.required_sponsors > 0),
+        executor_whitelist: msg
+            .executor_whitelist
+            .iter()
+            .map(|addr| deps.api.canonical_address(addr))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?,
+        partial_refund_ratio: msg.partial_refund_ratio,
+        veto_threshold: msg.veto_threshold,
+        emergency_council: match msg.emergency_council {
+            Some(emergency_council) => emergency_council_raw(&deps.api, emergency_council)?,
+            None => None,
+        },
+        max_active_votes: msg.max_active_votes,
+        reject_same_block_votes: msg.reject_same_block_votes,
+        quorum_presets: msg.quorum_presets,
+        token_info: None,
     };
 
     let state = State {
@@ -52,6 +150,18 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         poll_count: 0,
         total_share: Uint128::zero(),
         total_deposit: Uint128::zero(),
+        total_votes: 0,
+        unique_voters: 0,
+        polls_passed: 0,
+        polls_rejected: 0,
+        polls_executed: 0,
+        deposit_stake_share: Uint128::zero(),
+        turnout_ema: None,
+        migration_in_progress: false,
+        unclaimed_refund: Uint128::zero(),
+        emergency_paused: false,
+        vote_reward_pool: Uint128::zero(),
+        execution_bounty_pool: Uint128::zero(),
     };
 
     config_store(&mut deps.storage).save(&config)?;
@@ -65,6 +175,44 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     env: Env,
     msg: HandleMsg,
 ) -> StdResult<HandleResponse> {
+    // the contract is frozen once the stake is migrated to a successor, except for
+    // the vesting contract unlocking the claims it staked and the payouts of the
+    // partial refunds, vote rewards and execution bounties kept in the contract
+    if migration_read(&deps.storage).may_load()?.is_some()
+        && !matches!(
+            msg,
+            HandleMsg::UnlockVestingTokens { .. }
+                | HandleMsg::ClaimDepositRefund { .. }
+                | HandleMsg::ClaimVoteReward { .. }
+                | HandleMsg::ExecutePoll { .. }
+                | HandleMsg::ExpirePoll { .. }
+        )
+    {
+        return Err(StdError::generic_err("Stake has been migrated"));
+    }
+
+    apply_scheduled_config_update(deps, &env)?;
+
+    match &msg {
+        HandleMsg::WithdrawVotingTokens { .. }
+        | HandleMsg::CastVote { .. }
+        | HandleMsg::CommitVote { .. }
+        | HandleMsg::RevealVote { .. }
+        | HandleMsg::CastReferendumVote { .. } => assert_no_migration_in_progress(&deps.storage)?,
+        _ => {}
+    }
+
+    match &msg {
+        HandleMsg::CastVote { .. }
+        | HandleMsg::CommitVote { .. }
+        | HandleMsg::RevealVote { .. }
+        | HandleMsg::CastReferendumVote { .. }
+        | HandleMsg::CreatePollWithStakedDeposit { .. } => {
+            assert_not_emergency_paused(&deps.storage)?
+        }
+        _ => {}
+    }
+
     match msg {
         HandleMsg::Receive(msg) => receive_cw20(deps, env, msg),
         HandleMsg::RegisterContracts { anchor_token } => register_contracts(deps, anchor_token),
@@ -77,28 +225,153 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
             expiration_period,
             proposal_deposit,
             snapshot_period,
+            vesting_contract,
+            reveal_period,
+            min_vote_amount,
+            quorum_excluded_addresses,
+            tally_checkpoint_interval,
+            poll_retention_period,
+            max_poll_extension,
+            narrow_pass_margin,
+            community_contract,
+            adaptive_quorum,
+            poll_sponsorship,
+            executor_whitelist,
+            partial_refund_ratio,
+            veto_threshold,
+            emergency_council,
+            max_active_votes,
+            reject_same_block_votes,
+            quorum_presets,
         } => update_config(
             deps,
             env,
-            owner,
-            quorum,
-            threshold,
-            voting_period,
-            timelock_period,
-            expiration_period,
-            proposal_deposit,
-            snapshot_period,
+            ConfigUpdate {
+                owner,
+                quorum,
+                threshold,
+                voting_period,
+                timelock_period,
+                expiration_period,
+                proposal_deposit,
+                snapshot_period,
+                vesting_contract,
+                reveal_period,
+                min_vote_amount,
+                quorum_excluded_addresses,
+                tally_checkpoint_interval,
+                poll_retention_period,
+                max_poll_extension,
+                narrow_pass_margin,
+                community_contract,
+                adaptive_quorum,
+                poll_sponsorship,
+                executor_whitelist,
+                partial_refund_ratio,
+                veto_threshold,
+                emergency_council,
+                max_active_votes,
+                reject_same_block_votes,
+                quorum_presets,
+            },
         ),
+        HandleMsg::ScheduleConfigUpdate {
+            changes,
+            activation_height,
+        } => schedule_config_update(deps, env, changes, activation_height),
+        HandleMsg::ExtendPoll {
+            poll_id,
+            extra_blocks,
+        } => extend_poll(deps, env, poll_id, extra_blocks),
+        HandleMsg::SponsorPoll { poll_id } => sponsor_poll(deps, env, poll_id),
         HandleMsg::WithdrawVotingTokens { amount } => withdraw_voting_tokens(deps, env, amount),
         HandleMsg::CastVote {
             poll_id,
             vote,
             amount,
-        } => cast_vote(deps, env, poll_id, vote, amount),
+            voter,
+        } => match voter {
+            Some(voter) if voter != env.message.sender => {
+                cast_operator_vote(deps, env, voter, poll_id, vote, amount)
+            }
+            _ => {
+                let voter = env.message.sender.clone();
+                cast_vote(deps, env, voter, poll_id, vote, amount)
+            }
+        },
+        HandleMsg::ApproveOperator { operator, expires } => {
+            approve_operator(deps, env, operator, expires)
+        }
+        HandleMsg::RevokeOperator { operator } => revoke_operator(deps, env, operator),
+        HandleMsg::CommitVote { poll_id, hash } => commit_vote(deps, env, poll_id, hash),
+        HandleMsg::RevealVote {
+            poll_id,
+            vote,
+            amount,
+            salt,
+        } => reveal_vote(deps, env, poll_id, vote, amount, salt),
+        HandleMsg::CastReferendumVote {
+            poll_id,
+            vote,
+            balance,
+            proof,
+        } => cast_referendum_vote(deps, env, poll_id, vote, balance, proof),
         HandleMsg::EndPoll { poll_id } => end_poll(deps, env, poll_id),
         HandleMsg::ExecutePoll { poll_id } => execute_poll(deps, env, poll_id),
         HandleMsg::ExpirePoll { poll_id } => expire_poll(deps, env, poll_id),
+        HandleMsg::ExecuteRejectedPoll { poll_id } => execute_rejected_poll(deps, env, poll_id),
         HandleMsg::SnapshotPoll { poll_id } => snapshot_poll(deps, env, poll_id),
+        HandleMsg::PrunePolls {
+            before_poll_id,
+            limit,
+        } => prune_polls(deps, env, before_poll_id, limit),
+        HandleMsg::CreatePollWithStakedDeposit {
+            title,
+            description,
+            links,
+            execute_msgs,
+            commit_reveal,
+            content_hash,
+            referendum,
+            reserved_poll_id,
+            option_labels,
+        } => create_poll_with_staked_deposit(
+            deps,
+            env,
+            title,
+            description,
+            links,
+            execute_msgs,
+            commit_reveal,
+            content_hash,
+            referendum,
+            reserved_poll_id,
+            option_labels,
+        ),
+        HandleMsg::ReservePollId {} => reserve_poll_id(deps, env),
+        HandleMsg::MigrateStake {
+            new_gov_contract,
+            limit,
+        } => migrate_stake(deps, env, new_gov_contract, limit),
+        HandleMsg::ImportStake {
+            old_gov_contract,
+            limit,
+        } => import_stake(deps, env, old_gov_contract, limit),
+        HandleMsg::UnlockVestingTokens { staker, amount } => {
+            unlock_vesting_tokens(deps, env, staker, amount)
+        }
+        HandleMsg::SetParam { key, value } => set_param(deps, env, key, value),
+        HandleMsg::SetMigrationInProgress {
+            migration_in_progress,
+        } => set_migration_in_progress(deps, env, migration_in_progress),
+        HandleMsg::ClaimDepositRefund { poll_id } => claim_deposit_refund(deps, env, poll_id),
+        HandleMsg::ClaimVoteReward { poll_id } => claim_vote_reward(deps, env, poll_id),
+        HandleMsg::RefreshTokenInfo {} => refresh_token_info(deps, env),
+        HandleMsg::ReconcileTotalDeposit {} => reconcile_total_deposit(deps, env),
+        HandleMsg::SubmitEmergencyAction { action } => submit_emergency_action(deps, env, action),
+        HandleMsg::ApproveEmergencyAction { action_id } => {
+            approve_emergency_action(deps, env, action_id)
+        }
     }
 }
 
@@ -117,6 +390,70 @@ pub fn register_contracts<S: Storage, A: Api, Q: Querier>(
     Ok(HandleResponse::default())
 }
 
+/// RefreshTokenInfo stores the current metadata of the ANC token in the config
+pub fn refresh_token_info<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let mut config: Config = config_read(&deps.storage).load()?;
+    if config.owner != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    let token_info = load_token_info(&deps, &deps.api.human_address(&config.anchor_token)?)?;
+    let token_info = TokenInfoCache {
+        name: token_info.name,
+        symbol: token_info.symbol,
+        decimals: token_info.decimals,
+        refreshed_height: env.block.height,
+    };
+
+    let logs = vec![
+        log("action", "refresh_token_info"),
+        log("symbol", &token_info.symbol),
+        log("decimals", token_info.decimals),
+    ];
+
+    config.token_info = Some(token_info);
+    config_store(&mut deps.storage).save(&config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    })
+}
+
+/// ReconcileTotalDeposit resets the total deposit to the deposits locked by the
+/// polls, correcting a drift without scanning the polls on every transition
+pub fn reconcile_total_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    if config.owner != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut state: State = state_read(&deps.storage).load()?;
+    let locked_deposit = load_locked_deposit(&deps.storage, &state)?;
+
+    let logs = vec![
+        log("action", "reconcile_total_deposit"),
+        log("old_total_deposit", state.total_deposit),
+        log("total_deposit", locked_deposit),
+    ];
+
+    state.total_deposit = locked_deposit;
+    state_store(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    })
+}
+
 pub fn receive_cw20<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
@@ -125,89 +462,562 @@ pub fn receive_cw20<S: Storage, A: Api, Q: Querier>(
     // only asset contract can execute this message
     let config: Config = config_read(&deps.storage).load()?;
     if config.anchor_token != deps.api.canonical_address(&env.message.sender)? {
-        return Err(StdError::unauthorized());
+        // a poll escrow is funded in its own token
+        if let Some(Ok(Cw20HookMsg::FundPollEscrow { poll_id })) =
+            cw20_msg.msg.as_ref().map(from_binary)
+        {
+            return fund_poll_escrow(deps, env, cw20_msg.sender, cw20_msg.amount, poll_id);
+        }
+
+        return Err(StdError::generic_err(format!(
+            "Hook sender {} is not the registered anchor token",
+            env.message.sender
+        )));
     }
 
     if let Some(msg) = cw20_msg.msg {
-        match from_binary(&msg)? {
+        let hook_msg: Cw20HookMsg = from_binary(&msg).map_err(|err| {
+            StdError::generic_err(format!(
+                "Invalid hook msg, expected one of {}: {}",
+                CW20_HOOK_MSGS.join(", "),
+                err
+            ))
+        })?;
+
+        if !matches!(hook_msg, Cw20HookMsg::CreatePoll { .. }) {
+            assert_no_migration_in_progress(&deps.storage)?;
+        }
+        if matches!(
+            hook_msg,
+            Cw20HookMsg::CreatePoll { .. } | Cw20HookMsg::StakeAndVote { .. }
+        ) {
+            assert_not_emergency_paused(&deps.storage)?;
+        }
+
+        match hook_msg {
             Cw20HookMsg::StakeVotingTokens {} => {
                 stake_voting_tokens(deps, env, cw20_msg.sender, cw20_msg.amount)
             }
+            Cw20HookMsg::StakeVestingTokens { staker } => {
+                // only registered vesting contract can stake locked tokens
+                if config.vesting_contract != Some(deps.api.canonical_address(&cw20_msg.sender)?) {
+                    return Err(StdError::unauthorized());
+                }
+
+                stake_vesting_tokens(deps, env, staker, cw20_msg.amount)
+            }
+            Cw20HookMsg::StakeVotingTokensFor { staker } => {
+                stake_voting_tokens(deps, env, staker, cw20_msg.amount)
+            }
             Cw20HookMsg::CreatePoll {
                 title,
                 description,
-                link,
-                execute_msgs,
-            } => create_poll(
-                deps,
-                env,
-                cw20_msg.sender,
-                cw20_msg.amount,
-                title,
-                description,
-                link,
+                links,
                 execute_msgs,
-            ),
+                commit_reveal,
+                content_hash,
+                referendum,
+                stake_refund,
+                reserved_poll_id,
+                refund_address,
+                option_labels,
+                vote_reward,
+                execution_bounty,
+                on_reject_msgs,
+                preset,
+            } => {
+                let vote_reward = vote_reward.unwrap_or_default();
+                let deposit_amount = (cw20_msg.amount - vote_reward)
+                    .map_err(|_| StdError::generic_err("Vote reward exceeds the sent amount"))?;
+
+                create_poll(
+                    deps,
+                    env,
+                    cw20_msg.sender,
+                    deposit_amount,
+                    None,
+                    vote_reward,
+                    execution_bounty.unwrap_or_default(),
+                    title,
+                    description,
+                    links,
+                    execute_msgs,
+                    on_reject_msgs,
+                    commit_reveal,
+                    content_hash,
+                    referendum,
+                    stake_refund.unwrap_or(false),
+                    reserved_poll_id,
+                    refund_address,
+                    option_labels,
+                    preset,
+                )
+            }
+            Cw20HookMsg::StakeAndVote { poll_id, vote } => {
+                stake_and_vote(deps, env, cw20_msg.sender, cw20_msg.amount, poll_id, vote)
+            }
+            Cw20HookMsg::FundPollEscrow { poll_id } => {
+                fund_poll_escrow(deps, env, cw20_msg.sender, cw20_msg.amount, poll_id)
+            }
         }
     } else {
         Err(StdError::generic_err("data should be given"))
     }
 }
 
-#[allow(clippy::too_many_arguments)]
 pub fn update_config<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
-    owner: Option<HumanAddr>,
-    quorum: Option<Decimal>,
-    threshold: Option<Decimal>,
-    voting_period: Option<u64>,
-    timelock_period: Option<u64>,
-    expiration_period: Option<u64>,
-    proposal_deposit: Option<Uint128>,
-    snapshot_period: Option<u64>,
+    update: ConfigUpdate,
 ) -> HandleResult {
-    let api = deps.api;
-    config_store(&mut deps.storage).update(|mut config| {
-        if config.owner != api.canonical_address(&env.message.sender)? {
-            return Err(StdError::unauthorized());
-        }
+    let mut config: Config = config_read(&deps.storage).load()?;
+    let sender_address_raw = deps.api.canonical_address(&env.message.sender)?;
+    if config.owner != sender_address_raw {
+        return Err(StdError::unauthorized());
+    }
 
-        if let Some(owner) = owner {
-            config.owner = api.canonical_address(&owner)?;
-        }
+    let changes = apply_config_update(&deps.api, &mut config, update)?;
+    config_store(&mut deps.storage).save(&config)?;
 
-        if let Some(quorum) = quorum {
-            config.quorum = quorum;
-        }
+    // keep an on-chain record of the changed parameters
+    if !changes.is_empty() {
+        store_config_history(
+            &mut deps.storage,
+            env.block.height,
+            sender_address_raw,
+            changes,
+        )?;
+    }
 
-        if let Some(threshold) = threshold {
-            config.threshold = threshold;
-        }
+    Ok(HandleResponse::default())
+}
 
-        if let Some(voting_period) = voting_period {
-            config.voting_period = voting_period;
-        }
+/// ScheduleConfigUpdate stores parameter changes to be applied from the
+/// activation height on, so polls in progress are not judged by rules
+/// changed at an arbitrary height
+pub fn schedule_config_update<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    changes: ConfigUpdate,
+    activation_height: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let sender_address_raw = deps.api.canonical_address(&env.message.sender)?;
+    if config.owner != sender_address_raw {
+        return Err(StdError::unauthorized());
+    }
 
-        if let Some(timelock_period) = timelock_period {
-            config.timelock_period = timelock_period;
-        }
+    if Expiration::AtHeight(activation_height).is_expired(&env.block) {
+        return Err(StdError::generic_err(
+            "Activation height must be in the future",
+        ));
+    }
 
-        if let Some(expiration_period) = expiration_period {
-            config.expiration_period = expiration_period;
-        }
+    // the update is applied before any message from the activation height on,
+    // so an invalid one is rejected now instead of failing them all
+    apply_config_update(&deps.api, &mut config.clone(), changes.clone())?;
 
-        if let Some(proposal_deposit) = proposal_deposit {
-            config.proposal_deposit = proposal_deposit;
-        }
+    scheduled_config_update_store(&mut deps.storage).save(&ScheduledConfigUpdate {
+        changes,
+        activation_height,
+        sender: sender_address_raw,
+    })?;
 
-        if let Some(period) = snapshot_period {
-            config.snapshot_period = period;
-        }
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "schedule_config_update"),
+            log("activation_height", activation_height),
+        ],
+        data: None,
+    })
+}
 
-        Ok(config)
-    })?;
-    Ok(HandleResponse::default())
+// the scheduled update is applied lazily by the first message
+// handled at or after the activation height
+fn apply_scheduled_config_update<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+) -> StdResult<()> {
+    let scheduled = match scheduled_config_update_read(&deps.storage).may_load()? {
+        Some(scheduled) if scheduled.activation().is_expired(&env.block) => scheduled,
+        _ => return Ok(()),
+    };
+
+    let mut config: Config = config_read(&deps.storage).load()?;
+    let changes = apply_config_update(&deps.api, &mut config, scheduled.changes)?;
+    config_store(&mut deps.storage).save(&config)?;
+    scheduled_config_update_store(&mut deps.storage).remove();
+
+    if !changes.is_empty() {
+        store_config_history(
+            &mut deps.storage,
+            scheduled.activation_height,
+            scheduled.sender,
+            changes,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn apply_config_update<A: Api>(
+    api: &A,
+    config: &mut Config,
+    update: ConfigUpdate,
+) -> StdResult<Vec<ConfigChange>> {
+    let ConfigUpdate {
+        owner,
+        quorum,
+        threshold,
+        voting_period,
+        timelock_period,
+        expiration_period,
+        proposal_deposit,
+        snapshot_period,
+        vesting_contract,
+        reveal_period,
+        min_vote_amount,
+        quorum_excluded_addresses,
+        tally_checkpoint_interval,
+        poll_retention_period,
+        max_poll_extension,
+        narrow_pass_margin,
+        community_contract,
+        adaptive_quorum,
+        poll_sponsorship,
+        executor_whitelist,
+        partial_refund_ratio,
+        veto_threshold,
+        emergency_council,
+        max_active_votes,
+        reject_same_block_votes,
+        quorum_presets,
+    } = update;
+
+    let mut changes: Vec<ConfigChange> = vec![];
+
+    if let Some(owner) = owner {
+        record_change(
+            &mut changes,
+            "owner",
+            api.human_address(&config.owner)?,
+            owner.clone(),
+        );
+        config.owner = api.canonical_address(&owner)?;
+    }
+
+    if let Some(quorum) = quorum {
+        validate_quorum(quorum)?;
+        record_change(&mut changes, "quorum", config.quorum, quorum);
+        config.quorum = quorum;
+    }
+
+    if let Some(threshold) = threshold {
+        validate_threshold(threshold)?;
+        record_change(&mut changes, "threshold", config.threshold, threshold);
+        config.threshold = threshold;
+    }
+
+    if let Some(voting_period) = voting_period {
+        record_change(
+            &mut changes,
+            "voting_period",
+            config.voting_period,
+            voting_period,
+        );
+        config.voting_period = voting_period;
+    }
+
+    if let Some(timelock_period) = timelock_period {
+        record_change(
+            &mut changes,
+            "timelock_period",
+            config.timelock_period,
+            timelock_period,
+        );
+        config.timelock_period = timelock_period;
+    }
+
+    if let Some(expiration_period) = expiration_period {
+        record_change(
+            &mut changes,
+            "expiration_period",
+            config.expiration_period,
+            expiration_period,
+        );
+        config.expiration_period = expiration_period;
+    }
+
+    if let Some(proposal_deposit) = proposal_deposit {
+        record_change(
+            &mut changes,
+            "proposal_deposit",
+            config.proposal_deposit,
+            proposal_deposit,
+        );
+        config.proposal_deposit = proposal_deposit;
+    }
+
+    if let Some(period) = snapshot_period {
+        record_change(
+            &mut changes,
+            "snapshot_period",
+            config.snapshot_period,
+            period,
+        );
+        config.snapshot_period = period;
+    }
+
+    if let Some(vesting_contract) = vesting_contract {
+        let old_vesting_contract = match &config.vesting_contract {
+            Some(v) => api.human_address(v)?.to_string(),
+            None => "".to_string(),
+        };
+        record_change(
+            &mut changes,
+            "vesting_contract",
+            old_vesting_contract,
+            vesting_contract.to_string(),
+        );
+        config.vesting_contract = Some(api.canonical_address(&vesting_contract)?);
+    }
+
+    if let Some(reveal_period) = reveal_period {
+        record_change(
+            &mut changes,
+            "reveal_period",
+            config.reveal_period,
+            reveal_period,
+        );
+        config.reveal_period = reveal_period;
+    }
+
+    if let Some(min_vote_amount) = min_vote_amount {
+        record_change(
+            &mut changes,
+            "min_vote_amount",
+            config.min_vote_amount,
+            min_vote_amount,
+        );
+        config.min_vote_amount = min_vote_amount;
+    }
+
+    if let Some(quorum_excluded_addresses) = quorum_excluded_addresses {
+        let old_addresses = config
+            .quorum_excluded_addresses
+            .iter()
+            .map(|addr| Ok(api.human_address(addr)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?;
+        let new_addresses = quorum_excluded_addresses
+            .iter()
+            .map(|addr| addr.to_string())
+            .collect::<Vec<String>>();
+        record_change(
+            &mut changes,
+            "quorum_excluded_addresses",
+            old_addresses.join(","),
+            new_addresses.join(","),
+        );
+        config.quorum_excluded_addresses = quorum_excluded_addresses
+            .iter()
+            .map(|addr| api.canonical_address(addr))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    }
+
+    if let Some(tally_checkpoint_interval) = tally_checkpoint_interval {
+        record_change(
+            &mut changes,
+            "tally_checkpoint_interval",
+            config.tally_checkpoint_interval,
+            tally_checkpoint_interval,
+        );
+        config.tally_checkpoint_interval = tally_checkpoint_interval;
+    }
+
+    if let Some(poll_retention_period) = poll_retention_period {
+        record_change(
+            &mut changes,
+            "poll_retention_period",
+            config.poll_retention_period,
+            poll_retention_period,
+        );
+        config.poll_retention_period = poll_retention_period;
+    }
+
+    if let Some(max_poll_extension) = max_poll_extension {
+        record_change(
+            &mut changes,
+            "max_poll_extension",
+            config.max_poll_extension,
+            max_poll_extension,
+        );
+        config.max_poll_extension = max_poll_extension;
+    }
+
+    if let Some(narrow_pass_margin) = narrow_pass_margin {
+        record_change(
+            &mut changes,
+            "narrow_pass_margin",
+            config.narrow_pass_margin,
+            narrow_pass_margin,
+        );
+        config.narrow_pass_margin = narrow_pass_margin;
+    }
+
+    if let Some(community_contract) = community_contract {
+        let old_community_contract = match &config.community_contract {
+            Some(v) => api.human_address(v)?.to_string(),
+            None => "".to_string(),
+        };
+        record_change(
+            &mut changes,
+            "community_contract",
+            old_community_contract,
+            community_contract.to_string(),
+        );
+        config.community_contract = Some(api.canonical_address(&community_contract)?);
+    }
+
+    if let Some(adaptive_quorum) = adaptive_quorum {
+        let adaptive_quorum = validate_adaptive_quorum(adaptive_quorum)?;
+        record_change(
+            &mut changes,
+            "adaptive_quorum",
+            config
+                .adaptive_quorum
+                .as_ref()
+                .map_or_else(String::new, |v| v.to_string()),
+            adaptive_quorum
+                .as_ref()
+                .map_or_else(String::new, |v| v.to_string()),
+        );
+        config.adaptive_quorum = adaptive_quorum;
+    }
+
+    if let Some(poll_sponsorship) = poll_sponsorship {
+        let poll_sponsorship = Some(poll_sponsorship).filter(|v| v.required_sponsors > 0);
+        record_change(
+            &mut changes,
+            "poll_sponsorship",
+            config
+                .poll_sponsorship
+                .as_ref()
+                .map_or_else(String::new, |v| v.to_string()),
+            poll_sponsorship
+                .as_ref()
+                .map_or_else(String::new, |v| v.to_string()),
+        );
+        config.poll_sponsorship = poll_sponsorship;
+    }
+
+    if let Some(executor_whitelist) = executor_whitelist {
+        let old_addresses = config
+            .executor_whitelist
+            .iter()
+            .map(|addr| Ok(api.human_address(addr)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?;
+        let new_addresses = executor_whitelist
+            .iter()
+            .map(|addr| addr.to_string())
+            .collect::<Vec<String>>();
+        record_change(
+            &mut changes,
+            "executor_whitelist",
+            old_addresses.join(","),
+            new_addresses.join(","),
+        );
+        config.executor_whitelist = executor_whitelist
+            .iter()
+            .map(|addr| api.canonical_address(addr))
+            .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+    }
+
+    if let Some(partial_refund_ratio) = partial_refund_ratio {
+        validate_partial_refund_ratio(partial_refund_ratio)?;
+        record_change(
+            &mut changes,
+            "partial_refund_ratio",
+            config.partial_refund_ratio,
+            partial_refund_ratio,
+        );
+        config.partial_refund_ratio = partial_refund_ratio;
+    }
+
+    if let Some(veto_threshold) = veto_threshold {
+        validate_veto_threshold(veto_threshold)?;
+        record_change(
+            &mut changes,
+            "veto_threshold",
+            config.veto_threshold,
+            veto_threshold,
+        );
+        config.veto_threshold = veto_threshold;
+    }
+
+    if let Some(emergency_council) = emergency_council {
+        let old_council = match &config.emergency_council {
+            Some(council) => emergency_council_normal(api, council)?.to_string(),
+            None => String::new(),
+        };
+        let new_council = Some(&emergency_council)
+            .filter(|v| v.threshold > 0)
+            .map_or_else(String::new, |v| v.to_string());
+        record_change(&mut changes, "emergency_council", old_council, new_council);
+        config.emergency_council = emergency_council_raw(api, emergency_council)?;
+    }
+
+    if let Some(max_active_votes) = max_active_votes {
+        record_change(
+            &mut changes,
+            "max_active_votes",
+            config.max_active_votes,
+            max_active_votes,
+        );
+        config.max_active_votes = max_active_votes;
+    }
+
+    if let Some(reject_same_block_votes) = reject_same_block_votes {
+        record_change(
+            &mut changes,
+            "reject_same_block_votes",
+            config.reject_same_block_votes,
+            reject_same_block_votes,
+        );
+        config.reject_same_block_votes = reject_same_block_votes;
+    }
+
+    if let Some(quorum_presets) = quorum_presets {
+        validate_quorum_presets(&quorum_presets)?;
+        let presets_string = |presets: &[QuorumPreset]| {
+            presets
+                .iter()
+                .map(|preset| preset.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        record_change(
+            &mut changes,
+            "quorum_presets",
+            presets_string(&config.quorum_presets),
+            presets_string(&quorum_presets),
+        );
+        config.quorum_presets = quorum_presets;
+    }
+
+    Ok(changes)
+}
+
+fn record_change<T: ToString>(changes: &mut Vec<ConfigChange>, field: &str, old: T, new: T) {
+    let old_value = old.to_string();
+    let new_value = new.to_string();
+    if old_value != new_value {
+        changes.push(ConfigChange {
+            field: field.to_string(),
+            old_value,
+            new_value,
+        });
+    }
 }
 
 /// validate_title returns an error if the title is invalid
@@ -232,56 +1042,269 @@ fn validate_description(description: &str) -> StdResult<()> {
     }
 }
 
-/// validate_link returns an error if the link is invalid
-fn validate_link(link: &Option<String>) -> StdResult<()> {
-    if let Some(link) = link {
-        if link.len() < MIN_LINK_LENGTH {
-            Err(StdError::generic_err("Link too short"))
-        } else if link.len() > MAX_LINK_LENGTH {
-            Err(StdError::generic_err("Link too long"))
-        } else {
-            Ok(())
+/// validate_links returns an error if there are too many links or any link is invalid
+fn validate_links(links: &Option<Vec<PollLink>>) -> StdResult<()> {
+    if let Some(links) = links {
+        if links.len() > MAX_LINKS {
+            return Err(StdError::generic_err("Too many links"));
+        }
+
+        for link in links.iter() {
+            validate_link(&link.url)?;
         }
-    } else {
-        Ok(())
     }
+
+    Ok(())
 }
 
-/// validate_quorum returns an error if the quorum is invalid
-/// (we require 0-1)
-fn validate_quorum(quorum: Decimal) -> StdResult<()> {
-    if quorum > Decimal::one() {
-        Err(StdError::generic_err("quorum must be 0 to 1"))
-    } else {
-        Ok(())
+/// validate_link returns an error if the link is invalid
+/// (we require an http(s) url with a domain name host)
+fn validate_link(link: &str) -> StdResult<()> {
+    if link.len() < MIN_LINK_LENGTH {
+        return Err(StdError::generic_err("Link too short"));
+    } else if link.len() > MAX_LINK_LENGTH {
+        return Err(StdError::generic_err("Link too long"));
+    }
+
+    let rest = link
+        .strip_prefix("https://")
+        .or_else(|| link.strip_prefix("http://"))
+        .ok_or_else(|| StdError::generic_err("Link must be an http(s) url"))?;
+    let authority = rest.split(&['/', '?', '#'][..]).next().unwrap_or("");
+    let domain = authority.split(':').next().unwrap_or("");
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_label = |label: &&str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if labels.len() < 2 || !labels.iter().all(valid_label) {
+        return Err(StdError::generic_err("Invalid link domain"));
     }
+
+    Ok(())
 }
 
-/// validate_threshold returns an error if the threshold is invalid
-/// (we require 0-1)
-fn validate_threshold(threshold: Decimal) -> StdResult<()> {
-    if threshold > Decimal::one() {
-        Err(StdError::generic_err("threshold must be 0 to 1"))
+/// validate_content_hash returns an error if the content hash is invalid
+/// (we require 32 bytes)
+fn validate_content_hash(content_hash: &Option<Binary>) -> StdResult<()> {
+    if let Some(content_hash) = content_hash {
+        if content_hash.len() != CONTENT_HASH_LENGTH {
+            Err(StdError::generic_err("Invalid content hash"))
+        } else {
+            Ok(())
+        }
     } else {
         Ok(())
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-/// create a new poll
-pub fn create_poll<S: Storage, A: Api, Q: Querier>(
-    deps: &mut Extern<S, A, Q>,
-    env: Env,
-    proposer: HumanAddr,
-    deposit_amount: Uint128,
-    title: String,
-    description: String,
-    link: Option<String>,
-    execute_msgs: Option<Vec<ExecuteMsg>>,
-) -> StdResult<HandleResponse> {
-    validate_title(&title)?;
-    validate_description(&description)?;
-    validate_link(&link)?;
+/// validate_referendum returns an error if the snapshot root is not a 32 bytes hex,
+/// the snapshot is empty or the poll executes messages, as the snapshot is
+/// provided by the proposer
+fn validate_referendum(
+    referendum: &Option<Referendum>,
+    execute_msgs: &Option<Vec<ExecuteMsg>>,
+) -> StdResult<()> {
+    if let Some(referendum) = referendum {
+        if execute_msgs.is_some() {
+            return Err(StdError::generic_err(
+                "Referendum poll cannot execute messages",
+            ));
+        }
+
+        let mut root_buf: [u8; 32] = [0; 32];
+        if hex::decode_to_slice(&referendum.merkle_root, &mut root_buf).is_err() {
+            return Err(StdError::generic_err("Invalid hex encoded merkle root"));
+        }
+
+        if referendum.total_balance.is_zero() {
+            return Err(StdError::generic_err(
+                "Referendum total balance must be positive",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// validate_option_labels returns an error if the labels are not
+/// 2-8 distinct non-empty strings or the poll executes messages
+fn validate_option_labels(
+    option_labels: &Option<Vec<String>>,
+    execute_msgs: &Option<Vec<ExecuteMsg>>,
+) -> StdResult<()> {
+    if let Some(option_labels) = option_labels {
+        if execute_msgs.is_some() {
+            return Err(StdError::generic_err(
+                "Poll with option labels cannot execute messages",
+            ));
+        }
+
+        if option_labels.len() < MIN_OPTION_LABELS || option_labels.len() > MAX_OPTION_LABELS {
+            return Err(StdError::generic_err(format!(
+                "Poll must have {} to {} option labels",
+                MIN_OPTION_LABELS, MAX_OPTION_LABELS
+            )));
+        }
+
+        for (i, label) in option_labels.iter().enumerate() {
+            if label.is_empty() || label.len() > MAX_LABEL_LENGTH {
+                return Err(StdError::generic_err("Invalid option label length"));
+            }
+
+            if option_labels[..i].contains(label) {
+                return Err(StdError::generic_err("Duplicate option label"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// validate_on_reject_data returns the escrow of the poll, or an error unless
+/// the data is a single cw20 transfer other than the voting token to the
+/// proposer or the refund address of the poll
+fn validate_on_reject_data<A: Api>(
+    api: &A,
+    config: &Config,
+    proposer: &CanonicalAddr,
+    refund_address: &Option<CanonicalAddr>,
+    on_reject_data: &[ExecuteData],
+) -> StdResult<PollEscrow> {
+    let data = match on_reject_data {
+        [data] => data,
+        _ => {
+            return Err(StdError::generic_err(
+                "On reject messages must be a single cw20 transfer",
+            ))
+        }
+    };
+
+    if data.contract == config.anchor_token {
+        return Err(StdError::generic_err(
+            "On reject messages cannot transfer the voting token",
+        ));
+    }
+
+    let (recipient, amount) = match from_binary(&data.msg) {
+        Ok(Cw20HandleMsg::Transfer { recipient, amount }) => {
+            (api.canonical_address(&recipient)?, amount)
+        }
+        _ => {
+            return Err(StdError::generic_err(
+                "On reject messages must be cw20 transfers",
+            ))
+        }
+    };
+
+    if recipient != *proposer && Some(&recipient) != refund_address.as_ref() {
+        return Err(StdError::generic_err(
+            "On reject messages can only return funds to the proposer",
+        ));
+    }
+
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Poll escrow must not be zero"));
+    }
+
+    Ok(PollEscrow {
+        token: data.contract.clone(),
+        amount,
+        funded: false,
+    })
+}
+
+/// validate_quorum returns an error if the quorum is invalid
+/// (we require 0-1)
+fn validate_quorum(quorum: Decimal) -> StdResult<()> {
+    if quorum > Decimal::one() {
+        Err(StdError::generic_err("quorum must be 0 to 1"))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_quorum_presets(quorum_presets: &[QuorumPreset]) -> StdResult<()> {
+    for (i, preset) in quorum_presets.iter().enumerate() {
+        if preset.name.is_empty() || quorum_presets[..i].iter().any(|v| v.name == preset.name) {
+            return Err(StdError::generic_err(
+                "Quorum preset names must be unique and not empty",
+            ));
+        }
+
+        validate_quorum(preset.quorum)?;
+        validate_threshold(preset.threshold)?;
+    }
+
+    Ok(())
+}
+
+fn validate_partial_refund_ratio(partial_refund_ratio: Decimal) -> StdResult<()> {
+    if partial_refund_ratio > Decimal::one() {
+        Err(StdError::generic_err("partial_refund_ratio must be 0 to 1"))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_veto_threshold(veto_threshold: Decimal) -> StdResult<()> {
+    if veto_threshold > Decimal::one() {
+        Err(StdError::generic_err("veto_threshold must be 0 to 1"))
+    } else {
+        Ok(())
+    }
+}
+
+/// validate_threshold returns an error if the threshold is invalid
+/// (we require 0-1)
+fn validate_threshold(threshold: Decimal) -> StdResult<()> {
+    if threshold > Decimal::one() {
+        Err(StdError::generic_err("threshold must be 0 to 1"))
+    } else {
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+/// create a new poll
+pub fn create_poll<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    proposer: HumanAddr,
+    deposit_amount: Uint128,
+    deposit_share: Option<Uint128>,
+    vote_reward: Uint128,
+    execution_bounty: Uint128,
+    title: String,
+    description: String,
+    links: Option<Vec<PollLink>>,
+    execute_msgs: Option<Vec<ExecuteMsg>>,
+    on_reject_msgs: Option<Vec<ExecuteMsg>>,
+    commit_reveal: Option<bool>,
+    content_hash: Option<Binary>,
+    referendum: Option<Referendum>,
+    stake_refund: bool,
+    reserved_poll_id: Option<u64>,
+    refund_address: Option<HumanAddr>,
+    option_labels: Option<Vec<String>>,
+    preset: Option<String>,
+) -> StdResult<HandleResponse> {
+    validate_title(&title)?;
+    validate_description(&description)?;
+    validate_links(&links)?;
+    validate_content_hash(&content_hash)?;
+    validate_referendum(&referendum, &execute_msgs)?;
+    validate_option_labels(&option_labels, &execute_msgs)?;
+
+    if referendum.is_some() && commit_reveal.unwrap_or(false) {
+        return Err(StdError::generic_err(
+            "Referendum poll cannot use commit-reveal voting",
+        ));
+    }
 
     let config: Config = config_store(&mut deps.storage).load()?;
     if deposit_amount < config.proposal_deposit {
@@ -291,12 +1314,48 @@ pub fn create_poll<S: Storage, A: Api, Q: Querier>(
         )));
     }
 
+    if !execution_bounty.is_zero() {
+        if execute_msgs.is_none() {
+            return Err(StdError::generic_err(
+                "Execution bounty requires execute messages",
+            ));
+        }
+
+        if execution_bounty > deposit_amount {
+            return Err(StdError::generic_err(
+                "Execution bounty exceeds the deposit",
+            ));
+        }
+    }
+
+    let sender_address_raw = deps.api.canonical_address(&proposer)?;
+    let refund_address = match refund_address {
+        Some(refund_address) => Some(deps.api.canonical_address(&refund_address)?),
+        None => None,
+    };
+
     let mut state: State = state_store(&mut deps.storage).load()?;
-    let poll_id = state.poll_count + 1;
+    let poll_id = if let Some(poll_id) = reserved_poll_id {
+        let reservation =
+            match poll_reservation_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+                Some(v) => v,
+                None => return Err(StdError::generic_err("Poll id is not reserved")),
+            };
 
-    // Increase poll count & total deposit amount
-    state.poll_count += 1;
-    state.total_deposit += deposit_amount;
+        if reservation.creator != sender_address_raw {
+            return Err(StdError::unauthorized());
+        }
+
+        if reservation.expiration().is_expired(&env.block) {
+            return Err(StdError::generic_err("Poll id reservation has expired"));
+        }
+
+        poll_reservation_store(&mut deps.storage).remove(&poll_id.to_be_bytes());
+        poll_id
+    } else {
+        state.poll_count += 1;
+        state.poll_count
+    };
 
     let mut data_list: Vec<ExecuteData> = vec![];
     let all_execute_data = if let Some(exe_msgs) = execute_msgs {
@@ -313,43 +1372,310 @@ pub fn create_poll<S: Storage, A: Api, Q: Querier>(
         None
     };
 
-    let sender_address_raw = deps.api.canonical_address(&proposer)?;
-    let new_poll = Poll {
+    let (on_reject_data, escrow) = match on_reject_msgs {
+        Some(on_reject_msgs) => {
+            let on_reject_data = execute_data_raw(&deps.api, on_reject_msgs)?;
+            let escrow = validate_on_reject_data(
+                &deps.api,
+                &config,
+                &sender_address_raw,
+                &refund_address,
+                &on_reject_data,
+            )?;
+            (Some(on_reject_data), Some(escrow))
+        }
+        None => (None, None),
+    };
+
+    // a preset replaces the config parameters, the adaptive quorum included
+    let (quorum, threshold, voting_period) = match &preset {
+        Some(name) => match config.quorum_presets.iter().find(|v| v.name == *name) {
+            Some(v) => (v.quorum, v.threshold, v.voting_period),
+            None => return Err(StdError::generic_err("Quorum preset does not exist")),
+        },
+        None => (
+            effective_quorum(&config, &state),
+            config.threshold,
+            config.voting_period,
+        ),
+    };
+
+    let end_height = env.block.height + voting_period;
+    let reveal_end_height = if commit_reveal.unwrap_or(false) {
+        Some(end_height + config.reveal_period)
+    } else {
+        None
+    };
+
+    // with the poll sponsorship enabled, the end height is the sponsorship deadline
+    // until the poll has its sponsors
+    let (status, required_sponsors, sponsor_min_stake) = match &config.poll_sponsorship {
+        Some(sponsorship) => (
+            PollStatus::PendingSponsorship,
+            sponsorship.required_sponsors,
+            sponsorship.min_stake,
+        ),
+        None => (PollStatus::InProgress, 0, Uint128::zero()),
+    };
+
+    let option_labels = option_labels.unwrap_or_default();
+    let mut new_poll = Poll {
         id: poll_id,
         creator: sender_address_raw,
-        status: PollStatus::InProgress,
+        status: status.clone(),
         yes_votes: Uint128::zero(),
         no_votes: Uint128::zero(),
-        end_height: env.block.height + config.voting_period,
+        yes_shares: Uint128::zero(),
+        no_shares: Uint128::zero(),
+        veto_votes: Uint128::zero(),
+        vote_count: 0,
+        end_height,
         title,
         description,
-        link,
+        links: links.unwrap_or_default(),
         execute_data: all_execute_data,
         deposit_amount,
+        deposit_share,
+        deposit_stake_share: None,
         total_balance_at_end_poll: None,
         staked_amount: None,
+        staked_share: None,
+        reveal_end_height,
+        rejected_reason: None,
+        content_hash,
+        referendum,
+        stake_refund,
+        refund_address,
+        voters_root: None,
+        extended: false,
+        narrow_pass: false,
+        quorum_reached: false,
+        threshold_reached: false,
+        option_votes: vec![Uint128::zero(); option_labels.len()],
+        option_labels,
+        option_shares: Uint128::zero(),
+        required_sponsors,
+        sponsor_min_stake,
+        sponsors: vec![],
+        quorum,
+        threshold,
+        voting_period,
+        timelock_period: config.timelock_period,
+        expiration_period: config.expiration_period,
+        adaptive_quorum: match &preset {
+            Some(_) => None,
+            None => config.adaptive_quorum.clone(),
+        },
+        preset,
+        vote_reward: Uint128::zero(),
+        vote_reward_claimed: Uint128::zero(),
+        execution_bounty,
+        on_reject_data,
+        on_reject_executed: false,
+        escrow,
     };
 
+    // locked ahead of the deposit stake, so the balance it is staked against excludes it
+    lock_vote_reward(&mut state, &mut new_poll, vote_reward);
+
+    if deposit_share.is_none() && !deposit_amount.is_zero() {
+        new_poll.deposit_stake_share =
+            stake_poll_deposit(deps, &config, &mut state, deposit_amount, env.block.height)?;
+    }
+
+    // Increase total deposit amount;
+    // deposit made in staked shares is still counted as staked balance
+    lock_deposit(&mut deps.storage, &mut state, &new_poll)?;
+
     poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &new_poll)?;
-    poll_indexer_store(&mut deps.storage, &PollStatus::InProgress)
-        .save(&poll_id.to_be_bytes(), &true)?;
+    poll_indexer_store(&mut deps.storage, &status).save(&poll_id.to_be_bytes(), &true)?;
+    store_poll_event(
+        &mut deps.storage,
+        poll_id,
+        PollEventKind::Created,
+        env.block.height,
+    )?;
 
     state_store(&mut deps.storage).save(&state)?;
 
+    let mut logs = vec![
+        log("action", "create_poll"),
+        log(
+            "creator",
+            deps.api.human_address(&new_poll.creator)?.as_str(),
+        ),
+        log("poll_id", &poll_id.to_string()),
+        log("end_height", new_poll.end_height),
+    ];
+    if !vote_reward.is_zero() {
+        logs.push(log("vote_reward", vote_reward));
+    }
+    if !execution_bounty.is_zero() {
+        logs.push(log("execution_bounty", execution_bounty));
+    }
+
     let r = HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    };
+    Ok(r)
+}
+
+#[allow(clippy::too_many_arguments)]
+/// create a new poll locking the proposal deposit from the sender's staked shares
+pub fn create_poll_with_staked_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    title: String,
+    description: String,
+    links: Option<Vec<PollLink>>,
+    execute_msgs: Option<Vec<ExecuteMsg>>,
+    commit_reveal: Option<bool>,
+    content_hash: Option<Binary>,
+    referendum: Option<Referendum>,
+    reserved_poll_id: Option<u64>,
+    option_labels: Option<Vec<String>>,
+) -> StdResult<HandleResponse> {
+    let config: Config = config_read(&deps.storage).load()?;
+    let proposer = env.message.sender.clone();
+    let proposer_raw = deps.api.canonical_address(&proposer)?;
+
+    let deposit_share = lock_deposit_share(deps, &proposer_raw, config.proposal_deposit)?;
+    let mut res = create_poll(
+        deps,
+        env,
+        proposer,
+        config.proposal_deposit,
+        Some(deposit_share),
+        Uint128::zero(),
+        Uint128::zero(),
+        title,
+        description,
+        links,
+        execute_msgs,
+        None,
+        commit_reveal,
+        content_hash,
+        referendum,
+        false,
+        reserved_poll_id,
+        None,
+        option_labels,
+        None,
+    )?;
+
+    res.log
+        .push(log("deposit_share", deposit_share.to_string()));
+    Ok(res)
+}
+
+/// Allocate the next poll id to the sender; the reservation lapses
+/// unless the poll is created within the reservation period
+pub fn reserve_poll_id<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> HandleResult {
+    let mut state: State = state_read(&deps.storage).load()?;
+    state.poll_count += 1;
+
+    let poll_id = state.poll_count;
+    let expiry_height = env.block.height + POLL_RESERVATION_PERIOD;
+    poll_reservation_store(&mut deps.storage).save(
+        &poll_id.to_be_bytes(),
+        &PollReservation {
+            creator: deps.api.canonical_address(&env.message.sender)?,
+            expiry_height,
+        },
+    )?;
+    state_store(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
         messages: vec![],
         log: vec![
-            log("action", "create_poll"),
-            log(
-                "creator",
-                deps.api.human_address(&new_poll.creator)?.as_str(),
-            ),
-            log("poll_id", &poll_id.to_string()),
-            log("end_height", new_poll.end_height),
+            log("action", "reserve_poll_id"),
+            log("creator", env.message.sender.as_str()),
+            log("poll_id", poll_id),
+            log("expiry_height", expiry_height),
+        ],
+        data: None,
+    })
+}
+
+/// SetMigrationInProgress is executed by a passed poll to quiesce the contract
+/// before its state is exported. As votes are frozen meanwhile, the owner can
+/// also clear the flag in case the migration is abandoned
+pub fn set_migration_in_progress<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    migration_in_progress: bool,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let sender_raw = deps.api.canonical_address(&env.message.sender)?;
+    let is_owner = !migration_in_progress && sender_raw == config.owner;
+    if env.message.sender != env.contract.address && !is_owner {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut state: State = state_read(&deps.storage).load()?;
+    state.migration_in_progress = migration_in_progress;
+    state_store(&mut deps.storage).save(&state)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "set_migration_in_progress"),
+            log("migration_in_progress", migration_in_progress),
         ],
         data: None,
+    })
+}
+
+fn assert_no_migration_in_progress<S: Storage>(storage: &S) -> StdResult<()> {
+    let state: State = state_read(storage).load()?;
+    if state.migration_in_progress {
+        return Err(StdError::generic_err("Migration in progress"));
+    }
+
+    Ok(())
+}
+
+/// SetParam is executed by a passed poll to store a parameter
+/// which other contracts read through the Param query
+pub fn set_param<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    key: String,
+    value: Option<ParamValue>,
+) -> HandleResult {
+    if env.message.sender != env.contract.address {
+        return Err(StdError::unauthorized());
+    }
+
+    if key.is_empty() || key.len() > MAX_PARAM_KEY_LENGTH {
+        return Err(StdError::generic_err("Invalid param key"));
+    }
+
+    let log_value = match &value {
+        Some(value) => {
+            param_store(&mut deps.storage).save(key.as_bytes(), value)?;
+            value.to_string()
+        }
+        None => {
+            param_store(&mut deps.storage).remove(key.as_bytes());
+            String::new()
+        }
     };
-    Ok(r)
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "set_param"),
+            log("key", key),
+            log("value", log_value),
+        ],
+        data: None,
+    })
 }
 
 /*
@@ -362,99 +1688,579 @@ pub fn end_poll<S: Storage, A: Api, Q: Querier>(
 ) -> HandleResult {
     let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
 
-    if a_poll.status != PollStatus::InProgress {
+    // a poll still pending sponsorship at its end height is rejected
+    let pending_sponsorship = a_poll.status == PollStatus::PendingSponsorship;
+    if a_poll.status != PollStatus::InProgress && !pending_sponsorship {
         return Err(StdError::generic_err("Poll is not in progress"));
     }
 
-    if a_poll.end_height > env.block.height {
+    if !a_poll.end().is_expired(&env.block) {
         return Err(StdError::generic_err("Voting period has not expired"));
     }
 
+    if let Some(reveal_end) = a_poll.reveal_end() {
+        if !reveal_end.is_expired(&env.block) {
+            return Err(StdError::generic_err("Reveal period has not expired"));
+        }
+    }
+
     let no = a_poll.no_votes.u128();
     let yes = a_poll.yes_votes.u128();
+    let option_votes: u128 = a_poll.option_votes.iter().map(|votes| votes.u128()).sum();
 
-    let tallied_weight = yes + no;
+    let tallied_weight = yes + no + option_votes;
+    let tallied_share = a_poll.yes_shares + a_poll.no_shares + a_poll.option_shares;
 
     let mut poll_status = PollStatus::Rejected;
-    let mut rejected_reason = "";
+    let mut rejected_reason: Option<RejectedReason> = None;
     let mut passed = false;
+    let mut narrow_pass = false;
+    let mut refund_share: Option<Uint128> = None;
+    let mut winning_label: Option<String> = None;
 
     let mut messages: Vec<CosmosMsg> = vec![];
     let config: Config = config_read(&deps.storage).load()?;
     let mut state: State = state_read(&deps.storage).load()?;
 
-    let (quorum, staked_weight) = if state.total_share.u128() == 0 {
-        (Decimal::zero(), Uint128::zero())
-    } else if let Some(staked_amount) = a_poll.staked_amount {
+    // quorum is tallied in shares, so the votes are not affected
+    // by the share exchange rate drift during the voting period;
+    // referendum votes are token balances of the snapshot
+    let (quorum, staked_weight) = if let Some(referendum) = &a_poll.referendum {
         (
-            Decimal::from_ratio(tallied_weight, staked_amount),
-            staked_amount,
+            Decimal::from_ratio(tallied_weight, referendum.total_balance),
+            referendum.total_balance,
         )
+    } else if state.total_share.u128() == 0 {
+        (Decimal::zero(), Uint128::zero())
     } else {
-        let staked_weight = (load_token_balance(
+        let (staked_amount, staked_share) = match (a_poll.staked_amount, a_poll.staked_share) {
+            (Some(staked_amount), Some(staked_share)) => (staked_amount, staked_share),
+            _ => load_quorum_stake(&deps, &config, &state)?,
+        };
+
+        if staked_share.is_zero() {
+            (Decimal::zero(), staked_amount)
+        } else {
+            (
+                Decimal::from_ratio(tallied_share, staked_share),
+                staked_amount,
+            )
+        }
+    };
+
+    // the adaptive quorum of a poll created with it enabled applies at the end
+    // of the poll instead of its creation
+    if let Some(adaptive_quorum) = &a_poll.adaptive_quorum {
+        a_poll.quorum = adaptive_quorum_value(adaptive_quorum, &state, a_poll.quorum);
+    }
+
+    let mut deposit_interest = Uint128::zero();
+    if a_poll.deposit_stake_share.is_some() {
+        let total_balance = (load_token_balance(
             &deps,
             &deps.api.human_address(&config.anchor_token)?,
             &state.contract_addr,
         )? - state.total_deposit)?;
 
-        (
-            Decimal::from_ratio(tallied_weight, staked_weight),
-            staked_weight,
-        )
-    };
+        let (interest, interest_msgs) = unstake_poll_deposit(
+            deps,
+            &config,
+            &mut state,
+            &mut a_poll,
+            total_balance,
+            env.block.height,
+        )?;
+        deposit_interest = interest;
+        messages.extend(interest_msgs);
+    }
 
-    if tallied_weight == 0 || quorum < config.quorum {
+    if pending_sponsorship || tallied_weight == 0 || quorum < a_poll.quorum {
         // Quorum: More than quorum of the total staked tokens at the end of the voting
         // period need to have participated in the vote.
-        rejected_reason = "Quorum not reached";
+        rejected_reason = Some(if pending_sponsorship {
+            RejectedReason::SponsorshipNotReached
+        } else {
+            RejectedReason::QuorumNotReached
+        });
+
+        // Slash the share locked as deposit
+        if let Some(deposit_share) = a_poll.deposit_share {
+            release_deposit_share(
+                &mut deps.storage,
+                &mut state,
+                &a_poll.creator,
+                deposit_share,
+                true,
+                env.block.height,
+            )?;
+        }
+    } else if !config.veto_threshold.is_zero()
+        && Decimal::from_ratio(a_poll.veto_votes, tallied_weight) > config.veto_threshold
+    {
+        // Veto: the poll is rejected and the deposit slashed
+        // whatever the share of the yes votes
+        rejected_reason = Some(RejectedReason::Vetoed);
+
+        if let Some(deposit_share) = a_poll.deposit_share {
+            release_deposit_share(
+                &mut deps.storage,
+                &mut state,
+                &a_poll.creator,
+                deposit_share,
+                true,
+                env.block.height,
+            )?;
+        }
     } else {
-        if Decimal::from_ratio(yes, tallied_weight) > config.threshold {
+        if !a_poll.option_labels.is_empty() {
+            // a signal poll passes with a single leading option
+            if let Some(VoteOption::Label(index)) = read_leading_option(&a_poll) {
+                poll_status = PollStatus::Passed;
+                passed = true;
+                winning_label = Some(a_poll.option_labels[index as usize].clone());
+            } else {
+                rejected_reason = Some(RejectedReason::ThresholdNotReached);
+            }
+        } else if Decimal::from_ratio(yes, tallied_weight) > a_poll.threshold {
             //Threshold: More than 50% of the tokens that participated in the vote
             // (after excluding “Abstain” votes) need to have voted in favor of the proposal (“Yes”).
             poll_status = PollStatus::Passed;
             passed = true;
+
+            // the turnout barely cleared the quorum
+            narrow_pass = quorum < a_poll.quorum + config.narrow_pass_margin;
         } else {
-            rejected_reason = "Threshold not reached";
+            rejected_reason = Some(RejectedReason::ThresholdNotReached);
         }
 
+        // the execution bounty of a passed poll is held back from the refund
+        let refund_amount = if passed {
+            (a_poll.deposit_amount - a_poll.execution_bounty)?
+        } else {
+            a_poll.deposit_amount
+        };
+
         // Refunds deposit only when quorum is reached
-        if !a_poll.deposit_amount.is_zero() {
+        if let Some(deposit_share) = a_poll.deposit_share {
+            release_deposit_share(
+                &mut deps.storage,
+                &mut state,
+                &a_poll.creator,
+                deposit_share,
+                false,
+                env.block.height,
+            )?;
+        } else if a_poll.stake_refund {
+            refund_share = Some(stake_deposit_refund(
+                deps,
+                &config,
+                &mut state,
+                a_poll.refund_recipient(),
+                refund_amount,
+                env.block.height,
+            )?);
+        } else if !refund_amount.is_zero() {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: deps.api.human_address(&config.anchor_token)?,
                 send: vec![],
                 msg: to_binary(&Cw20HandleMsg::Transfer {
-                    recipient: deps.api.human_address(&a_poll.creator)?,
-                    amount: a_poll.deposit_amount,
+                    recipient: deps.api.human_address(a_poll.refund_recipient())?,
+                    amount: refund_amount,
                 })?,
             }))
         }
+
+        if passed {
+            hold_execution_bounty(&mut state, &a_poll);
+        }
+    }
+
+    // without voters, the vote reward goes back with the deposit refunds
+    if tallied_weight == 0 && !a_poll.vote_reward.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: deps.api.human_address(a_poll.refund_recipient())?,
+                amount: a_poll.vote_reward,
+            })?,
+        }));
+        let vote_reward = a_poll.vote_reward;
+        release_vote_reward(&mut state, &mut a_poll, vote_reward)?;
     }
 
     // Decrease total deposit amount
-    state.total_deposit = (state.total_deposit - a_poll.deposit_amount)?;
-    state_store(&mut deps.storage).save(&state)?;
+    state.total_deposit = (state.total_deposit - deposit_interest)?;
+    // part of a token deposit failing the quorum is claimable back,
+    // the rest goes to the community contract if one is set
+    let partially_refunded = rejected_reason == Some(RejectedReason::QuorumNotReached)
+        && a_poll.deposit_share.is_none()
+        && !a_poll.deposit_amount.is_zero()
+        && !config.partial_refund_ratio.is_zero();
+    let (deposit_status, deposit_destination) = match rejected_reason {
+        Some(RejectedReason::QuorumNotReached)
+        | Some(RejectedReason::SponsorshipNotReached)
+        | Some(RejectedReason::Vetoed) => (
+            DepositStatus::Slashed,
+            if partially_refunded {
+                config.community_contract.clone()
+            } else {
+                None
+            },
+        ),
+        _ => (DepositStatus::Refunded, Some(refund_destination(&a_poll))),
+    };
+    settle_deposit(
+        &mut deps.storage,
+        &mut state,
+        &a_poll,
+        deposit_status,
+        deposit_destination,
+        env.block.height,
+    )?;
+
+    let mut partial_refund: Option<Uint128> = None;
+    if partially_refunded {
+        let refund = hold_partial_refund(
+            &mut deps.storage,
+            &mut state,
+            &a_poll,
+            config.partial_refund_ratio,
+        )?;
+        let slashed_amount = (a_poll.deposit_amount - refund)?;
+        if let Some(community_contract) = &config.community_contract {
+            if !slashed_amount.is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps.api.human_address(&config.anchor_token)?,
+                    send: vec![],
+                    msg: to_binary(&Cw20HandleMsg::Transfer {
+                        recipient: deps.api.human_address(community_contract)?,
+                        amount: slashed_amount,
+                    })?,
+                }));
+            }
+        }
+        partial_refund = Some(refund);
+    }
 
     // Update poll indexer
-    poll_indexer_store(&mut deps.storage, &PollStatus::InProgress).remove(&a_poll.id.to_be_bytes());
+    poll_indexer_store(&mut deps.storage, &a_poll.status).remove(&a_poll.id.to_be_bytes());
     poll_indexer_store(&mut deps.storage, &poll_status).save(&a_poll.id.to_be_bytes(), &true)?;
 
-    // Update poll status
-    a_poll.status = poll_status;
-    a_poll.total_balance_at_end_poll = Some(staked_weight);
+    if passed {
+        state.polls_passed += 1;
+    } else {
+        state.polls_rejected += 1;
+    }
+
+    if !pending_sponsorship {
+        record_turnout(&config, &mut state, quorum);
+    }
+
+    state_store(&mut deps.storage).save(&state)?;
+
+    // Update poll status
+    a_poll.status = poll_status;
+    a_poll.total_balance_at_end_poll = Some(staked_weight);
+    a_poll.rejected_reason = rejected_reason.clone();
+    a_poll.narrow_pass = narrow_pass;
+    a_poll.voters_root = compute_voters_root(&deps.storage, &deps.api, poll_id)?;
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    store_poll_event(
+        &mut deps.storage,
+        poll_id,
+        PollEventKind::Ended,
+        env.block.height,
+    )?;
+
+    let mut logs = vec![
+        log("action", "end_poll"),
+        log("poll_id", &poll_id.to_string()),
+        log(
+            "rejected_reason",
+            rejected_reason.map_or_else(String::new, |reason| reason.to_string()),
+        ),
+        log("passed", &passed.to_string()),
+    ];
+    if a_poll.adaptive_quorum.is_some() {
+        logs.push(log("quorum", a_poll.quorum));
+    }
+    if narrow_pass {
+        logs.push(log("narrow_pass", "true"));
+    }
+    if let Some(refund_share) = refund_share {
+        logs.push(log("refund_share", refund_share.to_string()));
+    }
+    if let Some(partial_refund) = partial_refund {
+        logs.push(log("partial_refund", partial_refund));
+    }
+    if let Some(winning_label) = winning_label {
+        logs.push(log("winning_label", winning_label));
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: None,
+    })
+}
+
+/// ClaimDepositRefund pays out the partial refund held back for a poll which
+/// failed the quorum, staking it instead when the poll was created so and the
+/// stake is not migrated
+pub fn claim_deposit_refund<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let a_poll: Poll = match poll_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(StdError::generic_err("Poll does not exist")),
+    };
+
+    if deps.api.canonical_address(&env.message.sender)? != *a_poll.refund_recipient() {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut state: State = state_read(&deps.storage).load()?;
+    let refund = take_partial_refund(&mut deps.storage, &mut state, &a_poll)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut refund_share: Option<Uint128> = None;
+    if a_poll.stake_refund && migration_read(&deps.storage).may_load()?.is_none() {
+        // the refund is staked as still counted in the total deposit
+        state.total_deposit += refund;
+        refund_share = Some(stake_deposit_refund(
+            deps,
+            &config,
+            &mut state,
+            a_poll.refund_recipient(),
+            refund,
+            env.block.height,
+        )?);
+        state.total_deposit = (state.total_deposit - refund)?;
+    } else {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: env.message.sender,
+                amount: refund,
+            })?,
+        }));
+    }
+    state_store(&mut deps.storage).save(&state)?;
+
+    let mut logs = vec![
+        log("action", "claim_deposit_refund"),
+        log("poll_id", poll_id),
+        log("amount", refund),
+    ];
+    if let Some(refund_share) = refund_share {
+        logs.push(log("refund_share", refund_share));
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: None,
+    })
+}
+
+/// The vote reward of an ended poll is shared by its voters pro rata to the
+/// voted balance; the voter info is removed once the share is paid out
+pub fn claim_vote_reward<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let mut a_poll: Poll = match poll_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(StdError::generic_err("Poll does not exist")),
+    };
+
+    if a_poll.status == PollStatus::InProgress || a_poll.status == PollStatus::PendingSponsorship {
+        return Err(StdError::generic_err("Poll has not ended"));
+    }
+
+    if a_poll.vote_reward.is_zero() {
+        return Err(StdError::generic_err("Poll has no vote reward"));
+    }
+
+    let voter = deps.api.canonical_address(&env.message.sender)?;
+    let voter_info = match poll_voter_read(&deps.storage, poll_id).may_load(voter.as_slice())? {
+        Some(voter_info) => voter_info,
+        None => return Err(StdError::generic_err("No vote reward to claim")),
+    };
+
+    let tallied_weight = a_poll.yes_votes
+        + a_poll.no_votes
+        + a_poll
+            .option_votes
+            .iter()
+            .fold(Uint128::zero(), |sum, votes| sum + *votes);
+    let reward = mul_ratio(a_poll.vote_reward, voter_info.balance, tallied_weight)?;
+
+    let mut state: State = state_read(&deps.storage).load()?;
+    release_vote_reward(&mut state, &mut a_poll, reward)?;
+    poll_voter_store(&mut deps.storage, poll_id).remove(voter.as_slice());
     poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    state_store(&mut deps.storage).save(&state)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !reward.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: env.message.sender,
+                amount: reward,
+            })?,
+        }));
+    }
 
     Ok(HandleResponse {
         messages,
         log: vec![
-            log("action", "end_poll"),
-            log("poll_id", &poll_id.to_string()),
-            log("rejected_reason", rejected_reason),
-            log("passed", &passed.to_string()),
+            log("action", "claim_vote_reward"),
+            log("poll_id", poll_id),
+            log("amount", reward),
+        ],
+        data: None,
+    })
+}
+
+/// ExtendPoll prolongs the voting period, and the reveal period along with it,
+/// of a poll whose voting period has not ended yet; executable by the owner
+/// or by the gov contract itself through a passed poll
+pub fn extend_poll<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+    extra_blocks: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    if env.message.sender != env.contract.address
+        && config.owner != deps.api.canonical_address(&env.message.sender)?
+    {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::InProgress {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    // a poll whose voting period is over can't be reopened
+    if a_poll.end().is_expired(&env.block) {
+        return Err(StdError::generic_err("Voting period has expired"));
+    }
+
+    if a_poll.extended {
+        return Err(StdError::generic_err("Poll has already been extended"));
+    }
+
+    if extra_blocks == 0 || extra_blocks > config.max_poll_extension {
+        return Err(StdError::generic_err(format!(
+            "Poll can be extended by 1 to {} blocks",
+            config.max_poll_extension
+        )));
+    }
+
+    a_poll.end_height += extra_blocks;
+    a_poll.reveal_end_height = a_poll.reveal_end_height.map(|height| height + extra_blocks);
+    a_poll.extended = true;
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "extend_poll"),
+            log("poll_id", poll_id),
+            log("end_height", a_poll.end_height),
         ],
         data: None,
     })
 }
 
+/// SponsorPoll co-signs a poll pending sponsorship; with the last required
+/// sponsor the poll moves to InProgress and its voting period starts
+pub fn sponsor_poll<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::PendingSponsorship {
+        return Err(StdError::generic_err("Poll is not pending sponsorship"));
+    }
+
+    if a_poll.end().is_expired(&env.block) {
+        return Err(StdError::generic_err("Sponsorship period has expired"));
+    }
+
+    let sponsor_raw = deps.api.canonical_address(&env.message.sender)?;
+    if sponsor_raw == a_poll.creator {
+        return Err(StdError::generic_err(
+            "Poll creator cannot sponsor the poll",
+        ));
+    }
+
+    if a_poll.sponsors.contains(&sponsor_raw) {
+        return Err(StdError::generic_err(
+            "Poll is already sponsored by the sender",
+        ));
+    }
+
+    let staked_balance = query_staker(deps, env.message.sender.clone(), None)?.balance;
+    if staked_balance < a_poll.sponsor_min_stake {
+        return Err(StdError::generic_err(format!(
+            "Must stake at least {} token to sponsor",
+            a_poll.sponsor_min_stake
+        )));
+    }
+
+    // the stake backing the sponsorship stays locked until the poll ends
+    if !a_poll.sponsor_min_stake.is_zero() {
+        sponsor_lock_store(&mut deps.storage, &sponsor_raw)
+            .save(&poll_id.to_be_bytes(), &a_poll.sponsor_min_stake)?;
+    }
+    a_poll.sponsors.push(sponsor_raw);
+
+    let mut logs = vec![
+        log("action", "sponsor_poll"),
+        log("poll_id", poll_id),
+        log("sponsor", env.message.sender.as_str()),
+        log("sponsor_count", a_poll.sponsors.len()),
+    ];
+
+    if a_poll.sponsors.len() as u64 >= a_poll.required_sponsors {
+        a_poll.status = PollStatus::InProgress;
+        a_poll.end_height = env.block.height + a_poll.voting_period;
+        a_poll.reveal_end_height = a_poll
+            .reveal_end_height
+            .map(|_| a_poll.end_height + config.reveal_period);
+
+        poll_indexer_store(&mut deps.storage, &PollStatus::PendingSponsorship)
+            .remove(&poll_id.to_be_bytes());
+        poll_indexer_store(&mut deps.storage, &PollStatus::InProgress)
+            .save(&poll_id.to_be_bytes(), &true)?;
+
+        logs.push(log("end_height", a_poll.end_height));
+    }
+
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: logs,
+        data: None,
+    })
+}
+
 /*
  * Execute a msg of passed poll.
  */
@@ -463,14 +2269,23 @@ pub fn execute_poll<S: Storage, A: Api, Q: Querier>(
     env: Env,
     poll_id: u64,
 ) -> HandleResult {
+    // ending a poll is open to anyone, executing it can be left to the whitelist
     let config: Config = config_read(&deps.storage).load()?;
+    if !config.executor_whitelist.is_empty()
+        && !config
+            .executor_whitelist
+            .contains(&deps.api.canonical_address(&env.message.sender)?)
+    {
+        return Err(StdError::unauthorized());
+    }
+
     let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
 
     if a_poll.status != PollStatus::Passed {
         return Err(StdError::generic_err("Poll is not in passed status"));
     }
 
-    if a_poll.end_height + config.timelock_period > env.block.height {
+    if !a_poll.timelock_end().is_expired(&env.block) {
         return Err(StdError::generic_err("Timelock period has not expired"));
     }
 
@@ -480,39 +2295,139 @@ pub fn execute_poll<S: Storage, A: Api, Q: Querier>(
 
     a_poll.status = PollStatus::Executed;
     poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    store_poll_event(
+        &mut deps.storage,
+        poll_id,
+        PollEventKind::Executed,
+        env.block.height,
+    )?;
 
-    let mut messages: Vec<CosmosMsg> = vec![];
-    if let Some(all_msgs) = a_poll.execute_data {
-        let mut msgs = all_msgs;
-        msgs.sort();
-        for msg in msgs {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.human_address(&msg.contract)?,
-                msg: msg.msg,
-                send: vec![],
-            }))
-        }
-    } else {
-        return Err(StdError::generic_err("The poll does not have execute_data"));
+    let mut state: State = state_read(&deps.storage).load()?;
+    state.polls_executed += 1;
+
+    // the executor is paid the execution bounty of the poll
+    let mut messages = poll_execute_messages(&deps, &a_poll)?;
+    let execution_bounty = release_execution_bounty(&mut state, &a_poll)?;
+    if !execution_bounty.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: env.message.sender,
+                amount: execution_bounty,
+            })?,
+        }));
+    }
+
+    state_store(&mut deps.storage).save(&state)?;
+
+    let mut logs = vec![
+        log("action", "execute_poll"),
+        log("poll_id", poll_id.to_string()),
+    ];
+    if !execution_bounty.is_zero() {
+        logs.push(log("execution_bounty", execution_bounty));
     }
 
     Ok(HandleResponse {
         messages,
-        log: vec![
-            log("action", "execute_poll"),
-            log("poll_id", poll_id.to_string()),
-        ],
+        log: logs,
         data: None,
     })
 }
 
+// build the messages of the poll's execute data, ordered by their order field
+fn poll_execute_messages<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll: &Poll,
+) -> StdResult<Vec<CosmosMsg>> {
+    match &poll.execute_data {
+        Some(all_msgs) => execute_data_messages(deps, poll.id, all_msgs.clone()),
+        None => Err(StdError::generic_err("The poll does not have execute_data")),
+    }
+}
+
+fn execute_data_messages<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+    mut msgs: Vec<ExecuteData>,
+) -> StdResult<Vec<CosmosMsg>> {
+    msgs.sort();
+    msgs.into_iter()
+        .map(|msg| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.human_address(&msg.contract)?,
+                msg: substitute_placeholders(deps, poll_id, &msg)?,
+                send: vec![],
+            }))
+        })
+        .collect()
+}
+
+// Replaces the placeholders of the message with their current values; only the
+// top level message is resolved, placeholders in nested binaries are kept as is
+fn substitute_placeholders<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+    msg: &ExecuteData,
+) -> StdResult<Binary> {
+    let mut msg_str = match std::str::from_utf8(msg.msg.as_slice()) {
+        Ok(msg_str) if msg_str.contains("${") => msg_str.to_string(),
+        _ => return Ok(msg.msg.clone()),
+    };
+
+    msg_str = msg_str.replace(PLACEHOLDER_POLL_ID, &poll_id.to_string());
+    if msg_str.contains(PLACEHOLDER_CONTRACT_BALANCE) {
+        let config: Config = config_read(&deps.storage).load()?;
+        let balance = load_token_balance(
+            &deps,
+            &deps.api.human_address(&config.anchor_token)?,
+            &msg.contract,
+        )?;
+        msg_str = msg_str.replace(PLACEHOLDER_CONTRACT_BALANCE, &balance.to_string());
+    }
+
+    Ok(Binary::from(msg_str.into_bytes()))
+}
+
+fn execute_data_raw<A: Api>(api: &A, msgs: Vec<ExecuteMsg>) -> StdResult<Vec<ExecuteData>> {
+    msgs.into_iter()
+        .map(|msg| {
+            Ok(ExecuteData {
+                order: msg.order,
+                contract: api.canonical_address(&msg.contract)?,
+                msg: msg.msg,
+            })
+        })
+        .collect()
+}
+
+fn execute_msgs_normal<A: Api>(
+    api: &A,
+    data: &Option<Vec<ExecuteData>>,
+) -> StdResult<Option<Vec<ExecuteMsg>>> {
+    match data {
+        Some(data) => Ok(Some(
+            data.iter()
+                .map(|data| {
+                    Ok(ExecuteMsg {
+                        order: data.order,
+                        contract: api.human_address(&data.contract)?,
+                        msg: data.msg.clone(),
+                    })
+                })
+                .collect::<StdResult<Vec<ExecuteMsg>>>()?,
+        )),
+        None => Ok(None),
+    }
+}
+
 /// ExpirePoll is used to make the poll as expired state for querying purpose
 pub fn expire_poll<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     poll_id: u64,
 ) -> HandleResult {
-    let config: Config = config_read(&deps.storage).load()?;
     let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
 
     if a_poll.status != PollStatus::Passed {
@@ -525,7 +2440,7 @@ pub fn expire_poll<S: Storage, A: Api, Q: Querier>(
         ));
     }
 
-    if a_poll.end_height + config.expiration_period > env.block.height {
+    if !a_poll.expiration().is_expired(&env.block) {
         return Err(StdError::generic_err("Expire height has not been reached"));
     }
 
@@ -535,9 +2450,33 @@ pub fn expire_poll<S: Storage, A: Api, Q: Querier>(
 
     a_poll.status = PollStatus::Expired;
     poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    store_poll_event(
+        &mut deps.storage,
+        poll_id,
+        PollEventKind::Expired,
+        env.block.height,
+    )?;
+
+    // the execution bounty goes back to the refund recipient when nobody executed the poll
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut state: State = state_read(&deps.storage).load()?;
+    let execution_bounty = release_execution_bounty(&mut state, &a_poll)?;
+    if !execution_bounty.is_zero() {
+        let config: Config = config_read(&deps.storage).load()?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: deps.api.human_address(a_poll.refund_recipient())?,
+                amount: execution_bounty,
+            })?,
+        }));
+    }
+
+    state_store(&mut deps.storage).save(&state)?;
 
     Ok(HandleResponse {
-        messages: vec![],
+        messages,
         log: vec![
             log("action", "expire_poll"),
             log("poll_id", poll_id.to_string()),
@@ -546,82 +2485,855 @@ pub fn expire_poll<S: Storage, A: Api, Q: Querier>(
     })
 }
 
-/// SnapshotPoll is used to take a snapshot of the staked amount for quorum calculation
-pub fn snapshot_poll<S: Storage, A: Api, Q: Querier>(
+/// FundPollEscrow holds the escrow of a poll in progress, sent by its proposer
+/// in the token and amount returned by the on reject messages
+pub fn fund_poll_escrow<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
+    sender: HumanAddr,
+    amount: Uint128,
     poll_id: u64,
 ) -> HandleResult {
-    let config: Config = config_read(&deps.storage).load()?;
-    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+    let mut a_poll: Poll = match poll_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(StdError::generic_err("Poll does not exist")),
+    };
 
     if a_poll.status != PollStatus::InProgress {
         return Err(StdError::generic_err("Poll is not in progress"));
     }
 
-    let time_to_end = a_poll.end_height - env.block.height;
+    if a_poll.creator != deps.api.canonical_address(&sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    let escrow = match a_poll.escrow.as_mut() {
+        Some(escrow) => escrow,
+        None => return Err(StdError::generic_err("The poll does not have an escrow")),
+    };
+
+    if escrow.funded {
+        return Err(StdError::generic_err("Poll escrow is already funded"));
+    }
+
+    if escrow.token != deps.api.canonical_address(&env.message.sender)? || escrow.amount != amount {
+        return Err(StdError::generic_err(
+            "Poll escrow must be funded with the on reject transfer",
+        ));
+    }
+
+    escrow.funded = true;
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "fund_poll_escrow"),
+            log("poll_id", poll_id),
+            log("token", env.message.sender.as_str()),
+            log("amount", amount),
+        ],
+        data: None,
+    })
+}
+
+/// ExecuteRejectedPoll executes the on_reject_msgs of a poll voted down
+/// with the quorum reached, so never for a vetoed poll, after the timelock
+pub fn execute_rejected_poll<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    if !config.executor_whitelist.is_empty()
+        && !config
+            .executor_whitelist
+            .contains(&deps.api.canonical_address(&env.message.sender)?)
+    {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+
+    if a_poll.status != PollStatus::Rejected {
+        return Err(StdError::generic_err("Poll is not in rejected status"));
+    }
+
+    let on_reject_data = match &a_poll.on_reject_data {
+        Some(on_reject_data) => on_reject_data.clone(),
+        None => {
+            return Err(StdError::generic_err(
+                "The poll does not have on_reject_msgs",
+            ))
+        }
+    };
+
+    if a_poll.rejected_reason != Some(RejectedReason::ThresholdNotReached) {
+        return Err(StdError::generic_err(
+            "Poll was not voted down with the quorum reached",
+        ));
+    }
+
+    // polls stored before the messages were restricted are checked again,
+    // and only return the escrow the proposer funded for this poll
+    let escrow = validate_on_reject_data(
+        &deps.api,
+        &config,
+        &a_poll.creator,
+        &a_poll.refund_address,
+        &on_reject_data,
+    )?;
+    match &a_poll.escrow {
+        Some(v) if v.funded && v.token == escrow.token && v.amount == escrow.amount => {}
+        _ => return Err(StdError::generic_err("Poll escrow is not funded")),
+    }
+
+    if a_poll.on_reject_executed {
+        return Err(StdError::generic_err("On reject messages already executed"));
+    }
+
+    if !a_poll.timelock_end().is_expired(&env.block) {
+        return Err(StdError::generic_err("Timelock period has not expired"));
+    }
+
+    a_poll.on_reject_executed = true;
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(HandleResponse {
+        messages: execute_data_messages(&deps, poll_id, on_reject_data)?,
+        log: vec![
+            log("action", "execute_rejected_poll"),
+            log("poll_id", poll_id.to_string()),
+        ],
+        data: None,
+    })
+}
+
+/// SnapshotPoll is used to take a snapshot of the staked amount for quorum calculation
+pub fn snapshot_poll<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+
+    if a_poll.status != PollStatus::InProgress {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    let time_to_end = a_poll.end_height - env.block.height;
+
+    if time_to_end > config.snapshot_period {
+        return Err(StdError::generic_err("Cannot snapshot at this height"));
+    }
+
+    if a_poll.staked_amount.is_some() {
+        return Err(StdError::generic_err("Snapshot has already occurred"));
+    }
+
+    if a_poll.referendum.is_some() {
+        return Err(StdError::generic_err(
+            "Referendum poll uses the balance snapshot",
+        ));
+    }
+
+    // store the current staked amount for quorum calculation
+    let state: State = state_store(&mut deps.storage).load()?;
+
+    let (staked_amount, staked_share) = load_quorum_stake(&deps, &config, &state)?;
+
+    a_poll.staked_amount = Some(staked_amount);
+    a_poll.staked_share = Some(staked_share);
+
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    store_poll_event(
+        &mut deps.storage,
+        poll_id,
+        PollEventKind::Snapshot,
+        env.block.height,
+    )?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "snapshot_poll"),
+            log("poll_id", poll_id.to_string()),
+            log("staked_amount", staked_amount),
+            log("staked_share", staked_share),
+        ],
+        data: None,
+    })
+}
+
+/// PrunePolls removes the data of finished polls older than the retention period;
+/// a poll is deleted once all of its voters, commitments and checkpoints are removed,
+/// so a large poll can be pruned over several calls
+pub fn prune_polls<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    before_poll_id: u64,
+    limit: Option<u32>,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+
+    let polls = read_prunable_polls(
+        &deps.storage,
+        before_poll_id,
+        env.block.height,
+        config.poll_retention_period,
+        limit,
+    )?;
+
+    let mut removed_entries: usize = 0;
+    let mut pruned_polls: Vec<String> = vec![];
+    for mut a_poll in polls {
+        // the ledger of an unclaimed partial refund is kept
+        if read_deposit_ledger(&deps.storage, &a_poll)?
+            .claimable_refund
+            .is_some()
+        {
+            continue;
+        }
+
+        removed_entries += remove_poll_data(&mut deps.storage, a_poll.id, limit - removed_entries)?;
+        if removed_entries == limit {
+            break;
+        }
+
+        // the vote reward left unclaimed is shared by the stakers
+        let unclaimed_reward = (a_poll.vote_reward - a_poll.vote_reward_claimed)?;
+        if !unclaimed_reward.is_zero() {
+            let mut state: State = state_read(&deps.storage).load()?;
+            release_vote_reward(&mut state, &mut a_poll, unclaimed_reward)?;
+            state_store(&mut deps.storage).save(&state)?;
+        }
+
+        poll_indexer_store(&mut deps.storage, &a_poll.status).remove(&a_poll.id.to_be_bytes());
+        poll_store(&mut deps.storage).remove(&a_poll.id.to_be_bytes());
+        deposit_ledger_store(&mut deps.storage).remove(&a_poll.id.to_be_bytes());
+        poll_event_store(&mut deps.storage).remove(&a_poll.id.to_be_bytes());
+        removed_entries += 1;
+        pruned_polls.push(a_poll.id.to_string());
+        if removed_entries == limit {
+            break;
+        }
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "prune_polls"),
+            log("pruned_polls", pruned_polls.join(",")),
+            log("removed_entries", removed_entries),
+        ],
+        data: None,
+    })
+}
+
+/// load_staked_balance returns the token amount of the voter's staked share
+fn load_staked_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    state: &State,
+    voter: &CanonicalAddr,
+) -> StdResult<Uint128> {
+    if state.total_share.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let token_manager = bank_read(&deps.storage)
+        .may_load(voter.as_slice())?
+        .unwrap_or_default();
+    let total_balance = (load_token_balance(
+        &deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &state.contract_addr,
+    )? - state.total_deposit)?;
+
+    mul_ratio(token_manager.share, total_balance, state.total_share)
+}
+
+/// load_quorum_stake returns the staked amount and share used as the quorum
+/// denominator, excluding the stake of the quorum excluded addresses
+fn load_quorum_stake<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    state: &State,
+) -> StdResult<(Uint128, Uint128)> {
+    let staked_amount = (load_token_balance(
+        &deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &state.contract_addr,
+    )? - state.total_deposit)?;
+
+    if state.total_share.is_zero() {
+        return Ok((staked_amount, state.total_share));
+    }
+
+    // deposit stakes are held by no one and can't vote
+    let mut excluded_share = state.deposit_stake_share;
+    for address in config.quorum_excluded_addresses.iter() {
+        if let Some(token_manager) = bank_read(&deps.storage).may_load(address.as_slice())? {
+            excluded_share += token_manager.share;
+        }
+    }
+
+    Ok((
+        (staked_amount - mul_ratio(excluded_share, staked_amount, state.total_share)?)?,
+        (state.total_share - excluded_share)?,
+    ))
+}
+
+pub fn cast_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    voter: HumanAddr,
+    poll_id: u64,
+    vote: VoteOption,
+    amount: Option<Uint128>,
+) -> HandleResult {
+    let sender_address_raw = deps.api.canonical_address(&voter)?;
+    let config = config_read(&deps.storage).load()?;
+    let state = state_read(&deps.storage).load()?;
+    if poll_id == 0 || state.poll_count < poll_id {
+        return Err(StdError::generic_err("Poll does not exist"));
+    }
+
+    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::InProgress || a_poll.voting_end().is_expired(&env.block) {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    if a_poll.reveal_end_height.is_some() {
+        return Err(StdError::generic_err("Poll requires commit-reveal voting"));
+    }
+
+    if a_poll.referendum.is_some() {
+        return Err(StdError::generic_err("Poll requires referendum voting"));
+    }
+
+    // resolved at cast time, so the vote is not raced by a stale balance;
+    // a top up votes the balance not yet voted on the poll
+    let amount = match amount {
+        Some(amount) => amount,
+        None => {
+            let staked_balance = load_staked_balance(&deps, &config, &state, &sender_address_raw)?;
+            match poll_voter_read(&deps.storage, poll_id).may_load(sender_address_raw.as_slice())? {
+                Some(prior) => Uint128(staked_balance.u128().saturating_sub(prior.balance.u128())),
+                None => staked_balance,
+            }
+        }
+    };
+
+    let leading_option = read_leading_option(&a_poll);
+    record_vote(
+        deps,
+        &sender_address_raw,
+        &mut a_poll,
+        &vote,
+        amount,
+        env.block.height,
+    )?;
+
+    // processing snapshot
+    let time_to_end = a_poll.end_height - env.block.height;
+
+    if time_to_end < config.snapshot_period && a_poll.staked_amount.is_none() {
+        let (staked_amount, staked_share) = load_quorum_stake(&deps, &config, &state)?;
+        a_poll.staked_amount = Some(staked_amount);
+        a_poll.staked_share = Some(staked_share);
+        store_poll_event(
+            &mut deps.storage,
+            poll_id,
+            PollEventKind::Snapshot,
+            env.block.height,
+        )?;
+    }
+
+    let mut log = vec![
+        log("action", "cast_vote"),
+        log("poll_id", &poll_id.to_string()),
+        log("amount", &amount.to_string()),
+        log("voter", voter.as_str()),
+        log("vote_option", vote),
+    ];
+    log.extend(vote_milestones(
+        &deps,
+        &config,
+        &state,
+        &mut a_poll,
+        leading_option,
+    )?);
+
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    let r = HandleResponse {
+        messages: vec![],
+        log,
+        data: None,
+    };
+    Ok(r)
+}
+
+/// Votes with the stake of the voter, who approved the sender as its operator
+fn cast_operator_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    voter: HumanAddr,
+    poll_id: u64,
+    vote: VoteOption,
+    amount: Option<Uint128>,
+) -> HandleResult {
+    let voter_raw = deps.api.canonical_address(&voter)?;
+    let operator_raw = deps.api.canonical_address(&env.message.sender)?;
+    match operator_read(&deps.storage, &voter_raw).may_load(operator_raw.as_slice())? {
+        Some(expires) if !expires.is_expired(&env.block) => {}
+        Some(_) => return Err(StdError::generic_err("Operator approval expired")),
+        None => return Err(StdError::unauthorized()),
+    }
+
+    let operator = env.message.sender.clone();
+    let mut res = cast_vote(deps, env, voter, poll_id, vote, amount)?;
+    res.log.push(log("operator", operator.as_str()));
+    Ok(res)
+}
+
+pub fn approve_operator<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    operator: HumanAddr,
+    expires: Expiration,
+) -> HandleResult {
+    if operator == env.message.sender {
+        return Err(StdError::generic_err(
+            "Cannot approve the sender as operator",
+        ));
+    }
+
+    if expires.is_expired(&env.block) {
+        return Err(StdError::generic_err("Approval must expire in the future"));
+    }
+
+    let staker_raw = deps.api.canonical_address(&env.message.sender)?;
+    let operator_raw = deps.api.canonical_address(&operator)?;
+    operator_store(&mut deps.storage, &staker_raw).save(operator_raw.as_slice(), &expires)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "approve_operator"),
+            log("staker", env.message.sender.as_str()),
+            log("operator", operator.as_str()),
+            log("expires", expires),
+        ],
+        data: None,
+    })
+}
+
+pub fn revoke_operator<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    operator: HumanAddr,
+) -> HandleResult {
+    let staker_raw = deps.api.canonical_address(&env.message.sender)?;
+    let operator_raw = deps.api.canonical_address(&operator)?;
+    operator_store(&mut deps.storage, &staker_raw).remove(operator_raw.as_slice());
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "revoke_operator"),
+            log("staker", env.message.sender.as_str()),
+            log("operator", operator.as_str()),
+        ],
+        data: None,
+    })
+}
+
+/// Stakes the sent tokens and votes the whole staked balance of the sender,
+/// so a vote can be made with freshly sent tokens in a single transaction
+pub fn stake_and_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    sender: HumanAddr,
+    amount: Uint128,
+    poll_id: u64,
+    vote: VoteOption,
+) -> HandleResult {
+    // the vote always follows the stake in the same block
+    let config: Config = config_read(&deps.storage).load()?;
+    if config.reject_same_block_votes {
+        return Err(StdError::generic_err(
+            "Cannot stake and vote in one message while same block votes are rejected",
+        ));
+    }
+
+    let stake_res = stake_voting_tokens(deps, env.clone(), sender.clone(), amount)?;
+    let vote_res = cast_vote(deps, env, sender, poll_id, vote, None)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: [stake_res.log, vote_res.log].concat(),
+        data: None,
+    })
+}
+
+// The option with more votes, none on a tie
+fn read_leading_option(a_poll: &Poll) -> Option<VoteOption> {
+    if !a_poll.option_labels.is_empty() {
+        let max_votes = a_poll.option_votes.iter().max()?;
+        let mut leaders = a_poll
+            .option_votes
+            .iter()
+            .enumerate()
+            .filter(|(_, votes)| *votes == max_votes);
+
+        return match (leaders.next(), leaders.next()) {
+            (Some((index, votes)), None) if !votes.is_zero() => {
+                Some(VoteOption::Label(index as u8))
+            }
+            _ => None,
+        };
+    }
+
+    if a_poll.yes_votes > a_poll.no_votes {
+        Some(VoteOption::Yes)
+    } else if a_poll.no_votes > a_poll.yes_votes {
+        Some(VoteOption::No)
+    } else {
+        None
+    }
+}
+
+/// Logs the milestones a vote makes the poll reach: the first time it crosses
+/// the quorum and the threshold, and whenever it flips the leading option
+fn vote_milestones<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    state: &State,
+    a_poll: &mut Poll,
+    prev_leading_option: Option<VoteOption>,
+) -> StdResult<Vec<LogAttribute>> {
+    let mut logs: Vec<LogAttribute> = vec![];
+
+    if !a_poll.quorum_reached {
+        let staked_share = match a_poll.staked_share {
+            Some(staked_share) => staked_share,
+            None => load_quorum_stake(&deps, config, state)?.1,
+        };
+
+        let tallied_share = a_poll.yes_shares + a_poll.no_shares + a_poll.option_shares;
+        if !staked_share.is_zero()
+            && Decimal::from_ratio(tallied_share, staked_share) >= a_poll.quorum
+        {
+            a_poll.quorum_reached = true;
+            logs.push(log("quorum_reached", "true"));
+        }
+    }
+
+    // signal polls have no threshold
+    let tallied_weight = a_poll.yes_votes + a_poll.no_votes;
+    if !a_poll.threshold_reached
+        && a_poll.option_labels.is_empty()
+        && Decimal::from_ratio(a_poll.yes_votes, tallied_weight) > a_poll.threshold
+    {
+        a_poll.threshold_reached = true;
+        logs.push(log("threshold_reached", "true"));
+    }
+
+    if let (Some(prev), Some(leading_option)) = (prev_leading_option, read_leading_option(a_poll)) {
+        if prev != leading_option {
+            logs.push(log("leading_option", leading_option));
+        }
+    }
+
+    Ok(logs)
+}
+
+/// Commits a hidden vote on a commit-reveal poll
+pub fn commit_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+    hash: String,
+) -> HandleResult {
+    let sender_address_raw = deps.api.canonical_address(&env.message.sender)?;
+    let state = state_read(&deps.storage).load()?;
+    if poll_id == 0 || state.poll_count < poll_id {
+        return Err(StdError::generic_err("Poll does not exist"));
+    }
+
+    let a_poll: Poll = poll_read(&deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::InProgress || a_poll.voting_end().is_expired(&env.block) {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    if a_poll.reveal_end_height.is_none() {
+        return Err(StdError::generic_err("Poll is not a commit-reveal poll"));
+    }
+
+    let mut hash_buf: [u8; 32] = [0; 32];
+    if hex::decode_to_slice(&hash, &mut hash_buf).is_err() {
+        return Err(StdError::generic_err("Invalid hex encoded hash"));
+    }
+
+    if poll_commit_read(&deps.storage, poll_id)
+        .may_load(sender_address_raw.as_slice())?
+        .is_some()
+    {
+        return Err(StdError::generic_err("User has already voted."));
+    }
+
+    poll_commit_store(&mut deps.storage, poll_id)
+        .save(sender_address_raw.as_slice(), &hash.to_lowercase())?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "commit_vote"),
+            log("poll_id", &poll_id.to_string()),
+            log("voter", &env.message.sender.as_str()),
+        ],
+        data: None,
+    })
+}
+
+/// Reveals a committed vote and adds it to the poll tally
+pub fn reveal_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+    vote: VoteOption,
+    amount: Uint128,
+    salt: String,
+) -> HandleResult {
+    let sender_address_raw = deps.api.canonical_address(&env.message.sender)?;
+    let state = state_read(&deps.storage).load()?;
+    if poll_id == 0 || state.poll_count < poll_id {
+        return Err(StdError::generic_err("Poll does not exist"));
+    }
+
+    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+    let reveal_end_height = match a_poll.reveal_end_height {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Poll is not a commit-reveal poll")),
+    };
+
+    // votes are revealed through the reveal end height
+    if a_poll.status != PollStatus::InProgress
+        || !a_poll.voting_end().is_expired(&env.block)
+        || Expiration::AtHeight(reveal_end_height + 1).is_expired(&env.block)
+    {
+        return Err(StdError::generic_err("Poll is not in reveal period"));
+    }
+
+    let commitment =
+        match poll_commit_read(&deps.storage, poll_id).may_load(sender_address_raw.as_slice())? {
+            Some(v) => v,
+            None => return Err(StdError::generic_err("No committed vote")),
+        };
+
+    if commitment != compute_vote_hash(&env.message.sender, &vote, amount, &salt) {
+        return Err(StdError::generic_err("Vote does not match the commitment"));
+    }
+
+    record_vote(
+        deps,
+        &sender_address_raw,
+        &mut a_poll,
+        &vote,
+        amount,
+        env.block.height,
+    )?;
+    poll_commit_store(&mut deps.storage, poll_id).remove(sender_address_raw.as_slice());
+    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "reveal_vote"),
+            log("poll_id", &poll_id.to_string()),
+            log("amount", &amount.to_string()),
+            log("voter", &env.message.sender.as_str()),
+            log("vote_option", vote),
+        ],
+        data: None,
+    })
+}
+
+/// compute_vote_hash returns the hex encoded commitment of a vote; the strings
+/// are length prefixed and the amount fixed width, so the fields cannot shift
+pub fn compute_vote_hash(
+    voter: &HumanAddr,
+    vote: &VoteOption,
+    amount: Uint128,
+    salt: &str,
+) -> String {
+    let mut input: Vec<u8> = vec![];
+    for field in [voter.as_str(), &vote.to_string()].iter() {
+        input.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        input.extend_from_slice(field.as_bytes());
+    }
+    input.extend_from_slice(&amount.u128().to_be_bytes());
+    input.extend_from_slice(&(salt.len() as u32).to_be_bytes());
+    input.extend_from_slice(salt.as_bytes());
+    hex::encode(sha3::Keccak256::digest(&input))
+}
+
+/// Votes on a referendum poll with the balance proven against the poll's snapshot
+pub fn cast_referendum_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    poll_id: u64,
+    vote: VoteOption,
+    balance: Uint128,
+    proof: Vec<String>,
+) -> HandleResult {
+    let sender_address_raw = deps.api.canonical_address(&env.message.sender)?;
+    let config: Config = config_read(&deps.storage).load()?;
+    let state = state_read(&deps.storage).load()?;
+    if poll_id == 0 || state.poll_count < poll_id {
+        return Err(StdError::generic_err("Poll does not exist"));
+    }
+
+    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::InProgress || a_poll.voting_end().is_expired(&env.block) {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    let referendum = match &a_poll.referendum {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Poll is not a referendum poll")),
+    };
 
-    if time_to_end > config.snapshot_period {
-        return Err(StdError::generic_err("Cannot snapshot at this height"));
+    if balance.is_zero() {
+        return Err(StdError::generic_err("Cannot vote with zero amount"));
     }
 
-    if a_poll.staked_amount.is_some() {
-        return Err(StdError::generic_err("Snapshot has already occurred"));
+    if poll_voter_read(&deps.storage, poll_id)
+        .may_load(sender_address_raw.as_slice())?
+        .is_some()
+    {
+        return Err(StdError::generic_err("User has already voted."));
     }
 
-    // store the current staked amount for quorum calculation
-    let state: State = state_store(&mut deps.storage).load()?;
+    let leaf = env.message.sender.to_string() + &balance.to_string();
+    verify_merkle_proof(&referendum.merkle_root, &leaf, proof)?;
 
-    let staked_amount = (load_token_balance(
-        &deps,
-        &deps.api.human_address(&config.anchor_token)?,
-        &state.contract_addr,
-    )? - state.total_deposit)?;
+    tally_vote(&mut a_poll, &vote, balance, Uint128::zero())?;
 
-    a_poll.staked_amount = Some(staked_amount);
+    // referendum votes are not backed by stake, so no share is locked
+    poll_voter_store(&mut deps.storage, poll_id).save(
+        sender_address_raw.as_slice(),
+        &VoterInfo {
+            vote: vote.clone(),
+            balance,
+            share: Uint128::zero(),
+        },
+    )?;
 
+    count_vote(&mut deps.storage, &sender_address_raw)?;
+    record_tally_checkpoint(&mut deps.storage, &config, &mut a_poll, env.block.height)?;
     poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
     Ok(HandleResponse {
         messages: vec![],
         log: vec![
-            log("action", "snapshot_poll"),
-            log("poll_id", poll_id.to_string()),
-            log("staked_amount", staked_amount),
+            log("action", "cast_referendum_vote"),
+            log("poll_id", &poll_id.to_string()),
+            log("amount", balance.to_string()),
+            log("voter", &env.message.sender.as_str()),
+            log("vote_option", vote),
         ],
         data: None,
     })
 }
 
-pub fn cast_vote<S: Storage, A: Api, Q: Querier>(
+/// verify_merkle_proof checks the keccak256 leaf hash against the hex encoded root,
+/// hashing each sorted pair the same way as the airdrop contract
+fn verify_merkle_proof(merkle_root: &str, leaf: &str, proof: Vec<String>) -> StdResult<()> {
+    let mut hash: [u8; 32] = sha3::Keccak256::digest(leaf.as_bytes()).into();
+    for p in proof {
+        let mut proof_buf: [u8; 32] = [0; 32];
+        if hex::decode_to_slice(p, &mut proof_buf).is_err() {
+            return Err(StdError::generic_err("Invalid hex encoded proof"));
+        }
+
+        hash = if hash < proof_buf {
+            sha3::Keccak256::digest(&[hash, proof_buf].concat()).into()
+        } else {
+            sha3::Keccak256::digest(&[proof_buf, hash].concat()).into()
+        };
+    }
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    if hex::decode_to_slice(merkle_root, &mut root_buf).is_err() || root_buf != hash {
+        return Err(StdError::generic_err("Verification is failed"));
+    }
+
+    Ok(())
+}
+
+// Checks the voter's staked balance, updates the poll tally and locks
+// the voted amount along with its share weight.
+fn record_vote<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    env: Env,
-    poll_id: u64,
-    vote: VoteOption,
+    voter: &CanonicalAddr,
+    a_poll: &mut Poll,
+    vote: &VoteOption,
     amount: Uint128,
-) -> HandleResult {
-    let sender_address_raw = deps.api.canonical_address(&env.message.sender)?;
-    let config = config_read(&deps.storage).load()?;
-    let state = state_read(&deps.storage).load()?;
-    if poll_id == 0 || state.poll_count < poll_id {
-        return Err(StdError::generic_err("Poll does not exist"));
+    height: u64,
+) -> StdResult<()> {
+    let config: Config = config_read(&deps.storage).load()?;
+    let state: State = state_read(&deps.storage).load()?;
+    let poll_id = a_poll.id;
+
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Cannot vote with zero amount"));
     }
 
-    let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
-    if a_poll.status != PollStatus::InProgress || env.block.height > a_poll.end_height {
-        return Err(StdError::generic_err("Poll is not in progress"));
+    // reject dust votes which only bloat the voter storage
+    if amount < config.min_vote_amount {
+        return Err(StdError::generic_err(format!(
+            "Must vote with at least {} token",
+            config.min_vote_amount
+        )));
     }
 
-    // Check the voter already has a vote on the poll
-    if poll_voter_read(&deps.storage, poll_id)
-        .load(&sender_address_raw.as_slice())
-        .is_ok()
+    // an existing vote can only be topped up with the same option
+    let prior_vote = poll_voter_read(&deps.storage, poll_id).may_load(voter.as_slice())?;
+    if prior_vote
+        .as_ref()
+        .map_or(false, |prior| prior.vote != *vote)
     {
         return Err(StdError::generic_err("User has already voted."));
     }
 
-    let key = &sender_address_raw.as_slice();
-    let mut token_manager = bank_read(&deps.storage).may_load(key)?.unwrap_or_default();
+    let token_manager = bank_read(&deps.storage)
+        .may_load(voter.as_slice())?
+        .unwrap_or_default();
+
+    // the locks of the finished polls are pruned before counting the active ones,
+    // a top up keeps the lock of its poll
+    let locked_votes = prune_locked_balance(deps, voter)?;
+    if prior_vote.is_none()
+        && config.max_active_votes != 0
+        && locked_votes.len() as u64 >= config.max_active_votes
+    {
+        return Err(StdError::generic_err(format!(
+            "Cannot vote on more than {} polls in progress at once",
+            config.max_active_votes
+        )));
+    }
+
+    // a stake and a vote in the same block, as with flash loaned tokens, can be rejected
+    if config.reject_same_block_votes && token_manager.last_stake_height == height {
+        return Err(StdError::generic_err(
+            "Cannot vote in the same block as the last stake",
+        ));
+    }
 
     // convert share to amount
     let total_share = state.total_share;
@@ -631,59 +3343,122 @@ pub fn cast_vote<S: Storage, A: Api, Q: Querier>(
         &state.contract_addr,
     )? - state.total_deposit)?;
 
-    if token_manager
-        .share
-        .multiply_ratio(total_balance, total_share)
-        < amount
-    {
+    // the staked balance must cover the whole vote, top up included
+    let prior_balance = prior_vote
+        .as_ref()
+        .map_or(Uint128::zero(), |prior| prior.balance);
+    if mul_ratio(token_manager.share, total_balance, total_share)? < amount + prior_balance {
         return Err(StdError::generic_err(
             "User does not have enough staked tokens.",
         ));
     }
 
     // update tally info
-    if VoteOption::Yes == vote {
-        a_poll.yes_votes += amount;
-    } else {
-        a_poll.no_votes += amount;
-    }
+    let share = mul_ratio(amount, total_share, total_balance)?;
+    tally_vote(a_poll, vote, amount, share)?;
 
-    let vote_info = VoterInfo {
-        vote,
-        balance: amount,
+    let vote_info = match &prior_vote {
+        Some(prior) => VoterInfo {
+            vote: vote.clone(),
+            balance: prior.balance + amount,
+            share: prior.share + share,
+        },
+        None => VoterInfo {
+            vote: vote.clone(),
+            balance: amount,
+            share,
+        },
     };
-    token_manager
-        .locked_balance
-        .push((poll_id, vote_info.clone()));
-    bank_store(&mut deps.storage).save(key, &token_manager)?;
+    locked_vote_store(&mut deps.storage, voter).save(&poll_id.to_be_bytes(), &vote_info)?;
 
-    // store poll voter && and update poll data
-    poll_voter_store(&mut deps.storage, poll_id)
-        .save(&sender_address_raw.as_slice(), &vote_info)?;
+    // store poll voter
+    poll_voter_store(&mut deps.storage, poll_id).save(voter.as_slice(), &vote_info)?;
 
-    // processing snapshot
-    let time_to_end = a_poll.end_height - env.block.height;
+    // a top up is not counted as another vote
+    if prior_vote.is_some() {
+        return Ok(());
+    }
 
-    if time_to_end < config.snapshot_period && a_poll.staked_amount.is_none() {
-        a_poll.staked_amount = Some(total_balance);
+    count_vote(&mut deps.storage, voter)?;
+    record_tally_checkpoint(&mut deps.storage, &config, a_poll, height)
+}
+
+// Adds the vote to the tally of its option; polls with option labels
+// are only voted with a label and the others only with yes or no
+fn tally_vote(
+    a_poll: &mut Poll,
+    vote: &VoteOption,
+    amount: Uint128,
+    share: Uint128,
+) -> StdResult<()> {
+    match vote {
+        VoteOption::Label(index) => {
+            let index = *index as usize;
+            if index >= a_poll.option_labels.len() {
+                return Err(StdError::generic_err("Invalid vote option"));
+            }
+
+            a_poll.option_votes[index] += amount;
+            a_poll.option_shares += share;
+        }
+        _ if !a_poll.option_labels.is_empty() => {
+            return Err(StdError::generic_err(
+                "Poll must be voted with an option label",
+            ));
+        }
+        VoteOption::Yes => {
+            a_poll.yes_votes += amount;
+            a_poll.yes_shares += share;
+        }
+        VoteOption::No => {
+            a_poll.no_votes += amount;
+            a_poll.no_shares += share;
+        }
+        VoteOption::NoWithVeto => {
+            a_poll.no_votes += amount;
+            a_poll.no_shares += share;
+            a_poll.veto_votes += amount;
+        }
     }
 
-    poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    Ok(())
+}
 
-    let log = vec![
-        log("action", "cast_vote"),
-        log("poll_id", &poll_id.to_string()),
-        log("amount", &amount.to_string()),
-        log("voter", &env.message.sender.as_str()),
-        log("vote_option", vote_info.vote),
-    ];
+// updates the protocol wide vote counters
+fn count_vote<S: Storage>(storage: &mut S, voter: &CanonicalAddr) -> StdResult<()> {
+    let mut state: State = state_read(storage).load()?;
+    state.total_votes += 1;
+    if voted_read(storage).may_load(voter.as_slice())?.is_none() {
+        voted_store(storage).save(voter.as_slice(), &true)?;
+        state.unique_voters += 1;
+    }
 
-    let r = HandleResponse {
-        messages: vec![],
-        log,
-        data: None,
-    };
-    Ok(r)
+    state_store(storage).save(&state)
+}
+
+// checkpoints the running tally every `tally_checkpoint_interval` votes
+fn record_tally_checkpoint<S: Storage>(
+    storage: &mut S,
+    config: &Config,
+    a_poll: &mut Poll,
+    height: u64,
+) -> StdResult<()> {
+    a_poll.vote_count += 1;
+    if config.tally_checkpoint_interval > 0
+        && a_poll.vote_count % config.tally_checkpoint_interval == 0
+    {
+        tally_checkpoint_store(storage, a_poll.id).save(
+            &a_poll.vote_count.to_be_bytes(),
+            &TallyCheckpoint {
+                vote_count: a_poll.vote_count,
+                height,
+                yes_votes: a_poll.yes_votes,
+                no_votes: a_poll.no_votes,
+            },
+        )?;
+    }
+
+    Ok(())
 }
 
 pub fn query<S: Storage, A: Api, Q: Querier>(
@@ -693,7 +3468,11 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(&deps)?),
         QueryMsg::State {} => to_binary(&query_state(&deps)?),
-        QueryMsg::Staker { address } => to_binary(&query_staker(deps, address)?),
+        QueryMsg::Staker {
+            address,
+            token_balance,
+        } => to_binary(&query_staker(deps, address, token_balance)?),
+        QueryMsg::Withdrawable { address } => to_binary(&query_withdrawable(deps, address)?),
         QueryMsg::Poll { poll_id } => to_binary(&query_poll(deps, poll_id)?),
         QueryMsg::Polls {
             filter,
@@ -701,12 +3480,102 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
             limit,
             order_by,
         } => to_binary(&query_polls(deps, filter, start_after, limit, order_by)?),
+        QueryMsg::PollsByIds { poll_ids } => to_binary(&query_polls_by_ids(deps, poll_ids)?),
         QueryMsg::Voters {
             poll_id,
             start_after,
             limit,
             order_by,
         } => to_binary(&query_voters(deps, poll_id, start_after, limit, order_by)?),
+        QueryMsg::ConfigHistory { start_after, limit } => {
+            to_binary(&query_config_history(deps, start_after, limit)?)
+        }
+        QueryMsg::MigrationSnapshot { start_after, limit } => {
+            to_binary(&query_migration_snapshot(deps, start_after, limit)?)
+        }
+        QueryMsg::Overview {} => to_binary(&query_overview(deps)?),
+        QueryMsg::Param { key } => to_binary(&query_param(deps, key)?),
+        QueryMsg::Params { start_after, limit } => {
+            to_binary(&query_params(deps, start_after, limit)?)
+        }
+        QueryMsg::TallyCheckpoints {
+            poll_id,
+            start_after,
+            limit,
+        } => to_binary(&query_tally_checkpoints(deps, poll_id, start_after, limit)?),
+        QueryMsg::PollEvents { poll_id } => to_binary(&query_poll_events(deps, poll_id)?),
+        QueryMsg::DepositLedger { poll_id } => to_binary(&query_deposit_ledger(deps, poll_id)?),
+        QueryMsg::PollDeposit { poll_id } => to_binary(&query_poll_deposit(deps, poll_id)?),
+        QueryMsg::SimulateExecution { poll_id } => {
+            to_binary(&query_simulate_execution(deps, poll_id)?)
+        }
+        QueryMsg::Stats {} => to_binary(&query_stats(deps)?),
+        QueryMsg::PollTally { poll_id } => to_binary(&query_poll_tally(deps, poll_id)?),
+        QueryMsg::StakingSnapshot { address, height } => {
+            to_binary(&query_staking_snapshot(deps, address, height)?)
+        }
+        QueryMsg::ShareAccounting { start_after, limit } => {
+            to_binary(&query_share_accounting(deps, start_after, limit)?)
+        }
+        QueryMsg::RawPoll { poll_id } => {
+            let (key, value) = read_poll_raw(&deps.storage, poll_id);
+            to_binary(&raw_storage_response(key, value))
+        }
+        QueryMsg::ScheduledConfigUpdate {} => to_binary(&query_scheduled_config_update(deps)?),
+        QueryMsg::EmergencyAction { action_id } => {
+            to_binary(&query_emergency_action(deps, action_id)?)
+        }
+        QueryMsg::Operator { staker, operator } => {
+            to_binary(&query_operator(deps, staker, operator)?)
+        }
+        QueryMsg::EmergencyActions {
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&query_emergency_actions(
+            deps,
+            start_after,
+            limit,
+            order_by,
+        )?),
+        QueryMsg::RawBank { address } => {
+            let address_raw = deps.api.canonical_address(&address)?;
+            let (key, value) = read_bank_raw(&deps.storage, &address_raw);
+            to_binary(&raw_storage_response(key, value))
+        }
+        QueryMsg::ExportBanks { start_after, limit } => {
+            let start_after = match start_after {
+                Some(start_after) => Some(deps.api.canonical_address(&start_after)?),
+                None => None,
+            };
+            let records = read_bank_records(&deps.storage, start_after, limit)
+                .into_iter()
+                .map(|(k, v)| Ok((deps.api.human_address(&k)?.to_string(), v)))
+                .collect::<StdResult<Vec<(String, Vec<u8>)>>>()?;
+            to_binary(&export_response(records, limit))
+        }
+        QueryMsg::ExportPolls { start_after, limit } => {
+            let records = read_poll_records(&deps.storage, start_after, limit)
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+            to_binary(&export_response(records, limit))
+        }
+        QueryMsg::ExportVoters {
+            poll_id,
+            start_after,
+            limit,
+        } => {
+            let start_after = match start_after {
+                Some(start_after) => Some(deps.api.canonical_address(&start_after)?),
+                None => None,
+            };
+            let records = read_voter_records(&deps.storage, poll_id, start_after, limit)
+                .into_iter()
+                .map(|(k, v)| Ok((deps.api.human_address(&k)?.to_string(), v)))
+                .collect::<StdResult<Vec<(String, Vec<u8>)>>>()?;
+            to_binary(&export_response(records, limit))
+        }
     }
 }
 
@@ -724,15 +3593,122 @@ fn query_config<S: Storage, A: Api, Q: Querier>(
         expiration_period: config.expiration_period,
         proposal_deposit: config.proposal_deposit,
         snapshot_period: config.snapshot_period,
+        vesting_contract: if let Some(vesting_contract) = config.vesting_contract {
+            Some(deps.api.human_address(&vesting_contract)?)
+        } else {
+            None
+        },
+        reveal_period: config.reveal_period,
+        min_vote_amount: config.min_vote_amount,
+        quorum_excluded_addresses: config
+            .quorum_excluded_addresses
+            .iter()
+            .map(|addr| deps.api.human_address(addr))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        tally_checkpoint_interval: config.tally_checkpoint_interval,
+        poll_retention_period: config.poll_retention_period,
+        max_poll_extension: config.max_poll_extension,
+        narrow_pass_margin: config.narrow_pass_margin,
+        community_contract: if let Some(community_contract) = config.community_contract {
+            Some(deps.api.human_address(&community_contract)?)
+        } else {
+            None
+        },
+        adaptive_quorum: config.adaptive_quorum,
+        poll_sponsorship: config.poll_sponsorship,
+        executor_whitelist: config
+            .executor_whitelist
+            .iter()
+            .map(|addr| deps.api.human_address(addr))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        partial_refund_ratio: config.partial_refund_ratio,
+        veto_threshold: config.veto_threshold,
+        emergency_council: match config.emergency_council {
+            Some(emergency_council) => {
+                Some(emergency_council_normal(&deps.api, &emergency_council)?)
+            }
+            None => None,
+        },
+        max_active_votes: config.max_active_votes,
+        reject_same_block_votes: config.reject_same_block_votes,
+        quorum_presets: config.quorum_presets,
+        token_info: config.token_info,
     })
 }
 
 fn query_state<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<StateResponse> {
+    let config: Config = config_read(&deps.storage).load()?;
     let state: State = state_read(&deps.storage).load()?;
     Ok(StateResponse {
         poll_count: state.poll_count,
         total_share: state.total_share,
         total_deposit: state.total_deposit,
+        turnout_ema: state.turnout_ema,
+        quorum: effective_quorum(&config, &state),
+        migration_in_progress: state.migration_in_progress,
+        unclaimed_refund: state.unclaimed_refund,
+        emergency_paused: state.emergency_paused,
+        vote_reward_pool: state.vote_reward_pool,
+        execution_bounty_pool: state.execution_bounty_pool,
+    })
+}
+
+fn query_stats<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<StatsResponse> {
+    let state: State = state_read(&deps.storage).load()?;
+    Ok(StatsResponse {
+        total_votes: state.total_votes,
+        unique_voters: state.unique_voters,
+        polls_passed: state.polls_passed,
+        polls_rejected: state.polls_rejected,
+        polls_executed: state.polls_executed,
+    })
+}
+
+fn query_overview<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<OverviewResponse> {
+    let state = query_state(deps)?;
+
+    // the latest poll may have been pruned
+    let latest_poll = if state.poll_count > 0 {
+        poll_read(&deps.storage).may_load(&state.poll_count.to_be_bytes())?
+    } else {
+        None
+    };
+
+    Ok(OverviewResponse {
+        config: query_config(deps)?,
+        state,
+        latest_poll_id: latest_poll.as_ref().map(|poll| poll.id),
+        latest_poll_status: latest_poll.map(|poll| poll.status),
+    })
+}
+
+fn query_param<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    key: String,
+) -> StdResult<ParamResponse> {
+    let value = match param_read(&deps.storage).may_load(key.as_bytes())? {
+        Some(value) => value,
+        None => return Err(StdError::generic_err("Param does not exist")),
+    };
+
+    Ok(ParamResponse { key, value })
+}
+
+fn query_params<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ParamsResponse> {
+    let params = read_params(&deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(key, value)| ParamResponse { key, value })
+        .collect();
+
+    Ok(ParamsResponse {
+        params,
+        limit: page_limit(limit),
     })
 }
 
@@ -755,8 +3731,9 @@ fn query_poll<S: Storage, A: Api, Q: Querier>(
         end_height: poll.end_height,
         title: poll.title,
         description: poll.description,
-        link: poll.link,
+        links: poll.links,
         deposit_amount: poll.deposit_amount,
+        deposit_share: poll.deposit_share,
         execute_data: if let Some(exe_msgs) = poll.execute_data.clone() {
             for msg in exe_msgs {
                 let execute_data = ExecuteMsg {
@@ -772,8 +3749,37 @@ fn query_poll<S: Storage, A: Api, Q: Querier>(
         },
         yes_votes: poll.yes_votes,
         no_votes: poll.no_votes,
+        yes_shares: poll.yes_shares,
+        no_shares: poll.no_shares,
+        veto_votes: poll.veto_votes,
         staked_amount: poll.staked_amount,
+        staked_share: poll.staked_share,
         total_balance_at_end_poll: poll.total_balance_at_end_poll,
+        reveal_end_height: poll.reveal_end_height,
+        rejected_reason: poll.rejected_reason,
+        content_hash: poll.content_hash,
+        referendum: poll.referendum,
+        stake_refund: poll.stake_refund,
+        refund_address: match poll.refund_address {
+            Some(refund_address) => Some(deps.api.human_address(&refund_address)?),
+            None => None,
+        },
+        narrow_pass: poll.narrow_pass,
+        option_labels: poll.option_labels.clone(),
+        option_votes: poll.option_votes.clone(),
+        required_sponsors: poll.required_sponsors,
+        sponsors: poll
+            .sponsors
+            .iter()
+            .map(|sponsor| deps.api.human_address(sponsor))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        vote_reward: poll.vote_reward,
+        vote_reward_claimed: poll.vote_reward_claimed,
+        execution_bounty: poll.execution_bounty,
+        on_reject_msgs: execute_msgs_normal(&deps.api, &poll.on_reject_data)?,
+        on_reject_executed: poll.on_reject_executed,
+        escrow_funded: poll.escrow.as_ref().map_or(false, |escrow| escrow.funded),
+        preset: poll.preset.clone(),
     })
 }
 
@@ -796,8 +3802,9 @@ fn query_polls<S: Storage, A: Api, Q: Querier>(
                 end_height: poll.end_height,
                 title: poll.title.to_string(),
                 description: poll.description.to_string(),
-                link: poll.link.clone(),
+                links: poll.links.clone(),
                 deposit_amount: poll.deposit_amount,
+                deposit_share: poll.deposit_share,
                 execute_data: if let Some(exe_msgs) = poll.execute_data.clone() {
                     let mut data_list: Vec<ExecuteMsg> = vec![];
 
@@ -815,14 +3822,75 @@ fn query_polls<S: Storage, A: Api, Q: Querier>(
                 },
                 yes_votes: poll.yes_votes,
                 no_votes: poll.no_votes,
+                yes_shares: poll.yes_shares,
+                no_shares: poll.no_shares,
+                veto_votes: poll.veto_votes,
                 staked_amount: poll.staked_amount,
+                staked_share: poll.staked_share,
                 total_balance_at_end_poll: poll.total_balance_at_end_poll,
+                reveal_end_height: poll.reveal_end_height,
+                rejected_reason: poll.rejected_reason.clone(),
+                content_hash: poll.content_hash.clone(),
+                referendum: poll.referendum.clone(),
+                stake_refund: poll.stake_refund,
+                refund_address: match &poll.refund_address {
+                    Some(refund_address) => Some(deps.api.human_address(refund_address)?),
+                    None => None,
+                },
+                narrow_pass: poll.narrow_pass,
+                option_labels: poll.option_labels.clone(),
+                option_votes: poll.option_votes.clone(),
+                required_sponsors: poll.required_sponsors,
+                sponsors: poll
+                    .sponsors
+                    .iter()
+                    .map(|sponsor| deps.api.human_address(sponsor))
+                    .collect::<StdResult<Vec<HumanAddr>>>()?,
+                vote_reward: poll.vote_reward,
+                vote_reward_claimed: poll.vote_reward_claimed,
+                execution_bounty: poll.execution_bounty,
+                on_reject_msgs: execute_msgs_normal(&deps.api, &poll.on_reject_data)?,
+                on_reject_executed: poll.on_reject_executed,
+                escrow_funded: poll.escrow.as_ref().map_or(false, |escrow| escrow.funded),
+                preset: poll.preset.clone(),
             })
         })
         .collect();
 
     Ok(PollsResponse {
         polls: poll_responses?,
+        limit: page_limit(limit),
+    })
+}
+
+fn query_polls_by_ids<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_ids: Vec<u64>,
+) -> StdResult<PollsByIdsResponse> {
+    if poll_ids.len() > MAX_POLL_IDS {
+        return Err(StdError::generic_err(format!(
+            "Cannot query more than {} polls at once",
+            MAX_POLL_IDS
+        )));
+    }
+
+    let mut polls: Vec<PollResponse> = vec![];
+    let mut missing_poll_ids: Vec<u64> = vec![];
+    for poll_id in poll_ids {
+        if poll_read(&deps.storage)
+            .may_load(&poll_id.to_be_bytes())?
+            .is_none()
+        {
+            missing_poll_ids.push(poll_id);
+            continue;
+        }
+
+        polls.push(query_poll(deps, poll_id)?);
+    }
+
+    Ok(PollsByIdsResponse {
+        polls,
+        missing_poll_ids,
     })
 }
 
@@ -860,11 +3928,205 @@ fn query_voters<S: Storage, A: Api, Q: Querier>(
                 voter: deps.api.human_address(&voter_info.0)?,
                 vote: voter_info.1.vote.clone(),
                 balance: voter_info.1.balance,
+                share: voter_info.1.share,
             })
         })
         .collect();
 
     Ok(VotersResponse {
         voters: voters_response?,
+        limit: page_limit(limit),
+    })
+}
+
+fn query_config_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ConfigHistoryResponse> {
+    let records = read_config_history(&deps.storage, start_after, limit)?;
+
+    let records_response: StdResult<Vec<ConfigHistoryResponseItem>> = records
+        .into_iter()
+        .map(|record| {
+            Ok(ConfigHistoryResponseItem {
+                id: record.id,
+                height: record.height,
+                sender: deps.api.human_address(&record.sender)?,
+                changes: record.changes,
+            })
+        })
+        .collect();
+
+    Ok(ConfigHistoryResponse {
+        records: records_response?,
+        limit: page_limit(limit),
+    })
+}
+
+fn query_tally_checkpoints<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TallyCheckpointsResponse> {
+    if poll_read(&deps.storage)
+        .may_load(&poll_id.to_be_bytes())?
+        .is_none()
+    {
+        return Err(StdError::generic_err("Poll does not exist"));
+    }
+
+    let checkpoints = read_tally_checkpoints(&deps.storage, poll_id, start_after, limit)?;
+    Ok(TallyCheckpointsResponse {
+        checkpoints,
+        limit: page_limit(limit),
+    })
+}
+
+fn query_poll_events<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+) -> StdResult<PollEventsResponse> {
+    if poll_read(&deps.storage)
+        .may_load(&poll_id.to_be_bytes())?
+        .is_none()
+    {
+        return Err(StdError::generic_err("Poll does not exist"));
+    }
+
+    Ok(PollEventsResponse {
+        poll_id,
+        events: poll_event_read(&deps.storage)
+            .may_load(&poll_id.to_be_bytes())?
+            .unwrap_or_default(),
+    })
+}
+
+fn query_deposit_ledger<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+) -> StdResult<DepositLedgerResponse> {
+    let poll = match poll_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(StdError::generic_err("Poll does not exist")),
+    };
+
+    let ledger = read_deposit_ledger(&deps.storage, &poll)?;
+    Ok(DepositLedgerResponse {
+        poll_id,
+        depositor: deps.api.human_address(&poll.creator)?,
+        amount: ledger.amount,
+        share: ledger.share,
+        status: ledger.status,
+        settled_height: ledger.settled_height,
+        claimable_refund: ledger.claimable_refund,
+    })
+}
+
+fn query_poll_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+) -> StdResult<PollDepositResponse> {
+    let poll = match poll_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(StdError::generic_err("Poll does not exist")),
+    };
+
+    let ledger = read_deposit_ledger(&deps.storage, &poll)?;
+    Ok(PollDepositResponse {
+        poll_id,
+        amount: ledger.amount,
+        status: ledger.status,
+        destination: match ledger.destination {
+            Some(destination) => Some(deps.api.human_address(&destination)?),
+            None => None,
+        },
+        settled_height: ledger.settled_height,
+    })
+}
+
+fn query_simulate_execution<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+) -> StdResult<SimulateExecutionResponse> {
+    let poll = match poll_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(StdError::generic_err("Poll does not exist")),
+    };
+
+    Ok(SimulateExecutionResponse {
+        poll_id,
+        messages: poll_execute_messages(&deps, &poll)?,
+        status: poll.status,
+        executable_height: poll.end_height + poll.timelock_period,
+    })
+}
+
+fn query_poll_tally<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    poll_id: u64,
+) -> StdResult<PollTallyResponse> {
+    let poll = match poll_read(&deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(StdError::generic_err("Poll does not exist")),
+    };
+
+    if poll.status == PollStatus::InProgress || poll.status == PollStatus::PendingSponsorship {
+        return Err(StdError::generic_err("Poll is in progress"));
+    }
+
+    Ok(PollTallyResponse {
+        poll_id,
+        tally: encode_tally(&poll)?,
+        voters_root: poll.voters_root,
     })
 }
+
+fn query_scheduled_config_update<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ScheduledConfigUpdateResponse> {
+    match scheduled_config_update_read(&deps.storage).may_load()? {
+        Some(scheduled) => Ok(ScheduledConfigUpdateResponse {
+            changes: scheduled.changes,
+            activation_height: scheduled.activation_height,
+        }),
+        None => Err(StdError::generic_err("No config update is scheduled")),
+    }
+}
+
+fn query_operator<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    staker: HumanAddr,
+    operator: HumanAddr,
+) -> StdResult<OperatorResponse> {
+    let staker_raw = deps.api.canonical_address(&staker)?;
+    let operator_raw = deps.api.canonical_address(&operator)?;
+    let expires = operator_read(&deps.storage, &staker_raw).may_load(operator_raw.as_slice())?;
+
+    Ok(OperatorResponse {
+        staker,
+        operator,
+        expires,
+    })
+}
+
+fn raw_storage_response(key: Vec<u8>, value: Option<Vec<u8>>) -> RawStorageResponse {
+    RawStorageResponse {
+        key: hex::encode(key),
+        value: value.map(Binary),
+    }
+}
+
+fn export_response(records: Vec<(String, Vec<u8>)>, limit: Option<u32>) -> ExportResponse {
+    ExportResponse {
+        records: records
+            .into_iter()
+            .map(|(key, value)| ExportRecord {
+                key,
+                value: Binary(value),
+            })
+            .collect(),
+        limit: page_limit(limit),
+    }
+}
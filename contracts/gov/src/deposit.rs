@@ -0,0 +1,242 @@
+use crate::state::{
+    deposit_ledger_read, deposit_ledger_store, read_polls, DepositLedger, Poll, State,
+};
+
+use anchor_token::common::OrderBy;
+use anchor_token::gov::{DepositStatus, PollStatus, RejectedReason};
+use anchor_token::math::mul_decimal;
+use cosmwasm_std::{
+    CanonicalAddr, Decimal, ReadonlyStorage, StdError, StdResult, Storage, Uint128,
+};
+
+/// Opens the ledger entry of a new poll. Token deposits are held apart from
+/// the staked balance until the poll leaves the InProgress status
+pub fn lock_deposit<S: Storage>(storage: &mut S, state: &mut State, poll: &Poll) -> StdResult<()> {
+    if poll.deposit_share.is_none() && poll.deposit_stake_share.is_none() {
+        state.total_deposit += poll.deposit_amount;
+    }
+
+    deposit_ledger_store(storage).save(
+        &poll.id.to_be_bytes(),
+        &DepositLedger {
+            amount: poll.deposit_amount,
+            share: poll.deposit_share,
+            status: DepositStatus::Locked,
+            settled_height: None,
+            claimable_refund: None,
+            destination: None,
+        },
+    )
+}
+
+/// Settles the deposit of a poll leaving the InProgress status; a slashed
+/// token deposit stays in the contract and is shared by the stakers
+pub fn settle_deposit<S: Storage>(
+    storage: &mut S,
+    state: &mut State,
+    poll: &Poll,
+    status: DepositStatus,
+    destination: Option<CanonicalAddr>,
+    height: u64,
+) -> StdResult<()> {
+    let mut ledger = read_deposit_ledger(storage, poll)?;
+    if ledger.status != DepositStatus::Locked {
+        return Err(StdError::generic_err("Poll deposit is already settled"));
+    }
+
+    if ledger.share.is_none() {
+        assert_deposit_delta(state, ledger.amount)?;
+        state.total_deposit = (state.total_deposit - ledger.amount)
+            .map_err(|_| StdError::generic_err("Total deposit is less than the poll deposit"))?;
+    }
+
+    ledger.status = status;
+    ledger.settled_height = Some(height);
+    ledger.destination = destination;
+    deposit_ledger_store(storage).save(&poll.id.to_be_bytes(), &ledger)
+}
+
+/// Receiver of the refunded deposit; a deposit made in staked shares
+/// goes back to the creator's stake
+pub fn refund_destination(poll: &Poll) -> CanonicalAddr {
+    if poll.deposit_share.is_some() {
+        poll.creator.clone()
+    } else {
+        poll.refund_recipient().clone()
+    }
+}
+
+/// Holds back the partial refund of a slashed token deposit until the refund
+/// recipient claims it; the refund stays counted in the total deposit meanwhile
+pub fn hold_partial_refund<S: Storage>(
+    storage: &mut S,
+    state: &mut State,
+    poll: &Poll,
+    refund_ratio: Decimal,
+) -> StdResult<Uint128> {
+    let mut ledger = read_deposit_ledger(storage, poll)?;
+    if ledger.status != DepositStatus::Slashed || ledger.share.is_some() {
+        return Err(StdError::generic_err(
+            "Only a slashed token deposit can be partially refunded",
+        ));
+    }
+
+    let refund = mul_decimal(ledger.amount, refund_ratio)?;
+    state.total_deposit += refund;
+    state.unclaimed_refund += refund;
+
+    ledger.claimable_refund = Some(refund);
+    deposit_ledger_store(storage).save(&poll.id.to_be_bytes(), &ledger)?;
+    Ok(refund)
+}
+
+/// Releases the partial refund of the poll deposit for payout
+pub fn take_partial_refund<S: Storage>(
+    storage: &mut S,
+    state: &mut State,
+    poll: &Poll,
+) -> StdResult<Uint128> {
+    let mut ledger = read_deposit_ledger(storage, poll)?;
+    let refund = match ledger.claimable_refund.take() {
+        Some(refund) => refund,
+        None => return Err(StdError::generic_err("No deposit refund to claim")),
+    };
+
+    assert_deposit_delta(state, Uint128::zero())?;
+    state.total_deposit = (state.total_deposit - refund)?;
+    state.unclaimed_refund = (state.unclaimed_refund - refund)?;
+
+    deposit_ledger_store(storage).save(&poll.id.to_be_bytes(), &ledger)?;
+    Ok(refund)
+}
+
+/// Adds the vote reward sent along the poll deposit to the total deposit,
+/// so it is not counted as staked balance
+pub fn lock_vote_reward(state: &mut State, poll: &mut Poll, amount: Uint128) {
+    poll.vote_reward = amount;
+    state.vote_reward_pool += amount;
+    state.total_deposit += amount;
+}
+
+/// Takes the amount paid out from the vote reward of the poll
+pub fn release_vote_reward(state: &mut State, poll: &mut Poll, amount: Uint128) -> StdResult<()> {
+    poll.vote_reward_claimed += amount;
+    if poll.vote_reward_claimed > poll.vote_reward {
+        return Err(StdError::generic_err("Vote reward is already paid out"));
+    }
+
+    assert_deposit_delta(state, Uint128::zero())?;
+    state.vote_reward_pool = (state.vote_reward_pool - amount)?;
+    state.total_deposit = (state.total_deposit - amount)?;
+    Ok(())
+}
+
+/// Holds back the execution bounty from the deposit refund of a passed poll
+/// until the poll is executed; the bounty is counted in the total deposit meanwhile
+pub fn hold_execution_bounty(state: &mut State, poll: &Poll) {
+    state.execution_bounty_pool += poll.execution_bounty;
+    state.total_deposit += poll.execution_bounty;
+}
+
+/// Releases the execution bounty of the poll for payout
+pub fn release_execution_bounty(state: &mut State, poll: &Poll) -> StdResult<Uint128> {
+    assert_deposit_delta(state, Uint128::zero())?;
+    state.execution_bounty_pool = (state.execution_bounty_pool - poll.execution_bounty)?;
+    state.total_deposit = (state.total_deposit - poll.execution_bounty)?;
+    Ok(poll.execution_bounty)
+}
+
+/// Asserts the total deposit covers the partial refunds, vote rewards and
+/// execution bounties it holds plus the token deposit leaving it, so a drift
+/// is caught on the poll transition releasing a deposit without a scan
+fn assert_deposit_delta(state: &State, amount: Uint128) -> StdResult<()> {
+    let held_deposit =
+        state.unclaimed_refund + state.vote_reward_pool + state.execution_bounty_pool + amount;
+    if state.total_deposit < held_deposit {
+        return Err(StdError::generic_err(format!(
+            "Total deposit {} does not cover the held deposits {}",
+            state.total_deposit, held_deposit
+        )));
+    }
+
+    Ok(())
+}
+
+/// Sums the unstaked token deposits of the polls in progress or pending
+/// sponsorship plus the unclaimed partial refunds, vote rewards and execution
+/// bounties, which the total deposit tracks as they change; scans every such
+/// poll, so it is only used outside of the poll transitions, which check the
+/// deposit they release with assert_deposit_delta instead
+pub fn load_locked_deposit<S: ReadonlyStorage>(storage: &S, state: &State) -> StdResult<Uint128> {
+    let mut locked_deposit =
+        state.unclaimed_refund + state.vote_reward_pool + state.execution_bounty_pool;
+    for status in [PollStatus::PendingSponsorship, PollStatus::InProgress].iter() {
+        let mut start_after: Option<u64> = None;
+        loop {
+            let polls = read_polls(
+                storage,
+                Some(status.clone()),
+                start_after,
+                None,
+                Some(OrderBy::Asc),
+            )?;
+
+            let last_poll = match polls.last() {
+                Some(poll) => poll.id,
+                None => break,
+            };
+
+            for a_poll in polls {
+                if a_poll.deposit_share.is_none() && a_poll.deposit_stake_share.is_none() {
+                    locked_deposit += a_poll.deposit_amount;
+                }
+            }
+
+            start_after = Some(last_poll);
+        }
+    }
+
+    Ok(locked_deposit)
+}
+
+/// Asserts the total deposit equals the deposits locked by the polls, so no
+/// deposit is counted as staked balance or the other way round
+pub fn assert_total_deposit<S: ReadonlyStorage>(storage: &S, state: &State) -> StdResult<()> {
+    let locked_deposit = load_locked_deposit(storage, state)?;
+    if locked_deposit != state.total_deposit {
+        return Err(StdError::generic_err(format!(
+            "Total deposit {} does not match the locked deposits {}",
+            state.total_deposit, locked_deposit
+        )));
+    }
+
+    Ok(())
+}
+
+/// Loads the ledger entry of the poll; polls created before the ledger
+/// was introduced are settled according to their status
+pub fn read_deposit_ledger<S: ReadonlyStorage>(
+    storage: &S,
+    poll: &Poll,
+) -> StdResult<DepositLedger> {
+    if let Some(ledger) = deposit_ledger_read(storage).may_load(&poll.id.to_be_bytes())? {
+        return Ok(ledger);
+    }
+
+    let (status, destination) = match (&poll.status, &poll.rejected_reason) {
+        (PollStatus::PendingSponsorship, _) | (PollStatus::InProgress, _) => {
+            (DepositStatus::Locked, None)
+        }
+        (_, Some(RejectedReason::QuorumNotReached)) => (DepositStatus::Slashed, None),
+        _ => (DepositStatus::Refunded, Some(refund_destination(poll))),
+    };
+
+    Ok(DepositLedger {
+        amount: poll.deposit_amount,
+        share: poll.deposit_share,
+        status,
+        settled_height: None,
+        claimable_refund: None,
+        destination,
+    })
+}
@@ -0,0 +1,327 @@
+use crate::deposit::release_execution_bounty;
+use crate::state::{
+    config_read, emergency_action_read, emergency_action_store, next_emergency_action_id,
+    page_limit, poll_indexer_store, poll_store, read_emergency_actions, state_read, state_store,
+    Config, EmergencyActionRecord, EmergencyCouncilRaw, PausableContractRaw, Poll, State,
+};
+
+use anchor_token::common::OrderBy;
+use anchor_token::gov::{
+    EmergencyAction, EmergencyActionResponse, EmergencyActionsResponse, EmergencyCouncil,
+    PausableContract, PollStatus, RejectedReason,
+};
+use cosmwasm_std::{
+    log, to_binary, Api, CanonicalAddr, CosmosMsg, Env, Extern, HandleResponse, HandleResult,
+    HumanAddr, LogAttribute, Querier, StdError, StdResult, Storage, WasmMsg,
+};
+use cw20::Cw20HandleMsg;
+
+/// Blocks a pending emergency action can be approved for after its submission
+pub const EMERGENCY_ACTION_PERIOD: u64 = 14400;
+
+/// Converts the council of a config update, a zero threshold disables it
+pub fn emergency_council_raw<A: Api>(
+    api: &A,
+    council: EmergencyCouncil,
+) -> StdResult<Option<EmergencyCouncilRaw>> {
+    if council.threshold == 0 {
+        return Ok(None);
+    }
+
+    if council.threshold > council.members.len() as u64 {
+        return Err(StdError::generic_err(
+            "Emergency council threshold must be 1 to the number of members",
+        ));
+    }
+
+    let mut members: Vec<CanonicalAddr> = vec![];
+    for member in council.members.iter() {
+        let member = api.canonical_address(member)?;
+        if members.contains(&member) {
+            return Err(StdError::generic_err("Duplicate emergency council member"));
+        }
+        members.push(member);
+    }
+
+    Ok(Some(EmergencyCouncilRaw {
+        members,
+        threshold: council.threshold,
+        pausable_contracts: council
+            .pausable_contracts
+            .into_iter()
+            .map(|contract| {
+                Ok(PausableContractRaw {
+                    contract_addr: api.canonical_address(&contract.contract_addr)?,
+                    pause_msg: contract.pause_msg,
+                })
+            })
+            .collect::<StdResult<Vec<PausableContractRaw>>>()?,
+    }))
+}
+
+pub fn emergency_council_normal<A: Api>(
+    api: &A,
+    council: &EmergencyCouncilRaw,
+) -> StdResult<EmergencyCouncil> {
+    Ok(EmergencyCouncil {
+        members: council
+            .members
+            .iter()
+            .map(|member| api.human_address(member))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        threshold: council.threshold,
+        pausable_contracts: council
+            .pausable_contracts
+            .iter()
+            .map(|contract| {
+                Ok(PausableContract {
+                    contract_addr: api.human_address(&contract.contract_addr)?,
+                    pause_msg: contract.pause_msg.clone(),
+                })
+            })
+            .collect::<StdResult<Vec<PausableContract>>>()?,
+    })
+}
+
+pub fn assert_not_emergency_paused<S: Storage>(storage: &S) -> StdResult<()> {
+    let state: State = state_read(storage).load()?;
+    if state.emergency_paused {
+        return Err(StdError::generic_err("Paused by the emergency council"));
+    }
+
+    Ok(())
+}
+
+pub fn submit_emergency_action<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    action: EmergencyAction,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let council = load_council(&config)?;
+    let sender = deps.api.canonical_address(&env.message.sender)?;
+    if !council.members.contains(&sender) {
+        return Err(StdError::unauthorized());
+    }
+
+    if let EmergencyAction::PauseContract { contract_addr } = &action {
+        find_pausable_contract(&deps.api, council, contract_addr)?;
+    }
+
+    let mut record = EmergencyActionRecord {
+        id: next_emergency_action_id(&deps.storage)?,
+        action,
+        proposer: sender.clone(),
+        approvals: vec![sender],
+        submitted_height: env.block.height,
+        executed_height: None,
+    };
+
+    let mut logs = vec![
+        log("action", "submit_emergency_action"),
+        log("action_id", record.id),
+        log("emergency_action", record.action.to_string()),
+        log("proposer", env.message.sender.as_str()),
+    ];
+    let messages = approve_and_execute(deps, &env, council, &mut record, &mut logs)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: None,
+    })
+}
+
+pub fn approve_emergency_action<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    action_id: u64,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    let council = load_council(&config)?;
+    let sender = deps.api.canonical_address(&env.message.sender)?;
+    if !council.members.contains(&sender) {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut record = emergency_action_read(&deps.storage).load(&action_id.to_be_bytes())?;
+    if record.executed_height.is_some() {
+        return Err(StdError::generic_err("Emergency action already executed"));
+    }
+
+    if record.submitted_height + EMERGENCY_ACTION_PERIOD < env.block.height {
+        return Err(StdError::generic_err("Emergency action has expired"));
+    }
+
+    if record.approvals.contains(&sender) {
+        return Err(StdError::generic_err("Emergency action already approved"));
+    }
+    record.approvals.push(sender);
+
+    let mut logs = vec![
+        log("action", "approve_emergency_action"),
+        log("action_id", action_id),
+        log("emergency_action", record.action.to_string()),
+        log("approver", env.message.sender.as_str()),
+    ];
+    let messages = approve_and_execute(deps, &env, council, &mut record, &mut logs)?;
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: None,
+    })
+}
+
+fn load_council(config: &Config) -> StdResult<&EmergencyCouncilRaw> {
+    match &config.emergency_council {
+        Some(council) => Ok(council),
+        None => Err(StdError::generic_err("Emergency council is not set")),
+    }
+}
+
+fn find_pausable_contract<A: Api>(
+    api: &A,
+    council: &EmergencyCouncilRaw,
+    contract_addr: &HumanAddr,
+) -> StdResult<PausableContractRaw> {
+    let contract_addr = api.canonical_address(contract_addr)?;
+    council
+        .pausable_contracts
+        .iter()
+        .find(|contract| contract.contract_addr == contract_addr)
+        .cloned()
+        .ok_or_else(|| StdError::generic_err("Contract is not pausable by the emergency council"))
+}
+
+// Saves the record and executes its action once the approvals of
+// the current members reach the threshold
+fn approve_and_execute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    council: &EmergencyCouncilRaw,
+    record: &mut EmergencyActionRecord,
+    logs: &mut Vec<LogAttribute>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let approvals = record
+        .approvals
+        .iter()
+        .filter(|member| council.members.contains(member))
+        .count() as u64;
+    logs.push(log("approvals", approvals));
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if approvals >= council.threshold {
+        messages = execute_emergency_action(deps, council, &record.action)?;
+        record.executed_height = Some(env.block.height);
+        logs.push(log("executed", "true"));
+    }
+
+    emergency_action_store(&mut deps.storage).save(&record.id.to_be_bytes(), record)?;
+    Ok(messages)
+}
+
+fn execute_emergency_action<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    council: &EmergencyCouncilRaw,
+    action: &EmergencyAction,
+) -> StdResult<Vec<CosmosMsg>> {
+    match action {
+        EmergencyAction::PauseGov {} | EmergencyAction::UnpauseGov {} => {
+            let mut state: State = state_read(&deps.storage).load()?;
+            state.emergency_paused = *action == EmergencyAction::PauseGov {};
+            state_store(&mut deps.storage).save(&state)?;
+            Ok(vec![])
+        }
+        EmergencyAction::PauseContract { contract_addr } => {
+            let contract = find_pausable_contract(&deps.api, council, contract_addr)?;
+            Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                msg: contract.pause_msg,
+                send: vec![],
+            })])
+        }
+        EmergencyAction::VetoPoll { poll_id } => {
+            let mut a_poll: Poll = poll_store(&mut deps.storage).load(&poll_id.to_be_bytes())?;
+            if a_poll.status != PollStatus::Passed {
+                return Err(StdError::generic_err("Poll is not in passed status"));
+            }
+
+            poll_indexer_store(&mut deps.storage, &PollStatus::Passed)
+                .remove(&poll_id.to_be_bytes());
+            poll_indexer_store(&mut deps.storage, &PollStatus::Rejected)
+                .save(&poll_id.to_be_bytes(), &true)?;
+
+            a_poll.status = PollStatus::Rejected;
+            a_poll.rejected_reason = Some(RejectedReason::EmergencyVetoed);
+            poll_store(&mut deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+            let mut state: State = state_read(&deps.storage).load()?;
+            // polls passed before the counter was tracked are not counted
+            state.polls_passed = state.polls_passed.saturating_sub(1);
+            state.polls_rejected += 1;
+
+            // the execution bounty held back goes to the refund recipient
+            let mut messages: Vec<CosmosMsg> = vec![];
+            let execution_bounty = release_execution_bounty(&mut state, &a_poll)?;
+            if !execution_bounty.is_zero() {
+                let config: Config = config_read(&deps.storage).load()?;
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps.api.human_address(&config.anchor_token)?,
+                    send: vec![],
+                    msg: to_binary(&Cw20HandleMsg::Transfer {
+                        recipient: deps.api.human_address(a_poll.refund_recipient())?,
+                        amount: execution_bounty,
+                    })?,
+                }));
+            }
+
+            state_store(&mut deps.storage).save(&state)?;
+            Ok(messages)
+        }
+    }
+}
+
+pub fn query_emergency_action<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    action_id: u64,
+) -> StdResult<EmergencyActionResponse> {
+    let record = emergency_action_read(&deps.storage).load(&action_id.to_be_bytes())?;
+    emergency_action_response(&deps.api, record)
+}
+
+pub fn query_emergency_actions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<EmergencyActionsResponse> {
+    let actions = read_emergency_actions(&deps.storage, start_after, limit, order_by)?
+        .into_iter()
+        .map(|record| emergency_action_response(&deps.api, record))
+        .collect::<StdResult<Vec<EmergencyActionResponse>>>()?;
+
+    Ok(EmergencyActionsResponse {
+        actions,
+        limit: page_limit(limit),
+    })
+}
+
+fn emergency_action_response<A: Api>(
+    api: &A,
+    record: EmergencyActionRecord,
+) -> StdResult<EmergencyActionResponse> {
+    Ok(EmergencyActionResponse {
+        id: record.id,
+        action: record.action,
+        proposer: api.human_address(&record.proposer)?,
+        approvals: record
+            .approvals
+            .iter()
+            .map(|member| api.human_address(member))
+            .collect::<StdResult<Vec<HumanAddr>>>()?,
+        submitted_height: record.submitted_height,
+        expiry_height: record.submitted_height + EMERGENCY_ACTION_PERIOD,
+        executed_height: record.executed_height,
+    })
+}
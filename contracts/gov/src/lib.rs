@@ -1,12 +1,19 @@
 pub mod contract;
 
+mod deposit;
+mod emergency;
 mod querier;
+mod quorum;
 mod staking;
 mod state;
+mod tally;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod proptests;
+
 #[cfg(test)]
 mod mock_querier;
 
@@ -1,9 +1,11 @@
+use anchor_token::gov::{MigrationSnapshotResponse, QueryMsg};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_slice, to_binary, Api, CanonicalAddr, Coin, Empty, Extern, HumanAddr, Querier,
-    QuerierResult, QueryRequest, SystemError, Uint128, WasmQuery,
+    from_binary, from_slice, to_binary, Api, CanonicalAddr, Coin, Empty, Extern, HumanAddr,
+    Querier, QuerierResult, QueryRequest, SystemError, Uint128, WasmQuery,
 };
 use cosmwasm_storage::to_length_prefixed;
+use cw20::TokenInfoResponse;
 use std::collections::HashMap;
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -29,6 +31,8 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     token_querier: TokenQuerier,
+    token_infos: HashMap<HumanAddr, TokenInfoResponse>,
+    migration_querier: MigrationQuerier,
     canonical_length: usize,
 }
 
@@ -61,6 +65,12 @@ pub(crate) fn balances_to_map(
     balances_map
 }
 
+#[derive(Clone, Default)]
+pub struct MigrationQuerier {
+    // migration snapshot of the predecessor gov contract
+    snapshots: HashMap<HumanAddr, MigrationSnapshotResponse>,
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -82,6 +92,18 @@ impl WasmMockQuerier {
         match &request {
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
                 let key: &[u8] = key.as_slice();
+                if key == to_length_prefixed(b"token_info").as_slice() {
+                    return match self.token_infos.get(contract_addr) {
+                        Some(token_info) => Ok(to_binary(&to_binary(token_info).unwrap())),
+                        None => Err(SystemError::InvalidRequest {
+                            error: format!(
+                                "No token info exists for the contract {}",
+                                contract_addr
+                            ),
+                            request: key.into(),
+                        }),
+                    };
+                }
 
                 let balances: &HashMap<HumanAddr, Uint128> =
                     match self.token_querier.balances.get(contract_addr) {
@@ -128,6 +150,48 @@ impl WasmMockQuerier {
                     panic!("DO NOT ENTER HERE")
                 }
             }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    QueryMsg::MigrationSnapshot { start_after, limit } => {
+                        let snapshot = match self.migration_querier.snapshots.get(contract_addr) {
+                            Some(v) => v,
+                            None => {
+                                return Err(SystemError::InvalidRequest {
+                                    error: "Stake has not been migrated".to_string(),
+                                    request: msg.as_slice().into(),
+                                })
+                            }
+                        };
+
+                        let start = match start_after {
+                            Some(start_after) => {
+                                snapshot
+                                    .claims
+                                    .iter()
+                                    .position(|claim| claim.staker == start_after)
+                                    .unwrap()
+                                    + 1
+                            }
+                            None => 0,
+                        };
+
+                        let claims = snapshot
+                            .claims
+                            .iter()
+                            .skip(start)
+                            .take(limit.unwrap_or(10) as usize)
+                            .cloned()
+                            .collect();
+
+                        Ok(to_binary(&MigrationSnapshotResponse {
+                            claims,
+                            limit: limit.unwrap_or(10),
+                            ..snapshot.clone()
+                        }))
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
             _ => self.base.handle_query(request),
         }
     }
@@ -138,6 +202,8 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
+            token_infos: HashMap::new(),
+            migration_querier: MigrationQuerier::default(),
             canonical_length,
         }
     }
@@ -146,4 +212,20 @@ impl WasmMockQuerier {
     pub fn with_token_balances(&mut self, balances: &[(&HumanAddr, &[(&HumanAddr, &Uint128)])]) {
         self.token_querier = TokenQuerier::new(balances);
     }
+
+    // configure the token info of a cw20 contract
+    pub fn with_token_info(&mut self, contract_addr: &HumanAddr, token_info: TokenInfoResponse) {
+        self.token_infos.insert(contract_addr.clone(), token_info);
+    }
+
+    // configure the migration snapshot of the predecessor gov contract
+    pub fn with_migration_snapshot(
+        &mut self,
+        contract_addr: &HumanAddr,
+        snapshot: MigrationSnapshotResponse,
+    ) {
+        self.migration_querier
+            .snapshots
+            .insert(contract_addr.clone(), snapshot);
+    }
 }
@@ -0,0 +1,329 @@
+//! Property tests running random sequences of stake, vote, withdraw and end poll
+//! operations against the contract and checking the tally and share invariants
+//! after each of them
+
+use crate::contract::{handle, init};
+use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
+use crate::state::{bank_read, poll_read, poll_voter_read, state_read, Poll, State};
+
+use anchor_token::common::OrderBy;
+use anchor_token::gov::{Cw20HookMsg, HandleMsg, InitMsg, PollStatus, VoteOption};
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, to_binary, CosmosMsg, Decimal, Extern, HandleResponse, HumanAddr, ReadonlyStorage,
+    StdResult, Storage, Uint128, WasmMsg,
+};
+use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
+use proptest::prelude::*;
+
+const VOTING_TOKEN: &str = "voting_token";
+const CREATOR: &str = "creator";
+const VOTERS: [&str; 3] = ["voter1", "voter2", "voter3"];
+const VOTING_PERIOD: u64 = 100u64;
+const PROPOSAL_DEPOSIT: u128 = 1000u128;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Stake {
+        voter: usize,
+        amount: u128,
+    },
+    Withdraw {
+        voter: usize,
+        amount: Option<u128>,
+    },
+    CreatePoll,
+    Vote {
+        voter: usize,
+        poll_id: u64,
+        yes: bool,
+        amount: Option<u128>,
+    },
+    EndPoll {
+        poll_id: u64,
+    },
+    AdvanceBlocks(u64),
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..VOTERS.len(), 1..1_000_000u128).prop_map(|(voter, amount)| Op::Stake { voter, amount }),
+        (0..VOTERS.len(), proptest::option::of(1..1_000_000u128))
+            .prop_map(|(voter, amount)| Op::Withdraw { voter, amount }),
+        Just(Op::CreatePoll),
+        (
+            0..VOTERS.len(),
+            1..5u64,
+            any::<bool>(),
+            proptest::option::of(1..1_000_000u128)
+        )
+            .prop_map(|(voter, poll_id, yes, amount)| Op::Vote {
+                voter,
+                poll_id,
+                yes,
+                amount
+            }),
+        (1..5u64).prop_map(|poll_id| Op::EndPoll { poll_id }),
+        (1..VOTING_PERIOD).prop_map(Op::AdvanceBlocks),
+    ]
+}
+
+type MockDeps = Extern<MockStorage, MockApi, WasmMockQuerier>;
+
+/// Contract under test along with the token balance it holds
+struct Harness {
+    deps: MockDeps,
+    balance: u128,
+    height: u64,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let mut deps = mock_dependencies(20, &[]);
+        let msg = InitMsg {
+            quorum: Decimal::percent(30),
+            threshold: Decimal::percent(50),
+            voting_period: VOTING_PERIOD,
+            timelock_period: 0,
+            expiration_period: 0,
+            proposal_deposit: Uint128(PROPOSAL_DEPOSIT),
+            snapshot_period: 10,
+            reveal_period: 0,
+            min_vote_amount: Uint128::zero(),
+            quorum_excluded_addresses: vec![],
+            tally_checkpoint_interval: 0,
+            poll_retention_period: 0,
+            max_poll_extension: 0,
+            narrow_pass_margin: Decimal::zero(),
+            adaptive_quorum: None,
+            poll_sponsorship: None,
+            executor_whitelist: vec![],
+            partial_refund_ratio: Decimal::zero(),
+            veto_threshold: Decimal::zero(),
+            emergency_council: None,
+            max_active_votes: 0,
+            reject_same_block_votes: false,
+            quorum_presets: vec![],
+        };
+        let env = mock_env(CREATOR, &[]);
+        init(&mut deps, env.clone(), msg).unwrap();
+        handle(
+            &mut deps,
+            env,
+            HandleMsg::RegisterContracts {
+                anchor_token: HumanAddr::from(VOTING_TOKEN),
+            },
+        )
+        .unwrap();
+
+        Harness {
+            deps,
+            balance: 0,
+            height: 0,
+        }
+    }
+
+    fn set_balance(&mut self, balance: u128) {
+        self.balance = balance;
+        self.deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(balance))],
+        )]);
+    }
+
+    /// Applies the operation; a failed operation is reverted like an aborted
+    /// transaction, so only successful operations change the contract. A panic
+    /// is a contract bug and fails the property
+    fn apply(&mut self, op: &Op) {
+        let (sender, received, msg) = match op {
+            Op::Stake { voter, amount } => (
+                VOTING_TOKEN,
+                *amount,
+                receive_msg(VOTERS[*voter], *amount, &Cw20HookMsg::StakeVotingTokens {}),
+            ),
+            Op::Withdraw { voter, amount } => (
+                VOTERS[*voter],
+                0,
+                HandleMsg::WithdrawVotingTokens {
+                    amount: amount.map(Uint128),
+                },
+            ),
+            Op::CreatePoll => (
+                VOTING_TOKEN,
+                PROPOSAL_DEPOSIT,
+                receive_msg(CREATOR, PROPOSAL_DEPOSIT, &create_poll_hook()),
+            ),
+            Op::Vote {
+                voter,
+                poll_id,
+                yes,
+                amount,
+            } => (
+                VOTERS[*voter],
+                0,
+                HandleMsg::CastVote {
+                    poll_id: *poll_id,
+                    vote: if *yes {
+                        VoteOption::Yes
+                    } else {
+                        VoteOption::No
+                    },
+                    amount: amount.map(Uint128),
+                    voter: None,
+                },
+            ),
+            Op::EndPoll { poll_id } => (CREATOR, 0, HandleMsg::EndPoll { poll_id: *poll_id }),
+            Op::AdvanceBlocks(blocks) => {
+                self.height += blocks;
+                return;
+            }
+        };
+
+        let storage_snapshot = snapshot(&self.deps.storage);
+        let prev_balance = self.balance;
+        self.set_balance(prev_balance + received);
+
+        let mut env = mock_env(sender, &[]);
+        env.block.height = self.height;
+        match handle(&mut self.deps, env, msg) {
+            Ok(res) => {
+                // the contract never pays out more than it holds
+                let sent = sent_tokens(&res);
+                assert!(sent <= self.balance);
+                self.set_balance(self.balance - sent);
+            }
+            Err(_) => {
+                self.deps.storage = restore(storage_snapshot);
+                self.set_balance(prev_balance);
+            }
+        }
+    }
+
+    fn check_invariants(&self) -> StdResult<()> {
+        let storage = &self.deps.storage;
+        let state: State = state_read(storage).load()?;
+
+        // the deposits of the polls in progress are held apart from the stake
+        let polls: Vec<Poll> = poll_read(storage)
+            .range(None, None, OrderBy::Asc.into())
+            .map(|item| item.map(|(_, poll)| poll))
+            .collect::<StdResult<Vec<Poll>>>()?;
+        let locked_deposit: u128 = polls
+            .iter()
+            .filter(|poll| poll.status == PollStatus::InProgress)
+            .map(|poll| poll.deposit_amount.u128())
+            .sum();
+        assert_eq!(state.total_deposit.u128(), locked_deposit);
+        assert!(self.balance >= state.total_deposit.u128());
+
+        // every share is held by a staker
+        let staker_share: u128 = bank_read(storage)
+            .range(None, None, OrderBy::Asc.into())
+            .map(|item| item.map(|(_, token_manager)| token_manager.share.u128()))
+            .collect::<StdResult<Vec<u128>>>()?
+            .iter()
+            .sum();
+        assert_eq!(
+            state.total_share.u128(),
+            staker_share + state.deposit_stake_share.u128()
+        );
+
+        // voter records of ended polls are pruned on withdrawal
+        for poll in polls
+            .iter()
+            .filter(|poll| poll.status == PollStatus::InProgress)
+        {
+            // the tally is the sum of the recorded votes
+            let mut yes_votes = 0u128;
+            let mut no_votes = 0u128;
+            for item in poll_voter_read(storage, poll.id).range(None, None, OrderBy::Asc.into()) {
+                let (_, voter_info) = item?;
+                match voter_info.vote {
+                    VoteOption::Yes => yes_votes += voter_info.balance.u128(),
+                    _ => no_votes += voter_info.balance.u128(),
+                }
+            }
+            assert_eq!(poll.yes_votes.u128(), yes_votes);
+            assert_eq!(poll.no_votes.u128(), no_votes);
+
+            // votes are locked stake, so can't outweigh the staked balance
+            let staked_balance = self.balance - state.total_deposit.u128();
+            assert!(yes_votes + no_votes <= staked_balance);
+        }
+
+        Ok(())
+    }
+}
+
+fn receive_msg(sender: &str, amount: u128, hook_msg: &Cw20HookMsg) -> HandleMsg {
+    HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(sender),
+        amount: Uint128(amount),
+        msg: Some(to_binary(hook_msg).unwrap()),
+    })
+}
+
+fn create_poll_hook() -> Cw20HookMsg {
+    Cw20HookMsg::CreatePoll {
+        title: "test".to_string(),
+        description: "test".to_string(),
+        links: None,
+        execute_msgs: None,
+        commit_reveal: None,
+        content_hash: None,
+        referendum: None,
+        stake_refund: None,
+        reserved_poll_id: None,
+        refund_address: None,
+        option_labels: None,
+        vote_reward: None,
+        execution_bounty: None,
+        on_reject_msgs: None,
+        preset: None,
+    }
+}
+
+// voting tokens leaving the contract with the response messages
+fn sent_tokens(res: &HandleResponse) -> u128 {
+    res.messages
+        .iter()
+        .map(|msg| match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) if contract_addr.as_str() == VOTING_TOKEN => match from_binary(msg).unwrap() {
+                Cw20HandleMsg::Transfer { amount, .. } | Cw20HandleMsg::Send { amount, .. } => {
+                    amount.u128()
+                }
+                _ => 0,
+            },
+            _ => 0,
+        })
+        .sum()
+}
+
+fn snapshot(storage: &MockStorage) -> Vec<(Vec<u8>, Vec<u8>)> {
+    storage
+        .range(None, None, cosmwasm_std::Order::Ascending)
+        .collect()
+}
+
+fn restore(snapshot: Vec<(Vec<u8>, Vec<u8>)>) -> MockStorage {
+    let mut storage = MockStorage::new();
+    for (k, v) in snapshot {
+        storage.set(&k, &v);
+    }
+    storage
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn tally_invariants(ops in proptest::collection::vec(op_strategy(), 1..60)) {
+        let mut harness = Harness::new();
+        for op in ops.iter() {
+            harness.apply(op);
+            harness.check_invariants().unwrap();
+        }
+    }
+}
@@ -4,6 +4,7 @@ use cosmwasm_std::{
 };
 
 use cosmwasm_storage::to_length_prefixed;
+use cw20::TokenInfoResponse;
 
 pub fn load_token_balance<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
@@ -25,6 +26,18 @@ pub fn load_token_balance<S: Storage, A: Api, Q: Querier>(
     from_binary(&res)
 }
 
+pub fn load_token_info<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    contract_addr: &HumanAddr,
+) -> StdResult<TokenInfoResponse> {
+    let res: Binary = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Raw {
+        contract_addr: HumanAddr::from(contract_addr),
+        key: Binary::from(to_length_prefixed(b"token_info")),
+    }))?;
+
+    from_binary(&res)
+}
+
 #[inline]
 fn concat(namespace: &[u8], key: &[u8]) -> Vec<u8> {
     let mut k = namespace.to_vec();
@@ -0,0 +1,67 @@
+use crate::state::{Config, State};
+
+use anchor_token::gov::AdaptiveQuorum;
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128};
+
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000u128;
+
+/// Quorum required of the polls; with the adaptive quorum enabled it is the
+/// average turnout of the recently ended polls, bounded to the configured range
+pub fn effective_quorum(config: &Config, state: &State) -> Decimal {
+    match &config.adaptive_quorum {
+        Some(adaptive_quorum) => adaptive_quorum_value(adaptive_quorum, state, config.quorum),
+        None => config.quorum,
+    }
+}
+
+/// Average turnout of the recently ended polls bounded to the range of the
+/// adaptive quorum, the given quorum until a poll ended
+pub fn adaptive_quorum_value(
+    adaptive_quorum: &AdaptiveQuorum,
+    state: &State,
+    quorum: Decimal,
+) -> Decimal {
+    state
+        .turnout_ema
+        .unwrap_or(quorum)
+        .max(adaptive_quorum.min_quorum)
+        .min(adaptive_quorum.max_quorum)
+}
+
+/// Folds the turnout of an ended poll into the exponential moving average,
+/// weighting it by 2 / (poll_window + 1); the average starts from the quorum
+pub fn record_turnout(config: &Config, state: &mut State, turnout: Decimal) {
+    let adaptive_quorum = match &config.adaptive_quorum {
+        Some(adaptive_quorum) => adaptive_quorum,
+        None => return,
+    };
+
+    let scale = Uint128(DECIMAL_FRACTIONAL);
+    let turnout = (turnout.min(Decimal::one()) * scale).u128();
+    let average = (state.turnout_ema.unwrap_or(config.quorum) * scale).u128();
+    let window = adaptive_quorum.poll_window as u128;
+
+    state.turnout_ema = Some(Decimal::from_ratio(
+        2 * turnout + (window - 1) * average,
+        (window + 1) * DECIMAL_FRACTIONAL,
+    ));
+}
+
+/// Returns the adaptive quorum to store, a zero poll window disables it
+pub fn validate_adaptive_quorum(
+    adaptive_quorum: AdaptiveQuorum,
+) -> StdResult<Option<AdaptiveQuorum>> {
+    if adaptive_quorum.poll_window == 0 {
+        return Ok(None);
+    }
+
+    if adaptive_quorum.min_quorum > adaptive_quorum.max_quorum
+        || adaptive_quorum.max_quorum > Decimal::one()
+    {
+        return Err(StdError::generic_err(
+            "adaptive quorum bounds must be 0 to 1 with min not above max",
+        ));
+    }
+
+    Ok(Some(adaptive_quorum))
+}
@@ -1,19 +1,31 @@
+use crate::deposit::{
+    assert_total_deposit, refund_destination, release_vote_reward, settle_deposit,
+};
 use crate::querier::load_token_balance;
 use crate::state::{
-    bank_read, bank_store, config_read, config_store, poll_read, poll_voter_store, state_read,
-    state_store, Config, Poll, State, TokenManager,
+    bank_read, bank_store, config_read, config_store, import_progress_read, import_progress_store,
+    locked_vote_store, migration_read, migration_store, page_limit, poll_indexer_store, poll_read,
+    poll_store, poll_voter_store, read_locked_votes, read_polls, read_share_snapshot,
+    read_sponsor_locks, read_stakers, read_total_share_snapshot, sponsor_lock_store, state_read,
+    state_store, store_share_snapshot, store_total_share_snapshot, Config, ImportProgress,
+    Migration, Poll, State, TokenManager,
 };
 
-use anchor_token::gov::{PollStatus, StakerResponse};
+use anchor_token::common::OrderBy;
+use anchor_token::gov::{
+    DepositStatus, MigrationSnapshotResponse, PollStatus, QueryMsg, ShareAccountingResponse,
+    StakerClaim, StakerResponse, StakingSnapshotResponse, VoterInfo, WithdrawableResponse,
+};
+use anchor_token::math::{mul_ratio, mul_ratio_ceil};
 use cosmwasm_std::{
     log, to_binary, Api, CanonicalAddr, CosmosMsg, Env, Extern, HandleResponse, HandleResult,
-    HumanAddr, Querier, StdError, StdResult, Storage, Uint128, WasmMsg,
+    HumanAddr, Querier, QueryRequest, StdError, StdResult, Storage, Uint128, WasmMsg, WasmQuery,
 };
 use cw20::Cw20HandleMsg;
 
 pub fn stake_voting_tokens<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
+    env: Env,
     sender: HumanAddr,
     amount: Uint128,
 ) -> HandleResult {
@@ -38,14 +50,22 @@ pub fn stake_voting_tokens<S: Storage, A: Api, Q: Querier>(
     let share = if total_balance.is_zero() || state.total_share.is_zero() {
         amount
     } else {
-        amount.multiply_ratio(state.total_share, total_balance)
+        mul_ratio(amount, state.total_share, total_balance)?
     };
 
     token_manager.share += share;
+    token_manager.last_stake_height = env.block.height;
     state.total_share += share;
 
     state_store(&mut deps.storage).save(&state)?;
     bank_store(&mut deps.storage).save(key, &token_manager)?;
+    store_share_snapshot(
+        &mut deps.storage,
+        &sender_address_raw,
+        env.block.height,
+        token_manager.share,
+    )?;
+    store_total_share_snapshot(&mut deps.storage, env.block.height, state.total_share)?;
 
     Ok(HandleResponse {
         messages: vec![],
@@ -59,6 +79,246 @@ pub fn stake_voting_tokens<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+// Stake tokens sent by the vesting contract on behalf of the staker;
+// the staked amount is locked until the vesting contract unlocks it.
+pub fn stake_vesting_tokens<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    staker: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    let mut res = stake_voting_tokens(deps, env, staker.clone(), amount)?;
+
+    let staker_raw = deps.api.canonical_address(&staker)?;
+    let key = staker_raw.as_slice();
+    let mut token_manager = bank_read(&deps.storage).load(key)?;
+    token_manager.vesting_locked_balance += amount;
+    bank_store(&mut deps.storage).save(key, &token_manager)?;
+
+    res.log.push(log(
+        "vesting_locked_balance",
+        token_manager.vesting_locked_balance.to_string(),
+    ));
+    Ok(res)
+}
+
+// Unlock vested tokens, only vesting contract can execute this
+pub fn unlock_vesting_tokens<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    staker: HumanAddr,
+    amount: Uint128,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    if config.vesting_contract != Some(deps.api.canonical_address(&env.message.sender)?) {
+        return Err(StdError::unauthorized());
+    }
+
+    let staker_raw = deps.api.canonical_address(&staker)?;
+    let key = staker_raw.as_slice();
+    let mut token_manager = match bank_read(&deps.storage).may_load(key)? {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Nothing staked")),
+    };
+
+    token_manager.vesting_locked_balance = (token_manager.vesting_locked_balance - amount)?;
+    bank_store(&mut deps.storage).save(key, &token_manager)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        data: None,
+        log: vec![
+            log("action", "unlock_vesting_tokens"),
+            log("staker", staker.as_str()),
+            log("amount", amount.to_string()),
+        ],
+    })
+}
+
+// Lock the proposer's staked tokens as a proposal deposit
+// and returns the locked share.
+pub fn lock_deposit_share<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    proposer: &CanonicalAddr,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    let key = proposer.as_slice();
+    let mut token_manager = match bank_read(&deps.storage).may_load(key)? {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Nothing staked")),
+    };
+
+    let config: Config = config_read(&deps.storage).load()?;
+    let state: State = state_read(&deps.storage).load()?;
+    let total_balance = (load_token_balance(
+        &deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &state.contract_addr,
+    )? - state.total_deposit)?;
+
+    if total_balance.is_zero() || state.total_share.is_zero() {
+        return Err(StdError::generic_err("Nothing staked"));
+    }
+
+    // round up, so the locked share always covers the deposit amount
+    let share = mul_ratio_ceil(amount, state.total_share, total_balance)?;
+
+    if (token_manager.share - token_manager.deposit_locked_share)? < share {
+        return Err(StdError::generic_err(
+            "User does not have enough staked tokens.",
+        ));
+    }
+
+    token_manager.deposit_locked_share += share;
+    bank_store(&mut deps.storage).save(key, &token_manager)?;
+
+    Ok(share)
+}
+
+// Stake a refunded token deposit for the poll creator; the deposit is
+// already held by the contract, so only the share is issued.
+pub fn stake_deposit_refund<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    config: &Config,
+    state: &mut State,
+    creator: &CanonicalAddr,
+    amount: Uint128,
+    height: u64,
+) -> StdResult<Uint128> {
+    // the refunded deposit is still counted in the total deposit
+    let total_balance = (load_token_balance(
+        &deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &state.contract_addr,
+    )? - state.total_deposit)?;
+
+    let share = if total_balance.is_zero() || state.total_share.is_zero() {
+        amount
+    } else {
+        mul_ratio(amount, state.total_share, total_balance)?
+    };
+
+    let key = creator.as_slice();
+    let mut token_manager = bank_read(&deps.storage).may_load(key)?.unwrap_or_default();
+    token_manager.share += share;
+    state.total_share += share;
+
+    bank_store(&mut deps.storage).save(key, &token_manager)?;
+    store_share_snapshot(&mut deps.storage, creator, height, token_manager.share)?;
+    store_total_share_snapshot(&mut deps.storage, height, state.total_share)?;
+
+    Ok(share)
+}
+
+// Stake a token deposit on behalf of no staker when the community contract
+// is set, so its share appreciation during the poll goes to the community
+// instead of the other stakers
+pub fn stake_poll_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    config: &Config,
+    state: &mut State,
+    amount: Uint128,
+    height: u64,
+) -> StdResult<Option<Uint128>> {
+    if config.community_contract.is_none() {
+        return Ok(None);
+    }
+
+    // balance already increased, so subtract deposit amount
+    let total_balance = (load_token_balance(
+        &deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &state.contract_addr,
+    )? - (state.total_deposit + amount))?;
+
+    let share = if total_balance.is_zero() || state.total_share.is_zero() {
+        amount
+    } else {
+        mul_ratio(amount, state.total_share, total_balance)?
+    };
+
+    state.total_share += share;
+    state.deposit_stake_share += share;
+    store_total_share_snapshot(&mut deps.storage, height, state.total_share)?;
+
+    Ok(Some(share))
+}
+
+// Unstake the deposit of a poll leaving the InProgress status, so it is held
+// apart as a token deposit again; the appreciation over the deposit amount is
+// sent to the community contract. The share is priced against the given staked
+// balance; the sent appreciation is returned and stays in the total deposit
+// until the caller is done pricing shares.
+pub fn unstake_poll_deposit<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    config: &Config,
+    state: &mut State,
+    a_poll: &mut Poll,
+    total_balance: Uint128,
+    height: u64,
+) -> StdResult<(Uint128, Vec<CosmosMsg>)> {
+    let share = match a_poll.deposit_stake_share {
+        Some(share) => share,
+        None => return Ok((Uint128::zero(), vec![])),
+    };
+
+    let value = mul_ratio(share, total_balance, state.total_share)?;
+    state.total_share = (state.total_share - share)?;
+    state.deposit_stake_share = (state.deposit_stake_share - share)?;
+    state.total_deposit += a_poll.deposit_amount;
+    a_poll.deposit_stake_share = None;
+    store_total_share_snapshot(&mut deps.storage, height, state.total_share)?;
+
+    // the appreciation stays with the stakers if the community contract was unset
+    let community_contract = match &config.community_contract {
+        Some(community_contract) => deps.api.human_address(community_contract)?,
+        None => return Ok((Uint128::zero(), vec![])),
+    };
+
+    let interest = (value - a_poll.deposit_amount).unwrap_or_else(|_| Uint128::zero());
+    if interest.is_zero() {
+        return Ok((Uint128::zero(), vec![]));
+    }
+
+    state.total_deposit += interest;
+    Ok((
+        interest,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: community_contract,
+                amount: interest,
+            })?,
+        })],
+    ))
+}
+
+// Release the share locked as a proposal deposit.
+// When `slash` is set, the share is burned and its value is
+// distributed to the other stakers.
+pub fn release_deposit_share<S: Storage>(
+    storage: &mut S,
+    state: &mut State,
+    creator: &CanonicalAddr,
+    share: Uint128,
+    slash: bool,
+    height: u64,
+) -> StdResult<()> {
+    let key = creator.as_slice();
+    let mut token_manager = bank_read(storage).load(key)?;
+    token_manager.deposit_locked_share = (token_manager.deposit_locked_share - share)?;
+
+    if slash {
+        token_manager.share = (token_manager.share - share)?;
+        state.total_share = (state.total_share - share)?;
+        store_share_snapshot(storage, creator, height, token_manager.share)?;
+        store_total_share_snapshot(storage, height, state.total_share)?;
+    }
+
+    bank_store(storage).save(key, &token_manager)
+}
+
 // Withdraw amount if not staked. By default all funds will be withdrawn.
 pub fn withdraw_voting_tokens<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
@@ -81,16 +341,36 @@ pub fn withdraw_voting_tokens<S: Storage, A: Api, Q: Querier>(
         )? - state.total_deposit)?
             .u128();
 
-        let locked_balance = compute_locked_balance(deps, &mut token_manager, &sender_address_raw)?;
-        let locked_share = locked_balance * total_share / total_balance;
+        let vote_locked_balance = compute_locked_balance(deps, &sender_address_raw)?;
+        let locked_share = compute_locked_share(
+            &token_manager,
+            vote_locked_balance,
+            total_share,
+            total_balance,
+        )?;
         let user_share = token_manager.share.u128();
 
-        let withdraw_share = amount
-            .map(|v| std::cmp::max(v.multiply_ratio(total_share, total_balance).u128(), 1u128))
-            .unwrap_or_else(|| user_share - locked_share);
-        let withdraw_amount = amount
-            .map(|v| v.u128())
-            .unwrap_or_else(|| withdraw_share * total_balance / total_share);
+        let (withdraw_share, withdraw_amount) = match amount {
+            Some(amount) => (
+                std::cmp::max(
+                    mul_ratio(amount, Uint128(total_share), Uint128(total_balance))?.u128(),
+                    1u128,
+                ),
+                amount.u128(),
+            ),
+            None => {
+                let withdraw_share = user_share.saturating_sub(locked_share);
+                (
+                    withdraw_share,
+                    mul_ratio(
+                        Uint128(withdraw_share),
+                        Uint128(total_balance),
+                        Uint128(total_share),
+                    )?
+                    .u128(),
+                )
+            }
+        };
 
         if locked_share + withdraw_share > user_share {
             Err(StdError::generic_err(
@@ -105,6 +385,14 @@ pub fn withdraw_voting_tokens<S: Storage, A: Api, Q: Querier>(
             state.total_share = Uint128::from(total_share - withdraw_share);
             state_store(&mut deps.storage).save(&state)?;
 
+            store_share_snapshot(
+                &mut deps.storage,
+                &sender_address_raw,
+                env.block.height,
+                token_manager.share,
+            )?;
+            store_total_share_snapshot(&mut deps.storage, env.block.height, state.total_share)?;
+
             send_tokens(
                 &deps.api,
                 &config.anchor_token,
@@ -119,32 +407,108 @@ pub fn withdraw_voting_tokens<S: Storage, A: Api, Q: Querier>(
 }
 
 // removes not in-progress poll voter info & unlock tokens
-// and returns the largest locked amount in participated polls.
+// and returns the largest locked amount in participated or sponsored polls.
 fn compute_locked_balance<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    token_manager: &mut TokenManager,
     voter: &CanonicalAddr,
 ) -> StdResult<u128> {
-    // filter out not in-progress polls
-    token_manager.locked_balance.retain(|(poll_id, _)| {
-        let poll: Poll = poll_read(&deps.storage)
-            .load(&poll_id.to_be_bytes())
+    let vote_locked_balance = prune_locked_balance(deps, voter)?
+        .iter()
+        .map(|(_, v)| v.balance.u128())
+        .max()
+        .unwrap_or_default();
+    let sponsor_locked_balance = prune_sponsor_locks(deps, voter)?
+        .iter()
+        .map(|(_, v)| v.u128())
+        .max()
+        .unwrap_or_default();
+
+    Ok(std::cmp::max(vote_locked_balance, sponsor_locked_balance))
+}
+
+// removes the sponsor locks of the polls no longer pending sponsorship
+// or in progress and returns the remaining ones
+fn prune_sponsor_locks<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    sponsor: &CanonicalAddr,
+) -> StdResult<Vec<(u64, Uint128)>> {
+    let mut sponsor_locks = read_sponsor_locks(&deps.storage, sponsor)?;
+    sponsor_locks.retain(|(poll_id, _)| {
+        let locked = is_poll_locking(&deps.storage, *poll_id);
+        if !locked {
+            sponsor_lock_store(&mut deps.storage, sponsor).remove(&poll_id.to_be_bytes());
+        }
+
+        locked
+    });
+
+    Ok(sponsor_locks)
+}
+
+fn is_poll_locking<S: Storage>(storage: &S, poll_id: u64) -> bool {
+    let poll: Option<Poll> = poll_read(storage).may_load(&poll_id.to_be_bytes()).unwrap();
+    poll.map_or(false, |poll| {
+        poll.status == PollStatus::PendingSponsorship || poll.status == PollStatus::InProgress
+    })
+}
+
+// removes the locks and the voter info of the polls no longer in progress
+// and returns the remaining locks; only the removed entries are written
+pub fn prune_locked_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    voter: &CanonicalAddr,
+) -> StdResult<Vec<(u64, VoterInfo)>> {
+    let mut locked_votes = read_locked_votes(&deps.storage, voter)?;
+
+    // filter out not in-progress polls; pruned polls are no longer stored
+    locked_votes.retain(|(poll_id, _)| {
+        let poll: Option<Poll> = poll_read(&deps.storage)
+            .may_load(&poll_id.to_be_bytes())
             .unwrap();
+        // the voter info of a poll with a vote reward is kept until the reward is claimed
+        let (in_progress, vote_reward) = poll.map_or((false, false), |poll| {
+            (
+                poll.status == PollStatus::InProgress,
+                !poll.vote_reward.is_zero(),
+            )
+        });
+
+        if !in_progress {
+            locked_vote_store(&mut deps.storage, voter).remove(&poll_id.to_be_bytes());
+        }
 
-        if poll.status != PollStatus::InProgress {
+        if !in_progress && !vote_reward {
             // remove voter info from the poll
             poll_voter_store(&mut deps.storage, *poll_id).remove(&voter.as_slice());
         }
 
-        poll.status == PollStatus::InProgress
+        in_progress
     });
 
-    Ok(token_manager
-        .locked_balance
-        .iter()
-        .map(|(_, v)| v.balance.u128())
-        .max()
-        .unwrap_or_default())
+    Ok(locked_votes)
+}
+
+// tokens locked by votes can also be locked by vesting,
+// so the larger one needs to stay staked on top of the deposit locked share;
+// rounded up, so the share left staked is always worth the locked tokens
+fn compute_locked_share(
+    token_manager: &TokenManager,
+    vote_locked_balance: u128,
+    total_share: u128,
+    total_balance: u128,
+) -> StdResult<u128> {
+    let locked_balance = std::cmp::max(
+        vote_locked_balance,
+        token_manager.vesting_locked_balance.u128(),
+    );
+
+    Ok(mul_ratio_ceil(
+        Uint128(locked_balance),
+        Uint128(total_share),
+        Uint128(total_balance),
+    )?
+    .u128()
+        + token_manager.deposit_locked_share.u128())
 }
 
 fn send_tokens<A: Api>(
@@ -180,38 +544,493 @@ fn send_tokens<A: Api>(
 pub fn query_staker<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     address: HumanAddr,
+    token_balance: Option<Uint128>,
 ) -> StdResult<StakerResponse> {
     let addr_raw = deps.api.canonical_address(&address).unwrap();
     let config: Config = config_read(&deps.storage).load()?;
     let state: State = state_read(&deps.storage).load()?;
-    let mut token_manager = bank_read(&deps.storage)
+    let token_manager = bank_read(&deps.storage)
         .may_load(addr_raw.as_slice())?
         .unwrap_or_default();
 
-    // filter out not in-progress polls
-    token_manager.locked_balance.retain(|(poll_id, _)| {
-        let poll: Poll = poll_read(&deps.storage)
-            .load(&poll_id.to_be_bytes())
+    // filter out not in-progress polls; pruned polls are no longer stored
+    let mut locked_balance = read_locked_votes(&deps.storage, &addr_raw)?;
+    locked_balance.retain(|(poll_id, _)| {
+        let poll: Option<Poll> = poll_read(&deps.storage)
+            .may_load(&poll_id.to_be_bytes())
             .unwrap();
 
-        poll.status == PollStatus::InProgress
+        poll.map_or(false, |poll| poll.status == PollStatus::InProgress)
     });
 
-    let total_balance = (load_token_balance(
-        &deps,
-        &deps.api.human_address(&config.anchor_token)?,
-        &state.contract_addr,
-    )? - state.total_deposit)?;
+    let token_balance = match token_balance {
+        Some(token_balance) => token_balance,
+        None => load_token_balance(
+            &deps,
+            &deps.api.human_address(&config.anchor_token)?,
+            &state.contract_addr,
+        )?,
+    };
+    let total_balance = (token_balance - state.total_deposit)?;
 
     Ok(StakerResponse {
         balance: if !state.total_share.is_zero() {
-            token_manager
-                .share
-                .multiply_ratio(total_balance, state.total_share)
+            mul_ratio(token_manager.share, total_balance, state.total_share)?
         } else {
             Uint128::zero()
         },
         share: token_manager.share,
-        locked_balance: token_manager.locked_balance,
+        locked_balance,
+        vesting_locked_balance: token_manager.vesting_locked_balance,
+        deposit_locked_share: token_manager.deposit_locked_share,
+    })
+}
+
+pub fn query_withdrawable<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+) -> StdResult<WithdrawableResponse> {
+    let addr_raw = deps.api.canonical_address(&address)?;
+    let config: Config = config_read(&deps.storage).load()?;
+    let state: State = state_read(&deps.storage).load()?;
+    let token_manager = bank_read(&deps.storage)
+        .may_load(addr_raw.as_slice())?
+        .unwrap_or_default();
+
+    let total_share = state.total_share.u128();
+    let total_balance = (load_token_balance(
+        &deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &state.contract_addr,
+    )? - state.total_deposit)?
+        .u128();
+    if total_share == 0 || total_balance == 0 {
+        return Ok(WithdrawableResponse {
+            share: Uint128::zero(),
+            amount: Uint128::zero(),
+        });
+    }
+
+    // same as compute_locked_balance, without pruning the finished polls
+    let vote_locked_balance = read_locked_votes(&deps.storage, &addr_raw)?
+        .iter()
+        .filter(|(poll_id, _)| {
+            let poll: Option<Poll> = poll_read(&deps.storage)
+                .may_load(&poll_id.to_be_bytes())
+                .unwrap();
+
+            poll.map_or(false, |poll| poll.status == PollStatus::InProgress)
+        })
+        .map(|(_, v)| v.balance.u128())
+        .chain(
+            read_sponsor_locks(&deps.storage, &addr_raw)?
+                .iter()
+                .filter(|(poll_id, _)| is_poll_locking(&deps.storage, *poll_id))
+                .map(|(_, v)| v.u128()),
+        )
+        .max()
+        .unwrap_or_default();
+
+    let locked_share = compute_locked_share(
+        &token_manager,
+        vote_locked_balance,
+        total_share,
+        total_balance,
+    )?;
+    let withdraw_share = token_manager.share.u128().saturating_sub(locked_share);
+
+    Ok(WithdrawableResponse {
+        share: Uint128::from(withdraw_share),
+        amount: mul_ratio(
+            Uint128(withdraw_share),
+            Uint128(total_balance),
+            Uint128(total_share),
+        )?,
+    })
+}
+
+pub fn query_staking_snapshot<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+    height: u64,
+) -> StdResult<StakingSnapshotResponse> {
+    let addr_raw = deps.api.canonical_address(&address)?;
+
+    Ok(StakingSnapshotResponse {
+        share: read_share_snapshot(&deps.storage, &addr_raw, height)?,
+        total_share: read_total_share_snapshot(&deps.storage, height)?,
+    })
+}
+
+pub fn query_share_accounting<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<ShareAccountingResponse> {
+    let state: State = state_read(&deps.storage).load()?;
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.canonical_address(&start_after)?)
+    } else {
+        None
+    };
+
+    let stakers = read_stakers(&deps.storage, start_after, limit)?;
+    let mut page_share = Uint128::zero();
+    let mut page_deposit_locked_share = Uint128::zero();
+    for (_, token_manager) in stakers.iter() {
+        page_share += token_manager.share;
+        page_deposit_locked_share += token_manager.deposit_locked_share;
+    }
+
+    let last_staker = match stakers.last() {
+        Some((staker, _)) => Some(deps.api.human_address(staker)?),
+        None => None,
+    };
+
+    Ok(ShareAccountingResponse {
+        total_share: state.total_share,
+        page_share,
+        page_deposit_locked_share,
+        stakers: stakers.len() as u32,
+        last_staker,
+        limit: page_limit(limit),
+    })
+}
+
+// Transfer the whole staked balance to the successor contract; in progress polls
+// are cancelled with their deposits refunded, a page of them per call, and the
+// stakers' shares are frozen as the claim snapshot the successor imports once
+// none is left. Stake, withdrawals and votes are frozen between the calls. The
+// partial refunds, vote rewards and execution bounties stay in the contract,
+// where they can still be claimed.
+pub fn migrate_stake<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    new_gov_contract: HumanAddr,
+    limit: Option<u32>,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    if config.owner != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    let mut state: State = state_read(&deps.storage).load()?;
+    state.migration_in_progress = true;
+    let mut total_balance = (load_token_balance(
+        &deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &state.contract_addr,
+    )? - state.total_deposit)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut remaining = page_limit(limit);
+    let mut cancelled_polls = 0u32;
+    // polls pending sponsorship hold deposits too
+    for status in [PollStatus::PendingSponsorship, PollStatus::InProgress].iter() {
+        // cancelled polls leave the status index, so every page starts from the first poll
+        let polls = read_polls(
+            &deps.storage,
+            Some(status.clone()),
+            None,
+            Some(remaining),
+            Some(OrderBy::Asc),
+        )?;
+        remaining -= polls.len() as u32;
+        cancelled_polls += polls.len() as u32;
+
+        for mut a_poll in polls {
+            // deposit stakes are taken out of the balance sent to the successor
+            if a_poll.deposit_stake_share.is_some() {
+                let (deposit_interest, interest_msgs) = unstake_poll_deposit(
+                    deps,
+                    &config,
+                    &mut state,
+                    &mut a_poll,
+                    total_balance,
+                    env.block.height,
+                )?;
+                total_balance = (total_balance - (a_poll.deposit_amount + deposit_interest))?;
+                state.total_deposit = (state.total_deposit - deposit_interest)?;
+                messages.extend(interest_msgs);
+            }
+
+            if let Some(deposit_share) = a_poll.deposit_share {
+                release_deposit_share(
+                    &mut deps.storage,
+                    &mut state,
+                    &a_poll.creator,
+                    deposit_share,
+                    false,
+                    env.block.height,
+                )?;
+            } else if !a_poll.deposit_amount.is_zero() {
+                // the stake leaves for the successor, so refunds are never staked
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps.api.human_address(&config.anchor_token)?,
+                    send: vec![],
+                    msg: to_binary(&Cw20HandleMsg::Transfer {
+                        recipient: deps.api.human_address(a_poll.refund_recipient())?,
+                        amount: a_poll.deposit_amount,
+                    })?,
+                }));
+            }
+
+            // a funded escrow goes back with the deposit
+            let refund_recipient = deps.api.human_address(a_poll.refund_recipient())?;
+            if let Some(escrow) = a_poll.escrow.as_mut().filter(|escrow| escrow.funded) {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps.api.human_address(&escrow.token)?,
+                    send: vec![],
+                    msg: to_binary(&Cw20HandleMsg::Transfer {
+                        recipient: refund_recipient,
+                        amount: escrow.amount,
+                    })?,
+                }));
+                escrow.funded = false;
+            }
+
+            let vote_reward = a_poll.vote_reward;
+            if !vote_reward.is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps.api.human_address(&config.anchor_token)?,
+                    send: vec![],
+                    msg: to_binary(&Cw20HandleMsg::Transfer {
+                        recipient: deps.api.human_address(a_poll.refund_recipient())?,
+                        amount: vote_reward,
+                    })?,
+                }));
+                release_vote_reward(&mut state, &mut a_poll, vote_reward)?;
+            }
+
+            settle_deposit(
+                &mut deps.storage,
+                &mut state,
+                &a_poll,
+                DepositStatus::Refunded,
+                Some(refund_destination(&a_poll)),
+                env.block.height,
+            )?;
+
+            poll_indexer_store(&mut deps.storage, status).remove(&a_poll.id.to_be_bytes());
+            poll_indexer_store(&mut deps.storage, &PollStatus::Rejected)
+                .save(&a_poll.id.to_be_bytes(), &true)?;
+
+            a_poll.status = PollStatus::Rejected;
+            poll_store(&mut deps.storage).save(&a_poll.id.to_be_bytes(), &a_poll)?;
+            state.polls_rejected += 1;
+        }
+
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    let polls_left = [PollStatus::PendingSponsorship, PollStatus::InProgress]
+        .iter()
+        .map(|status| read_polls(&deps.storage, Some(status.clone()), None, Some(1), None))
+        .collect::<StdResult<Vec<Vec<Poll>>>>()?
+        .iter()
+        .any(|polls| !polls.is_empty());
+    if polls_left {
+        state_store(&mut deps.storage).save(&state)?;
+        return Ok(HandleResponse {
+            messages,
+            data: None,
+            log: vec![
+                log("action", "migrate_stake"),
+                log("cancelled_polls", cancelled_polls),
+            ],
+        });
+    }
+
+    assert_total_deposit(&deps.storage, &state)?;
+
+    if !total_balance.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: new_gov_contract.clone(),
+                amount: total_balance,
+            })?,
+        }));
+    }
+
+    state_store(&mut deps.storage).save(&state)?;
+    migration_store(&mut deps.storage).save(&Migration {
+        new_gov_contract: deps.api.canonical_address(&new_gov_contract)?,
+        total_balance,
+        total_share: state.total_share,
+    })?;
+
+    Ok(HandleResponse {
+        messages,
+        data: None,
+        log: vec![
+            log("action", "migrate_stake"),
+            log("new_gov_contract", new_gov_contract.as_str()),
+            log("amount", total_balance.to_string()),
+        ],
+    })
+}
+
+// Credit the next page of stakers from the predecessor's claim snapshot.
+// The share for the whole snapshot is minted on the first import, so the
+// balance of the existing stakers is not affected by the pending claims.
+pub fn import_stake<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    old_gov_contract: HumanAddr,
+    limit: Option<u32>,
+) -> HandleResult {
+    let config: Config = config_read(&deps.storage).load()?;
+    if config.owner != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    let old_gov_contract_raw = deps.api.canonical_address(&old_gov_contract)?;
+    let progress = import_progress_read(&deps.storage).may_load()?;
+    let start_after = match &progress {
+        Some(progress) => {
+            if progress.old_gov_contract != old_gov_contract_raw {
+                return Err(StdError::generic_err(
+                    "Import from another contract is in progress",
+                ));
+            }
+
+            if let Some(last_staker) = &progress.last_staker {
+                Some(deps.api.human_address(last_staker)?)
+            } else {
+                None
+            }
+        }
+        None => None,
+    };
+
+    let snapshot: MigrationSnapshotResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: old_gov_contract,
+            msg: to_binary(&QueryMsg::MigrationSnapshot { start_after, limit })?,
+        }))?;
+
+    if snapshot.new_gov_contract != env.contract.address {
+        return Err(StdError::generic_err(
+            "Stake was not migrated to this contract",
+        ));
+    }
+
+    if snapshot.claims.is_empty() {
+        return Err(StdError::generic_err("Nothing to import"));
+    }
+
+    let mut state: State = state_read(&deps.storage).load()?;
+    let mut progress = match progress {
+        Some(progress) => progress,
+        None => {
+            // migrated tokens are already transferred, so exclude them
+            let total_balance = (load_token_balance(
+                &deps,
+                &deps.api.human_address(&config.anchor_token)?,
+                &state.contract_addr,
+            )? - (state.total_deposit + snapshot.total_balance))?;
+
+            let import_total_share = if total_balance.is_zero() || state.total_share.is_zero() {
+                snapshot.total_balance
+            } else {
+                mul_ratio(snapshot.total_balance, state.total_share, total_balance)?
+            };
+
+            state.total_share += import_total_share;
+            ImportProgress {
+                old_gov_contract: old_gov_contract_raw,
+                old_total_share: snapshot.total_share,
+                import_total_share,
+                last_staker: None,
+            }
+        }
+    };
+
+    let mut imported_share = Uint128::zero();
+    for claim in snapshot.claims.iter() {
+        let staker_raw = deps.api.canonical_address(&claim.staker)?;
+        let key = staker_raw.as_slice();
+        let mut token_manager = bank_read(&deps.storage).may_load(key)?.unwrap_or_default();
+
+        let share = mul_ratio(
+            claim.share,
+            progress.import_total_share,
+            progress.old_total_share,
+        )?;
+        token_manager.share += share;
+        token_manager.vesting_locked_balance += claim.vesting_locked_balance;
+        bank_store(&mut deps.storage).save(key, &token_manager)?;
+        store_share_snapshot(
+            &mut deps.storage,
+            &staker_raw,
+            env.block.height,
+            token_manager.share,
+        )?;
+
+        imported_share += share;
+        progress.last_staker = Some(staker_raw);
+    }
+
+    state_store(&mut deps.storage).save(&state)?;
+    store_total_share_snapshot(&mut deps.storage, env.block.height, state.total_share)?;
+    import_progress_store(&mut deps.storage).save(&progress)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        data: None,
+        log: vec![
+            log("action", "import_stake"),
+            log("stakers", snapshot.claims.len()),
+            log("share", imported_share.to_string()),
+        ],
+    })
+}
+
+pub fn query_migration_snapshot<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<MigrationSnapshotResponse> {
+    let migration: Migration = match migration_read(&deps.storage).may_load()? {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Stake has not been migrated")),
+    };
+
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.canonical_address(&start_after)?)
+    } else {
+        None
+    };
+
+    let claims: StdResult<Vec<StakerClaim>> = read_stakers(&deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(staker, token_manager)| {
+            Ok(StakerClaim {
+                staker: deps.api.human_address(&staker)?,
+                share: token_manager.share,
+                balance: if migration.total_share.is_zero() {
+                    Uint128::zero()
+                } else {
+                    mul_ratio(
+                        token_manager.share,
+                        migration.total_balance,
+                        migration.total_share,
+                    )?
+                },
+                vesting_locked_balance: token_manager.vesting_locked_balance,
+            })
+        })
+        .collect();
+
+    Ok(MigrationSnapshotResponse {
+        new_gov_contract: deps.api.human_address(&migration.new_gov_contract)?,
+        total_balance: migration.total_balance,
+        total_share: migration.total_share,
+        claims: claims?,
+        limit: page_limit(limit),
     })
 }
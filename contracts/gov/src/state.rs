@@ -1,22 +1,43 @@
 use cosmwasm_std::{Binary, CanonicalAddr, Decimal, ReadonlyStorage, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
-    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
-    Singleton,
+    bucket, bucket_read, singleton, singleton_read, to_length_prefixed, Bucket, PrefixedStorage,
+    ReadonlyBucket, ReadonlyPrefixedStorage, ReadonlySingleton, Singleton,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use anchor_token::common::OrderBy;
-use anchor_token::gov::{PollStatus, VoterInfo};
+use anchor_token::common::{Expiration, OrderBy};
+use anchor_token::gov::{
+    AdaptiveQuorum, ConfigChange, ConfigUpdate, DepositStatus, EmergencyAction, ParamValue,
+    PollEvent, PollEventKind, PollLink, PollSponsorship, PollStatus, QuorumPreset, Referendum,
+    RejectedReason, TallyCheckpoint, TokenInfoCache, VoterInfo,
+};
 use std::cmp::Ordering;
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_STATE: &[u8] = b"state";
+static KEY_MIGRATION: &[u8] = b"migration";
+static KEY_IMPORT_PROGRESS: &[u8] = b"import_progress";
+static KEY_SCHEDULED_CONFIG_UPDATE: &[u8] = b"scheduled_config_update";
 
 static PREFIX_POLL_INDEXER: &[u8] = b"poll_indexer";
 static PREFIX_POLL_VOTER: &[u8] = b"poll_voter";
 static PREFIX_POLL: &[u8] = b"poll";
 static PREFIX_BANK: &[u8] = b"bank";
+static PREFIX_LOCKED_VOTE: &[u8] = b"locked_vote";
+static PREFIX_SPONSOR_LOCK: &[u8] = b"sponsor_lock";
+static PREFIX_POLL_COMMIT: &[u8] = b"poll_commit";
+static PREFIX_CONFIG_HISTORY: &[u8] = b"config_history";
+static PREFIX_TALLY_CHECKPOINT: &[u8] = b"tally_checkpoint";
+static PREFIX_POLL_EVENT: &[u8] = b"poll_event";
+static PREFIX_PARAM: &[u8] = b"param";
+static PREFIX_DEPOSIT_LEDGER: &[u8] = b"deposit_ledger";
+static PREFIX_SHARE_SNAPSHOT: &[u8] = b"share_snapshot";
+static PREFIX_TOTAL_SHARE_SNAPSHOT: &[u8] = b"total_share_snapshot";
+static PREFIX_POLL_RESERVATION: &[u8] = b"poll_reservation";
+static PREFIX_VOTED: &[u8] = b"voted";
+static PREFIX_EMERGENCY_ACTION: &[u8] = b"emergency_action";
+static PREFIX_OPERATOR: &[u8] = b"operator";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -29,6 +50,41 @@ pub struct Config {
     pub expiration_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub vesting_contract: Option<CanonicalAddr>,
+    pub reveal_period: u64,
+    pub min_vote_amount: Uint128,
+    // stakers not expected to vote, excluded from the quorum calculation
+    pub quorum_excluded_addresses: Vec<CanonicalAddr>,
+    // number of votes between tally checkpoints, zero disables them
+    pub tally_checkpoint_interval: u64,
+    // blocks a finished poll is kept after its end height before it can be pruned
+    pub poll_retention_period: u64,
+    // most blocks the voting period of a poll can be extended by, zero disables it
+    pub max_poll_extension: u64,
+    // a poll passing with a turnout below quorum + margin is flagged as a narrow pass
+    pub narrow_pass_margin: Decimal,
+    // when set, token deposits are staked while their poll is in progress
+    // and the appreciation of the stake is sent here at resolution
+    pub community_contract: Option<CanonicalAddr>,
+    // when set, the quorum follows the turnout of the recently ended polls
+    pub adaptive_quorum: Option<AdaptiveQuorum>,
+    // when set, new polls wait for co-sponsors before their voting period starts
+    pub poll_sponsorship: Option<PollSponsorship>,
+    // addresses allowed to execute passed polls, anyone when empty
+    pub executor_whitelist: Vec<CanonicalAddr>,
+    // part of a token deposit refunded when its poll fails the quorum
+    pub partial_refund_ratio: Decimal,
+    // a poll with more no with veto votes than this share of the tally is vetoed
+    pub veto_threshold: Decimal,
+    // members jointly executing the emergency actions, disabled when none
+    pub emergency_council: Option<EmergencyCouncilRaw>,
+    // most polls in progress a staker can have votes locked in, zero for no limit
+    pub max_active_votes: u64,
+    // votes are rejected in the block of the voter's last stake
+    pub reject_same_block_votes: bool,
+    // named parameters a poll can be created with instead of the ones above
+    pub quorum_presets: Vec<QuorumPreset>,
+    pub token_info: Option<TokenInfoCache>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,12 +93,113 @@ pub struct State {
     pub poll_count: u64,
     pub total_share: Uint128,
     pub total_deposit: Uint128,
+    // counters of all votes cast, by stake or referendum, and of the distinct voters
+    pub total_votes: u64,
+    pub unique_voters: u64,
+    pub polls_passed: u64,
+    pub polls_rejected: u64,
+    pub polls_executed: u64,
+    // share minted for the staked token deposits, held by no staker
+    pub deposit_stake_share: Uint128,
+    // average turnout of the polls ended with the adaptive quorum enabled
+    pub turnout_ema: Option<Decimal>,
+    // stake, withdraw and votes are frozen while set
+    pub migration_in_progress: bool,
+    // partial deposit refunds held in the total deposit until claimed
+    pub unclaimed_refund: Uint128,
+    // poll creation, votes and poll execution are stopped by the emergency council
+    pub emergency_paused: bool,
+    // vote rewards of the polls not yet claimed, held in the total deposit
+    pub vote_reward_pool: Uint128,
+    // execution bounties of the passed polls not yet executed, held in the total deposit
+    pub execution_bounty_pool: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmergencyCouncilRaw {
+    pub members: Vec<CanonicalAddr>,
+    pub threshold: u64,
+    pub pausable_contracts: Vec<PausableContractRaw>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PausableContractRaw {
+    pub contract_addr: CanonicalAddr,
+    pub pause_msg: Binary,
+}
+
+/// Emergency action with the council members who approved it,
+/// executed once the approvals reach the council threshold
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmergencyActionRecord {
+    pub id: u64,
+    pub action: EmergencyAction,
+    pub proposer: CanonicalAddr,
+    pub approvals: Vec<CanonicalAddr>,
+    pub submitted_height: u64,
+    pub executed_height: Option<u64>,
+}
+
+/// Frozen totals of a contract which migrated its stake to a successor
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Migration {
+    pub new_gov_contract: CanonicalAddr,
+    pub total_balance: Uint128,
+    pub total_share: Uint128,
+}
+
+/// Progress of importing the stakers of a predecessor contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ImportProgress {
+    pub old_gov_contract: CanonicalAddr,
+    pub old_total_share: Uint128,
+    /// share minted for the whole snapshot, credited to stakers page by page
+    pub import_total_share: Uint128,
+    pub last_staker: Option<CanonicalAddr>,
 }
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TokenManager {
-    pub share: Uint128,                        // total staked balance
-    pub locked_balance: Vec<(u64, VoterInfo)>, // maps poll_id to weight voted
+    pub share: Uint128,                  // total staked balance
+    pub vesting_locked_balance: Uint128, // unvested balance staked via vesting contract
+    pub deposit_locked_share: Uint128,   // share locked as proposal deposits
+    pub last_stake_height: u64,          // height of the last stake
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollReservation {
+    pub creator: CanonicalAddr,
+    // last height the reserved id can be used to create the poll
+    pub expiry_height: u64,
+}
+
+impl PollReservation {
+    pub fn expiration(&self) -> Expiration {
+        Expiration::AtHeight(self.expiry_height + 1)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositLedger {
+    pub amount: Uint128,
+    // set when the deposit is locked from the creator's staked share
+    pub share: Option<Uint128>,
+    pub status: DepositStatus,
+    pub settled_height: Option<u64>,
+    // partial refund of a slashed token deposit, cleared once claimed
+    pub claimable_refund: Option<Uint128>,
+    // receiver of the settled deposit, unset while locked or when
+    // the slashed deposit stays in the contract for the stakers
+    pub destination: Option<CanonicalAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollEscrow {
+    pub token: CanonicalAddr,
+    pub amount: Uint128,
+    // set once the proposer sent the amount, which only the poll's
+    // on reject messages can return
+    pub funded: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -52,15 +209,127 @@ pub struct Poll {
     pub status: PollStatus,
     pub yes_votes: Uint128,
     pub no_votes: Uint128,
+    /// Share weight of the votes, used for the quorum
+    pub yes_shares: Uint128,
+    pub no_shares: Uint128,
+    /// Part of the no votes voting NoWithVeto
+    pub veto_votes: Uint128,
+    pub vote_count: u64,
     pub end_height: u64,
     pub title: String,
     pub description: String,
-    pub link: Option<String>,
+    pub links: Vec<PollLink>,
     pub execute_data: Option<Vec<ExecuteData>>,
     pub deposit_amount: Uint128,
+    /// Share locked from the creator's stake when the deposit is made in staked shares
+    pub deposit_share: Option<Uint128>,
+    /// Share minted for the token deposit while the poll is in progress
+    pub deposit_stake_share: Option<Uint128>,
     /// Total balance at the end poll
     pub total_balance_at_end_poll: Option<Uint128>,
     pub staked_amount: Option<Uint128>,
+    pub staked_share: Option<Uint128>,
+    /// Set for commit-reveal polls; votes are revealed until this height
+    pub reveal_end_height: Option<u64>,
+    /// Set when the poll is rejected at end poll
+    pub rejected_reason: Option<RejectedReason>,
+    /// Hash of the off-chain proposal document
+    pub content_hash: Option<Binary>,
+    /// Set for referendum polls voted with the snapshot balances
+    pub referendum: Option<Referendum>,
+    // the refunded token deposit is staked for the creator
+    pub stake_refund: bool,
+    // receives the refunded token deposit instead of the creator
+    pub refund_address: Option<CanonicalAddr>,
+    // hex encoded merkle root over the voter records, stored at end poll
+    pub voters_root: Option<String>,
+    // the voting period can only be extended once
+    pub extended: bool,
+    // passed with a turnout within the narrow pass margin of the quorum
+    pub narrow_pass: bool,
+    // milestones already announced by a vote, so each is logged once
+    pub quorum_reached: bool,
+    pub threshold_reached: bool,
+    // custom options of a signal poll, tallied instead of yes/no
+    pub option_labels: Vec<String>,
+    pub option_votes: Vec<Uint128>,
+    pub option_shares: Uint128,
+    // co-sponsors of a poll pending sponsorship, staking at least the min stake
+    pub required_sponsors: u64,
+    pub sponsor_min_stake: Uint128,
+    pub sponsors: Vec<CanonicalAddr>,
+    // config values in effect at the poll creation, so the poll
+    // is not judged by rules changed while it is live
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub timelock_period: u64,
+    pub expiration_period: u64,
+    // name of the quorum preset the values above were taken from
+    pub preset: Option<String>,
+    // adaptive quorum in effect at the poll creation, which sets the
+    // quorum from the average turnout when the poll ends
+    pub adaptive_quorum: Option<AdaptiveQuorum>,
+    // tokens shared by the voters once the poll ends, and the part already paid out
+    pub vote_reward: Uint128,
+    pub vote_reward_claimed: Uint128,
+    // part of the deposit paid to the executor once the poll passes
+    pub execution_bounty: Uint128,
+    // executed instead when the poll is rejected with the quorum reached
+    pub on_reject_data: Option<Vec<ExecuteData>>,
+    pub on_reject_executed: bool,
+    // cw20 funds escrowed by the proposer, returned by the on reject messages
+    pub escrow: Option<PollEscrow>,
+}
+
+impl Poll {
+    pub fn refund_recipient(&self) -> &CanonicalAddr {
+        self.refund_address.as_ref().unwrap_or(&self.creator)
+    }
+
+    /// The poll can be ended from its end height, which is also the
+    /// sponsorship deadline of a poll pending sponsorship
+    pub fn end(&self) -> Expiration {
+        Expiration::AtHeight(self.end_height)
+    }
+
+    /// Votes are cast through the end height, which the reveal period follows
+    pub fn voting_end(&self) -> Expiration {
+        Expiration::AtHeight(self.end_height + 1)
+    }
+
+    pub fn reveal_end(&self) -> Option<Expiration> {
+        self.reveal_end_height.map(Expiration::AtHeight)
+    }
+
+    pub fn timelock_end(&self) -> Expiration {
+        Expiration::AtHeight(self.end_height + self.timelock_period)
+    }
+
+    pub fn expiration(&self) -> Expiration {
+        Expiration::AtHeight(self.end_height + self.expiration_period)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduledConfigUpdate {
+    pub changes: ConfigUpdate,
+    pub activation_height: u64,
+    pub sender: CanonicalAddr,
+}
+
+impl ScheduledConfigUpdate {
+    pub fn activation(&self) -> Expiration {
+        Expiration::AtHeight(self.activation_height)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigHistoryRecord {
+    pub id: u64,
+    pub height: u64,
+    pub sender: CanonicalAddr,
+    pub changes: Vec<ConfigChange>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -105,6 +374,34 @@ pub fn state_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, State> {
     singleton_read(storage, KEY_STATE)
 }
 
+pub fn migration_store<S: Storage>(storage: &mut S) -> Singleton<S, Migration> {
+    singleton(storage, KEY_MIGRATION)
+}
+
+pub fn migration_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, Migration> {
+    singleton_read(storage, KEY_MIGRATION)
+}
+
+pub fn import_progress_store<S: Storage>(storage: &mut S) -> Singleton<S, ImportProgress> {
+    singleton(storage, KEY_IMPORT_PROGRESS)
+}
+
+pub fn import_progress_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, ImportProgress> {
+    singleton_read(storage, KEY_IMPORT_PROGRESS)
+}
+
+pub fn scheduled_config_update_store<S: Storage>(
+    storage: &mut S,
+) -> Singleton<S, ScheduledConfigUpdate> {
+    singleton(storage, KEY_SCHEDULED_CONFIG_UPDATE)
+}
+
+pub fn scheduled_config_update_read<S: Storage>(
+    storage: &S,
+) -> ReadonlySingleton<S, ScheduledConfigUpdate> {
+    singleton_read(storage, KEY_SCHEDULED_CONFIG_UPDATE)
+}
+
 pub fn poll_store<S: Storage>(storage: &mut S) -> Bucket<S, Poll> {
     bucket(PREFIX_POLL, storage)
 }
@@ -134,6 +431,45 @@ pub fn poll_voter_read<S: ReadonlyStorage>(
     ReadonlyBucket::multilevel(&[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()], storage)
 }
 
+pub fn poll_commit_store<S: Storage>(storage: &mut S, poll_id: u64) -> Bucket<S, String> {
+    Bucket::multilevel(&[PREFIX_POLL_COMMIT, &poll_id.to_be_bytes()], storage)
+}
+
+pub fn poll_commit_read<S: ReadonlyStorage>(
+    storage: &S,
+    poll_id: u64,
+) -> ReadonlyBucket<S, String> {
+    ReadonlyBucket::multilevel(&[PREFIX_POLL_COMMIT, &poll_id.to_be_bytes()], storage)
+}
+
+pub fn tally_checkpoint_store<S: Storage>(
+    storage: &mut S,
+    poll_id: u64,
+) -> Bucket<S, TallyCheckpoint> {
+    Bucket::multilevel(&[PREFIX_TALLY_CHECKPOINT, &poll_id.to_be_bytes()], storage)
+}
+
+pub fn read_tally_checkpoints<S: ReadonlyStorage>(
+    storage: &S,
+    poll_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<TallyCheckpoint>> {
+    let limit = page_limit(limit) as usize;
+    let start = calc_range_start(start_after);
+
+    let checkpoints: ReadonlyBucket<S, TallyCheckpoint> =
+        ReadonlyBucket::multilevel(&[PREFIX_TALLY_CHECKPOINT, &poll_id.to_be_bytes()], storage);
+    checkpoints
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
 pub fn read_poll_voters<'a, S: ReadonlyStorage>(
     storage: &'a S,
     poll_id: u64,
@@ -141,7 +477,7 @@ pub fn read_poll_voters<'a, S: ReadonlyStorage>(
     limit: Option<u32>,
     order_by: Option<OrderBy>,
 ) -> StdResult<Vec<(CanonicalAddr, VoterInfo)>> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = page_limit(limit) as usize;
     let (start, end, order_by) = match order_by {
         Some(OrderBy::Asc) => (calc_range_start_addr(start_after), None, OrderBy::Asc),
         _ => (None, calc_range_end_addr(start_after), OrderBy::Desc),
@@ -159,8 +495,39 @@ pub fn read_poll_voters<'a, S: ReadonlyStorage>(
         .collect()
 }
 
+pub fn poll_event_store<S: Storage>(storage: &mut S) -> Bucket<S, Vec<PollEvent>> {
+    bucket(PREFIX_POLL_EVENT, storage)
+}
+
+pub fn poll_event_read<S: ReadonlyStorage>(storage: &S) -> ReadonlyBucket<S, Vec<PollEvent>> {
+    bucket_read(PREFIX_POLL_EVENT, storage)
+}
+
+/// Appends an event to the lifecycle log of the poll
+pub fn store_poll_event<S: Storage>(
+    storage: &mut S,
+    poll_id: u64,
+    kind: PollEventKind,
+    height: u64,
+) -> StdResult<()> {
+    poll_event_store(storage).update(&poll_id.to_be_bytes(), |events| {
+        let mut events = events.unwrap_or_default();
+        events.push(PollEvent { kind, height });
+        Ok(events)
+    })?;
+
+    Ok(())
+}
+
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+
+/// Page size of the paginated queries, the requested limit clamped
+/// to `MAX_LIMIT` or `DEFAULT_LIMIT` if none is given
+pub fn page_limit(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT)
+}
+
 pub fn read_polls<'a, S: ReadonlyStorage>(
     storage: &'a S,
     filter: Option<PollStatus>,
@@ -168,7 +535,7 @@ pub fn read_polls<'a, S: ReadonlyStorage>(
     limit: Option<u32>,
     order_by: Option<OrderBy>,
 ) -> StdResult<Vec<Poll>> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let limit = page_limit(limit) as usize;
     let (start, end, order_by) = match order_by {
         Some(OrderBy::Asc) => (calc_range_start(start_after), None, OrderBy::Asc),
         _ => (None, calc_range_end(start_after), OrderBy::Desc),
@@ -201,6 +568,302 @@ pub fn read_polls<'a, S: ReadonlyStorage>(
     }
 }
 
+/// Returns up to `limit` polls below `before_poll_id` which reached a final status
+/// and ended more than `retention_period` blocks before `height`
+pub fn read_prunable_polls<S: ReadonlyStorage>(
+    storage: &S,
+    before_poll_id: u64,
+    height: u64,
+    retention_period: u64,
+    limit: usize,
+) -> StdResult<Vec<Poll>> {
+    let polls: ReadonlyBucket<S, Poll> = ReadonlyBucket::new(PREFIX_POLL, storage);
+    polls
+        .range(
+            None,
+            Some(&before_poll_id.to_be_bytes()),
+            OrderBy::Asc.into(),
+        )
+        .filter(|item| match item {
+            Ok((_, poll)) => {
+                let finished = match poll.status {
+                    PollStatus::PendingSponsorship | PollStatus::InProgress => false,
+                    // passed polls can still be executed or expired
+                    PollStatus::Passed => poll.execute_data.is_none(),
+                    _ => true,
+                };
+                finished && poll.end_height + retention_period < height
+            }
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+/// Removes up to `limit` voters, commitments and tally checkpoints of the poll
+/// and returns the number of removed entries
+pub fn remove_poll_data<S: Storage>(
+    storage: &mut S,
+    poll_id: u64,
+    limit: usize,
+) -> StdResult<usize> {
+    let mut removed: usize = 0;
+    for prefix in [
+        PREFIX_POLL_VOTER,
+        PREFIX_POLL_COMMIT,
+        PREFIX_TALLY_CHECKPOINT,
+    ]
+    .iter()
+    {
+        let keys: Vec<Vec<u8>> =
+            ReadonlyPrefixedStorage::multilevel(&[prefix, &poll_id.to_be_bytes()], storage)
+                .range(None, None, OrderBy::Asc.into())
+                .take(limit - removed)
+                .map(|(k, _)| k)
+                .collect();
+
+        let mut poll_data = PrefixedStorage::multilevel(&[prefix, &poll_id.to_be_bytes()], storage);
+        for key in keys.iter() {
+            poll_data.remove(key);
+        }
+
+        removed += keys.len();
+        if removed == limit {
+            break;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Appends a config history record and returns its id
+pub fn store_config_history<S: Storage>(
+    storage: &mut S,
+    height: u64,
+    sender: CanonicalAddr,
+    changes: Vec<ConfigChange>,
+) -> StdResult<u64> {
+    let last_id = ReadonlyBucket::<S, ConfigHistoryRecord>::new(PREFIX_CONFIG_HISTORY, storage)
+        .range(None, None, OrderBy::Desc.into())
+        .next()
+        .transpose()?
+        .map(|(_, record)| record.id)
+        .unwrap_or_default();
+
+    let id = last_id + 1;
+    let mut history: Bucket<S, ConfigHistoryRecord> = Bucket::new(PREFIX_CONFIG_HISTORY, storage);
+    history.save(
+        &id.to_be_bytes(),
+        &ConfigHistoryRecord {
+            id,
+            height,
+            sender,
+            changes,
+        },
+    )?;
+
+    Ok(id)
+}
+
+pub fn read_config_history<S: ReadonlyStorage>(
+    storage: &S,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ConfigHistoryRecord>> {
+    let limit = page_limit(limit) as usize;
+    let start = calc_range_start(start_after);
+
+    let history: ReadonlyBucket<S, ConfigHistoryRecord> =
+        ReadonlyBucket::new(PREFIX_CONFIG_HISTORY, storage);
+    history
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn emergency_action_store<S: Storage>(storage: &mut S) -> Bucket<S, EmergencyActionRecord> {
+    bucket(PREFIX_EMERGENCY_ACTION, storage)
+}
+
+pub fn emergency_action_read<S: ReadonlyStorage>(
+    storage: &S,
+) -> ReadonlyBucket<S, EmergencyActionRecord> {
+    bucket_read(PREFIX_EMERGENCY_ACTION, storage)
+}
+
+/// Id of the next emergency action, following the last submitted one
+pub fn next_emergency_action_id<S: ReadonlyStorage>(storage: &S) -> StdResult<u64> {
+    let last_id = emergency_action_read(storage)
+        .range(None, None, OrderBy::Desc.into())
+        .next()
+        .transpose()?
+        .map(|(_, record)| record.id)
+        .unwrap_or_default();
+
+    Ok(last_id + 1)
+}
+
+pub fn read_emergency_actions<S: ReadonlyStorage>(
+    storage: &S,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<EmergencyActionRecord>> {
+    let limit = page_limit(limit) as usize;
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (calc_range_start(start_after), None, OrderBy::Asc),
+        _ => (None, calc_range_end(start_after), OrderBy::Desc),
+    };
+
+    emergency_action_read(storage)
+        .range(start.as_deref(), end.as_deref(), order_by.into())
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+// expiration of the operators approved by the staker
+pub fn operator_store<'a, S: Storage>(
+    storage: &'a mut S,
+    staker: &CanonicalAddr,
+) -> Bucket<'a, S, Expiration> {
+    Bucket::multilevel(&[PREFIX_OPERATOR, staker.as_slice()], storage)
+}
+
+pub fn operator_read<'a, S: ReadonlyStorage>(
+    storage: &'a S,
+    staker: &CanonicalAddr,
+) -> ReadonlyBucket<'a, S, Expiration> {
+    ReadonlyBucket::multilevel(&[PREFIX_OPERATOR, staker.as_slice()], storage)
+}
+
+pub fn voted_store<S: Storage>(storage: &mut S) -> Bucket<S, bool> {
+    bucket(PREFIX_VOTED, storage)
+}
+
+pub fn voted_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, bool> {
+    bucket_read(PREFIX_VOTED, storage)
+}
+
+pub fn poll_reservation_store<S: Storage>(storage: &mut S) -> Bucket<S, PollReservation> {
+    bucket(PREFIX_POLL_RESERVATION, storage)
+}
+
+pub fn poll_reservation_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, PollReservation> {
+    bucket_read(PREFIX_POLL_RESERVATION, storage)
+}
+
+/// Full storage key of a poll record and the bytes stored under it
+pub fn read_poll_raw<S: ReadonlyStorage>(storage: &S, poll_id: u64) -> (Vec<u8>, Option<Vec<u8>>) {
+    read_raw(storage, PREFIX_POLL, &poll_id.to_be_bytes())
+}
+
+/// Full storage key of a staker record and the bytes stored under it
+pub fn read_bank_raw<S: ReadonlyStorage>(
+    storage: &S,
+    address: &CanonicalAddr,
+) -> (Vec<u8>, Option<Vec<u8>>) {
+    read_raw(storage, PREFIX_BANK, address.as_slice())
+}
+
+/// Addresses and stored bytes of a page of staker records
+pub fn read_bank_records<S: ReadonlyStorage>(
+    storage: &S,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> Vec<(CanonicalAddr, Vec<u8>)> {
+    read_raw_records(
+        storage,
+        &[PREFIX_BANK],
+        start_after.map(|addr| addr.as_slice().to_vec()),
+        limit,
+    )
+    .into_iter()
+    .map(|(k, v)| (CanonicalAddr::from(k), v))
+    .collect()
+}
+
+/// Poll ids and stored bytes of a page of poll records
+pub fn read_poll_records<S: ReadonlyStorage>(
+    storage: &S,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Vec<(u64, Vec<u8>)> {
+    read_raw_records(
+        storage,
+        &[PREFIX_POLL],
+        start_after.map(|id| id.to_be_bytes().to_vec()),
+        limit,
+    )
+    .into_iter()
+    .map(|(k, v)| {
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&k);
+        (u64::from_be_bytes(id), v)
+    })
+    .collect()
+}
+
+/// Addresses and stored bytes of a page of voter records of the poll
+pub fn read_voter_records<S: ReadonlyStorage>(
+    storage: &S,
+    poll_id: u64,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> Vec<(CanonicalAddr, Vec<u8>)> {
+    read_raw_records(
+        storage,
+        &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()],
+        start_after.map(|addr| addr.as_slice().to_vec()),
+        limit,
+    )
+    .into_iter()
+    .map(|(k, v)| (CanonicalAddr::from(k), v))
+    .collect()
+}
+
+// keys and stored bytes of a page of records under the namespaces, in key order
+fn read_raw_records<S: ReadonlyStorage>(
+    storage: &S,
+    namespaces: &[&[u8]],
+    start_after: Option<Vec<u8>>,
+    limit: Option<u32>,
+) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let limit = page_limit(limit) as usize;
+    let start = start_after.map(|mut key| {
+        key.push(1);
+        key
+    });
+
+    ReadonlyPrefixedStorage::multilevel(namespaces, storage)
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .collect()
+}
+
+fn read_raw<S: ReadonlyStorage>(
+    storage: &S,
+    namespace: &[u8],
+    key: &[u8],
+) -> (Vec<u8>, Option<Vec<u8>>) {
+    let mut full_key = to_length_prefixed(namespace);
+    full_key.extend_from_slice(key);
+
+    let value = storage.get(&full_key);
+    (full_key, value)
+}
+
 pub fn bank_store<S: Storage>(storage: &mut S) -> Bucket<S, TokenManager> {
     bucket(PREFIX_BANK, storage)
 }
@@ -209,6 +872,170 @@ pub fn bank_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, TokenManager> {
     bucket_read(PREFIX_BANK, storage)
 }
 
+/// Votes locking the stake of the staker keyed by poll id, kept apart from
+/// the token manager so a vote or a prune only writes the changed entries
+pub fn locked_vote_store<'a, S: Storage>(
+    storage: &'a mut S,
+    staker: &CanonicalAddr,
+) -> Bucket<'a, S, VoterInfo> {
+    Bucket::multilevel(&[PREFIX_LOCKED_VOTE, staker.as_slice()], storage)
+}
+
+pub fn locked_vote_read<'a, S: ReadonlyStorage>(
+    storage: &'a S,
+    staker: &CanonicalAddr,
+) -> ReadonlyBucket<'a, S, VoterInfo> {
+    ReadonlyBucket::multilevel(&[PREFIX_LOCKED_VOTE, staker.as_slice()], storage)
+}
+
+/// Locked votes of the staker in ascending poll id order
+pub fn read_locked_votes<S: ReadonlyStorage>(
+    storage: &S,
+    staker: &CanonicalAddr,
+) -> StdResult<Vec<(u64, VoterInfo)>> {
+    locked_vote_read(storage, staker)
+        .range(None, None, OrderBy::Asc.into())
+        .map(|item| {
+            let (k, v) = item?;
+            let mut poll_id = [0u8; 8];
+            poll_id.copy_from_slice(&k);
+            Ok((u64::from_be_bytes(poll_id), v))
+        })
+        .collect()
+}
+
+/// Stake locked by the sponsorships of the staker keyed by poll id,
+/// released like the vote locks once the poll ends
+pub fn sponsor_lock_store<'a, S: Storage>(
+    storage: &'a mut S,
+    sponsor: &CanonicalAddr,
+) -> Bucket<'a, S, Uint128> {
+    Bucket::multilevel(&[PREFIX_SPONSOR_LOCK, sponsor.as_slice()], storage)
+}
+
+/// Sponsor locks of the staker in ascending poll id order
+pub fn read_sponsor_locks<S: ReadonlyStorage>(
+    storage: &S,
+    sponsor: &CanonicalAddr,
+) -> StdResult<Vec<(u64, Uint128)>> {
+    ReadonlyBucket::multilevel(&[PREFIX_SPONSOR_LOCK, sponsor.as_slice()], storage)
+        .range(None, None, OrderBy::Asc.into())
+        .map(|item| {
+            let (k, v) = item?;
+            let mut poll_id = [0u8; 8];
+            poll_id.copy_from_slice(&k);
+            Ok((u64::from_be_bytes(poll_id), v))
+        })
+        .collect()
+}
+
+pub fn deposit_ledger_store<S: Storage>(storage: &mut S) -> Bucket<S, DepositLedger> {
+    bucket(PREFIX_DEPOSIT_LEDGER, storage)
+}
+
+pub fn deposit_ledger_read<S: ReadonlyStorage>(storage: &S) -> ReadonlyBucket<S, DepositLedger> {
+    bucket_read(PREFIX_DEPOSIT_LEDGER, storage)
+}
+
+/// Records the share of the staker from the given height on
+pub fn store_share_snapshot<S: Storage>(
+    storage: &mut S,
+    staker: &CanonicalAddr,
+    height: u64,
+    share: Uint128,
+) -> StdResult<()> {
+    Bucket::multilevel(&[PREFIX_SHARE_SNAPSHOT, staker.as_slice()], storage)
+        .save(&height.to_be_bytes(), &share)
+}
+
+/// Records the total share from the given height on
+pub fn store_total_share_snapshot<S: Storage>(
+    storage: &mut S,
+    height: u64,
+    total_share: Uint128,
+) -> StdResult<()> {
+    bucket(PREFIX_TOTAL_SHARE_SNAPSHOT, storage).save(&height.to_be_bytes(), &total_share)
+}
+
+pub fn read_share_snapshot<S: ReadonlyStorage>(
+    storage: &S,
+    staker: &CanonicalAddr,
+    height: u64,
+) -> StdResult<Uint128> {
+    read_snapshot_at(
+        ReadonlyBucket::multilevel(&[PREFIX_SHARE_SNAPSHOT, staker.as_slice()], storage),
+        height,
+    )
+}
+
+pub fn read_total_share_snapshot<S: ReadonlyStorage>(
+    storage: &S,
+    height: u64,
+) -> StdResult<Uint128> {
+    read_snapshot_at(bucket_read(PREFIX_TOTAL_SHARE_SNAPSHOT, storage), height)
+}
+
+// the latest value recorded at or before the height
+fn read_snapshot_at<S: ReadonlyStorage>(
+    snapshots: ReadonlyBucket<S, Uint128>,
+    height: u64,
+) -> StdResult<Uint128> {
+    let end = (height + 1).to_be_bytes();
+    snapshots
+        .range(None, Some(&end), OrderBy::Desc.into())
+        .next()
+        .transpose()
+        .map(|item| item.map(|(_, v)| v).unwrap_or_default())
+}
+
+pub fn param_store<S: Storage>(storage: &mut S) -> Bucket<S, ParamValue> {
+    bucket(PREFIX_PARAM, storage)
+}
+
+pub fn param_read<S: Storage>(storage: &S) -> ReadonlyBucket<S, ParamValue> {
+    bucket_read(PREFIX_PARAM, storage)
+}
+
+pub fn read_params<S: Storage>(
+    storage: &S,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, ParamValue)>> {
+    let limit = page_limit(limit) as usize;
+    let start = start_after.map(|key| {
+        let mut v = key.into_bytes();
+        v.push(1);
+        v
+    });
+
+    param_read(storage)
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((String::from_utf8_lossy(&k).to_string(), v))
+        })
+        .collect()
+}
+
+pub fn read_stakers<S: Storage>(
+    storage: &S,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, TokenManager)>> {
+    let limit = page_limit(limit) as usize;
+    let start = calc_range_start_addr(start_after);
+
+    bank_read(storage)
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 fn calc_range_start(start_after: Option<u64>) -> Option<Vec<u8>> {
     start_after.map(|id| {
@@ -0,0 +1,71 @@
+use crate::state::{poll_voter_read, Poll};
+
+use anchor_token::common::OrderBy;
+use anchor_token::gov::PollStatus;
+use cosmwasm_std::{Api, Binary, CanonicalAddr, ReadonlyStorage, StdError, StdResult};
+use sha3::Digest;
+
+/// Hex encoded merkle root over the voter records of the poll. Leaves are the
+/// keccak256 hash of (address + vote option + balance) and each pair is hashed
+/// in sorted order, the same way referendum and airdrop proofs are verified
+pub fn compute_voters_root<S: ReadonlyStorage, A: Api>(
+    storage: &S,
+    api: &A,
+    poll_id: u64,
+) -> StdResult<Option<String>> {
+    let mut layer: Vec<[u8; 32]> = vec![];
+    for item in poll_voter_read(storage, poll_id).range(None, None, OrderBy::Asc.into()) {
+        let (k, v) = item?;
+        let voter = api.human_address(&CanonicalAddr::from(k))?;
+        let leaf = voter.to_string() + &v.vote.to_string() + &v.balance.to_string();
+        layer.push(sha3::Keccak256::digest(leaf.as_bytes()).into());
+    }
+
+    if layer.is_empty() {
+        return Ok(None);
+    }
+
+    // an odd node is carried up to the next layer unhashed
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] if a < b => sha3::Keccak256::digest(&[*a, *b].concat()).into(),
+                [a, b] => sha3::Keccak256::digest(&[*b, *a].concat()).into(),
+                _ => pair[0],
+            })
+            .collect();
+    }
+
+    Ok(Some(hex::encode(layer[0])))
+}
+
+/// Fixed width big endian encoding of the final tally:
+/// poll id (8) | end height (8) | passed (1) | yes votes (16) | no votes (16) |
+/// total balance at end poll (16) | voters root (32, zero without voters)
+pub fn encode_tally(poll: &Poll) -> StdResult<Binary> {
+    let passed = poll.status != PollStatus::Rejected;
+
+    let mut tally: Vec<u8> = vec![];
+    tally.extend_from_slice(&poll.id.to_be_bytes());
+    tally.extend_from_slice(&poll.end_height.to_be_bytes());
+    tally.push(passed as u8);
+    tally.extend_from_slice(&poll.yes_votes.u128().to_be_bytes());
+    tally.extend_from_slice(&poll.no_votes.u128().to_be_bytes());
+    tally.extend_from_slice(
+        &poll
+            .total_balance_at_end_poll
+            .unwrap_or_default()
+            .u128()
+            .to_be_bytes(),
+    );
+
+    let mut root: [u8; 32] = [0; 32];
+    if let Some(voters_root) = &poll.voters_root {
+        hex::decode_to_slice(voters_root, &mut root)
+            .map_err(|_| StdError::generic_err("Invalid voters root"))?;
+    }
+    tally.extend_from_slice(&root);
+
+    Ok(Binary(tally))
+}
@@ -1,23 +1,38 @@
-use crate::contract::{handle, init, query};
+use crate::contract::{compute_vote_hash, handle, init, query};
+use crate::emergency::EMERGENCY_ACTION_PERIOD;
 use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
 use crate::state::{
-    bank_read, bank_store, config_read, poll_store, poll_voter_read, poll_voter_store, state_read,
-    Config, Poll, State, TokenManager,
+    bank_store, config_read, locked_vote_store, poll_read, poll_store, poll_voter_read,
+    poll_voter_store, read_locked_votes, state_read, state_store, Config, Poll, State,
+    TokenManager,
 };
 
 use crate::querier::load_token_balance;
-use anchor_token::common::OrderBy;
+use anchor_token::common::{Expiration, OrderBy};
 use anchor_token::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, HandleMsg, InitMsg, PollResponse, PollStatus,
-    PollsResponse, QueryMsg, StakerResponse, VoteOption, VoterInfo, VotersResponse,
-    VotersResponseItem,
+    AdaptiveQuorum, ConfigChange, ConfigHistoryResponse, ConfigHistoryResponseItem, ConfigResponse,
+    ConfigUpdate, Cw20HookMsg, DepositLedgerResponse, DepositStatus, EmergencyAction,
+    EmergencyActionResponse, EmergencyActionsResponse, EmergencyCouncil, ExecuteMsg,
+    ExportResponse, HandleMsg, InitMsg, LinkType, MigrationSnapshotResponse, OperatorResponse,
+    OverviewResponse, ParamResponse, ParamValue, ParamsResponse, PausableContract,
+    PollDepositResponse, PollEvent, PollEventKind, PollEventsResponse, PollLink, PollResponse,
+    PollSponsorship, PollStatus, PollTallyResponse, PollsByIdsResponse, PollsResponse, QueryMsg,
+    QuorumPreset, RawStorageResponse, Referendum, RejectedReason, ScheduledConfigUpdateResponse,
+    ShareAccountingResponse, SimulateExecutionResponse, StakerClaim, StakerResponse,
+    StakingSnapshotResponse, StateResponse, StatsResponse, TallyCheckpoint,
+    TallyCheckpointsResponse, TokenInfoCache, VoteOption, VoterInfo, VotersResponse,
+    VotersResponseItem, WithdrawableResponse,
 };
 use cosmwasm_std::testing::{mock_env, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    coins, from_binary, log, to_binary, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, Env, Extern,
-    HandleResponse, HumanAddr, StdError, Uint128, WasmMsg,
+    coins, from_binary, from_slice, log, to_binary, Api, Binary, CanonicalAddr, Coin, CosmosMsg,
+    Decimal, Env, Extern, HandleResponse, HumanAddr, Order, ReadonlyStorage, StdError, Storage,
+    Uint128, WasmMsg, KV,
 };
-use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
+use cosmwasm_storage::to_length_prefixed;
+use cw20::{Cw20HandleMsg, Cw20ReceiveMsg, TokenInfoResponse};
+use sha3::Digest;
+use std::cell::Cell;
 
 const VOTING_TOKEN: &str = "voting_token";
 const TEST_CREATOR: &str = "creator";
@@ -27,6 +42,7 @@ const TEST_VOTER_3: &str = "voter3";
 const DEFAULT_QUORUM: u64 = 30u64;
 const DEFAULT_THRESHOLD: u64 = 50u64;
 const DEFAULT_VOTING_PERIOD: u64 = 10000u64;
+const DEFAULT_REVEAL_PERIOD: u64 = 100u64;
 const DEFAULT_FIX_PERIOD: u64 = 10u64;
 const DEFAULT_TIMELOCK_PERIOD: u64 = 10000u64;
 const DEFAULT_EXPIRATION_PERIOD: u64 = 20000u64;
@@ -41,6 +57,22 @@ fn mock_init(mut deps: &mut Extern<MockStorage, MockApi, WasmMockQuerier>) {
         expiration_period: DEFAULT_EXPIRATION_PERIOD,
         proposal_deposit: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        reveal_period: DEFAULT_REVEAL_PERIOD,
+        min_vote_amount: Uint128::zero(),
+        quorum_excluded_addresses: vec![],
+        tally_checkpoint_interval: 0,
+        poll_retention_period: 0,
+        max_poll_extension: 0,
+        narrow_pass_margin: Decimal::zero(),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: vec![],
+        partial_refund_ratio: Decimal::zero(),
+        veto_threshold: Decimal::zero(),
+        emergency_council: None,
+        max_active_votes: 0,
+        reject_same_block_votes: false,
+        quorum_presets: vec![],
     };
 
     let env = mock_env(TEST_CREATOR, &[]);
@@ -69,6 +101,22 @@ fn init_msg() -> InitMsg {
         expiration_period: DEFAULT_EXPIRATION_PERIOD,
         proposal_deposit: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        reveal_period: DEFAULT_REVEAL_PERIOD,
+        min_vote_amount: Uint128::zero(),
+        quorum_excluded_addresses: vec![],
+        tally_checkpoint_interval: 0,
+        poll_retention_period: 0,
+        max_poll_extension: 0,
+        narrow_pass_margin: Decimal::zero(),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: vec![],
+        partial_refund_ratio: Decimal::zero(),
+        veto_threshold: Decimal::zero(),
+        emergency_council: None,
+        max_active_votes: 0,
+        reject_same_block_votes: false,
+        quorum_presets: vec![],
     }
 }
 
@@ -96,7 +144,26 @@ fn proper_initialization() {
             timelock_period: DEFAULT_TIMELOCK_PERIOD,
             expiration_period: DEFAULT_EXPIRATION_PERIOD,
             proposal_deposit: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
-            snapshot_period: DEFAULT_FIX_PERIOD
+            snapshot_period: DEFAULT_FIX_PERIOD,
+            vesting_contract: None,
+            reveal_period: DEFAULT_REVEAL_PERIOD,
+            min_vote_amount: Uint128::zero(),
+            quorum_excluded_addresses: vec![],
+            tally_checkpoint_interval: 0,
+            poll_retention_period: 0,
+            max_poll_extension: 0,
+            narrow_pass_margin: Decimal::zero(),
+            community_contract: None,
+            adaptive_quorum: None,
+            poll_sponsorship: None,
+            executor_whitelist: vec![],
+            partial_refund_ratio: Decimal::zero(),
+            veto_threshold: Decimal::zero(),
+            emergency_council: None,
+            max_active_votes: 0,
+            reject_same_block_votes: false,
+            quorum_presets: vec![],
+            token_info: None,
         }
     );
 
@@ -123,6 +190,18 @@ fn proper_initialization() {
             poll_count: 0,
             total_share: Uint128::zero(),
             total_deposit: Uint128::zero(),
+            total_votes: 0,
+            unique_voters: 0,
+            polls_passed: 0,
+            polls_rejected: 0,
+            polls_executed: 0,
+            deposit_stake_share: Uint128::zero(),
+            turnout_ema: None,
+            migration_in_progress: false,
+            unclaimed_refund: Uint128::zero(),
+            emergency_paused: false,
+            vote_reward_pool: Uint128::zero(),
+            execution_bounty_pool: Uint128::zero(),
         }
     );
 }
@@ -153,6 +232,22 @@ fn fails_init_invalid_quorum() {
         expiration_period: DEFAULT_EXPIRATION_PERIOD,
         proposal_deposit: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        reveal_period: DEFAULT_REVEAL_PERIOD,
+        min_vote_amount: Uint128::zero(),
+        quorum_excluded_addresses: vec![],
+        tally_checkpoint_interval: 0,
+        poll_retention_period: 0,
+        max_poll_extension: 0,
+        narrow_pass_margin: Decimal::zero(),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: vec![],
+        partial_refund_ratio: Decimal::zero(),
+        veto_threshold: Decimal::zero(),
+        emergency_council: None,
+        max_active_votes: 0,
+        reject_same_block_votes: false,
+        quorum_presets: vec![],
     };
 
     let res = init(&mut deps, env, msg);
@@ -176,6 +271,22 @@ fn fails_init_invalid_threshold() {
         expiration_period: DEFAULT_EXPIRATION_PERIOD,
         proposal_deposit: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        reveal_period: DEFAULT_REVEAL_PERIOD,
+        min_vote_amount: Uint128::zero(),
+        quorum_excluded_addresses: vec![],
+        tally_checkpoint_interval: 0,
+        poll_retention_period: 0,
+        max_poll_extension: 0,
+        narrow_pass_margin: Decimal::zero(),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: vec![],
+        partial_refund_ratio: Decimal::zero(),
+        veto_threshold: Decimal::zero(),
+        emergency_council: None,
+        max_active_votes: 0,
+        reject_same_block_votes: false,
+        quorum_presets: vec![],
     };
 
     let res = init(&mut deps, env, msg);
@@ -199,6 +310,22 @@ fn fails_contract_already_registered() {
         expiration_period: DEFAULT_EXPIRATION_PERIOD,
         proposal_deposit: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        reveal_period: DEFAULT_REVEAL_PERIOD,
+        min_vote_amount: Uint128::zero(),
+        quorum_excluded_addresses: vec![],
+        tally_checkpoint_interval: 0,
+        poll_retention_period: 0,
+        max_poll_extension: 0,
+        narrow_pass_margin: Decimal::zero(),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: vec![],
+        partial_refund_ratio: Decimal::zero(),
+        veto_threshold: Decimal::zero(),
+        emergency_council: None,
+        max_active_votes: 0,
+        reject_same_block_votes: false,
+        quorum_presets: vec![],
     };
 
     let _res = init(&mut deps, env.clone(), msg).unwrap();
@@ -274,13 +401,45 @@ fn fails_create_poll_invalid_link() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
+    let forum_link = |url: &str| PollLink {
+        link_type: LinkType::Forum,
+        url: url.to_string(),
+    };
+    let env = mock_env(VOTING_TOKEN, &vec![]);
+    for (url, err) in [
+        ("http://hih", "Link too short"),
+        ("0123456789012345678901234567890123456789012345678901234567890123401234567890123456789012345678901234567890123456789012345678901234012345678901234567890123456789012345678901234567890123456789012340123456789012345678901234567890123456789012345678901234567890123401234567890123456789012345678901234567890123456789012345678901234", "Link too long"),
+        ("ipfs://QmYwAPJzv5CZsnA", "Link must be an http(s) url"),
+        ("https://localhost/proposal", "Invalid link domain"),
+        ("https://forum..anchor/1", "Invalid link domain"),
+        ("https://-forum.anchor.com", "Invalid link domain"),
+        ("https://forum_anchor.com", "Invalid link domain"),
+    ]
+    .iter()
+    {
+        let msg = create_poll_msg(
+            "test".to_string(),
+            "test".to_string(),
+            Some(vec![forum_link(url)]),
+            None,
+        );
+        match handle(&mut deps, env.clone(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, *err),
+            Err(_) => panic!("Unknown error"),
+        }
+    }
+
+    // every link is validated
     let msg = create_poll_msg(
         "test".to_string(),
         "test".to_string(),
-        Some("http://hih".to_string()),
+        Some(vec![
+            forum_link("https://forum.anchorprotocol.com/t/1"),
+            forum_link("http://hih"),
+        ]),
         None,
     );
-    let env = mock_env(VOTING_TOKEN, &vec![]);
     match handle(&mut deps, env.clone(), msg) {
         Ok(_) => panic!("Must return error"),
         Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Link too short"),
@@ -288,17 +447,247 @@ fn fails_create_poll_invalid_link() {
     }
 
     let msg = create_poll_msg(
-            "test".to_string(),
-            "test".to_string(),
-            Some("0123456789012345678901234567890123456789012345678901234567890123401234567890123456789012345678901234567890123456789012345678901234012345678901234567890123456789012345678901234567890123456789012340123456789012345678901234567890123456789012345678901234567890123401234567890123456789012345678901234567890123456789012345678901234".to_string()),
-            None,
-        );
-
+        "test".to_string(),
+        "test".to_string(),
+        Some(vec![forum_link("https://forum.anchorprotocol.com/t/1"); 5]),
+        None,
+    );
     match handle(&mut deps, env.clone(), msg) {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Link too long"),
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Too many links"),
+        Err(_) => panic!("Unknown error"),
+    }
+
+    let links = vec![
+        forum_link("https://forum.anchorprotocol.com:443/t/1?page=2"),
+        PollLink {
+            link_type: LinkType::CodeDiff,
+            url: "https://github.com/anchor-protocol/anchor-token-contracts/pull/1".to_string(),
+        },
+        PollLink {
+            link_type: LinkType::Audit,
+            url: "https://audits.example-firm.io/anchor.pdf".to_string(),
+        },
+    ];
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        Some(links.clone()),
+        None,
+    );
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.links, links);
+}
+
+#[test]
+fn create_poll_with_content_hash() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let create_msg = |content_hash: Binary| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: Some(
+                to_binary(&Cw20HookMsg::CreatePoll {
+                    title: "test".to_string(),
+                    description: "test".to_string(),
+                    links: None,
+                    execute_msgs: None,
+                    commit_reveal: None,
+                    content_hash: Some(content_hash),
+                    referendum: None,
+                    stake_refund: None,
+                    reserved_poll_id: None,
+                    refund_address: None,
+                    option_labels: None,
+                    vote_reward: None,
+                    execution_bounty: None,
+                    on_reject_msgs: None,
+                    preset: None,
+                })
+                .unwrap(),
+            ),
+        })
+    };
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    match handle(
+        &mut deps,
+        env.clone(),
+        create_msg(Binary::from(vec![1u8; 31])),
+    ) {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid content hash"),
         Err(_) => panic!("Unknown error"),
     }
+
+    let _res = handle(&mut deps, env, create_msg(Binary::from(vec![1u8; 32]))).unwrap();
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Binary::from(vec![1u8; 32])), value.content_hash);
+}
+
+#[test]
+fn referendum_vote() {
+    let mut deps = mock_dependencies(44, &[]);
+    mock_init(&mut deps);
+
+    let referendum_voter = "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8";
+    let create_msg = |commit_reveal: Option<bool>, execute_msgs: Option<Vec<ExecuteMsg>>| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: Some(
+                to_binary(&Cw20HookMsg::CreatePoll {
+                    title: "test".to_string(),
+                    description: "test".to_string(),
+                    links: None,
+                    execute_msgs,
+                    commit_reveal,
+                    content_hash: None,
+                    referendum: Some(Referendum {
+                        merkle_root:
+                            "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+                                .to_string(),
+                        total_balance: Uint128(2000000u128),
+                    }),
+                    stake_refund: None,
+                    reserved_poll_id: None,
+                    refund_address: None,
+                    option_labels: None,
+                    vote_reward: None,
+                    execution_bounty: None,
+                    on_reject_msgs: None,
+                    preset: None,
+                })
+                .unwrap(),
+            ),
+        })
+    };
+
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    match handle(&mut deps, env.clone(), create_msg(Some(true), None)) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Referendum poll cannot use commit-reveal voting")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    // the snapshot is provided by the proposer, so it cannot pass messages
+    let execute_msgs = vec![ExecuteMsg {
+        order: 1u64,
+        contract: HumanAddr::from(VOTING_TOKEN),
+        msg: Binary::from(vec![1u8]),
+    }];
+    match handle(&mut deps, env.clone(), create_msg(None, Some(execute_msgs))) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Referendum poll cannot execute messages")
+        }
+        _ => panic!("Must return error"),
+    }
+    let _res = handle(&mut deps, env, create_msg(None, None)).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // stakers can't vote on a referendum with their stake
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128(1000001u128)),
+        voter: None,
+    };
+    let env = mock_env_height(referendum_voter, &[], 0, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll requires referendum voting"),
+        _ => panic!("Must return error"),
+    }
+
+    let msg = HandleMsg::CastReferendumVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        balance: Uint128(1000002u128),
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+    };
+    let env = mock_env_height(referendum_voter, &[], 0, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Verification is failed"),
+        _ => panic!("Must return error"),
+    }
+
+    let msg = HandleMsg::CastReferendumVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        balance: Uint128(1000001u128),
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+    };
+    let env = mock_env_height(referendum_voter, &[], 0, 10000);
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "cast_referendum_vote"),
+            log("poll_id", "1"),
+            log("amount", "1000001"),
+            log("voter", referendum_voter),
+            log("vote_option", "yes"),
+        ]
+    );
+
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "User has already voted."),
+        _ => panic!("Must return error"),
+    }
+
+    // the referendum voter has no stake locked
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(referendum_voter),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let staker: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(staker.locked_balance, vec![]);
+
+    // quorum is measured against the snapshot total balance
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", ""),
+            log("passed", "true"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Uint128(1000001u128), value.yes_votes);
+    assert_eq!(Some(Uint128(2000000u128)), value.total_balance_at_end_poll);
 }
 
 #[test]
@@ -313,8 +702,19 @@ fn fails_create_poll_invalid_deposit() {
             to_binary(&Cw20HookMsg::CreatePoll {
                 title: "TESTTEST".to_string(),
                 description: "TESTTEST".to_string(),
-                link: None,
+                links: None,
                 execute_msgs: None,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: None,
+                execution_bounty: None,
+                on_reject_msgs: None,
+                preset: None,
             })
             .unwrap(),
         ),
@@ -333,7 +733,7 @@ fn fails_create_poll_invalid_deposit() {
 fn create_poll_msg(
     title: String,
     description: String,
-    link: Option<String>,
+    links: Option<Vec<PollLink>>,
     execute_msg: Option<Vec<ExecuteMsg>>,
 ) -> HandleMsg {
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
@@ -343,8 +743,19 @@ fn create_poll_msg(
             to_binary(&Cw20HookMsg::CreatePoll {
                 title,
                 description,
-                link,
+                links,
                 execute_msgs: execute_msg,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: None,
+                execution_bounty: None,
+                on_reject_msgs: None,
+                preset: None,
             })
             .unwrap(),
         ),
@@ -370,6 +781,223 @@ fn happy_days_create_poll() {
     );
 }
 
+#[test]
+fn create_poll_with_reserved_id() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(TEST_CREATOR, &[], 0, 10000);
+    let res = handle(&mut deps, env, HandleMsg::ReservePollId {}).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "reserve_poll_id"),
+            log("creator", TEST_CREATOR),
+            log("poll_id", 1),
+            log("expiry_height", 1000),
+        ]
+    );
+
+    // polls created meanwhile skip the reserved id
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(res.log[2], log("poll_id", 2));
+
+    let reserved_poll_msg = |sender: &str| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(sender),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: Some(
+                to_binary(&Cw20HookMsg::CreatePoll {
+                    title: "test".to_string(),
+                    description: "test".to_string(),
+                    links: None,
+                    execute_msgs: None,
+                    commit_reveal: None,
+                    content_hash: None,
+                    referendum: None,
+                    stake_refund: None,
+                    reserved_poll_id: Some(1),
+                    refund_address: None,
+                    option_labels: None,
+                    vote_reward: None,
+                    execution_bounty: None,
+                    on_reject_msgs: None,
+                    preset: None,
+                })
+                .unwrap(),
+            ),
+        })
+    };
+
+    let env = mock_env_height(VOTING_TOKEN, &[], 10, 10000);
+    match handle(&mut deps, env.clone(), reserved_poll_msg(TEST_VOTER)) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let expired_env = mock_env_height(VOTING_TOKEN, &[], 1001, 10000);
+    match handle(&mut deps, expired_env, reserved_poll_msg(TEST_CREATOR)) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll id reservation has expired"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, env.clone(), reserved_poll_msg(TEST_CREATOR)).unwrap();
+    assert_eq!(res.log[2], log("poll_id", 1));
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.creator, HumanAddr::from(TEST_CREATOR));
+    assert_eq!(value.end_height, 10 + DEFAULT_VOTING_PERIOD);
+
+    // the reservation is used up
+    match handle(&mut deps, env, reserved_poll_msg(TEST_CREATOR)) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll id is not reserved"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.poll_count, 2);
+    assert_eq!(state.total_deposit, Uint128(DEFAULT_PROPOSAL_DEPOSIT * 2));
+}
+
+#[test]
+fn query_overview() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let res = query(&deps, QueryMsg::Overview {}).unwrap();
+    let overview: OverviewResponse = from_binary(&res).unwrap();
+    assert_eq!(overview.config.quorum, Decimal::percent(DEFAULT_QUORUM));
+    assert_eq!(overview.state.poll_count, 0);
+    assert_eq!(overview.latest_poll_id, None);
+    assert_eq!(overview.latest_poll_status, None);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::Overview {}).unwrap();
+    let overview: OverviewResponse = from_binary(&res).unwrap();
+    assert_eq!(overview.state.poll_count, 1);
+    assert_eq!(
+        overview.state.total_deposit,
+        Uint128(DEFAULT_PROPOSAL_DEPOSIT)
+    );
+    assert_eq!(overview.latest_poll_id, Some(1));
+    assert_eq!(overview.latest_poll_status, Some(PollStatus::InProgress));
+}
+
+#[test]
+fn set_param() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::SetParam {
+        key: "max_borrow_factor".to_string(),
+        value: Some(ParamValue::Decimal(Decimal::percent(95))),
+    };
+
+    // only a passed poll can set params
+    let env = mock_env(TEST_CREATOR, &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let env = mock_env(MOCK_CONTRACT_ADDR, &[]);
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "set_param"),
+            log("key", "max_borrow_factor"),
+            log("value", "0.95"),
+        ]
+    );
+
+    let msg = HandleMsg::SetParam {
+        key: "emission_enabled".to_string(),
+        value: Some(ParamValue::Bool(true)),
+    };
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::SetParam {
+        key: "".to_string(),
+        value: Some(ParamValue::Bool(true)),
+    };
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid param key"),
+        _ => panic!("Must return error"),
+    }
+
+    let res = query(
+        &deps,
+        QueryMsg::Param {
+            key: "max_borrow_factor".to_string(),
+        },
+    )
+    .unwrap();
+    let param: ParamResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        param,
+        ParamResponse {
+            key: "max_borrow_factor".to_string(),
+            value: ParamValue::Decimal(Decimal::percent(95)),
+        }
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::Params {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let params: ParamsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        params.params,
+        vec![
+            ParamResponse {
+                key: "emission_enabled".to_string(),
+                value: ParamValue::Bool(true),
+            },
+            ParamResponse {
+                key: "max_borrow_factor".to_string(),
+                value: ParamValue::Decimal(Decimal::percent(95)),
+            },
+        ]
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::Params {
+            start_after: Some("emission_enabled".to_string()),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let params: ParamsResponse = from_binary(&res).unwrap();
+    assert_eq!(params.params.len(), 1);
+
+    // remove the param
+    let msg = HandleMsg::SetParam {
+        key: "max_borrow_factor".to_string(),
+        value: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+    match query(
+        &deps,
+        QueryMsg::Param {
+            key: "max_borrow_factor".to_string(),
+        },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Param does not exist"),
+        _ => panic!("Must return error"),
+    }
+}
+
 #[test]
 fn query_polls() {
     let mut deps = mock_dependencies(20, &[]);
@@ -411,7 +1039,10 @@ fn query_polls() {
     let msg = create_poll_msg(
         "test".to_string(),
         "test".to_string(),
-        Some("http://google.com".to_string()),
+        Some(vec![PollLink {
+            link_type: LinkType::Forum,
+            url: "http://google.com".to_string(),
+        }]),
         Some(execute_msgs.clone()),
     );
 
@@ -440,13 +1071,39 @@ fn query_polls() {
                 end_height: 10000u64,
                 title: "test".to_string(),
                 description: "test".to_string(),
-                link: Some("http://google.com".to_string()),
+                links: vec![PollLink {
+                    link_type: LinkType::Forum,
+                    url: "http://google.com".to_string(),
+                }],
                 deposit_amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
                 execute_data: Some(execute_msgs.clone()),
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
                 staked_amount: None,
                 total_balance_at_end_poll: None,
+                deposit_share: None,
+                reveal_end_height: None,
+                rejected_reason: None,
+                content_hash: None,
+                yes_shares: Uint128::zero(),
+                no_shares: Uint128::zero(),
+                staked_share: None,
+                referendum: None,
+                stake_refund: false,
+                refund_address: None,
+                narrow_pass: false,
+                option_labels: vec![],
+                option_votes: vec![],
+                required_sponsors: 0,
+                sponsors: vec![],
+                veto_votes: Uint128::zero(),
+                vote_reward: Uint128::zero(),
+                vote_reward_claimed: Uint128::zero(),
+                execution_bounty: Uint128::zero(),
+                on_reject_msgs: None,
+                preset: None,
+                on_reject_executed: false,
+                escrow_funded: false,
             },
             PollResponse {
                 id: 2u64,
@@ -455,13 +1112,36 @@ fn query_polls() {
                 end_height: 10000u64,
                 title: "test2".to_string(),
                 description: "test2".to_string(),
-                link: None,
+                links: vec![],
                 deposit_amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
                 execute_data: None,
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
                 staked_amount: None,
                 total_balance_at_end_poll: None,
+                deposit_share: None,
+                reveal_end_height: None,
+                rejected_reason: None,
+                content_hash: None,
+                yes_shares: Uint128::zero(),
+                no_shares: Uint128::zero(),
+                staked_share: None,
+                referendum: None,
+                stake_refund: false,
+                refund_address: None,
+                narrow_pass: false,
+                option_labels: vec![],
+                option_votes: vec![],
+                required_sponsors: 0,
+                sponsors: vec![],
+                veto_votes: Uint128::zero(),
+                vote_reward: Uint128::zero(),
+                vote_reward_claimed: Uint128::zero(),
+                execution_bounty: Uint128::zero(),
+                on_reject_msgs: None,
+                preset: None,
+                on_reject_executed: false,
+                escrow_funded: false,
             },
         ]
     );
@@ -486,13 +1166,36 @@ fn query_polls() {
             end_height: 10000u64,
             title: "test2".to_string(),
             description: "test2".to_string(),
-            link: None,
+            links: vec![],
             deposit_amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
             staked_amount: None,
             total_balance_at_end_poll: None,
+            deposit_share: None,
+            reveal_end_height: None,
+            rejected_reason: None,
+            content_hash: None,
+            yes_shares: Uint128::zero(),
+            no_shares: Uint128::zero(),
+            staked_share: None,
+            referendum: None,
+            stake_refund: false,
+            refund_address: None,
+            narrow_pass: false,
+            option_labels: vec![],
+            option_votes: vec![],
+            required_sponsors: 0,
+            sponsors: vec![],
+            veto_votes: Uint128::zero(),
+            vote_reward: Uint128::zero(),
+            vote_reward_claimed: Uint128::zero(),
+            execution_bounty: Uint128::zero(),
+            on_reject_msgs: None,
+            preset: None,
+            on_reject_executed: false,
+            escrow_funded: false,
         },]
     );
 
@@ -516,13 +1219,39 @@ fn query_polls() {
             end_height: 10000u64,
             title: "test".to_string(),
             description: "test".to_string(),
-            link: Some("http://google.com".to_string()),
+            links: vec![PollLink {
+                link_type: LinkType::Forum,
+                url: "http://google.com".to_string(),
+            }],
             deposit_amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
             execute_data: Some(execute_msgs),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
             staked_amount: None,
             total_balance_at_end_poll: None,
+            deposit_share: None,
+            reveal_end_height: None,
+            rejected_reason: None,
+            content_hash: None,
+            yes_shares: Uint128::zero(),
+            no_shares: Uint128::zero(),
+            staked_share: None,
+            referendum: None,
+            stake_refund: false,
+            refund_address: None,
+            narrow_pass: false,
+            option_labels: vec![],
+            option_votes: vec![],
+            required_sponsors: 0,
+            sponsors: vec![],
+            veto_votes: Uint128::zero(),
+            vote_reward: Uint128::zero(),
+            vote_reward_claimed: Uint128::zero(),
+            execution_bounty: Uint128::zero(),
+            on_reject_msgs: None,
+            preset: None,
+            on_reject_executed: false,
+            escrow_funded: false,
         }]
     );
 
@@ -546,13 +1275,36 @@ fn query_polls() {
             end_height: 10000u64,
             title: "test2".to_string(),
             description: "test2".to_string(),
-            link: None,
+            links: vec![],
             deposit_amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
             staked_amount: None,
             total_balance_at_end_poll: None,
+            deposit_share: None,
+            reveal_end_height: None,
+            rejected_reason: None,
+            content_hash: None,
+            yes_shares: Uint128::zero(),
+            no_shares: Uint128::zero(),
+            staked_share: None,
+            referendum: None,
+            stake_refund: false,
+            refund_address: None,
+            narrow_pass: false,
+            option_labels: vec![],
+            option_votes: vec![],
+            required_sponsors: 0,
+            sponsors: vec![],
+            veto_votes: Uint128::zero(),
+            vote_reward: Uint128::zero(),
+            vote_reward_claimed: Uint128::zero(),
+            execution_bounty: Uint128::zero(),
+            on_reject_msgs: None,
+            preset: None,
+            on_reject_executed: false,
+            escrow_funded: false,
         },]
     );
 
@@ -571,30 +1323,114 @@ fn query_polls() {
 }
 
 #[test]
-fn create_poll_no_quorum() {
+fn query_polls_by_ids() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
     let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
 
     let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _handle_res = handle(&mut deps, env.clone(), msg).unwrap();
+    let msg = create_poll_msg("test2".to_string(), "test2".to_string(), None, None);
+    let _handle_res = handle(&mut deps, env.clone(), msg).unwrap();
 
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_create_poll_result(
-        1,
-        DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        handle_res,
-        &mut deps,
+    let res = query(
+        &deps,
+        QueryMsg::PollsByIds {
+            poll_ids: vec![2u64, 5u64, 1u64],
+        },
+    )
+    .unwrap();
+    let response: PollsByIdsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response
+            .polls
+            .iter()
+            .map(|poll| poll.id)
+            .collect::<Vec<u64>>(),
+        vec![2u64, 1u64]
     );
-}
-
-#[test]
-fn fails_end_poll_before_end_height() {
-    let mut deps = mock_dependencies(20, &[]);
-    mock_init(&mut deps);
-    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    assert_eq!(response.polls[0].title, "test2".to_string());
+    assert_eq!(response.missing_poll_ids, vec![5u64]);
 
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let res = query(
+        &deps,
+        QueryMsg::PollsByIds {
+            poll_ids: (1u64..=31u64).collect(),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot query more than 30 polls at once")
+        }
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn query_page_limit() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+
+    for _ in 0..35 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _handle_res = handle(&mut deps, env.clone(), msg).unwrap();
+    }
+
+    for &(limit, applied) in [(None, 10u32), (Some(5), 5u32), (Some(100), 30u32)].iter() {
+        let res = query(
+            &deps,
+            QueryMsg::Polls {
+                filter: None,
+                start_after: None,
+                limit,
+                order_by: None,
+            },
+        )
+        .unwrap();
+        let response: PollsResponse = from_binary(&res).unwrap();
+        assert_eq!(response.limit, applied);
+        assert_eq!(response.polls.len(), applied as usize);
+
+        let res = query(
+            &deps,
+            QueryMsg::ExportPolls {
+                start_after: None,
+                limit,
+            },
+        )
+        .unwrap();
+        let response: ExportResponse = from_binary(&res).unwrap();
+        assert_eq!(response.limit, applied);
+        assert_eq!(response.records.len(), applied as usize);
+    }
+}
+
+#[test]
+fn create_poll_no_quorum() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_create_poll_result(
+        1,
+        DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        handle_res,
+        &mut deps,
+    );
+}
+
+#[test]
+fn fails_end_poll_before_end_height() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
 
     let handle_res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
     assert_create_poll_result(
@@ -713,7 +1549,8 @@ fn happy_days_end_poll() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER, &[], POLL_START_HEIGHT, 10000);
     let handle_res = handle(&mut deps, env, msg).unwrap();
@@ -726,6 +1563,8 @@ fn happy_days_end_poll() {
             log("amount", "1000"),
             log("voter", TEST_VOTER),
             log("vote_option", "yes"),
+            log("quorum_reached", "true"),
+            log("threshold_reached", "true"),
         ]
     );
 
@@ -869,6 +1708,7 @@ fn happy_days_end_poll() {
         &deps,
         QueryMsg::Staker {
             address: HumanAddr::from(TEST_VOTER),
+            token_balance: None,
         },
     )
     .unwrap();
@@ -878,7 +1718,9 @@ fn happy_days_end_poll() {
         StakerResponse {
             balance: Uint128(stake_amount),
             share: Uint128(stake_amount),
-            locked_balance: vec![]
+            locked_balance: vec![],
+            vesting_locked_balance: Uint128::zero(),
+            deposit_locked_share: Uint128::zero(),
         }
     );
 
@@ -895,19 +1737,18 @@ fn happy_days_end_poll() {
         VoterInfo {
             vote: VoteOption::Yes,
             balance: Uint128(stake_amount),
+            share: Uint128(stake_amount),
         }
     );
 
-    let token_manager = bank_read(&deps.storage)
-        .load(&voter_addr_raw.as_slice())
-        .unwrap();
     assert_eq!(
-        token_manager.locked_balance,
+        read_locked_votes(&deps.storage, &voter_addr_raw).unwrap(),
         vec![(
             1u64,
             VoterInfo {
                 vote: VoteOption::Yes,
                 balance: Uint128(stake_amount),
+                share: Uint128(stake_amount),
             }
         )]
     );
@@ -983,7 +1824,8 @@ fn expire_poll() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER, &[], POLL_START_HEIGHT, 10000);
     let handle_res = handle(&mut deps, env, msg).unwrap();
@@ -996,6 +1838,8 @@ fn expire_poll() {
             log("amount", "1000"),
             log("voter", TEST_VOTER),
             log("vote_option", "yes"),
+            log("quorum_reached", "true"),
+            log("threshold_reached", "true"),
         ]
     );
 
@@ -1063,1083 +1907,6410 @@ fn expire_poll() {
     .unwrap();
     let polls_res: PollsResponse = from_binary(&res).unwrap();
     assert_eq!(polls_res.polls[0], poll_res);
+
+    let res = query(&deps, QueryMsg::PollEvents { poll_id: 1 }).unwrap();
+    let events_res: PollEventsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        events_res.events,
+        vec![
+            PollEvent {
+                kind: PollEventKind::Created,
+                height: POLL_START_HEIGHT,
+            },
+            PollEvent {
+                kind: PollEventKind::Ended,
+                height: POLL_START_HEIGHT + DEFAULT_TIMELOCK_PERIOD,
+            },
+            PollEvent {
+                kind: PollEventKind::Expired,
+                height: POLL_START_HEIGHT + DEFAULT_TIMELOCK_PERIOD + DEFAULT_EXPIRATION_PERIOD,
+            },
+        ]
+    );
 }
 
 #[test]
-fn end_poll_zero_quorum() {
-    let mut deps = mock_dependencies(20, &coins(1000, VOTING_TOKEN));
+fn execute_rejected_poll() {
+    let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
-    let mut creator_env = mock_env_height(VOTING_TOKEN, &vec![], 1000, 10000);
-
-    let mut execute_msgs: Vec<ExecuteMsg> = vec![];
-    execute_msgs.push(ExecuteMsg {
-        order: 1u64,
-        contract: HumanAddr::from(VOTING_TOKEN),
-        msg: to_binary(&Cw20HandleMsg::Burn {
-            amount: Uint128(123),
-        })
-        .unwrap(),
-    });
-
-    let msg = create_poll_msg(
-        "test".to_string(),
-        "test".to_string(),
-        None,
-        Some(execute_msgs),
-    );
-
-    let handle_res = handle(&mut deps, creator_env.clone(), msg).unwrap();
-    assert_create_poll_result(
-        1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        handle_res,
-        &mut deps,
-    );
-    let stake_amount = 100;
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(
-            &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
-        )],
-    )]);
 
+    let on_reject_msg = to_binary(&Cw20HandleMsg::Transfer {
+        recipient: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(123),
+    })
+    .unwrap();
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(stake_amount as u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: None,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: None,
+                execution_bounty: None,
+                on_reject_msgs: Some(vec![ExecuteMsg {
+                    order: 1u64,
+                    contract: HumanAddr::from("escrow0000"),
+                    msg: on_reject_msg.clone(),
+                }]),
+                preset: None,
+            })
+            .unwrap(),
+        ),
     });
+    // poll 2 returns the same escrow, which its proposer never funds
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let env = mock_env(VOTING_TOKEN, &[]);
-    handle(&mut deps, env, msg.clone()).unwrap();
+    let fund_msg = |sender: &str, amount: u128| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(sender),
+            amount: Uint128(amount),
+            msg: Some(to_binary(&Cw20HookMsg::FundPollEscrow { poll_id: 1 }).unwrap()),
+        })
+    };
+    let env = mock_env_height("escrow0000", &[], 0, 10000);
+    match handle(&mut deps, env.clone(), fund_msg(TEST_VOTER, 123u128)) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
 
-    let msg = HandleMsg::EndPoll { poll_id: 1 };
-    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
-    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
+    match handle(&mut deps, env.clone(), fund_msg(TEST_CREATOR, 122u128)) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Poll escrow must be funded with the on reject transfer"
+        ),
+        _ => panic!("Must return generic error"),
+    }
 
-    let handle_res = handle(&mut deps, creator_env.clone(), msg).unwrap();
+    let other_env = mock_env_height("other0000", &[], 0, 10000);
+    match handle(&mut deps, other_env, fund_msg(TEST_CREATOR, 123u128)) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Poll escrow must be funded with the on reject transfer"
+        ),
+        _ => panic!("Must return generic error"),
+    }
 
+    let res = handle(&mut deps, env.clone(), fund_msg(TEST_CREATOR, 123u128)).unwrap();
     assert_eq!(
-        handle_res.log,
+        res.log,
         vec![
-            log("action", "end_poll"),
-            log("poll_id", "1"),
-            log("rejected_reason", "Quorum not reached"),
-            log("passed", "false"),
+            log("action", "fund_poll_escrow"),
+            log("poll_id", 1),
+            log("token", "escrow0000"),
+            log("amount", 123),
         ]
     );
 
-    assert_eq!(handle_res.messages.len(), 0usize);
-
-    // Query rejected polls
-    let res = query(
-        &deps,
-        QueryMsg::Polls {
-            filter: Some(PollStatus::Rejected),
-            start_after: None,
-            limit: None,
-            order_by: Some(OrderBy::Desc),
-        },
-    )
-    .unwrap();
-    let response: PollsResponse = from_binary(&res).unwrap();
-    assert_eq!(response.polls.len(), 1);
-
-    let res = query(
-        &deps,
-        QueryMsg::Polls {
-            filter: Some(PollStatus::InProgress),
-            start_after: None,
-            limit: None,
-            order_by: None,
-        },
-    )
-    .unwrap();
-    let response: PollsResponse = from_binary(&res).unwrap();
-    assert_eq!(response.polls.len(), 0);
-
-    let res = query(
-        &deps,
-        QueryMsg::Polls {
-            filter: Some(PollStatus::Passed),
-            start_after: None,
-            limit: None,
-            order_by: None,
-        },
-    )
-    .unwrap();
-    let response: PollsResponse = from_binary(&res).unwrap();
-    assert_eq!(response.polls.len(), 0);
-}
-
-#[test]
-fn end_poll_quorum_rejected() {
-    let mut deps = mock_dependencies(20, &coins(100, VOTING_TOKEN));
-    mock_init(&mut deps);
+    match handle(&mut deps, env, fund_msg(TEST_CREATOR, 123u128)) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll escrow is already funded")
+        }
+        _ => panic!("Must return generic error"),
+    }
 
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-    let mut creator_env = mock_env(VOTING_TOKEN, &vec![]);
-    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        handle_res.log,
-        vec![
-            log("action", "create_poll"),
-            log("creator", TEST_CREATOR),
-            log("poll_id", "1"),
-            log("end_height", "22345"),
-        ]
-    );
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert!(poll_res.escrow_funded);
 
-    let stake_amount = 100;
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
         &[(
             &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+            &Uint128(100u128 + 2 * DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(stake_amount as u128),
+        amount: Uint128::from(100u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
-
     let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(
-        stake_amount,
-        DEFAULT_PROPOSAL_DEPOSIT,
-        stake_amount,
-        1,
-        handle_res,
-        &mut deps,
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    for poll_id in 1..3 {
+        let msg = HandleMsg::CastVote {
+            poll_id,
+            vote: VoteOption::No,
+            amount: Some(Uint128::from(100u128)),
+            voter: None,
+        };
+        let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+        let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id }).unwrap();
+
+        // the deposit is refunded
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(100u128 + (2 - poll_id as u128) * DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let msg = HandleMsg::ExecuteRejectedPoll { poll_id: 1 };
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Timelock period has not expired"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let env = mock_env_height(
+        TEST_CREATOR,
+        &[],
+        DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD,
+        10000,
     );
 
-    let msg = HandleMsg::CastVote {
-        poll_id: 1,
-        vote: VoteOption::Yes,
-        amount: Uint128::from(10u128),
-    };
-    let env = mock_env(TEST_VOTER, &[]);
-    let handle_res = handle(&mut deps, env, msg).unwrap();
+    // poll 2 cannot return the escrow of poll 1
+    match handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::ExecuteRejectedPoll { poll_id: 2 },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll escrow is not funded"),
+        _ => panic!("Must return generic error"),
+    }
 
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
     assert_eq!(
-        handle_res.log,
-        vec![
-            log("action", "cast_vote"),
-            log("poll_id", "1"),
-            log("amount", "10"),
-            log("voter", TEST_VOTER),
-            log("vote_option", "yes"),
-        ]
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("escrow0000"),
+            msg: on_reject_msg,
+            send: vec![],
+        })]
     );
 
-    let msg = HandleMsg::EndPoll { poll_id: 1 };
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "On reject messages already executed")
+        }
+        _ => panic!("Must return generic error"),
+    }
 
-    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
-    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
-
-    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        handle_res.log,
-        vec![
-            log("action", "end_poll"),
-            log("poll_id", "1"),
-            log("rejected_reason", "Quorum not reached"),
-            log("passed", "false"),
-        ]
-    );
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Rejected);
+    assert!(poll_res.on_reject_executed);
 }
 
 #[test]
-fn end_poll_quorum_rejected_noting_staked() {
-    let mut deps = mock_dependencies(20, &coins(100, VOTING_TOKEN));
+fn execute_rejected_poll_hostile_payload() {
+    let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-    let mut creator_env = mock_env(VOTING_TOKEN, &vec![]);
-    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        handle_res.log,
-        vec![
-            log("action", "create_poll"),
-            log("creator", TEST_CREATOR),
-            log("poll_id", "1"),
-            log("end_height", "22345"),
-        ]
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: Some(Decimal::percent(33)),
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let create_poll_msg = |contract: &str, msg: Binary| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: Some(
+                to_binary(&Cw20HookMsg::CreatePoll {
+                    title: "test".to_string(),
+                    description: "test".to_string(),
+                    links: None,
+                    execute_msgs: None,
+                    commit_reveal: None,
+                    content_hash: None,
+                    referendum: None,
+                    stake_refund: None,
+                    reserved_poll_id: None,
+                    refund_address: None,
+                    option_labels: None,
+                    vote_reward: None,
+                    execution_bounty: None,
+                    on_reject_msgs: Some(vec![ExecuteMsg {
+                        order: 1u64,
+                        contract: HumanAddr::from(contract),
+                        msg,
+                    }]),
+                    preset: None,
+                })
+                .unwrap(),
+            ),
+        })
+    };
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+
+    // the staked voting tokens cannot be paid out
+    let msg = create_poll_msg(
+        VOTING_TOKEN,
+        to_binary(&Cw20HandleMsg::Transfer {
+            recipient: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(1000000u128),
+        })
+        .unwrap(),
     );
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "On reject messages cannot transfer the voting token")
+        }
+        _ => panic!("Must return generic error"),
+    }
 
-    let msg = HandleMsg::EndPoll { poll_id: 1 };
+    // escrowed funds only go back to the proposer
+    let msg = create_poll_msg(
+        "escrow0000",
+        to_binary(&Cw20HandleMsg::Transfer {
+            recipient: HumanAddr::from(TEST_VOTER),
+            amount: Uint128(123u128),
+        })
+        .unwrap(),
+    );
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "On reject messages can only return funds to the proposer"
+        ),
+        _ => panic!("Must return generic error"),
+    }
 
-    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
-    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
+    let msg = create_poll_msg(
+        "escrow0000",
+        to_binary(&Cw20HandleMsg::Burn {
+            amount: Uint128(123u128),
+        })
+        .unwrap(),
+    );
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "On reject messages must be cw20 transfers")
+        }
+        _ => panic!("Must return generic error"),
+    }
 
-    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        handle_res.log,
-        vec![
-            log("action", "end_poll"),
-            log("poll_id", "1"),
-            log("rejected_reason", "Quorum not reached"),
-            log("passed", "false"),
-        ]
+    // the escrow is a single transfer
+    let transfer = to_binary(&Cw20HandleMsg::Transfer {
+        recipient: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(123u128),
+    })
+    .unwrap();
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: None,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: None,
+                execution_bounty: None,
+                on_reject_msgs: Some(vec![
+                    ExecuteMsg {
+                        order: 1u64,
+                        contract: HumanAddr::from("escrow0000"),
+                        msg: transfer.clone(),
+                    },
+                    ExecuteMsg {
+                        order: 2u64,
+                        contract: HumanAddr::from("escrow0001"),
+                        msg: transfer,
+                    },
+                ]),
+                preset: None,
+            })
+            .unwrap(),
+        ),
+    });
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "On reject messages must be a single cw20 transfer")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // a vetoed poll never executes its on reject messages
+    let msg = create_poll_msg(
+        "escrow0000",
+        to_binary(&Cw20HandleMsg::Transfer {
+            recipient: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(123u128),
+        })
+        .unwrap(),
     );
-}
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-#[test]
-fn end_poll_nay_rejected() {
-    let voter1_stake = 100;
-    let voter2_stake = 1000;
-    let mut deps = mock_dependencies(20, &[]);
-    mock_init(&mut deps);
-    let mut creator_env = mock_env(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+    for (voter, amount, vote) in [
+        (TEST_VOTER, 600u128, VoteOption::No),
+        (TEST_VOTER_2, 400u128, VoteOption::NoWithVeto),
+    ]
+    .iter()
+    {
+        let staked = if *voter == TEST_VOTER { 0 } else { 600u128 };
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(staked + amount + DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128(*amount),
+            msg: Some(
+                to_binary(&Cw20HookMsg::StakeAndVote {
+                    poll_id: 1,
+                    vote: vote.clone(),
+                })
+                .unwrap(),
+            ),
+        });
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
 
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
 
-    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        handle_res.log,
-        vec![
-            log("action", "create_poll"),
-            log("creator", TEST_CREATOR),
-            log("poll_id", "1"),
-            log("end_height", "22345"),
-        ]
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.rejected_reason, Some(RejectedReason::Vetoed));
+
+    let env = mock_env_height(
+        TEST_CREATOR,
+        &[],
+        DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD,
+        10000,
     );
+    match handle(
+        &mut deps,
+        env,
+        HandleMsg::ExecuteRejectedPoll { poll_id: 1 },
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll was not voted down with the quorum reached")
+        }
+        _ => panic!("Must return generic error"),
+    }
 
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(
-            &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128((voter1_stake + DEFAULT_PROPOSAL_DEPOSIT) as u128),
-        )],
-    )]);
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert!(!poll_res.on_reject_executed);
+}
+
+#[test]
+fn end_poll_stake_refund() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(voter1_stake as u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: None,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: Some(true),
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: None,
+                execution_bounty: None,
+                on_reject_msgs: None,
+                preset: None,
+            })
+            .unwrap(),
+        ),
     });
-
-    let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg).unwrap();
-    assert_stake_tokens_result(
-        voter1_stake,
-        DEFAULT_PROPOSAL_DEPOSIT,
-        voter1_stake,
-        1,
-        handle_res,
-        &mut deps,
-    );
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
         &[(
             &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128((voter1_stake + voter2_stake + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER_2),
-        amount: Uint128::from(voter2_stake as u128),
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
-
     let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg).unwrap();
-    assert_stake_tokens_result(
-        voter1_stake + voter2_stake,
-        DEFAULT_PROPOSAL_DEPOSIT,
-        voter2_stake,
-        1,
-        handle_res,
-        &mut deps,
-    );
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let env = mock_env(TEST_VOTER_2, &[]);
     let msg = HandleMsg::CastVote {
         poll_id: 1,
-        vote: VoteOption::No,
-        amount: Uint128::from(voter2_stake),
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(100u128)),
+        voter: None,
     };
-    let handle_res = handle(&mut deps, env, msg).unwrap();
-    assert_cast_vote_success(TEST_VOTER_2, voter2_stake, 1, VoteOption::No, handle_res);
-
-    let msg = HandleMsg::EndPoll { poll_id: 1 };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
-    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
-    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
+    // the deposit is staked for the creator instead of transferred
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(res.messages, vec![]);
     assert_eq!(
-        handle_res.log,
+        res.log,
         vec![
             log("action", "end_poll"),
             log("poll_id", "1"),
-            log("rejected_reason", "Threshold not reached"),
-            log("passed", "false"),
+            log("rejected_reason", ""),
+            log("passed", "true"),
+            log("refund_share", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
         ]
     );
+
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_CREATOR),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let staker: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(staker.balance, Uint128(DEFAULT_PROPOSAL_DEPOSIT));
+    assert_eq!(staker.share, Uint128(DEFAULT_PROPOSAL_DEPOSIT));
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+    assert_eq!(
+        state.total_share,
+        Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT)
+    );
 }
 
 #[test]
-fn fails_cast_vote_not_enough_staked() {
+fn end_poll_voters_root() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
-    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
 
     let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_create_poll_result(
-        1,
-        DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        handle_res,
-        &mut deps,
-    );
-
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(
-            &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128(10u128 + DEFAULT_PROPOSAL_DEPOSIT),
-        )],
-    )]);
+    let mut balance = DEFAULT_PROPOSAL_DEPOSIT;
+    for (voter, amount, vote) in [
+        (TEST_VOTER, 100u128, VoteOption::Yes),
+        (TEST_VOTER_2, 50u128, VoteOption::No),
+    ]
+    .iter()
+    {
+        balance += amount;
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(balance))],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128::from(*amount),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+        let msg = HandleMsg::CastVote {
+            poll_id: 1,
+            vote: vote.clone(),
+            amount: Some(Uint128::from(*amount)),
+            voter: None,
+        };
+        let env = mock_env_height(voter, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
 
-    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(10u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
-    });
+    // the tally is only final once the poll ended
+    match query(&deps, QueryMsg::PollTally { poll_id: 1 }) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll is in progress"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(10, DEFAULT_PROPOSAL_DEPOSIT, 10, 1, handle_res, &mut deps);
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
 
-    let env = mock_env_height(TEST_VOTER, &coins(11, VOTING_TOKEN), 0, 10000);
-    let msg = HandleMsg::CastVote {
-        poll_id: 1,
-        vote: VoteOption::Yes,
-        amount: Uint128::from(11u128),
+    let leaf_1: [u8; 32] = sha3::Keccak256::digest(b"voter1yes100").into();
+    let leaf_2: [u8; 32] = sha3::Keccak256::digest(b"voter2no50").into();
+    let root: [u8; 32] = if leaf_1 < leaf_2 {
+        sha3::Keccak256::digest(&[leaf_1, leaf_2].concat()).into()
+    } else {
+        sha3::Keccak256::digest(&[leaf_2, leaf_1].concat()).into()
     };
 
-    let res = handle(&mut deps, env, msg);
-
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "User does not have enough staked tokens.")
-        }
-        Err(e) => panic!("Unexpected error: {:?}", e),
-    }
-}
+    let res = query(&deps, QueryMsg::PollTally { poll_id: 1 }).unwrap();
+    let response: PollTallyResponse = from_binary(&res).unwrap();
+    assert_eq!(response.voters_root, Some(hex::encode(root)));
+
+    let mut tally: Vec<u8> = vec![];
+    tally.extend_from_slice(&1u64.to_be_bytes());
+    tally.extend_from_slice(&DEFAULT_VOTING_PERIOD.to_be_bytes());
+    tally.push(1);
+    tally.extend_from_slice(&100u128.to_be_bytes());
+    tally.extend_from_slice(&50u128.to_be_bytes());
+    tally.extend_from_slice(&150u128.to_be_bytes());
+    tally.extend_from_slice(&root);
+    assert_eq!(response.tally, Binary(tally));
+}
 
 #[test]
-fn happy_days_cast_vote() {
+fn end_poll_refund_address() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
-    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: None,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: Some(HumanAddr::from("treasury0000")),
+                option_labels: None,
+                vote_reward: None,
+                execution_bounty: None,
+                on_reject_msgs: None,
+                preset: None,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_create_poll_result(
-        1,
-        DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        handle_res,
-        &mut deps,
-    );
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.creator, HumanAddr::from(TEST_CREATOR));
+    assert_eq!(poll.refund_address, Some(HumanAddr::from("treasury0000")));
 
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
         &[(
             &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(11u128),
+        amount: Uint128::from(100u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
-
     let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, handle_res, &mut deps);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let env = mock_env_height(TEST_VOTER, &coins(11, VOTING_TOKEN), 0, 10000);
-    let amount = 10u128;
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
+        amount: Some(Uint128::from(100u128)),
+        voter: None,
     };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_cast_vote_success(TEST_VOTER, amount, 1, VoteOption::Yes, handle_res);
-
-    // balance be double
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(
-            &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128(22u128 + DEFAULT_PROPOSAL_DEPOSIT),
-        )],
-    )]);
+    let res = query(&deps, QueryMsg::PollDeposit { poll_id: 1 }).unwrap();
+    let response: PollDepositResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, DepositStatus::Locked);
+    assert_eq!(response.destination, None);
 
-    // Query staker
-    let res = query(
-        &deps,
-        QueryMsg::Staker {
-            address: HumanAddr::from(TEST_VOTER),
-        },
-    )
-    .unwrap();
-    let response: StakerResponse = from_binary(&res).unwrap();
+    // the deposit is refunded to the refund address
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
     assert_eq!(
-        response,
-        StakerResponse {
-            balance: Uint128(22u128),
-            share: Uint128(11u128),
-            locked_balance: vec![(
-                1u64,
-                VoterInfo {
-                    vote: VoteOption::Yes,
-                    balance: Uint128::from(amount),
-                }
-            )]
-        }
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("treasury0000"),
+                amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
     );
 
-    // Query voters
-    let res = query(
-        &deps,
-        QueryMsg::Voters {
-            poll_id: 1u64,
-            start_after: None,
-            limit: None,
-            order_by: Some(OrderBy::Desc),
-        },
-    )
-    .unwrap();
-    let response: VotersResponse = from_binary(&res).unwrap();
+    let res = query(&deps, QueryMsg::PollDeposit { poll_id: 1 }).unwrap();
+    let response: PollDepositResponse = from_binary(&res).unwrap();
     assert_eq!(
-        response.voters,
-        vec![VotersResponseItem {
-            voter: HumanAddr::from(TEST_VOTER),
-            vote: VoteOption::Yes,
-            balance: Uint128::from(amount),
-        }]
+        response,
+        PollDepositResponse {
+            poll_id: 1,
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            status: DepositStatus::Refunded,
+            destination: Some(HumanAddr::from("treasury0000")),
+            settled_height: Some(DEFAULT_VOTING_PERIOD),
+        }
     );
-
-    let res = query(
-        &deps,
-        QueryMsg::Voters {
-            poll_id: 1u64,
-            start_after: Some(HumanAddr::from(TEST_VOTER)),
-            limit: None,
-            order_by: None,
-        },
-    )
-    .unwrap();
-    let response: VotersResponse = from_binary(&res).unwrap();
-    assert_eq!(response.voters.len(), 0);
 }
 
 #[test]
-fn happy_days_withdraw_voting_tokens() {
+fn deposit_stake_interest_to_community() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: Some(HumanAddr::from("community0000")),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(1000u128))],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(11u128),
+        amount: Uint128(1000u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
-
     let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
-
-    let state: State = state_read(&mut deps.storage).load().unwrap();
-    assert_eq!(
-        state,
-        State {
-            contract_addr: deps
-                .api
-                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
-                .unwrap(),
-            poll_count: 0,
-            total_share: Uint128::from(11u128),
-            total_deposit: Uint128::zero(),
-        }
-    );
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    // double the balance, only half will be withdrawn
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(22u128))],
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
     )]);
 
-    let env = mock_env(TEST_VOTER, &[]);
-    let msg = HandleMsg::WithdrawVotingTokens {
-        amount: Some(Uint128::from(11u128)),
-    };
-
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    let msg = handle_res.messages.get(0).expect("no message");
+    // the deposit is staked for no one
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
+    let state: State = state_read(&deps.storage).load().unwrap();
     assert_eq!(
-        msg,
-        &CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: HumanAddr::from(VOTING_TOKEN),
-            msg: to_binary(&Cw20HandleMsg::Transfer {
-                recipient: HumanAddr::from(TEST_VOTER),
-                amount: Uint128::from(11u128),
-            })
-            .unwrap(),
-            send: vec![],
-        })
+        state.total_share,
+        Uint128(1000u128 + DEFAULT_PROPOSAL_DEPOSIT)
     );
+    assert_eq!(state.deposit_stake_share, Uint128(DEFAULT_PROPOSAL_DEPOSIT));
+    assert_eq!(state.total_deposit, Uint128::zero());
 
-    let state: State = state_read(&mut deps.storage).load().unwrap();
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the staked balance doubles during the poll
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(2 * (1000u128 + DEFAULT_PROPOSAL_DEPOSIT)),
+        )],
+    )]);
+
+    // the deposit stake doesn't count against the quorum
+    let msg = HandleMsg::EndPoll { poll_id: 1 };
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(res.log[3], log("passed", "true"));
     assert_eq!(
-        state,
-        State {
-            contract_addr: deps
-                .api
-                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(VOTING_TOKEN),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("community0000"),
+                    amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+                })
                 .unwrap(),
-            poll_count: 0,
-            total_share: Uint128::from(6u128),
-            total_deposit: Uint128::zero(),
-        }
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(VOTING_TOKEN),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from(TEST_CREATOR),
+                    amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+                })
+                .unwrap(),
+            }),
+        ]
     );
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_share, Uint128(1000u128));
+    assert_eq!(state.deposit_stake_share, Uint128::zero());
+    assert_eq!(state.total_deposit, Uint128::zero());
 }
 
 #[test]
-fn happy_days_withdraw_voting_tokens_all() {
+fn extend_poll() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
-    )]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(11u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
-    });
+    // extension is disabled by default
+    let msg = HandleMsg::ExtendPoll {
+        poll_id: 1,
+        extra_blocks: 100,
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], 10, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll can be extended by 1 to 0 blocks")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
+    let update_msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: Some(100),
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, update_msg).unwrap();
 
-    let state: State = state_read(&mut deps.storage).load().unwrap();
+    let env = mock_env_height(TEST_VOTER, &[], 10, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], 10, 10000);
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::ExtendPoll {
+            poll_id: 1,
+            extra_blocks: 101,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll can be extended by 1 to 100 blocks")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
     assert_eq!(
-        state,
-        State {
-            contract_addr: deps
-                .api
-                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
-                .unwrap(),
-            poll_count: 0,
-            total_share: Uint128::from(11u128),
-            total_deposit: Uint128::zero(),
-        }
+        res.log,
+        vec![
+            log("action", "extend_poll"),
+            log("poll_id", "1"),
+            log("end_height", (DEFAULT_VOTING_PERIOD + 100).to_string()),
+        ]
     );
 
-    // double the balance, all balance withdrawn
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(22u128))],
-    )]);
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.end_height, DEFAULT_VOTING_PERIOD + 100);
 
-    let env = mock_env(TEST_VOTER, &[]);
-    let msg = HandleMsg::WithdrawVotingTokens { amount: None };
+    // a poll can only be extended once
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll has already been extended"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    let msg = handle_res.messages.get(0).expect("no message");
+    // the poll cannot be ended at the original end height
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 });
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Voting period has not expired"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    assert_eq!(
-        msg,
-        &CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: HumanAddr::from(VOTING_TOKEN),
-            msg: to_binary(&Cw20HandleMsg::Transfer {
-                recipient: HumanAddr::from(TEST_VOTER),
-                amount: Uint128::from(22u128),
-            })
-            .unwrap(),
-            send: vec![],
-        })
-    );
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let state: State = state_read(&mut deps.storage).load().unwrap();
+    // a poll past its end height is not reopened
+    let msg = HandleMsg::ExtendPoll {
+        poll_id: 2,
+        extra_blocks: 100,
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Voting period has expired"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the gov contract extends the poll through a passed poll
+    let env = mock_env_height(MOCK_CONTRACT_ADDR, &[], 10, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
     assert_eq!(
-        state,
-        State {
-            contract_addr: deps
-                .api
-                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
-                .unwrap(),
-            poll_count: 0,
-            total_share: Uint128::zero(),
-            total_deposit: Uint128::zero(),
-        }
+        res.log,
+        vec![
+            log("action", "extend_poll"),
+            log("poll_id", "2"),
+            log("end_height", (DEFAULT_VOTING_PERIOD + 100).to_string()),
+        ]
     );
 }
 
 #[test]
-fn withdraw_voting_tokens_remove_not_in_progress_poll_voter_info() {
+fn end_poll_narrow_pass() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: Some(Decimal::percent(10)),
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // 35% of the staked tokens vote, within 10% of the 30% quorum
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(35u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(11u128),
+        amount: Uint128::from(35u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
-
     let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
-
-    // make fake polls; one in progress & one in passed
-    poll_store(&mut deps.storage)
-        .save(
-            &1u64.to_be_bytes(),
-            &Poll {
-                id: 1u64,
-                creator: CanonicalAddr::default(),
-                status: PollStatus::InProgress,
-                yes_votes: Uint128::zero(),
-                no_votes: Uint128::zero(),
-                end_height: 0u64,
-                title: "title".to_string(),
-                description: "description".to_string(),
-                deposit_amount: Uint128::zero(),
-                link: None,
-                execute_data: None,
-                total_balance_at_end_poll: None,
-                staked_amount: None,
-            },
-        )
-        .unwrap();
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    poll_store(&mut deps.storage)
-        .save(
-            &2u64.to_be_bytes(),
-            &Poll {
-                id: 1u64,
-                creator: CanonicalAddr::default(),
-                status: PollStatus::Passed,
-                yes_votes: Uint128::zero(),
-                no_votes: Uint128::zero(),
-                end_height: 0u64,
-                title: "title".to_string(),
-                description: "description".to_string(),
-                deposit_amount: Uint128::zero(),
-                link: None,
-                execute_data: None,
-                total_balance_at_end_poll: None,
-                staked_amount: None,
-            },
-        )
-        .unwrap();
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
 
-    let voter_addr_raw = deps
-        .api
-        .canonical_address(&HumanAddr::from(TEST_VOTER))
-        .unwrap();
-    poll_voter_store(&mut deps.storage, 1u64)
-        .save(
-            &voter_addr_raw.as_slice(),
-            &VoterInfo {
-                vote: VoteOption::Yes,
-                balance: Uint128(5u128),
-            },
-        )
-        .unwrap();
-    poll_voter_store(&mut deps.storage, 2u64)
-        .save(
-            &voter_addr_raw.as_slice(),
-            &VoterInfo {
-                vote: VoteOption::Yes,
-                balance: Uint128(5u128),
-            },
-        )
-        .unwrap();
-    bank_store(&mut deps.storage)
-        .save(
-            &voter_addr_raw.as_slice(),
-            &TokenManager {
-                share: Uint128(11u128),
-                locked_balance: vec![
-                    (
-                        1u64,
-                        VoterInfo {
-                            vote: VoteOption::Yes,
-                            balance: Uint128(5u128),
-                        },
-                    ),
-                    (
-                        2u64,
-                        VoterInfo {
-                            vote: VoteOption::Yes,
-                            balance: Uint128(5u128),
-                        },
-                    ),
-                ],
-            },
-        )
-        .unwrap();
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER_2),
+        amount: Uint128::from(65u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    // withdraw voting token must remove not in-progress votes infos from the store
-    let env = mock_env(TEST_VOTER, &[]);
-    let msg = HandleMsg::WithdrawVotingTokens {
-        amount: Some(Uint128::from(5u128)),
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(35u128)),
+        voter: None,
     };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let _ = handle(&mut deps, env, msg).unwrap();
-    let voter = poll_voter_read(&deps.storage, 1u64)
-        .load(&voter_addr_raw.as_slice())
-        .unwrap();
-    assert_eq!(
-        voter,
-        VoterInfo {
-            vote: VoteOption::Yes,
-            balance: Uint128(5u128),
-        }
-    );
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
     assert_eq!(
-        poll_voter_read(&deps.storage, 2u64)
-            .load(&voter_addr_raw.as_slice())
-            .is_err(),
-        true
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", ""),
+            log("passed", "true"),
+            log("narrow_pass", "true"),
+        ]
     );
 
-    let token_manager = bank_read(&deps.storage)
-        .load(&voter_addr_raw.as_slice())
-        .unwrap();
-    assert_eq!(
-        token_manager.locked_balance,
-        vec![(
-            1u64,
-            VoterInfo {
-                vote: VoteOption::Yes,
-                balance: Uint128(5u128),
-            }
-        )]
-    );
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.status, PollStatus::Passed);
+    assert!(poll.narrow_pass);
 }
 
 #[test]
-fn fails_withdraw_voting_tokens_no_stake() {
+fn end_poll_with_creation_config() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
-    let env = mock_env(TEST_VOTER, &coins(11, VOTING_TOKEN));
-    let msg = HandleMsg::WithdrawVotingTokens {
-        amount: Some(Uint128::from(11u128)),
-    };
-
-    let res = handle(&mut deps, env, msg);
-
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Nothing staked"),
-        Err(e) => panic!("Unexpected error: {:?}", e),
-    }
-}
-
-#[test]
-fn fails_withdraw_too_many_tokens() {
-    let mut deps = mock_dependencies(20, &[]);
-    mock_init(&mut deps);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(10u128))],
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(10u128),
+        amount: Uint128::from(100u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
-
     let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(10, 0, 10, 0, handle_res, &mut deps);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let env = mock_env(TEST_VOTER, &[]);
-    let msg = HandleMsg::WithdrawVotingTokens {
-        amount: Some(Uint128::from(11u128)),
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(60u128)),
+        voter: None,
     };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
+    // raising the quorum and the timelock while the poll is live
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: Some(Decimal::percent(80)),
+        threshold: None,
+        voting_period: None,
+        timelock_period: Some(DEFAULT_TIMELOCK_PERIOD * 2),
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the poll is judged by the config at its creation
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(res.log[3], log("passed", "true"));
+
+    let poll: Poll = poll_store(&mut deps.storage)
+        .load(&1u64.to_be_bytes())
+        .unwrap();
+    assert_eq!(poll.timelock_period, DEFAULT_TIMELOCK_PERIOD);
+
+    // a poll created now uses the new config
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let poll: Poll = poll_store(&mut deps.storage)
+        .load(&2u64.to_be_bytes())
+        .unwrap();
+    assert_eq!(poll.quorum, Decimal::percent(80));
+    assert_eq!(poll.timelock_period, DEFAULT_TIMELOCK_PERIOD * 2);
+}
+
+#[test]
+fn end_poll_zero_quorum() {
+    let mut deps = mock_dependencies(20, &coins(1000, VOTING_TOKEN));
+    mock_init(&mut deps);
+    let mut creator_env = mock_env_height(VOTING_TOKEN, &vec![], 1000, 10000);
+
+    let mut execute_msgs: Vec<ExecuteMsg> = vec![];
+    execute_msgs.push(ExecuteMsg {
+        order: 1u64,
+        contract: HumanAddr::from(VOTING_TOKEN),
+        msg: to_binary(&Cw20HandleMsg::Burn {
+            amount: Uint128(123),
+        })
+        .unwrap(),
+    });
+
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+
+    let handle_res = handle(&mut deps, creator_env.clone(), msg).unwrap();
+    assert_create_poll_result(
+        1,
+        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        handle_res,
+        &mut deps,
+    );
+    let stake_amount = 100;
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(stake_amount as u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    handle(&mut deps, env, msg.clone()).unwrap();
+
+    let msg = HandleMsg::EndPoll { poll_id: 1 };
+    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
+    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
+
+    let handle_res = handle(&mut deps, creator_env.clone(), msg).unwrap();
+
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "quorum_not_reached"),
+            log("passed", "false"),
+        ]
+    );
+
+    assert_eq!(handle_res.messages.len(), 0usize);
+
+    // Query rejected polls
+    let res = query(
+        &deps,
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Rejected),
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Desc),
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+
+    let res = query(
+        &deps,
+        QueryMsg::Polls {
+            filter: Some(PollStatus::InProgress),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 0);
+
+    let res = query(
+        &deps,
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Passed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 0);
+}
+
+#[test]
+fn end_poll_vetoed() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: Some(Decimal::percent(33)),
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the yes votes reach the threshold, but 40% of the tally vetoes
+    for (voter, amount, vote) in [
+        (TEST_VOTER, 600u128, VoteOption::Yes),
+        (TEST_VOTER_2, 400u128, VoteOption::NoWithVeto),
+    ]
+    .iter()
+    {
+        let staked = if *voter == TEST_VOTER { 0 } else { 600u128 };
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(staked + amount + DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128(*amount),
+            msg: Some(
+                to_binary(&Cw20HookMsg::StakeAndVote {
+                    poll_id: 1,
+                    vote: vote.clone(),
+                })
+                .unwrap(),
+            ),
+        });
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "vetoed"),
+            log("passed", "false"),
+        ]
+    );
+    // the deposit is slashed
+    assert_eq!(res.messages, vec![]);
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let response: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, PollStatus::Rejected);
+    assert_eq!(response.no_votes, Uint128(400u128));
+    assert_eq!(response.veto_votes, Uint128(400u128));
+    assert_eq!(response.rejected_reason, Some(RejectedReason::Vetoed));
+
+    let res = query(&deps, QueryMsg::DepositLedger { poll_id: 1 }).unwrap();
+    let response: DepositLedgerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, DepositStatus::Slashed);
+}
+
+#[test]
+fn emergency_council_actions() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: Some(EmergencyCouncil {
+            members: vec![
+                HumanAddr::from(TEST_VOTER),
+                HumanAddr::from(TEST_VOTER_2),
+                HumanAddr::from(TEST_VOTER_3),
+            ],
+            threshold: 2,
+            pausable_contracts: vec![PausableContract {
+                contract_addr: HumanAddr::from("collector"),
+                pause_msg: Binary::from(b"pause"),
+            }],
+        }),
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128(1000u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeAndVote {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    // only council members submit actions
+    let msg = HandleMsg::SubmitEmergencyAction {
+        action: EmergencyAction::VetoPoll { poll_id: 1 },
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "submit_emergency_action"),
+            log("action_id", 1),
+            log("emergency_action", "veto_poll/1"),
+            log("proposer", TEST_VOTER),
+            log("approvals", 1),
+        ]
+    );
+
+    let msg = HandleMsg::ApproveEmergencyAction { action_id: 1 };
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Emergency action already approved")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the second approval reaches the threshold and vetoes the passed poll
+    let env = mock_env_height(TEST_VOTER_2, &[], DEFAULT_VOTING_PERIOD + 1, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "approve_emergency_action"),
+            log("action_id", 1),
+            log("emergency_action", "veto_poll/1"),
+            log("approver", TEST_VOTER_2),
+            log("approvals", 2),
+            log("executed", "true"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let response: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, PollStatus::Rejected);
+    assert_eq!(
+        response.rejected_reason,
+        Some(RejectedReason::EmergencyVetoed)
+    );
+
+    let env = mock_env_height(
+        TEST_CREATOR,
+        &[],
+        DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD,
+        10000,
+    );
+    match handle(&mut deps, env, HandleMsg::ExecutePoll { poll_id: 1 }) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll is not in passed status"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the registered contracts can be paused
+    let msg = HandleMsg::SubmitEmergencyAction {
+        action: EmergencyAction::PauseContract {
+            contract_addr: HumanAddr::from("distributor"),
+        },
+    };
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Contract is not pausable by the emergency council")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::SubmitEmergencyAction {
+        action: EmergencyAction::PauseContract {
+            contract_addr: HumanAddr::from("collector"),
+        },
+    };
+    let env = mock_env_height(TEST_VOTER_3, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+    let msg = HandleMsg::ApproveEmergencyAction { action_id: 2 };
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("collector"),
+            msg: Binary::from(b"pause"),
+            send: vec![],
+        })]
+    );
+
+    // pausing the gov contract stops poll creation
+    let msg = HandleMsg::SubmitEmergencyAction {
+        action: EmergencyAction::PauseGov {},
+    };
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+    let msg = HandleMsg::ApproveEmergencyAction { action_id: 3 };
+    let env = mock_env_height(TEST_VOTER_2, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert!(response.emergency_paused);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Paused by the emergency council"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // poll execution is not paused
+    let env = mock_env_height(
+        TEST_CREATOR,
+        &[],
+        DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD,
+        10000,
+    );
+    match handle(&mut deps, env, HandleMsg::ExecutePoll { poll_id: 1 }) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll is not in passed status"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // pending actions cannot be approved once expired
+    let msg = HandleMsg::SubmitEmergencyAction {
+        action: EmergencyAction::UnpauseGov {},
+    };
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+    let msg = HandleMsg::ApproveEmergencyAction { action_id: 4 };
+    let env = mock_env_height(
+        TEST_VOTER_2,
+        &[],
+        DEFAULT_VOTING_PERIOD + EMERGENCY_ACTION_PERIOD + 1,
+        10000,
+    );
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Emergency action has expired"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        &deps,
+        QueryMsg::EmergencyActions {
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Asc),
+        },
+    )
+    .unwrap();
+    let response: EmergencyActionsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.actions.len(), 4);
+    assert_eq!(
+        response.actions[0],
+        EmergencyActionResponse {
+            id: 1,
+            action: EmergencyAction::VetoPoll { poll_id: 1 },
+            proposer: HumanAddr::from(TEST_VOTER),
+            approvals: vec![HumanAddr::from(TEST_VOTER), HumanAddr::from(TEST_VOTER_2)],
+            submitted_height: DEFAULT_VOTING_PERIOD,
+            expiry_height: DEFAULT_VOTING_PERIOD + EMERGENCY_ACTION_PERIOD,
+            executed_height: Some(DEFAULT_VOTING_PERIOD + 1),
+        }
+    );
+}
+
+#[test]
+fn claim_vote_reward() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    // 300 of the sent amount are the vote reward
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT + 300u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: None,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: Some(Uint128(300u128)),
+                execution_bounty: None,
+                on_reject_msgs: None,
+                preset: None,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(res.log.last(), Some(&log("vote_reward", 300)));
+
+    for (voter, amount, vote) in [
+        (TEST_VOTER, 600u128, VoteOption::Yes),
+        (TEST_VOTER_2, 300u128, VoteOption::No),
+    ]
+    .iter()
+    {
+        let staked = if *voter == TEST_VOTER { 0 } else { 600u128 };
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(staked + amount + DEFAULT_PROPOSAL_DEPOSIT + 300u128),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128(*amount),
+            msg: Some(
+                to_binary(&Cw20HookMsg::StakeAndVote {
+                    poll_id: 1,
+                    vote: vote.clone(),
+                })
+                .unwrap(),
+            ),
+        });
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let msg = HandleMsg::ClaimVoteReward { poll_id: 1 };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll has not ended"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    // 600 of the 900 voted balance
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_VOTER),
+                amount: Uint128(200u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No vote reward to claim"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.vote_reward_pool, Uint128(100u128));
+    assert_eq!(response.total_deposit, Uint128(100u128));
+
+    // the voter info is kept through a withdrawal until the reward is claimed
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(1000u128))],
+    )]);
+    let env = mock_env_height(TEST_VOTER_2, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::WithdrawVotingTokens { amount: None },
+    )
+    .unwrap();
+
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "claim_vote_reward"),
+            log("poll_id", 1),
+            log("amount", 100),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let response: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(response.vote_reward, Uint128(300u128));
+    assert_eq!(response.vote_reward_claimed, Uint128(300u128));
+}
+
+#[test]
+fn execution_bounty() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let execute_msgs = vec![ExecuteMsg {
+        order: 1u64,
+        contract: HumanAddr::from(VOTING_TOKEN),
+        msg: to_binary(&Cw20HandleMsg::Burn {
+            amount: Uint128(123),
+        })
+        .unwrap(),
+    }];
+    let create_msg = |execute_msgs: Option<Vec<ExecuteMsg>>, execution_bounty: u128| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: Some(
+                to_binary(&Cw20HookMsg::CreatePoll {
+                    title: "test".to_string(),
+                    description: "test".to_string(),
+                    links: None,
+                    execute_msgs,
+                    commit_reveal: None,
+                    content_hash: None,
+                    referendum: None,
+                    stake_refund: None,
+                    reserved_poll_id: None,
+                    refund_address: None,
+                    option_labels: None,
+                    vote_reward: None,
+                    execution_bounty: Some(Uint128(execution_bounty)),
+                    on_reject_msgs: None,
+                    preset: None,
+                })
+                .unwrap(),
+            ),
+        })
+    };
+
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    match handle(&mut deps, env.clone(), create_msg(None, 100u128)) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Execution bounty requires execute messages")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match handle(
+        &mut deps,
+        env.clone(),
+        create_msg(Some(execute_msgs.clone()), DEFAULT_PROPOSAL_DEPOSIT + 1),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Execution bounty exceeds the deposit")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, env, create_msg(Some(execute_msgs), 100u128)).unwrap();
+    assert_eq!(res.log.last(), Some(&log("execution_bounty", 100)));
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128(1000u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeAndVote {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the bounty is held back from the deposit refund of the passed poll
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_CREATOR),
+                amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT - 100u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.execution_bounty_pool, Uint128(100u128));
+    assert_eq!(response.total_deposit, Uint128(100u128));
+
+    // anyone executing the poll is paid the bounty
+    let env = mock_env_height(
+        "executor0000",
+        &[],
+        DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD,
+        10000,
+    );
+    let res = handle(&mut deps, env, HandleMsg::ExecutePoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.messages.last(),
+        Some(&CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("executor0000"),
+                amount: Uint128(100u128),
+            })
+            .unwrap(),
+            send: vec![],
+        }))
+    );
+    assert_eq!(res.log.last(), Some(&log("execution_bounty", 100)));
+
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.execution_bounty_pool, Uint128::zero());
+    assert_eq!(response.total_deposit, Uint128::zero());
+}
+
+#[test]
+fn end_poll_quorum_rejected() {
+    let mut deps = mock_dependencies(20, &coins(100, VOTING_TOKEN));
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env(VOTING_TOKEN, &vec![]);
+    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "create_poll"),
+            log("creator", TEST_CREATOR),
+            log("poll_id", "1"),
+            log("end_height", "22345"),
+        ]
+    );
+
+    let stake_amount = 100;
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(stake_amount as u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(
+        stake_amount,
+        DEFAULT_PROPOSAL_DEPOSIT,
+        stake_amount,
+        1,
+        handle_res,
+        &mut deps,
+    );
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(10u128)),
+        voter: None,
+    };
+    let env = mock_env(TEST_VOTER, &[]);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "cast_vote"),
+            log("poll_id", "1"),
+            log("amount", "10"),
+            log("voter", TEST_VOTER),
+            log("vote_option", "yes"),
+            log("threshold_reached", "true"),
+        ]
+    );
+
+    let msg = HandleMsg::EndPoll { poll_id: 1 };
+
+    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
+    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
+
+    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "quorum_not_reached"),
+            log("passed", "false"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::Rejected, value.status);
+    assert_eq!(
+        Some(RejectedReason::QuorumNotReached),
+        value.rejected_reason
+    );
+}
+
+#[test]
+fn end_poll_adaptive_quorum() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let adaptive_quorum = |min_quorum, max_quorum| HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: Some(AdaptiveQuorum {
+            min_quorum,
+            max_quorum,
+            poll_window: 3,
+        }),
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+
+    let env = mock_env(TEST_CREATOR, &[]);
+    match handle(
+        &mut deps,
+        env.clone(),
+        adaptive_quorum(Decimal::percent(50), Decimal::percent(10)),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "adaptive quorum bounds must be 0 to 1 with min not above max"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = handle(
+        &mut deps,
+        env,
+        adaptive_quorum(Decimal::percent(12), Decimal::percent(50)),
+    )
+    .unwrap();
+
+    // the average starts from the configured quorum
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.turnout_ema, None);
+    assert_eq!(response.quorum, Decimal::percent(DEFAULT_QUORUM));
+
+    for _ in 0..3 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + 3 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // turnouts of 10%, 10% and 20%
+    for (poll_id, amount) in [(1u64, 10u128), (2, 10), (3, 20)].iter() {
+        let msg = HandleMsg::CastVote {
+            poll_id: *poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(*amount)),
+            voter: None,
+        };
+        let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env.clone(), HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "quorum_not_reached"),
+            log("passed", "false"),
+            log("quorum", "0.3"),
+        ]
+    );
+
+    // (2 * 0.1 + 2 * 0.3) / 4
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.turnout_ema, Some(Decimal::percent(20)));
+    assert_eq!(response.quorum, Decimal::percent(20));
+
+    let res = handle(&mut deps, env.clone(), HandleMsg::EndPoll { poll_id: 2 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "2"),
+            log("rejected_reason", "quorum_not_reached"),
+            log("passed", "false"),
+            log("quorum", "0.2"),
+        ]
+    );
+
+    // (2 * 0.1 + 2 * 0.2) / 4, the quorum tracks the lower turnout
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.turnout_ema, Some(Decimal::percent(15)));
+    assert_eq!(response.quorum, Decimal::percent(15));
+
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 3 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "3"),
+            log("rejected_reason", ""),
+            log("passed", "true"),
+            log("quorum", "0.15"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 3 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::Passed);
+
+    // (2 * 0.2 + 2 * 0.15) / 4, the quorum rises back with the turnout
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.turnout_ema,
+        Some(Decimal::from_ratio(175u128, 1000u128))
+    );
+    assert_eq!(response.quorum, Decimal::from_ratio(175u128, 1000u128));
+}
+
+#[test]
+fn end_poll_adaptive_quorum_toggled() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let adaptive_quorum = |poll_window| HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: Some(AdaptiveQuorum {
+            min_quorum: Decimal::percent(12),
+            max_quorum: Decimal::percent(50),
+            poll_window,
+        }),
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+
+    // poll 1 is created with the fixed quorum, polls 2 and 3 with the adaptive one
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, adaptive_quorum(3)).unwrap();
+
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + 3 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // turnouts of 20%, 10% and 20%
+    for (poll_id, amount) in [(1u64, 20u128), (2, 10), (3, 20)].iter() {
+        let msg = HandleMsg::CastVote {
+            poll_id: *poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(*amount)),
+            voter: None,
+        };
+        let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    // (2 * 0.1 + 2 * 0.3) / 4
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env.clone(), HandleMsg::EndPoll { poll_id: 2 }).unwrap();
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.turnout_ema, Some(Decimal::percent(20)));
+
+    // poll 1 keeps the fixed quorum its voters saw
+    let res = handle(&mut deps, env.clone(), HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "quorum_not_reached"),
+            log("passed", "false"),
+        ]
+    );
+
+    // poll 3 keeps the adaptive quorum after it is disabled
+    let _res = handle(&mut deps, mock_env(TEST_CREATOR, &[]), adaptive_quorum(0)).unwrap();
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 3 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "3"),
+            log("rejected_reason", ""),
+            log("passed", "true"),
+            log("quorum", "0.2"),
+        ]
+    );
+}
+
+#[test]
+fn sponsor_poll() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: Some(PollSponsorship {
+            required_sponsors: 2,
+            min_stake: Uint128(10),
+        }),
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let mut staked = 0u128;
+    for (voter, amount) in [(TEST_VOTER, 20u128), (TEST_VOTER_2, 5), (TEST_VOTER_3, 10)].iter() {
+        staked += amount;
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(staked + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128(*amount),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::PendingSponsorship);
+    assert_eq!(value.required_sponsors, 2);
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128(10)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 10, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll is not in progress"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::SponsorPoll { poll_id: 1 };
+    let env = mock_env_height(TEST_CREATOR, &[], 10, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll creator cannot sponsor the poll")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_VOTER_2, &[], 10, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Must stake at least 10 token to sponsor")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_VOTER, &[], 10, 10000);
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "sponsor_poll"),
+            log("poll_id", "1"),
+            log("sponsor", TEST_VOTER),
+            log("sponsor_count", "1"),
+        ]
+    );
+
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll is already sponsored by the sender")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the voting period starts with the last required sponsor
+    let env = mock_env_height(TEST_VOTER_3, &[], 20, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "sponsor_poll"),
+            log("poll_id", "1"),
+            log("sponsor", TEST_VOTER_3),
+            log("sponsor_count", "2"),
+            log("end_height", (20 + DEFAULT_VOTING_PERIOD).to_string()),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::InProgress);
+    assert_eq!(value.end_height, 20 + DEFAULT_VOTING_PERIOD);
+    assert_eq!(
+        value.sponsors,
+        vec![HumanAddr::from(TEST_VOTER), HumanAddr::from(TEST_VOTER_3)]
+    );
+
+    // the sponsor stake is locked until the poll ends
+    let res = query(
+        &deps,
+        QueryMsg::Withdrawable {
+            address: HumanAddr::from(TEST_VOTER_3),
+        },
+    )
+    .unwrap();
+    let withdrawable: WithdrawableResponse = from_binary(&res).unwrap();
+    assert_eq!(withdrawable.amount, Uint128::zero());
+
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128(10)),
+    };
+    let env = mock_env_height(TEST_VOTER_3, &[], 20, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "User is trying to withdraw too many tokens.")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128(10)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 30, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the unsponsored poll is rejected at its end height
+    let msg = HandleMsg::SponsorPoll { poll_id: 2 };
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Sponsorship period has expired"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 2 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "2"),
+            log("rejected_reason", "sponsorship_not_reached"),
+            log("passed", "false"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::DepositLedger { poll_id: 2 }).unwrap();
+    let response: DepositLedgerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, DepositStatus::Slashed);
+
+    // and released once it has ended
+    let env = mock_env_height(TEST_CREATOR, &[], 20 + DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128(10)),
+    };
+    let env = mock_env_height(TEST_VOTER_3, &[], 20 + DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+}
+
+#[test]
+fn claim_partial_deposit_refund() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: Some(HumanAddr::from("community0000")),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: Some(Decimal::percent(40)),
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the poll fails the quorum, the rest of the deposit goes to the community
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("community0000"),
+                amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT * 6 / 10),
+            })
+            .unwrap(),
+        })]
+    );
+    assert!(res
+        .log
+        .contains(&log("partial_refund", DEFAULT_PROPOSAL_DEPOSIT * 4 / 10)));
+
+    let res = query(&deps, QueryMsg::DepositLedger { poll_id: 1 }).unwrap();
+    let response: DepositLedgerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, DepositStatus::Slashed);
+    assert_eq!(
+        response.claimable_refund,
+        Some(Uint128(DEFAULT_PROPOSAL_DEPOSIT * 4 / 10))
+    );
+
+    let res = query(&deps, QueryMsg::PollDeposit { poll_id: 1 }).unwrap();
+    let response: PollDepositResponse = from_binary(&res).unwrap();
+    assert_eq!(response.destination, Some(HumanAddr::from("community0000")));
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(
+        state.total_deposit,
+        Uint128(DEFAULT_PROPOSAL_DEPOSIT * 4 / 10)
+    );
+    assert_eq!(
+        state.unclaimed_refund,
+        Uint128(DEFAULT_PROPOSAL_DEPOSIT * 4 / 10)
+    );
+
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, HandleMsg::ClaimDepositRefund { poll_id: 1 }) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::ClaimDepositRefund { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_CREATOR),
+                amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT * 4 / 10),
+            })
+            .unwrap(),
+        })]
+    );
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+    assert_eq!(state.unclaimed_refund, Uint128::zero());
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, HandleMsg::ClaimDepositRefund { poll_id: 1 }) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No deposit refund to claim"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn deposit_delta_on_transition() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT + 300u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: None,
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: Some(Uint128(300u128)),
+                execution_bounty: None,
+                on_reject_msgs: None,
+                preset: None,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the total deposit lost the vote reward, which goes unnoticed until the deposit leaves
+    let mut state: State = state_read(&deps.storage).load().unwrap();
+    state.total_deposit = Uint128(DEFAULT_PROPOSAL_DEPOSIT);
+    state_store(&mut deps.storage).save(&state).unwrap();
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Total deposit 9999999700 does not cover the held deposits 10000000000"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn deposit_ledger() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::DepositLedger { poll_id: 1 }).unwrap();
+    let response: DepositLedgerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        DepositLedgerResponse {
+            poll_id: 1,
+            depositor: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            share: None,
+            status: DepositStatus::Locked,
+            settled_height: None,
+            claimable_refund: None,
+        }
+    );
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128(DEFAULT_PROPOSAL_DEPOSIT));
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // the deposit is slashed without quorum
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    let res = query(&deps, QueryMsg::DepositLedger { poll_id: 1 }).unwrap();
+    let response: DepositLedgerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, DepositStatus::Slashed);
+    assert_eq!(response.settled_height, Some(DEFAULT_VOTING_PERIOD));
+
+    // the slashed deposit stays with the stakers
+    let res = query(&deps, QueryMsg::PollDeposit { poll_id: 1 }).unwrap();
+    let response: PollDepositResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, DepositStatus::Slashed);
+    assert_eq!(response.destination, None);
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+
+    // drifted total deposit is rejected on the next transition
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let mut state: State = state_read(&deps.storage).load().unwrap();
+    state.total_deposit = Uint128::zero();
+    state_store(&mut deps.storage).save(&state).unwrap();
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD * 2, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 2 });
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(
+                msg,
+                "Total deposit 0 does not cover the held deposits 10000000000"
+            )
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(&deps, QueryMsg::DepositLedger { poll_id: 3 });
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll does not exist"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn reconcile_total_deposit() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // a drifted total deposit does not block the poll transitions
+    let mut state: State = state_read(&deps.storage).load().unwrap();
+    state.total_deposit = Uint128(DEFAULT_PROPOSAL_DEPOSIT + 100u128);
+    state_store(&mut deps.storage).save(&state).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(DEFAULT_PROPOSAL_DEPOSIT + 100u128),
+        )],
+    )]);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    // and is reset to the deposits locked by the polls by the owner
+    let env = mock_env(TEST_VOTER, &[]);
+    match handle(&mut deps, env, HandleMsg::ReconcileTotalDeposit {}) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env(TEST_CREATOR, &[]);
+    let res = handle(&mut deps, env, HandleMsg::ReconcileTotalDeposit {}).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "reconcile_total_deposit"),
+            log(
+                "old_total_deposit",
+                (DEFAULT_PROPOSAL_DEPOSIT + 100u128).to_string()
+            ),
+            log("total_deposit", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
+        ]
+    );
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128(DEFAULT_PROPOSAL_DEPOSIT));
+}
+
+#[test]
+fn query_tally_checkpoints() {
+    let mut deps = mock_dependencies(20, &coins(100, VOTING_TOKEN));
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: Some(2),
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env(VOTING_TOKEN, &vec![]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let voters = [
+        (TEST_VOTER, VoteOption::Yes),
+        (TEST_VOTER_2, VoteOption::No),
+        (TEST_VOTER_3, VoteOption::Yes),
+    ];
+    for (i, (voter, vote)) in voters.iter().enumerate() {
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(100u128 * (i as u128 + 1) + DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128::from(100u128),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let env = mock_env(VOTING_TOKEN, &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+
+        let msg = HandleMsg::CastVote {
+            poll_id: 1,
+            vote: vote.clone(),
+            amount: Some(Uint128::from(100u128)),
+            voter: None,
+        };
+        let env = mock_env_height(voter, &[], 12345 + i as u64, 0);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let res = query(
+        &deps,
+        QueryMsg::TallyCheckpoints {
+            poll_id: 1,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: TallyCheckpointsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.checkpoints,
+        vec![TallyCheckpoint {
+            vote_count: 2,
+            height: 12346,
+            yes_votes: Uint128(100u128),
+            no_votes: Uint128(100u128),
+        }]
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::TallyCheckpoints {
+            poll_id: 2,
+            start_after: None,
+            limit: None,
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll does not exist"),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn end_poll_quorum_excluded_addresses() {
+    let mut deps = mock_dependencies(20, &coins(100, VOTING_TOKEN));
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: Some(vec![HumanAddr::from("community")]),
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env(VOTING_TOKEN, &vec![]);
+    let _res = handle(&mut deps, creator_env.clone(), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(400u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // the excluded address stakes 75% of the total stake
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("community"),
+        amount: Uint128::from(300u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(100u128)),
+        voter: None,
+    };
+    let env = mock_env(TEST_VOTER, &[]);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 100, 1, VoteOption::Yes, handle_res);
+
+    let msg = HandleMsg::EndPoll { poll_id: 1 };
+    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
+    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
+
+    let handle_res = handle(&mut deps, creator_env, msg).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", ""),
+            log("passed", "true"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Uint128(100u128)), value.total_balance_at_end_poll);
+}
+
+#[test]
+fn cast_vote_snapshot_quorum_excluded_addresses() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: Some(vec![HumanAddr::from("community")]),
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    for (staker, amount, balance) in [
+        (TEST_VOTER, 100u128, 100u128),
+        ("community", 300u128, 400u128),
+    ]
+    .iter()
+    {
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(*balance + DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*staker),
+            amount: Uint128::from(*amount),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let env = mock_env(VOTING_TOKEN, &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    // the vote within the snapshot period takes the snapshot without the excluded stake
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(100u128)),
+        voter: None,
+    };
+    let env = mock_env_height(
+        TEST_VOTER,
+        &[],
+        DEFAULT_VOTING_PERIOD - DEFAULT_FIX_PERIOD + 1,
+        10000,
+    );
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 100, 1, VoteOption::Yes, handle_res);
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Uint128(100u128)), value.staked_amount);
+}
+
+#[test]
+fn create_poll_with_staked_deposit() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let stake_amount = 2 * DEFAULT_PROPOSAL_DEPOSIT;
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(stake_amount))],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128::from(stake_amount),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_stake_tokens_result(stake_amount, 0, stake_amount, 0, handle_res, &mut deps);
+
+    let msg = HandleMsg::CreatePollWithStakedDeposit {
+        title: "test".to_string(),
+        description: "test".to_string(),
+        links: None,
+        execute_msgs: None,
+        commit_reveal: None,
+        content_hash: None,
+        referendum: None,
+        reserved_poll_id: None,
+        option_labels: None,
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], 0, 10000);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(handle_res.messages, vec![]);
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "create_poll"),
+            log("creator", TEST_CREATOR),
+            log("poll_id", "1"),
+            log("end_height", DEFAULT_VOTING_PERIOD.to_string()),
+            log("deposit_share", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
+        ]
+    );
+
+    // staked deposit is not counted as total deposit
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.deposit_amount, Uint128(DEFAULT_PROPOSAL_DEPOSIT));
+    assert_eq!(poll.deposit_share, Some(Uint128(DEFAULT_PROPOSAL_DEPOSIT)));
+
+    // locked deposit share cannot be withdrawn
+    let env = mock_env(TEST_CREATOR, &[]);
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128(DEFAULT_PROPOSAL_DEPOSIT + 1)),
+    };
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "User is trying to withdraw too many tokens.")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    // cannot lock more shares than left
+    let msg = HandleMsg::CreatePollWithStakedDeposit {
+        title: "test".to_string(),
+        description: "test".to_string(),
+        links: None,
+        execute_msgs: None,
+        commit_reveal: None,
+        content_hash: None,
+        referendum: None,
+        reserved_poll_id: None,
+        option_labels: None,
+    };
+    let handle_res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log.get(4).expect("no log"),
+        &log("deposit_share", DEFAULT_PROPOSAL_DEPOSIT.to_string())
+    );
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "User does not have enough staked tokens.")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128(stake_amount)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // quorum reached; the deposit share is unlocked without any transfer
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let handle_res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(handle_res.messages, vec![]);
+
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_CREATOR),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.share, Uint128(stake_amount));
+    assert_eq!(
+        response.deposit_locked_share,
+        Uint128(DEFAULT_PROPOSAL_DEPOSIT)
+    );
+}
+
+#[test]
+fn end_poll_quorum_rejected_slashes_staked_deposit() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let msg = HandleMsg::CreatePollWithStakedDeposit {
+        title: "test".to_string(),
+        description: "test".to_string(),
+        links: None,
+        execute_msgs: None,
+        commit_reveal: None,
+        content_hash: None,
+        referendum: None,
+        reserved_poll_id: None,
+        option_labels: None,
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let handle_res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "quorum_not_reached"),
+            log("passed", "false"),
+        ]
+    );
+
+    // creator's locked share is burned and distributed to the other stakers
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.total_share, Uint128(DEFAULT_PROPOSAL_DEPOSIT));
+
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_CREATOR),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.share, Uint128::zero());
+    assert_eq!(response.deposit_locked_share, Uint128::zero());
+
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_VOTER),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128(2 * DEFAULT_PROPOSAL_DEPOSIT));
+
+    // the balance follows the token balance passed by the caller
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_VOTER),
+            token_balance: Some(Uint128(3 * DEFAULT_PROPOSAL_DEPOSIT)),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128(3 * DEFAULT_PROPOSAL_DEPOSIT));
+}
+
+#[test]
+fn end_poll_quorum_rejected_noting_staked() {
+    let mut deps = mock_dependencies(20, &coins(100, VOTING_TOKEN));
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env(VOTING_TOKEN, &vec![]);
+    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "create_poll"),
+            log("creator", TEST_CREATOR),
+            log("poll_id", "1"),
+            log("end_height", "22345"),
+        ]
+    );
+
+    let msg = HandleMsg::EndPoll { poll_id: 1 };
+
+    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
+    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
+
+    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "quorum_not_reached"),
+            log("passed", "false"),
+        ]
+    );
+}
+
+#[test]
+fn end_poll_nay_rejected() {
+    let voter1_stake = 100;
+    let voter2_stake = 1000;
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+    let mut creator_env = mock_env(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+
+    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "create_poll"),
+            log("creator", TEST_CREATOR),
+            log("poll_id", "1"),
+            log("end_height", "22345"),
+        ]
+    );
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128((voter1_stake + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(voter1_stake as u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_stake_tokens_result(
+        voter1_stake,
+        DEFAULT_PROPOSAL_DEPOSIT,
+        voter1_stake,
+        1,
+        handle_res,
+        &mut deps,
+    );
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128((voter1_stake + voter2_stake + DEFAULT_PROPOSAL_DEPOSIT) as u128),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER_2),
+        amount: Uint128::from(voter2_stake as u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_stake_tokens_result(
+        voter1_stake + voter2_stake,
+        DEFAULT_PROPOSAL_DEPOSIT,
+        voter2_stake,
+        1,
+        handle_res,
+        &mut deps,
+    );
+
+    let env = mock_env(TEST_VOTER_2, &[]);
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+        amount: Some(Uint128::from(voter2_stake)),
+        voter: None,
+    };
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER_2, voter2_stake, 1, VoteOption::No, handle_res);
+
+    let msg = HandleMsg::EndPoll { poll_id: 1 };
+
+    creator_env.message.sender = HumanAddr::from(TEST_CREATOR);
+    creator_env.block.height = &creator_env.block.height + DEFAULT_VOTING_PERIOD;
+    let handle_res = handle(&mut deps, creator_env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", "threshold_not_reached"),
+            log("passed", "false"),
+        ]
+    );
+}
+
+#[test]
+fn cast_vote_entire_staked_balance() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // staked balance grows with the rewards
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(22u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 22, 1, VoteOption::Yes, handle_res);
+
+    let env = mock_env_height(TEST_VOTER_2, &[], 0, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Cannot vote with zero amount"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn cast_vote_by_operator() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let vote_msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+        voter: Some(HumanAddr::from(TEST_VOTER)),
+    };
+    let env = mock_env_height(TEST_VOTER_2, &[], 0, 10000);
+    match handle(&mut deps, env.clone(), vote_msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let msg = HandleMsg::ApproveOperator {
+        operator: HumanAddr::from(TEST_VOTER_2),
+        expires: Expiration::AtHeight(100),
+    };
+    let _res = handle(&mut deps, mock_env_height(TEST_VOTER, &[], 0, 10000), msg).unwrap();
+
+    let res = query(
+        &deps,
+        QueryMsg::Operator {
+            staker: HumanAddr::from(TEST_VOTER),
+            operator: HumanAddr::from(TEST_VOTER_2),
+        },
+    )
+    .unwrap();
+    let response: OperatorResponse = from_binary(&res).unwrap();
+    assert_eq!(response.expires, Some(Expiration::AtHeight(100)));
+
+    // the approval can't be used once expired
+    let expired_env = mock_env_height(TEST_VOTER_2, &[], 100, 10000);
+    match handle(&mut deps, expired_env, vote_msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Operator approval expired"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // the vote is cast with the stake of the staker
+    let res = handle(&mut deps, env, vote_msg).unwrap();
+    assert_eq!(res.log.last(), Some(&log("operator", TEST_VOTER_2)));
+    let res = query(
+        &deps,
+        QueryMsg::Voters {
+            poll_id: 1,
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![VotersResponseItem {
+            voter: HumanAddr::from(TEST_VOTER),
+            vote: VoteOption::Yes,
+            balance: Uint128::from(11u128),
+            share: Uint128::from(11u128),
+        }]
+    );
+
+    let msg = HandleMsg::RevokeOperator {
+        operator: HumanAddr::from(TEST_VOTER_2),
+    };
+    let _res = handle(&mut deps, mock_env(TEST_VOTER, &[]), msg).unwrap();
+    let res = query(
+        &deps,
+        QueryMsg::Operator {
+            staker: HumanAddr::from(TEST_VOTER),
+            operator: HumanAddr::from(TEST_VOTER_2),
+        },
+    )
+    .unwrap();
+    let response: OperatorResponse = from_binary(&res).unwrap();
+    assert_eq!(response.expires, None);
+}
+
+#[test]
+fn cast_vote_milestones() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let mut total_stake = 0u128;
+    for (voter, stake) in &[
+        (TEST_VOTER, 100u128),
+        (TEST_VOTER_2, 300),
+        (TEST_VOTER_3, 600),
+    ] {
+        total_stake += stake;
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(total_stake + DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128(*stake),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let env = mock_env(VOTING_TOKEN, &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    // 10% turnout, all in favor
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        handle_res.log[5..].to_vec(),
+        vec![log("threshold_reached", "true")]
+    );
+
+    // 40% turnout, the no votes take the lead
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+        amount: None,
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER_2, &[], 0, 10000);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        handle_res.log[5..].to_vec(),
+        vec![log("quorum_reached", "true"), log("leading_option", "no")]
+    );
+
+    // milestones already reached are not logged again
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER_3, &[], 0, 10000);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        handle_res.log[5..].to_vec(),
+        vec![log("leading_option", "yes")]
+    );
+}
+
+#[test]
+fn signal_poll_option_labels() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let create_msg = |option_labels: Vec<&str>, execute_msgs: Option<Vec<ExecuteMsg>>| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: Some(
+                to_binary(&Cw20HookMsg::CreatePoll {
+                    title: "Which integration next?".to_string(),
+                    description: "TESTTEST".to_string(),
+                    links: None,
+                    execute_msgs,
+                    commit_reveal: None,
+                    content_hash: None,
+                    referendum: None,
+                    stake_refund: None,
+                    reserved_poll_id: None,
+                    refund_address: None,
+                    option_labels: Some(option_labels.iter().map(|l| l.to_string()).collect()),
+                    vote_reward: None,
+                    execution_bounty: None,
+                    on_reject_msgs: None,
+                    preset: None,
+                })
+                .unwrap(),
+            ),
+        })
+    };
+
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    match handle(&mut deps, env.clone(), create_msg(vec!["wallet"], None)) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll must have 2 to 8 option labels")
+        }
+        _ => panic!("Must return error"),
+    }
+    match handle(
+        &mut deps,
+        env.clone(),
+        create_msg(vec!["wallet", "wallet"], None),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Duplicate option label"),
+        _ => panic!("Must return error"),
+    }
+    let execute_msgs = vec![ExecuteMsg {
+        order: 1u64,
+        contract: HumanAddr::from(VOTING_TOKEN),
+        msg: to_binary(&Cw20HandleMsg::Burn {
+            amount: Uint128(123),
+        })
+        .unwrap(),
+    }];
+    match handle(
+        &mut deps,
+        env.clone(),
+        create_msg(vec!["wallet", "bridge"], Some(execute_msgs)),
+    ) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll with option labels cannot execute messages")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let _res = handle(
+        &mut deps,
+        env,
+        create_msg(vec!["wallet", "bridge", "oracle"], None),
+    )
+    .unwrap();
+
+    let mut total_stake = 0u128;
+    for (voter, stake) in &[(TEST_VOTER, 100u128), (TEST_VOTER_2, 300)] {
+        total_stake += stake;
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(total_stake + DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128(*stake),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let env = mock_env(VOTING_TOKEN, &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    // yes/no votes and unknown labels are rejected
+    for (vote, err) in &[
+        (VoteOption::Yes, "Poll must be voted with an option label"),
+        (VoteOption::Label(3), "Invalid vote option"),
+    ] {
+        let msg = HandleMsg::CastVote {
+            poll_id: 1,
+            vote: vote.clone(),
+            amount: None,
+            voter: None,
+        };
+        let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+        match handle(&mut deps, env, msg) {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, *err),
+            _ => panic!("Must return error"),
+        }
+    }
+
+    for (voter, label) in &[(TEST_VOTER, 2u8), (TEST_VOTER_2, 1u8)] {
+        let msg = HandleMsg::CastVote {
+            poll_id: 1,
+            vote: VoteOption::Label(*label),
+            amount: None,
+            voter: None,
+        };
+        let env = mock_env_height(voter, &[], 0, 10000);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.option_votes,
+        vec![Uint128::zero(), Uint128(300u128), Uint128(100u128)]
+    );
+    assert_eq!(value.yes_votes, Uint128::zero());
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "end_poll"),
+            log("poll_id", "1"),
+            log("rejected_reason", ""),
+            log("passed", "true"),
+            log("winning_label", "bridge"),
+        ]
+    );
+}
+
+#[test]
+fn execute_msg_placeholders() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let execute_msgs = vec![
+        ExecuteMsg {
+            order: 1u64,
+            contract: HumanAddr::from("community0000"),
+            msg: Binary::from(
+                br#"{"spend":{"recipient":"addr0000","amount":"${contract_balance}"}}"#.to_vec(),
+            ),
+        },
+        ExecuteMsg {
+            order: 2u64,
+            contract: HumanAddr::from("registry0000"),
+            msg: Binary::from(br#"{"register":{"poll_id":${poll_id}}}"#.to_vec()),
+        },
+    ];
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // the balance is read when the messages are built, not at poll creation
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[
+            (
+                &HumanAddr::from(MOCK_CONTRACT_ADDR),
+                &Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            ),
+            (&HumanAddr::from("community0000"), &Uint128(12345u128)),
+        ],
+    )]);
+
+    let res = query(&deps, QueryMsg::SimulateExecution { poll_id: 1 }).unwrap();
+    let response: SimulateExecutionResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("community0000"),
+                msg: Binary::from(
+                    br#"{"spend":{"recipient":"addr0000","amount":"12345"}}"#.to_vec()
+                ),
+                send: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("registry0000"),
+                msg: Binary::from(br#"{"register":{"poll_id":1}}"#.to_vec()),
+                send: vec![],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn execute_poll_executor_whitelist() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: Some(vec![HumanAddr::from("executor0000")]),
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let execute_msgs = vec![ExecuteMsg {
+        order: 1u64,
+        contract: HumanAddr::from(VOTING_TOKEN),
+        msg: to_binary(&Cw20HandleMsg::Burn {
+            amount: Uint128(123),
+        })
+        .unwrap(),
+    }];
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs),
+    );
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeAndVote {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // anyone can still end the poll
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    let height = DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD;
+    let env = mock_env_height(TEST_VOTER, &[], height, 10000);
+    match handle(&mut deps, env, HandleMsg::ExecutePoll { poll_id: 1 }) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height("executor0000", &[], height, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::ExecutePoll { poll_id: 1 }).unwrap();
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::Executed);
+}
+
+#[test]
+fn migration_in_progress_freezes_stake() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // only the contract itself can set the flag
+    let msg = HandleMsg::SetMigrationInProgress {
+        migration_in_progress: true,
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], 0, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(MOCK_CONTRACT_ADDR, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert!(response.migration_in_progress);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Migration in progress"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::WithdrawVotingTokens { amount: None };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Migration in progress"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Migration in progress"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // polls can still be ended
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    // the owner can lift the freeze
+    let msg = HandleMsg::SetMigrationInProgress {
+        migration_in_progress: false,
+    };
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::WithdrawVotingTokens { amount: None };
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+}
+
+#[test]
+fn stake_and_vote() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // the poll must exist
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeAndVote {
+                poll_id: 2,
+                vote: VoteOption::Yes,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll does not exist"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeAndVote {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        handle_res.log[..9].to_vec(),
+        vec![
+            log("action", "staking"),
+            log("sender", TEST_VOTER),
+            log("share", "100"),
+            log("amount", "100"),
+            log("action", "cast_vote"),
+            log("poll_id", "1"),
+            log("amount", "100"),
+            log("voter", TEST_VOTER),
+            log("vote_option", "yes"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Uint128::from(100u128), poll.yes_votes);
+}
+
+#[test]
+fn fails_cast_vote_not_enough_staked() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_create_poll_result(
+        1,
+        DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        handle_res,
+        &mut deps,
+    );
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(10u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(10u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(10, DEFAULT_PROPOSAL_DEPOSIT, 10, 1, handle_res, &mut deps);
+
+    let env = mock_env_height(TEST_VOTER, &coins(11, VOTING_TOKEN), 0, 10000);
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(11u128)),
+        voter: None,
+    };
+
+    let res = handle(&mut deps, env, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "User does not have enough staked tokens.")
+        }
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn fails_cast_vote_below_min_vote_amount() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env(TEST_CREATOR, &[]);
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: Some(Uint128(5u128)),
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(10u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(10u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::zero()),
+        voter: None,
+    };
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Cannot vote with zero amount"),
+        _ => panic!("Must return error"),
+    }
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(4u128)),
+        voter: None,
+    };
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Must vote with at least 5 token"),
+        _ => panic!("Must return error"),
+    }
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(5u128)),
+        voter: None,
+    };
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 5, 1, VoteOption::Yes, handle_res);
+}
+
+#[test]
+fn happy_days_cast_vote() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_create_poll_result(
+        1,
+        DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        handle_res,
+        &mut deps,
+    );
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, handle_res, &mut deps);
+
+    let env = mock_env_height(TEST_VOTER, &coins(11, VOTING_TOKEN), 0, 10000);
+    let amount = 10u128;
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(amount)),
+        voter: None,
+    };
+
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_cast_vote_success(TEST_VOTER, amount, 1, VoteOption::Yes, handle_res);
+
+    // balance be double
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(22u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    // Query staker
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_VOTER),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        StakerResponse {
+            balance: Uint128(22u128),
+            share: Uint128(11u128),
+            locked_balance: vec![(
+                1u64,
+                VoterInfo {
+                    vote: VoteOption::Yes,
+                    balance: Uint128::from(amount),
+                    share: Uint128::from(amount),
+                }
+            )],
+            vesting_locked_balance: Uint128::zero(),
+            deposit_locked_share: Uint128::zero(),
+        }
+    );
+
+    // Query voters
+    let res = query(
+        &deps,
+        QueryMsg::Voters {
+            poll_id: 1u64,
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Desc),
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![VotersResponseItem {
+            voter: HumanAddr::from(TEST_VOTER),
+            vote: VoteOption::Yes,
+            balance: Uint128::from(amount),
+            share: Uint128::from(amount),
+        }]
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::Voters {
+            poll_id: 1u64,
+            start_after: Some(HumanAddr::from(TEST_VOTER)),
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(response.voters.len(), 0);
+}
+
+#[test]
+fn commit_reveal_vote() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: None,
+                commit_reveal: Some(true),
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: None,
+                execution_bounty: None,
+                on_reject_msgs: None,
+                preset: None,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &vec![], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        poll.reveal_end_height,
+        Some(DEFAULT_VOTING_PERIOD + DEFAULT_REVEAL_PERIOD)
+    );
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // plain votes are not allowed
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128(10u128)),
+        voter: None,
+    };
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Poll requires commit-reveal voting")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let hash = compute_vote_hash(
+        &HumanAddr::from(TEST_VOTER),
+        &VoteOption::Yes,
+        Uint128(10u128),
+        "salt",
+    );
+    let msg = HandleMsg::CommitVote { poll_id: 1, hash };
+    let handle_res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "commit_vote"),
+            log("poll_id", "1"),
+            log("voter", TEST_VOTER),
+        ]
+    );
+
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "User has already voted."),
+        _ => panic!("Must return error"),
+    }
+
+    // commitment is not counted until revealed
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.yes_votes, Uint128::zero());
+
+    let msg = HandleMsg::RevealVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Uint128(10u128),
+        salt: "salt".to_string(),
+    };
+
+    // cannot reveal during the voting period
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll is not in reveal period"),
+        _ => panic!("Must return error"),
+    }
+
+    // cannot end the poll before the reveal period ends
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD + 1, 10000);
+    match handle(&mut deps, env.clone(), HandleMsg::EndPoll { poll_id: 1 }) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Reveal period has not expired"),
+        _ => panic!("Must return error"),
+    }
+
+    // mismatched reveal
+    let wrong_msg = HandleMsg::RevealVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+        amount: Uint128(10u128),
+        salt: "salt".to_string(),
+    };
+    match handle(&mut deps, env.clone(), wrong_msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Vote does not match the commitment")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    // digits shifted from the amount into the salt do not match either
+    let shifted_msg = HandleMsg::RevealVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Uint128(1u128),
+        salt: "0salt".to_string(),
+    };
+    match handle(&mut deps, env.clone(), shifted_msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Vote does not match the commitment")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let handle_res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        handle_res.log,
+        vec![
+            log("action", "reveal_vote"),
+            log("poll_id", "1"),
+            log("amount", "10"),
+            log("voter", TEST_VOTER),
+            log("vote_option", "yes"),
+        ]
+    );
+
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No committed vote"),
+        _ => panic!("Must return error"),
+    }
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll.yes_votes, Uint128(10u128));
+
+    let env = mock_env_height(
+        TEST_VOTER,
+        &[],
+        DEFAULT_VOTING_PERIOD + DEFAULT_REVEAL_PERIOD,
+        10000,
+    );
+    let handle_res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    assert_eq!(
+        handle_res.log.get(3).expect("no log"),
+        &log("passed", "true")
+    );
+}
+
+#[test]
+fn happy_days_withdraw_voting_tokens() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
+
+    let state: State = state_read(&mut deps.storage).load().unwrap();
+    assert_eq!(
+        state,
+        State {
+            contract_addr: deps
+                .api
+                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
+                .unwrap(),
+            poll_count: 0,
+            total_share: Uint128::from(11u128),
+            total_deposit: Uint128::zero(),
+            total_votes: 0,
+            unique_voters: 0,
+            polls_passed: 0,
+            polls_rejected: 0,
+            polls_executed: 0,
+            deposit_stake_share: Uint128::zero(),
+            turnout_ema: None,
+            migration_in_progress: false,
+            unclaimed_refund: Uint128::zero(),
+            emergency_paused: false,
+            vote_reward_pool: Uint128::zero(),
+            execution_bounty_pool: Uint128::zero(),
+        }
+    );
+
+    // double the balance, only half will be withdrawn
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(22u128))],
+    )]);
+
+    let env = mock_env(TEST_VOTER, &[]);
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(11u128)),
+    };
+
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    let msg = handle_res.messages.get(0).expect("no message");
+
+    assert_eq!(
+        msg,
+        &CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_VOTER),
+                amount: Uint128::from(11u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })
+    );
+
+    let state: State = state_read(&mut deps.storage).load().unwrap();
+    assert_eq!(
+        state,
+        State {
+            contract_addr: deps
+                .api
+                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
+                .unwrap(),
+            poll_count: 0,
+            total_share: Uint128::from(6u128),
+            total_deposit: Uint128::zero(),
+            total_votes: 0,
+            unique_voters: 0,
+            polls_passed: 0,
+            polls_rejected: 0,
+            polls_executed: 0,
+            deposit_stake_share: Uint128::zero(),
+            turnout_ema: None,
+            migration_in_progress: false,
+            unclaimed_refund: Uint128::zero(),
+            emergency_paused: false,
+            vote_reward_pool: Uint128::zero(),
+            execution_bounty_pool: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn happy_days_withdraw_voting_tokens_all() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
+
+    let state: State = state_read(&mut deps.storage).load().unwrap();
+    assert_eq!(
+        state,
+        State {
+            contract_addr: deps
+                .api
+                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
+                .unwrap(),
+            poll_count: 0,
+            total_share: Uint128::from(11u128),
+            total_deposit: Uint128::zero(),
+            total_votes: 0,
+            unique_voters: 0,
+            polls_passed: 0,
+            polls_rejected: 0,
+            polls_executed: 0,
+            deposit_stake_share: Uint128::zero(),
+            turnout_ema: None,
+            migration_in_progress: false,
+            unclaimed_refund: Uint128::zero(),
+            emergency_paused: false,
+            vote_reward_pool: Uint128::zero(),
+            execution_bounty_pool: Uint128::zero(),
+        }
+    );
+
+    // double the balance, all balance withdrawn
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(22u128))],
+    )]);
+
+    let env = mock_env(TEST_VOTER, &[]);
+    let msg = HandleMsg::WithdrawVotingTokens { amount: None };
+
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    let msg = handle_res.messages.get(0).expect("no message");
+
+    assert_eq!(
+        msg,
+        &CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_VOTER),
+                amount: Uint128::from(22u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })
+    );
+
+    let state: State = state_read(&mut deps.storage).load().unwrap();
+    assert_eq!(
+        state,
+        State {
+            contract_addr: deps
+                .api
+                .canonical_address(&HumanAddr::from(MOCK_CONTRACT_ADDR))
+                .unwrap(),
+            poll_count: 0,
+            total_share: Uint128::zero(),
+            total_deposit: Uint128::zero(),
+            total_votes: 0,
+            unique_voters: 0,
+            polls_passed: 0,
+            polls_rejected: 0,
+            polls_executed: 0,
+            deposit_stake_share: Uint128::zero(),
+            turnout_ema: None,
+            migration_in_progress: false,
+            unclaimed_refund: Uint128::zero(),
+            emergency_paused: false,
+            vote_reward_pool: Uint128::zero(),
+            execution_bounty_pool: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn withdraw_voting_tokens_remove_not_in_progress_poll_voter_info() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
+
+    // make fake polls; one in progress & one in passed
+    poll_store(&mut deps.storage)
+        .save(
+            &1u64.to_be_bytes(),
+            &Poll {
+                id: 1u64,
+                creator: CanonicalAddr::default(),
+                status: PollStatus::InProgress,
+                yes_votes: Uint128::zero(),
+                no_votes: Uint128::zero(),
+                veto_votes: Uint128::zero(),
+                end_height: 0u64,
+                title: "title".to_string(),
+                description: "description".to_string(),
+                deposit_amount: Uint128::zero(),
+                links: vec![],
+                execute_data: None,
+                total_balance_at_end_poll: None,
+                staked_amount: None,
+                deposit_share: None,
+                reveal_end_height: None,
+                rejected_reason: None,
+                content_hash: None,
+                yes_shares: Uint128::zero(),
+                no_shares: Uint128::zero(),
+                staked_share: None,
+                vote_count: 0,
+                referendum: None,
+                stake_refund: false,
+                refund_address: None,
+                voters_root: None,
+                extended: false,
+                narrow_pass: false,
+                quorum_reached: false,
+                threshold_reached: false,
+                quorum: Decimal::percent(DEFAULT_QUORUM),
+                threshold: Decimal::percent(DEFAULT_THRESHOLD),
+                voting_period: DEFAULT_VOTING_PERIOD,
+                timelock_period: DEFAULT_TIMELOCK_PERIOD,
+                expiration_period: DEFAULT_EXPIRATION_PERIOD,
+                preset: None,
+                adaptive_quorum: None,
+                deposit_stake_share: None,
+                option_labels: vec![],
+                option_votes: vec![],
+                option_shares: Uint128::zero(),
+                required_sponsors: 0,
+                sponsor_min_stake: Uint128::zero(),
+                sponsors: vec![],
+                vote_reward: Uint128::zero(),
+                vote_reward_claimed: Uint128::zero(),
+                execution_bounty: Uint128::zero(),
+                on_reject_data: None,
+                on_reject_executed: false,
+                escrow: None,
+            },
+        )
+        .unwrap();
+
+    poll_store(&mut deps.storage)
+        .save(
+            &2u64.to_be_bytes(),
+            &Poll {
+                id: 1u64,
+                creator: CanonicalAddr::default(),
+                status: PollStatus::Passed,
+                yes_votes: Uint128::zero(),
+                no_votes: Uint128::zero(),
+                veto_votes: Uint128::zero(),
+                end_height: 0u64,
+                title: "title".to_string(),
+                description: "description".to_string(),
+                deposit_amount: Uint128::zero(),
+                links: vec![],
+                execute_data: None,
+                total_balance_at_end_poll: None,
+                staked_amount: None,
+                deposit_share: None,
+                reveal_end_height: None,
+                rejected_reason: None,
+                content_hash: None,
+                yes_shares: Uint128::zero(),
+                no_shares: Uint128::zero(),
+                staked_share: None,
+                vote_count: 0,
+                referendum: None,
+                stake_refund: false,
+                refund_address: None,
+                voters_root: None,
+                extended: false,
+                narrow_pass: false,
+                quorum_reached: false,
+                threshold_reached: false,
+                quorum: Decimal::percent(DEFAULT_QUORUM),
+                threshold: Decimal::percent(DEFAULT_THRESHOLD),
+                voting_period: DEFAULT_VOTING_PERIOD,
+                timelock_period: DEFAULT_TIMELOCK_PERIOD,
+                expiration_period: DEFAULT_EXPIRATION_PERIOD,
+                preset: None,
+                adaptive_quorum: None,
+                deposit_stake_share: None,
+                option_labels: vec![],
+                option_votes: vec![],
+                option_shares: Uint128::zero(),
+                required_sponsors: 0,
+                sponsor_min_stake: Uint128::zero(),
+                sponsors: vec![],
+                vote_reward: Uint128::zero(),
+                vote_reward_claimed: Uint128::zero(),
+                execution_bounty: Uint128::zero(),
+                on_reject_data: None,
+                on_reject_executed: false,
+                escrow: None,
+            },
+        )
+        .unwrap();
+
+    let voter_addr_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from(TEST_VOTER))
+        .unwrap();
+    poll_voter_store(&mut deps.storage, 1u64)
+        .save(
+            &voter_addr_raw.as_slice(),
+            &VoterInfo {
+                vote: VoteOption::Yes,
+                balance: Uint128(5u128),
+                share: Uint128(5u128),
+            },
+        )
+        .unwrap();
+    poll_voter_store(&mut deps.storage, 2u64)
+        .save(
+            &voter_addr_raw.as_slice(),
+            &VoterInfo {
+                vote: VoteOption::Yes,
+                balance: Uint128(5u128),
+                share: Uint128(5u128),
+            },
+        )
+        .unwrap();
+    bank_store(&mut deps.storage)
+        .save(
+            &voter_addr_raw.as_slice(),
+            &TokenManager {
+                share: Uint128(11u128),
+                vesting_locked_balance: Uint128::zero(),
+                deposit_locked_share: Uint128::zero(),
+                last_stake_height: 0,
+            },
+        )
+        .unwrap();
+    for poll_id in [1u64, 2u64].iter() {
+        locked_vote_store(&mut deps.storage, &voter_addr_raw)
+            .save(
+                &poll_id.to_be_bytes(),
+                &VoterInfo {
+                    vote: VoteOption::Yes,
+                    balance: Uint128(5u128),
+                    share: Uint128(5u128),
+                },
+            )
+            .unwrap();
+    }
+
+    // withdraw voting token must remove not in-progress votes infos from the store
+    let env = mock_env(TEST_VOTER, &[]);
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(5u128)),
+    };
+
+    let _ = handle(&mut deps, env, msg).unwrap();
+    let voter = poll_voter_read(&deps.storage, 1u64)
+        .load(&voter_addr_raw.as_slice())
+        .unwrap();
+    assert_eq!(
+        voter,
+        VoterInfo {
+            vote: VoteOption::Yes,
+            balance: Uint128(5u128),
+            share: Uint128(5u128),
+        }
+    );
+    assert_eq!(
+        poll_voter_read(&deps.storage, 2u64)
+            .load(&voter_addr_raw.as_slice())
+            .is_err(),
+        true
+    );
+
+    assert_eq!(
+        read_locked_votes(&deps.storage, &voter_addr_raw).unwrap(),
+        vec![(
+            1u64,
+            VoterInfo {
+                vote: VoteOption::Yes,
+                balance: Uint128(5u128),
+                share: Uint128(5u128),
+            }
+        )]
+    );
+}
+
+#[test]
+fn query_withdrawable() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
+    )]);
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(40u128)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // double the staked balance, the locked vote balance stays the same
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(200u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let res = query(
+        &deps,
+        QueryMsg::Withdrawable {
+            address: HumanAddr::from(TEST_VOTER),
+        },
+    )
+    .unwrap();
+    let withdrawable: WithdrawableResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        withdrawable,
+        WithdrawableResponse {
+            share: Uint128(80u128),
+            amount: Uint128(160u128),
+        }
+    );
+
+    let msg = HandleMsg::WithdrawVotingTokens { amount: None };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_VOTER),
+                amount: withdrawable.amount,
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+}
+
+#[test]
+fn fails_withdraw_voting_tokens_no_stake() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env(TEST_VOTER, &coins(11, VOTING_TOKEN));
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(11u128)),
+    };
+
+    let res = handle(&mut deps, env, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Nothing staked"),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn fails_withdraw_too_many_tokens() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(10u128))],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(10u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(10, 0, 10, 0, handle_res, &mut deps);
+
+    let env = mock_env(TEST_VOTER, &[]);
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(11u128)),
+    };
+
+    let res = handle(&mut deps, env, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "User is trying to withdraw too many tokens.")
+        }
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn fails_cast_vote_twice() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &coins(2, VOTING_TOKEN), 0, 10000);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let handle_res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+
+    assert_create_poll_result(
+        1,
+        env.block.height + DEFAULT_VOTING_PERIOD,
+        TEST_CREATOR,
+        handle_res,
+        &mut deps,
+    );
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, handle_res, &mut deps);
+
+    let amount = 1u128;
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(amount)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let handle_res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, amount, 1, VoteOption::Yes, handle_res);
+
+    // only a vote with the same option tops up the existing one
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+        amount: Some(Uint128::from(amount)),
+        voter: None,
+    };
+    let res = handle(&mut deps, env, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "User has already voted."),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn top_up_vote() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(10u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(10u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: None,
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 10, 10000);
+    let _res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+
+    // nothing left to top up with before staking more
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Cannot vote with zero amount"),
+        _ => panic!("Must return error"),
+    }
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(20u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    let msg_stake = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(10u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg_stake).unwrap();
+
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(res.log[2], log("amount", "10"));
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128(1u128)),
+        voter: None,
+    };
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "User does not have enough staked tokens.")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let poll: PollResponse =
+        from_binary(&query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap()).unwrap();
+    assert_eq!(poll.yes_votes, Uint128(20u128));
+
+    let voter_addr_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from(TEST_VOTER))
+        .unwrap();
+    let vote_info = VoterInfo {
+        vote: VoteOption::Yes,
+        balance: Uint128(20u128),
+        share: Uint128(20u128),
+    };
+    assert_eq!(
+        poll_voter_read(&deps.storage, 1u64)
+            .load(voter_addr_raw.as_slice())
+            .unwrap(),
+        vote_info
+    );
+    assert_eq!(
+        read_locked_votes(&deps.storage, &voter_addr_raw).unwrap(),
+        vec![(1u64, vote_info)]
+    );
+
+    let stats: StatsResponse = from_binary(&query(&deps, QueryMsg::Stats {}).unwrap()).unwrap();
+    assert_eq!(stats.total_votes, 1);
+}
+
+#[test]
+fn fails_cast_vote_without_poll() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 0,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(1u128)),
+        voter: None,
+    };
+    let env = mock_env(TEST_VOTER, &coins(11, VOTING_TOKEN));
+
+    let res = handle(&mut deps, env, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll does not exist"),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn happy_days_stake_voting_tokens() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
+}
+
+#[test]
+fn query_staking_snapshot() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
+    )]);
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 10, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(300u128))],
+    )]);
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER_2),
+        amount: Uint128::from(200u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 20, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::WithdrawVotingTokens { amount: None };
+    let env = mock_env_height(TEST_VOTER, &[], 30, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let snapshot = |height: u64| -> StakingSnapshotResponse {
+        let res = query(
+            &deps,
+            QueryMsg::StakingSnapshot {
+                address: HumanAddr::from(TEST_VOTER),
+                height,
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
+    };
+
+    assert_eq!(
+        snapshot(9),
+        StakingSnapshotResponse {
+            share: Uint128::zero(),
+            total_share: Uint128::zero(),
+        }
+    );
+    assert_eq!(
+        snapshot(10),
+        StakingSnapshotResponse {
+            share: Uint128(100u128),
+            total_share: Uint128(100u128),
+        }
+    );
+    assert_eq!(
+        snapshot(29),
+        StakingSnapshotResponse {
+            share: Uint128(100u128),
+            total_share: Uint128(300u128),
+        }
+    );
+    assert_eq!(
+        snapshot(30),
+        StakingSnapshotResponse {
+            share: Uint128::zero(),
+            total_share: Uint128(200u128),
+        }
+    );
+}
+
+#[test]
+fn query_share_accounting() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let mut balance = 0u128;
+    for (staker, amount) in [
+        (TEST_VOTER, 100u128),
+        (TEST_VOTER_2, 200u128),
+        (TEST_VOTER_3, 300u128),
+    ]
+    .iter()
+    {
+        balance += amount;
+        deps.querier.with_token_balances(&[(
+            &HumanAddr::from(VOTING_TOKEN),
+            &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(balance))],
+        )]);
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*staker),
+            amount: Uint128::from(*amount),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+
+    let mut sum = Uint128::zero();
+    let mut start_after: Option<HumanAddr> = None;
+    let mut pages: Vec<u32> = vec![];
+    loop {
+        let res = query(
+            &deps,
+            QueryMsg::ShareAccounting {
+                start_after: start_after.clone(),
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+        let response: ShareAccountingResponse = from_binary(&res).unwrap();
+        assert_eq!(response.total_share, Uint128(600u128));
+        assert_eq!(response.page_deposit_locked_share, Uint128::zero());
+
+        sum += response.page_share;
+        pages.push(response.stakers);
+        match response.last_staker {
+            Some(last_staker) => start_after = Some(last_staker),
+            None => break,
+        }
+    }
+
+    assert_eq!(pages, vec![2, 1, 0]);
+    assert_eq!(sum, Uint128(600u128));
+}
+
+#[test]
+fn query_raw_storage() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(&deps, QueryMsg::RawPoll { poll_id: 1 }).unwrap();
+    let response: RawStorageResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.key,
+        hex::encode([to_length_prefixed(b"poll"), 1u64.to_be_bytes().to_vec()].concat())
+    );
+    let poll: Poll = from_slice(response.value.unwrap().as_slice()).unwrap();
+    assert_eq!(poll.id, 1);
+    assert_eq!(
+        poll.creator,
+        deps.api
+            .canonical_address(&HumanAddr::from(TEST_CREATOR))
+            .unwrap()
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::RawBank {
+            address: HumanAddr::from(TEST_VOTER),
+        },
+    )
+    .unwrap();
+    let response: RawStorageResponse = from_binary(&res).unwrap();
+    let token_manager: TokenManager = from_slice(response.value.unwrap().as_slice()).unwrap();
+    assert_eq!(token_manager.share, Uint128::from(11u128));
+
+    // nothing is stored for an unknown staker
+    let res = query(
+        &deps,
+        QueryMsg::RawBank {
+            address: HumanAddr::from(TEST_VOTER_2),
+        },
+    )
+    .unwrap();
+    let response: RawStorageResponse = from_binary(&res).unwrap();
+    assert_eq!(response.value, None);
+}
+
+#[test]
+fn query_export() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _res = handle(&mut deps, env.clone(), msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(22u128 + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    for voter in [TEST_VOTER, TEST_VOTER_2].iter() {
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*voter),
+            amount: Uint128::from(11u128),
+            msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        });
+        let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+        let msg = HandleMsg::CastVote {
+            poll_id: 2,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(11u128)),
+            voter: None,
+        };
+        let _res = handle(&mut deps, mock_env_height(voter, &[], 0, 10000), msg).unwrap();
+    }
+
+    // polls are paged by the key of the last record
+    let res = query(
+        &deps,
+        QueryMsg::ExportPolls {
+            start_after: None,
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let response: ExportResponse = from_binary(&res).unwrap();
+    assert_eq!(response.records.len(), 1);
+    assert_eq!(response.records[0].key, "1");
+    let poll: Poll = from_slice(response.records[0].value.as_slice()).unwrap();
+    assert_eq!(poll.id, 1);
+
+    let res = query(
+        &deps,
+        QueryMsg::ExportPolls {
+            start_after: Some(1),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: ExportResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response
+            .records
+            .iter()
+            .map(|record| record.key.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["2"]
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::ExportBanks {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: ExportResponse = from_binary(&res).unwrap();
+    assert_eq!(response.records.len(), 2);
+    for record in response.records.iter() {
+        let token_manager: TokenManager = from_slice(record.value.as_slice()).unwrap();
+        assert_eq!(token_manager.share, Uint128::from(11u128));
+    }
+
+    let res = query(
+        &deps,
+        QueryMsg::ExportBanks {
+            start_after: Some(HumanAddr::from(response.records[0].key.clone())),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let next_page: ExportResponse = from_binary(&res).unwrap();
+    assert_eq!(next_page.records, vec![response.records[1].clone()]);
+
+    let res = query(
+        &deps,
+        QueryMsg::ExportVoters {
+            poll_id: 2,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: ExportResponse = from_binary(&res).unwrap();
+    let mut voters = response
+        .records
+        .iter()
+        .map(|record| record.key.as_str())
+        .collect::<Vec<&str>>();
+    voters.sort_unstable();
+    assert_eq!(voters, vec![TEST_VOTER, TEST_VOTER_2]);
+    let voter_info: VoterInfo = from_slice(response.records[0].value.as_slice()).unwrap();
+    assert_eq!(voter_info.balance, Uint128::from(11u128));
+}
+
+#[test]
+fn fails_insufficient_funds() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    // initialize the store
+    mock_init(&mut deps);
+
+    // insufficient token
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(0u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let res = handle(&mut deps, env, msg);
+
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Insufficient funds sent"),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn fails_staking_wrong_token() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    // initialize the store
+    mock_init(&mut deps);
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+    )]);
+
+    // wrong token
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN.to_string() + "2", &[]);
     let res = handle(&mut deps, env, msg);
 
-    match res {
+    match res {
+        Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Hook sender voting_token2 is not the registered anchor token"
+        ),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn fails_receive_invalid_hook_msg() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(Binary::from(br#"{"unknown_hook":{}}"#.to_vec())),
+    });
+
+    let env = mock_env(VOTING_TOKEN, &[]);
+    match handle(&mut deps, env, msg) {
         Ok(_) => panic!("Must return error"),
+        Err(StdError::GenericErr { msg, .. }) => assert!(msg.starts_with(
+            "Invalid hook msg, expected one of stake_voting_tokens, stake_vesting_tokens, stake_voting_tokens_for, create_poll, stake_and_vote, fund_poll_escrow: "
+        )),
+        Err(e) => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn stake_vesting_tokens() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: Some(HumanAddr::from("vesting0000")),
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let _res = handle(&mut deps, mock_env(TEST_CREATOR, &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
+    )]);
+
+    // only vesting contract can stake locked tokens
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeVestingTokens {
+                staker: HumanAddr::from(TEST_VOTER),
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("vesting0000"),
+        amount: Uint128::from(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeVestingTokens {
+                staker: HumanAddr::from(TEST_VOTER),
+            })
+            .unwrap(),
+        ),
+    });
+    let handle_res = handle(&mut deps, env, msg).unwrap();
+    assert_stake_tokens_result(100, 0, 100, 0, handle_res, &mut deps);
+
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_VOTER),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128(100u128));
+    assert_eq!(response.vesting_locked_balance, Uint128(100u128));
+
+    // locked tokens cannot be withdrawn
+    let env = mock_env(TEST_VOTER, &[]);
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128(1u128)),
+    };
+    match handle(&mut deps, env.clone(), msg.clone()) {
         Err(StdError::GenericErr { msg, .. }) => {
             assert_eq!(msg, "User is trying to withdraw too many tokens.")
         }
-        Err(e) => panic!("Unexpected error: {:?}", e),
+        _ => panic!("Must return error"),
+    }
+
+    // only vesting contract can unlock
+    let unlock_msg = HandleMsg::UnlockVestingTokens {
+        staker: HumanAddr::from(TEST_VOTER),
+        amount: Uint128(40u128),
+    };
+    match handle(&mut deps, env.clone(), unlock_msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let res = handle(&mut deps, mock_env("vesting0000", &[]), unlock_msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "unlock_vesting_tokens"),
+            log("staker", TEST_VOTER),
+            log("amount", "40"),
+        ]
+    );
+
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128(40u128)),
+    };
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_VOTER),
+                amount: Uint128(40u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+}
+
+#[test]
+fn share_calculation() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    // initialize the store
+    mock_init(&mut deps);
+
+    // create 100 share
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN.to_string(), &[]);
+    let _res = handle(&mut deps, env, msg);
+
+    // add more balance(100) to make share:balance = 1:2
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(200u128 + 100u128),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+
+    let env = mock_env(VOTING_TOKEN.to_string(), &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "staking"),
+            log("sender", TEST_VOTER),
+            log("share", "50"),
+            log("amount", "100"),
+        ]
+    );
+
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128(100u128)),
+    };
+    let env = mock_env(TEST_VOTER.to_string(), &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "withdraw"),
+            log("recipient", TEST_VOTER),
+            log("amount", "100"),
+        ]
+    );
+
+    // 100 tokens withdrawn
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(200u128))],
+    )]);
+
+    let res = query(
+        &mut deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_VOTER),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let stake_info: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(stake_info.share, Uint128(100));
+    assert_eq!(stake_info.balance, Uint128(200));
+    assert_eq!(stake_info.locked_balance, vec![]);
+}
+
+#[test]
+fn migrate_stake() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::MigrateStake {
+        new_gov_contract: HumanAddr::from("new_gov"),
+        limit: None,
+    };
+    let env = mock_env(TEST_VOTER, &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env(TEST_CREATOR, &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(VOTING_TOKEN),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from(TEST_CREATOR),
+                    amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+                })
+                .unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(VOTING_TOKEN),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("new_gov"),
+                    amount: Uint128(100u128),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "migrate_stake"),
+            log("new_gov_contract", "new_gov"),
+            log("amount", "100"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::Rejected);
+
+    // every handle is rejected after the migration
+    let msg = HandleMsg::WithdrawVotingTokens { amount: None };
+    let env = mock_env(TEST_VOTER, &[]);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Stake has been migrated"),
+        _ => panic!("DO NOT ENTER HERE"),
     }
+
+    let res = query(
+        &deps,
+        QueryMsg::MigrationSnapshot {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: MigrationSnapshotResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        MigrationSnapshotResponse {
+            new_gov_contract: HumanAddr::from("new_gov"),
+            total_balance: Uint128(100u128),
+            total_share: Uint128(100u128),
+            claims: vec![StakerClaim {
+                staker: HumanAddr::from(TEST_VOTER),
+                share: Uint128(100u128),
+                balance: Uint128(100u128),
+                vesting_locked_balance: Uint128::zero(),
+            }],
+            limit: 10,
+        }
+    );
 }
 
 #[test]
-fn fails_cast_vote_twice() {
+fn migrate_stake_paginated() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
-    let env = mock_env_height(VOTING_TOKEN, &coins(2, VOTING_TOKEN), 0, 10000);
-
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-    let handle_res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: Some(HumanAddr::from("vesting0000")),
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let _res = handle(&mut deps, mock_env(TEST_CREATOR, &[]), msg).unwrap();
 
-    assert_create_poll_result(
-        1,
-        env.block.height + DEFAULT_VOTING_PERIOD,
-        TEST_CREATOR,
-        handle_res,
-        &mut deps,
-    );
+    for _ in 0..3 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+    }
 
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
         &[(
             &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+            &Uint128(100u128 + 3 * DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(11u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        sender: HumanAddr::from("vesting0000"),
+        amount: Uint128::from(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeVestingTokens {
+                staker: HumanAddr::from(TEST_VOTER),
+            })
+            .unwrap(),
+        ),
     });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
 
-    let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, handle_res, &mut deps);
-
-    let amount = 1u128;
-    let msg = HandleMsg::CastVote {
-        poll_id: 1,
-        vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
+    // the first call cancels a page of polls and holds back the stake
+    let msg = HandleMsg::MigrateStake {
+        new_gov_contract: HumanAddr::from("new_gov"),
+        limit: Some(2),
     };
-    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
-    let handle_res = handle(&mut deps, env.clone(), msg).unwrap();
-    assert_cast_vote_success(TEST_VOTER, amount, 1, VoteOption::Yes, handle_res);
+    let res = handle(&mut deps, mock_env(TEST_CREATOR, &[]), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.log,
+        vec![log("action", "migrate_stake"), log("cancelled_polls", 2)]
+    );
 
-    let msg = HandleMsg::CastVote {
-        poll_id: 1,
-        vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
-    };
-    let res = handle(&mut deps, env, msg);
+    let res = query(&deps, QueryMsg::Poll { poll_id: 3 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.status, PollStatus::InProgress);
 
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "User has already voted."),
-        Err(e) => panic!("Unexpected error: {:?}", e),
+    // withdrawals are frozen between the calls
+    let msg_withdraw = HandleMsg::WithdrawVotingTokens { amount: None };
+    match handle(&mut deps, mock_env(TEST_VOTER, &[]), msg_withdraw) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Migration in progress"),
+        _ => panic!("DO NOT ENTER HERE"),
     }
-}
 
-#[test]
-fn fails_cast_vote_without_poll() {
-    let mut deps = mock_dependencies(20, &[]);
-    mock_init(&mut deps);
+    // two deposits refunded
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
 
-    let msg = HandleMsg::CastVote {
-        poll_id: 0,
-        vote: VoteOption::Yes,
-        amount: Uint128::from(1u128),
-    };
-    let env = mock_env(TEST_VOTER, &coins(11, VOTING_TOKEN));
+    let res = handle(&mut deps, mock_env(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(VOTING_TOKEN),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from(TEST_CREATOR),
+                    amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+                })
+                .unwrap(),
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(VOTING_TOKEN),
+                send: vec![],
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("new_gov"),
+                    amount: Uint128(100u128),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "migrate_stake"),
+            log("new_gov_contract", "new_gov"),
+            log("amount", "100"),
+        ]
+    );
 
-    let res = handle(&mut deps, env, msg);
+    // the vesting contract keeps unlocking after the migration
+    let msg = HandleMsg::UnlockVestingTokens {
+        staker: HumanAddr::from(TEST_VOTER),
+        amount: Uint128(40u128),
+    };
+    let _res = handle(&mut deps, mock_env("vesting0000", &[]), msg).unwrap();
 
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll does not exist"),
-        Err(e) => panic!("Unexpected error: {:?}", e),
-    }
+    let res = query(
+        &deps,
+        QueryMsg::MigrationSnapshot {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: MigrationSnapshotResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.claims,
+        vec![StakerClaim {
+            staker: HumanAddr::from(TEST_VOTER),
+            share: Uint128(100u128),
+            balance: Uint128(100u128),
+            vesting_locked_balance: Uint128(60u128),
+        }]
+    );
 }
 
 #[test]
-fn happy_days_stake_voting_tokens() {
+fn migrate_stake_keeps_pending_payouts() {
     let mut deps = mock_dependencies(20, &[]);
     mock_init(&mut deps);
 
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: Some(HumanAddr::from("community0000")),
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: Some(Decimal::percent(40)),
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let _res = handle(&mut deps, mock_env(TEST_CREATOR, &[]), msg).unwrap();
+
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(2 * DEFAULT_PROPOSAL_DEPOSIT + 300u128),
+        )],
     )]);
 
+    // poll 1 carries a vote reward and an execution bounty, poll 2 fails the quorum
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(11u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        sender: HumanAddr::from(TEST_CREATOR),
+        amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT + 300u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                links: None,
+                execute_msgs: Some(vec![ExecuteMsg {
+                    order: 1u64,
+                    contract: HumanAddr::from(VOTING_TOKEN),
+                    msg: to_binary(&Cw20HandleMsg::Burn {
+                        amount: Uint128(123),
+                    })
+                    .unwrap(),
+                }]),
+                commit_reveal: None,
+                content_hash: None,
+                referendum: None,
+                stake_refund: None,
+                reserved_poll_id: None,
+                refund_address: None,
+                option_labels: None,
+                vote_reward: Some(Uint128(300u128)),
+                execution_bounty: Some(Uint128(100u128)),
+                on_reject_msgs: None,
+                preset: None,
+            })
+            .unwrap(),
+        ),
     });
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
-    let env = mock_env(VOTING_TOKEN, &[]);
-    let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
-    assert_stake_tokens_result(11, 0, 11, 0, handle_res, &mut deps);
-}
-
-#[test]
-fn fails_insufficient_funds() {
-    let mut deps = mock_dependencies(20, &[]);
-
-    // initialize the store
-    mock_init(&mut deps);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
-    // insufficient token
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000u128 + 2 * DEFAULT_PROPOSAL_DEPOSIT + 300u128),
+        )],
+    )]);
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(0u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+        amount: Uint128(1000u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeAndVote {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+            })
+            .unwrap(),
+        ),
     });
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let env = mock_env(VOTING_TOKEN, &[]);
-    let res = handle(&mut deps, env, msg);
-
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Insufficient funds sent"),
-        Err(e) => panic!("Unexpected error: {:?}", e),
-    }
-}
-
-#[test]
-fn fails_staking_wrong_token() {
-    let mut deps = mock_dependencies(20, &[]);
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env.clone(), HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+    let _res = handle(&mut deps, env.clone(), HandleMsg::EndPoll { poll_id: 2 }).unwrap();
 
-    // initialize the store
-    mock_init(&mut deps);
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.vote_reward_pool, Uint128(300u128));
+    assert_eq!(state.execution_bounty_pool, Uint128(100u128));
+    assert_eq!(
+        state.unclaimed_refund,
+        Uint128(DEFAULT_PROPOSAL_DEPOSIT * 4 / 10)
+    );
 
+    // the deposit refund of poll 1 and the slashed part of poll 2 are paid out
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(11u128))],
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(1000u128 + 400u128 + DEFAULT_PROPOSAL_DEPOSIT * 4 / 10),
+        )],
     )]);
 
-    // wrong token
-    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(11u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
-    });
+    // only the staked balance leaves for the successor
+    let msg = HandleMsg::MigrateStake {
+        new_gov_contract: HumanAddr::from("new_gov"),
+        limit: None,
+    };
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("new_gov"),
+                amount: Uint128(1000u128),
+            })
+            .unwrap(),
+        })]
+    );
 
-    let env = mock_env(VOTING_TOKEN.to_string() + "2", &[]);
-    let res = handle(&mut deps, env, msg);
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::ClaimVoteReward { poll_id: 1 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_VOTER),
+                amount: Uint128(300u128),
+            })
+            .unwrap(),
+        })]
+    );
 
-    match res {
-        Ok(_) => panic!("Must return error"),
-        Err(StdError::Unauthorized { .. }) => {}
-        Err(e) => panic!("Unexpected error: {:?}", e),
-    }
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let res = handle(&mut deps, env, HandleMsg::ClaimDepositRefund { poll_id: 2 }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from(VOTING_TOKEN),
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from(TEST_CREATOR),
+                amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT * 4 / 10),
+            })
+            .unwrap(),
+        })]
+    );
+
+    let env = mock_env_height(
+        "executor0000",
+        &[],
+        DEFAULT_VOTING_PERIOD + DEFAULT_TIMELOCK_PERIOD,
+        10000,
+    );
+    let res = handle(&mut deps, env, HandleMsg::ExecutePoll { poll_id: 1 }).unwrap();
+    assert_eq!(res.log.last(), Some(&log("execution_bounty", 100)));
+
+    let state: State = state_read(&deps.storage).load().unwrap();
+    assert_eq!(state.vote_reward_pool, Uint128::zero());
+    assert_eq!(state.execution_bounty_pool, Uint128::zero());
+    assert_eq!(state.unclaimed_refund, Uint128::zero());
+    assert_eq!(state.total_deposit, Uint128::zero());
 }
 
 #[test]
-fn share_calculation() {
+fn import_stake() {
     let mut deps = mock_dependencies(20, &[]);
-
-    // initialize the store
     mock_init(&mut deps);
 
-    // create 100 share
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
-    )]);
-
-    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
-        amount: Uint128::from(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
-    });
-
-    let env = mock_env(VOTING_TOKEN.to_string(), &[]);
-    let _res = handle(&mut deps, env, msg);
-
-    // add more balance(100) to make share:balance = 1:2
     deps.querier.with_token_balances(&[(
         &HumanAddr::from(VOTING_TOKEN),
-        &[(
-            &HumanAddr::from(MOCK_CONTRACT_ADDR),
-            &Uint128(200u128 + 100u128),
-        )],
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(100u128))],
     )]);
 
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from(TEST_VOTER),
+        sender: HumanAddr::from(TEST_VOTER_3),
         amount: Uint128::from(100u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let env = mock_env(VOTING_TOKEN.to_string(), &[]);
-    let res = handle(&mut deps, env, msg).unwrap();
+    // the predecessor transferred 300 tokens, staked with 1:2 share ratio
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(400u128))],
+    )]);
+    deps.querier.with_migration_snapshot(
+        &HumanAddr::from("old_gov"),
+        MigrationSnapshotResponse {
+            new_gov_contract: HumanAddr::from(MOCK_CONTRACT_ADDR),
+            total_balance: Uint128(300u128),
+            total_share: Uint128(150u128),
+            claims: vec![
+                StakerClaim {
+                    staker: HumanAddr::from(TEST_VOTER),
+                    share: Uint128(100u128),
+                    balance: Uint128(200u128),
+                    vesting_locked_balance: Uint128::zero(),
+                },
+                StakerClaim {
+                    staker: HumanAddr::from(TEST_VOTER_2),
+                    share: Uint128(50u128),
+                    balance: Uint128(100u128),
+                    vesting_locked_balance: Uint128::zero(),
+                },
+            ],
+            limit: 10,
+        },
+    );
+
+    let msg = HandleMsg::ImportStake {
+        old_gov_contract: HumanAddr::from("old_gov"),
+        limit: Some(1),
+    };
+    let env = mock_env(TEST_VOTER, &[]);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env(TEST_CREATOR, &[]);
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.log,
         vec![
-            log("action", "staking"),
-            log("sender", TEST_VOTER),
-            log("share", "50"),
-            log("amount", "100"),
+            log("action", "import_stake"),
+            log("stakers", "1"),
+            log("share", "200"),
         ]
     );
 
-    let msg = HandleMsg::WithdrawVotingTokens {
-        amount: Some(Uint128(100u128)),
-    };
-    let env = mock_env(TEST_VOTER.to_string(), &[]);
-    let res = handle(&mut deps, env, msg).unwrap();
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.log,
         vec![
-            log("action", "withdraw"),
-            log("recipient", TEST_VOTER),
-            log("amount", "100"),
+            log("action", "import_stake"),
+            log("stakers", "1"),
+            log("share", "100"),
         ]
     );
 
-    // 100 tokens withdrawn
-    deps.querier.with_token_balances(&[(
-        &HumanAddr::from(VOTING_TOKEN),
-        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(200u128))],
-    )]);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Nothing to import"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let res = query(
-        &mut deps,
-        QueryMsg::Staker {
-            address: HumanAddr::from(TEST_VOTER),
-        },
-    )
-    .unwrap();
-    let stake_info: StakerResponse = from_binary(&res).unwrap();
-    assert_eq!(stake_info.share, Uint128(100));
-    assert_eq!(stake_info.balance, Uint128(200));
-    assert_eq!(stake_info.locked_balance, vec![]);
+    for (staker, balance) in [
+        (TEST_VOTER, 200u128),
+        (TEST_VOTER_2, 100u128),
+        (TEST_VOTER_3, 100u128),
+    ]
+    .iter()
+    {
+        let res = query(
+            &deps,
+            QueryMsg::Staker {
+                address: HumanAddr::from(*staker),
+                token_balance: None,
+            },
+        )
+        .unwrap();
+        let stake_info: StakerResponse = from_binary(&res).unwrap();
+        assert_eq!(stake_info.balance, Uint128(*balance));
+    }
 }
 
 // helper to confirm the expected create_poll response
@@ -2172,6 +8343,18 @@ fn assert_create_poll_result(
             poll_count: 1,
             total_share: Uint128::zero(),
             total_deposit: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            total_votes: 0,
+            unique_voters: 0,
+            polls_passed: 0,
+            polls_rejected: 0,
+            polls_executed: 0,
+            deposit_stake_share: Uint128::zero(),
+            turnout_ema: None,
+            migration_in_progress: false,
+            unclaimed_refund: Uint128::zero(),
+            emergency_paused: false,
+            vote_reward_pool: Uint128::zero(),
+            execution_bounty_pool: Uint128::zero(),
         }
     );
 }
@@ -2200,6 +8383,18 @@ fn assert_stake_tokens_result(
             poll_count,
             total_share: Uint128(total_share),
             total_deposit: Uint128(total_deposit),
+            total_votes: 0,
+            unique_voters: 0,
+            polls_passed: 0,
+            polls_rejected: 0,
+            polls_executed: 0,
+            deposit_stake_share: Uint128::zero(),
+            turnout_ema: None,
+            migration_in_progress: false,
+            unclaimed_refund: Uint128::zero(),
+            emergency_paused: false,
+            vote_reward_pool: Uint128::zero(),
+            execution_bounty_pool: Uint128::zero(),
         }
     );
 }
@@ -2211,8 +8406,9 @@ fn assert_cast_vote_success(
     vote_option: VoteOption,
     handle_res: HandleResponse,
 ) {
+    // milestone logs following the vote are asserted by the milestone test
     assert_eq!(
-        handle_res.log,
+        handle_res.log[..5].to_vec(),
         vec![
             log("action", "cast_vote"),
             log("poll_id", poll_id.to_string()),
@@ -2223,6 +8419,300 @@ fn assert_cast_vote_success(
     );
 }
 
+#[test]
+fn refresh_token_info() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    deps.querier.with_token_info(
+        &HumanAddr::from(VOTING_TOKEN),
+        TokenInfoResponse {
+            name: "Anchor Token".to_string(),
+            symbol: "ANC".to_string(),
+            decimals: 6,
+            total_supply: Uint128(1000000000000000u128),
+        },
+    );
+
+    let env = mock_env_height(TEST_VOTER, &[], 100, 10000);
+    match handle(&mut deps, env, HandleMsg::RefreshTokenInfo {}) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], 100, 10000);
+    let res = handle(&mut deps, env, HandleMsg::RefreshTokenInfo {}).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "refresh_token_info"),
+            log("symbol", "ANC"),
+            log("decimals", 6),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config.token_info,
+        Some(TokenInfoCache {
+            name: "Anchor Token".to_string(),
+            symbol: "ANC".to_string(),
+            decimals: 6,
+            refreshed_height: 100,
+        })
+    );
+}
+
+#[test]
+fn max_active_votes() {
+    let mut deps = mock_dependencies(20, &[]);
+    let msg = InitMsg {
+        max_active_votes: 2,
+        ..init_msg()
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+    let msg = HandleMsg::RegisterContracts {
+        anchor_token: HumanAddr::from(VOTING_TOKEN),
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // poll 1 ends before the others
+    for height in [0, 100, 100].iter() {
+        let env = mock_env_height(VOTING_TOKEN, &[], *height, 10000);
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + 3 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let vote_msg = |poll_id: u64| HandleMsg::CastVote {
+        poll_id,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(10u128)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 100, 10000);
+    let _res = handle(&mut deps, env.clone(), vote_msg(1)).unwrap();
+    let _res = handle(&mut deps, env.clone(), vote_msg(2)).unwrap();
+    match handle(&mut deps, env, vote_msg(3)) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot vote on more than 2 polls in progress at once")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // the lock of the ended poll is pruned on the next vote
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    let env = mock_env_height(TEST_VOTER, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, vote_msg(3)).unwrap();
+
+    let voter_addr_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from(TEST_VOTER))
+        .unwrap();
+    assert_eq!(
+        read_locked_votes(&deps.storage, &voter_addr_raw)
+            .unwrap()
+            .iter()
+            .map(|(poll_id, _)| *poll_id)
+            .collect::<Vec<u64>>(),
+        vec![2, 3]
+    );
+}
+
+/// Storage charging the gas of the cosmos sdk kv store
+struct GasMeterStorage {
+    storage: MockStorage,
+    read_gas: Cell<u64>,
+    write_gas: u64,
+}
+
+impl ReadonlyStorage for GasMeterStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let value = self.storage.get(key);
+        let len = value.as_ref().map_or(0, |value| value.len()) as u64;
+        self.read_gas.set(self.read_gas.get() + 1000 + 3 * len);
+        value
+    }
+
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = KV> + 'a> {
+        Box::new(self.storage.range(start, end, order).map(move |(k, v)| {
+            let len = (k.len() + v.len()) as u64;
+            self.read_gas.set(self.read_gas.get() + 30 + 3 * len);
+            (k, v)
+        }))
+    }
+}
+
+impl Storage for GasMeterStorage {
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.write_gas += 2000 + 30 * (key.len() + value.len()) as u64;
+        self.storage.set(key, value);
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.write_gas += 1000;
+        self.storage.remove(key);
+    }
+}
+
+// Write gas of a withdraw by a voter locked in the given number of polls
+fn withdraw_write_gas(active_votes: u64) -> u64 {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    for _ in 0..active_votes {
+        let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + active_votes as u128 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let _res = handle(&mut deps, mock_env(VOTING_TOKEN, &[]), msg).unwrap();
+
+    for poll_id in 1..=active_votes {
+        let env = mock_env_height(TEST_VOTER, &[], 100, 10000);
+        let msg = HandleMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Some(Uint128::from(10u128)),
+            voter: None,
+        };
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    let mut deps = Extern {
+        storage: GasMeterStorage {
+            storage: deps.storage,
+            read_gas: Cell::new(0),
+            write_gas: 0,
+        },
+        api: deps.api,
+        querier: deps.querier,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 100, 10000);
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(1u128)),
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.storage.write_gas
+}
+
+#[test]
+fn withdraw_write_gas_benchmark() {
+    // the locked votes are read but not rewritten, so the writes of a withdraw
+    // don't grow with the vote history; both runs store state numbers of equal length
+    assert_eq!(withdraw_write_gas(10), withdraw_write_gas(20));
+}
+
+#[test]
+fn reject_same_block_votes() {
+    let mut deps = mock_dependencies(20, &[]);
+    let msg = InitMsg {
+        reject_same_block_votes: true,
+        ..init_msg()
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+    let msg = HandleMsg::RegisterContracts {
+        anchor_token: HumanAddr::from(VOTING_TOKEN),
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(11u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(11u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 100, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(10u128)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 100, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot vote in the same block as the last stake")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let env = mock_env_height(TEST_VOTER, &[], 101, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // stake and vote is rejected up front, its vote following the stake
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER_2),
+        amount: Uint128::from(11u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::StakeAndVote {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+            })
+            .unwrap(),
+        ),
+    });
+    let env = mock_env_height(VOTING_TOKEN, &[], 102, 10000);
+    match handle(&mut deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Cannot stake and vote in one message while same block votes are rejected"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+}
+
 #[test]
 fn update_config() {
     let mut deps = mock_dependencies(20, &[]);
@@ -2239,6 +8729,24 @@ fn update_config() {
         expiration_period: None,
         proposal_deposit: None,
         snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -2265,6 +8773,24 @@ fn update_config() {
         expiration_period: Some(30000u64),
         proposal_deposit: Some(Uint128(123u128)),
         snapshot_period: Some(11),
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
     };
 
     let res = handle(&mut deps, env, msg).unwrap();
@@ -2282,23 +8808,289 @@ fn update_config() {
     assert_eq!(123u128, config.proposal_deposit.u128());
     assert_eq!(11u64, config.snapshot_period);
 
-    // Unauthorzied err
-    let env = mock_env(TEST_CREATOR, &[]);
+    // Unauthorzied err
+    let env = mock_env(TEST_CREATOR, &[]);
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+
+    let res = handle(&mut deps, env, msg);
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn config_history() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let env = mock_env(TEST_CREATOR, &[]);
+    let msg = HandleMsg::UpdateConfig {
+        owner: Some(HumanAddr("addr0001".to_string())),
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // unchanged values are not recorded
+    let env = mock_env_height("addr0001", &[], 100, 10000);
     let msg = HandleMsg::UpdateConfig {
         owner: None,
-        quorum: None,
+        quorum: Some(Decimal::percent(20)),
         threshold: None,
-        voting_period: None,
+        voting_period: Some(DEFAULT_VOTING_PERIOD),
         timelock_period: None,
         expiration_period: None,
         proposal_deposit: None,
         snapshot_period: None,
+        vesting_contract: Some(HumanAddr::from("vesting0000")),
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
     };
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    let res = handle(&mut deps, env, msg);
-    match res {
+    let res = query(
+        &deps,
+        QueryMsg::ConfigHistory {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: ConfigHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.records,
+        vec![
+            ConfigHistoryResponseItem {
+                id: 1,
+                height: mock_env(TEST_CREATOR, &[]).block.height,
+                sender: HumanAddr::from(TEST_CREATOR),
+                changes: vec![ConfigChange {
+                    field: "owner".to_string(),
+                    old_value: TEST_CREATOR.to_string(),
+                    new_value: "addr0001".to_string(),
+                }],
+            },
+            ConfigHistoryResponseItem {
+                id: 2,
+                height: 100,
+                sender: HumanAddr::from("addr0001"),
+                changes: vec![
+                    ConfigChange {
+                        field: "quorum".to_string(),
+                        old_value: Decimal::percent(DEFAULT_QUORUM).to_string(),
+                        new_value: "0.2".to_string(),
+                    },
+                    ConfigChange {
+                        field: "vesting_contract".to_string(),
+                        old_value: "".to_string(),
+                        new_value: "vesting0000".to_string(),
+                    },
+                ],
+            },
+        ]
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::ConfigHistory {
+            start_after: Some(1),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let response: ConfigHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(response.records.len(), 1);
+    assert_eq!(response.records[0].id, 2);
+}
+
+#[test]
+fn schedule_config_update() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::ScheduleConfigUpdate {
+        changes: ConfigUpdate {
+            quorum: Some(Decimal::percent(40)),
+            ..ConfigUpdate::default()
+        },
+        activation_height: 200,
+    };
+
+    let env = mock_env_height(TEST_VOTER, &[], 100, 10000);
+    match handle(&mut deps, env, msg.clone()) {
         Err(StdError::Unauthorized { .. }) => {}
-        _ => panic!("Must return unauthorized error"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], 200, 10000);
+    match handle(&mut deps, env, msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Activation height must be in the future")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // invalid values are rejected when scheduled
+    let env = mock_env_height(TEST_CREATOR, &[], 100, 10000);
+    let invalid_msg = HandleMsg::ScheduleConfigUpdate {
+        changes: ConfigUpdate {
+            quorum: Some(Decimal::percent(101)),
+            ..ConfigUpdate::default()
+        },
+        activation_height: 200,
+    };
+    match handle(&mut deps, env.clone(), invalid_msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "quorum must be 0 to 1"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let invalid_msg = HandleMsg::ScheduleConfigUpdate {
+        changes: ConfigUpdate {
+            emergency_council: Some(EmergencyCouncil {
+                members: vec![HumanAddr::from(TEST_VOTER)],
+                threshold: 2,
+                pausable_contracts: vec![],
+            }),
+            ..ConfigUpdate::default()
+        },
+        activation_height: 200,
+    };
+    match handle(&mut deps, env, invalid_msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Emergency council threshold must be 1 to the number of members"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match query(&deps, QueryMsg::ScheduledConfigUpdate {}) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No config update is scheduled"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env_height(TEST_CREATOR, &[], 100, 10000);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "schedule_config_update"),
+            log("activation_height", "200"),
+        ]
+    );
+
+    let res = query(&deps, QueryMsg::ScheduledConfigUpdate {}).unwrap();
+    let response: ScheduledConfigUpdateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.activation_height, 200);
+    assert_eq!(response.changes.quorum, Some(Decimal::percent(40)));
+
+    // the update is not applied before the activation height
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 199, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let config: Config = config_read(&deps.storage).load().unwrap();
+    assert_eq!(config.quorum, Decimal::percent(DEFAULT_QUORUM));
+
+    // and applied by the first message handled from then on
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 250, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let config: Config = config_read(&deps.storage).load().unwrap();
+    assert_eq!(config.quorum, Decimal::percent(40));
+
+    let res = query(
+        &deps,
+        QueryMsg::ConfigHistory {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: ConfigHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.records,
+        vec![ConfigHistoryResponseItem {
+            id: 1,
+            height: 200,
+            sender: HumanAddr::from(TEST_CREATOR),
+            changes: vec![ConfigChange {
+                field: "quorum".to_string(),
+                old_value: Decimal::percent(DEFAULT_QUORUM).to_string(),
+                new_value: "0.4".to_string(),
+            }],
+        }]
+    );
+
+    match query(&deps, QueryMsg::ScheduledConfigUpdate {}) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No config update is scheduled"),
+        _ => panic!("DO NOT ENTER HERE"),
     }
 }
 
@@ -2481,7 +9273,8 @@ fn execute_poll_with_order() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER, &[], POLL_START_HEIGHT, 10000);
     let handle_res = handle(&mut deps, env, msg).unwrap();
@@ -2494,6 +9287,8 @@ fn execute_poll_with_order() {
             log("amount", "1000"),
             log("voter", TEST_VOTER),
             log("vote_option", "yes"),
+            log("quorum_reached", "true"),
+            log("threshold_reached", "true"),
         ]
     );
 
@@ -2534,9 +9329,19 @@ fn execute_poll_with_order() {
         )],
     )]);
 
+    // the simulation is available during the timelock
+    let res = query(&deps, QueryMsg::SimulateExecution { poll_id: 1 }).unwrap();
+    let simulation: SimulateExecutionResponse = from_binary(&res).unwrap();
+    assert_eq!(simulation.status, PollStatus::Passed);
+    assert_eq!(
+        simulation.executable_height,
+        creator_env.block.height + DEFAULT_TIMELOCK_PERIOD
+    );
+
     creator_env.block.height = &creator_env.block.height + DEFAULT_TIMELOCK_PERIOD;
     let msg = HandleMsg::ExecutePoll { poll_id: 1 };
     let handle_res = handle(&mut deps, creator_env, msg).unwrap();
+    assert_eq!(handle_res.messages, simulation.messages);
     assert_eq!(
         handle_res.messages,
         vec![
@@ -2571,6 +9376,183 @@ fn execute_poll_with_order() {
         handle_res.log,
         vec![log("action", "execute_poll"), log("poll_id", "1"),]
     );
+
+    let res = query(&deps, QueryMsg::Stats {}).unwrap();
+    assert_eq!(
+        from_binary::<StatsResponse>(&res).unwrap(),
+        StatsResponse {
+            total_votes: 1,
+            unique_voters: 1,
+            polls_passed: 1,
+            polls_rejected: 0,
+            polls_executed: 1,
+        }
+    );
+}
+
+#[test]
+fn prune_polls() {
+    let mut deps = mock_dependencies(20, &[]);
+    mock_init(&mut deps);
+
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: Some(100),
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: None,
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from(TEST_VOTER),
+        amount: Uint128::from(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
+    });
+    let env = mock_env(VOTING_TOKEN, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Some(Uint128::from(100u128)),
+        voter: None,
+    };
+    let env = mock_env_height(TEST_VOTER, &[], 0, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let env = mock_env_height(TEST_CREATOR, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, HandleMsg::EndPoll { poll_id: 1 }).unwrap();
+
+    // the second poll is still in progress
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from(VOTING_TOKEN),
+        &[(
+            &HumanAddr::from(MOCK_CONTRACT_ADDR),
+            &Uint128(100u128 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let env = mock_env_height(VOTING_TOKEN, &[], DEFAULT_VOTING_PERIOD, 10000);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // within the retention period
+    let msg = HandleMsg::PrunePolls {
+        before_poll_id: 3,
+        limit: Some(1),
+    };
+    let env = mock_env_height(TEST_VOTER_2, &[], DEFAULT_VOTING_PERIOD + 100, 10000);
+    let res = handle(&mut deps, env, msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "prune_polls"),
+            log("pruned_polls", ""),
+            log("removed_entries", "0"),
+        ]
+    );
+
+    // the voter is removed first, then the poll itself
+    let env = mock_env_height(TEST_VOTER_2, &[], DEFAULT_VOTING_PERIOD + 101, 10000);
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "prune_polls"),
+            log("pruned_polls", ""),
+            log("removed_entries", "1"),
+        ]
+    );
+
+    let res = handle(&mut deps, env.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "prune_polls"),
+            log("pruned_polls", "1"),
+            log("removed_entries", "1"),
+        ]
+    );
+
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "prune_polls"),
+            log("pruned_polls", ""),
+            log("removed_entries", "0"),
+        ]
+    );
+
+    assert!(query(&deps, QueryMsg::Poll { poll_id: 1 }).is_err());
+    let res = query(
+        &deps,
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Passed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls, vec![]);
+
+    let res = query(&deps, QueryMsg::Poll { poll_id: 2 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(PollStatus::InProgress, value.status);
+
+    // the pruned poll no longer locks the voter's stake
+    let res = query(
+        &deps,
+        QueryMsg::Staker {
+            address: HumanAddr::from(TEST_VOTER),
+            token_balance: None,
+        },
+    )
+    .unwrap();
+    let staker: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(staker.locked_balance, vec![]);
+
+    let msg = HandleMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(100u128)),
+    };
+    let env = mock_env(TEST_VOTER, &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
 }
 
 #[test]
@@ -2629,6 +9611,7 @@ fn snapshot_poll() {
             log("action", "snapshot_poll"),
             log("poll_id", "1"),
             log("staked_amount", stake_amount),
+            log("staked_share", "0"),
         ]
     );
 
@@ -2687,7 +9670,8 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(amount),
+        amount: Some(Uint128::from(amount)),
+        voter: None,
     };
 
     let handle_res = handle(&mut deps, env, msg.clone()).unwrap();
@@ -2721,7 +9705,8 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(10u128),
+        amount: Some(Uint128::from(10u128)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER_2, &[], end_height - 9, 10000);
     let handle_res = handle(&mut deps, env.clone(), msg).unwrap();
@@ -2764,7 +9749,8 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(10u128),
+        amount: Some(Uint128::from(10u128)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER_3, &[], end_height - 8, 10000);
     let handle_res = handle(&mut deps, env.clone(), msg).unwrap();
@@ -2855,7 +9841,8 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER, &[], POLL_START_HEIGHT, 10000);
     let handle_res = handle(&mut deps, env, msg).unwrap();
@@ -2868,6 +9855,8 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
             log("amount", "1000"),
             log("voter", TEST_VOTER),
             log("vote_option", "yes"),
+            log("quorum_reached", "true"),
+            log("threshold_reached", "true"),
         ]
     );
 
@@ -2887,7 +9876,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     //cast another vote
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from(TEST_VOTER_2),
-        amount: Uint128::from(8 * stake_amount as u128),
+        amount: Uint128::from(9 * stake_amount as u128),
         msg: Some(to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap()),
     });
 
@@ -2898,7 +9887,8 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER_2, &[], creator_env.block.height, 10000);
     let handle_res = handle(&mut deps, env, msg).unwrap();
@@ -2926,7 +9916,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
         vec![
             log("action", "end_poll"),
             log("poll_id", "1"),
-            log("rejected_reason", "Quorum not reached"),
+            log("rejected_reason", "quorum_not_reached"),
             log("passed", "false"),
         ]
     );
@@ -3019,7 +10009,8 @@ fn happy_days_end_poll_with_controlled_quorum() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(stake_amount),
+        amount: Some(Uint128::from(stake_amount)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER, &[], POLL_START_HEIGHT, 10000);
     let handle_res = handle(&mut deps, env, msg).unwrap();
@@ -3032,6 +10023,8 @@ fn happy_days_end_poll_with_controlled_quorum() {
             log("amount", "1000"),
             log("voter", TEST_VOTER),
             log("vote_option", "yes"),
+            log("quorum_reached", "true"),
+            log("threshold_reached", "true"),
         ]
     );
 
@@ -3051,6 +10044,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
             log("action", "snapshot_poll"),
             log("poll_id", "1"),
             log("staked_amount", stake_amount),
+            log("staked_share", stake_amount),
         ]
     );
 
@@ -3076,7 +10070,8 @@ fn happy_days_end_poll_with_controlled_quorum() {
     let msg = HandleMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
-        amount: Uint128::from(8 * stake_amount),
+        amount: Some(Uint128::from(8 * stake_amount)),
+        voter: None,
     };
     let env = mock_env_height(TEST_VOTER_2, &[], creator_env.block.height, 10000);
     let handle_res = handle(&mut deps, env, msg).unwrap();
@@ -3145,3 +10140,115 @@ fn happy_days_end_poll_with_controlled_quorum() {
 
     assert_eq!(actual_staked_weight.u128(), (10 * stake_amount))
 }
+
+#[test]
+fn quorum_presets() {
+    let mut deps = mock_dependencies(20, &[]);
+    let expedited = QuorumPreset {
+        name: "expedited".to_string(),
+        quorum: Decimal::percent(40),
+        threshold: Decimal::percent(60),
+        voting_period: 100,
+    };
+
+    let msg = InitMsg {
+        quorum_presets: vec![expedited.clone(), expedited.clone()],
+        ..init_msg()
+    };
+    match init(&mut deps, mock_env(TEST_CREATOR, &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Quorum preset names must be unique and not empty")
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let msg = InitMsg {
+        quorum_presets: vec![expedited.clone()],
+        ..init_msg()
+    };
+    let env = mock_env(TEST_CREATOR, &[]);
+    let _res = init(&mut deps, env.clone(), msg).unwrap();
+    let msg = HandleMsg::RegisterContracts {
+        anchor_token: HumanAddr::from(VOTING_TOKEN),
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let create_poll_msg = |preset: &str| {
+        HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(TEST_CREATOR),
+            amount: Uint128(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: Some(
+                to_binary(&Cw20HookMsg::CreatePoll {
+                    title: "test".to_string(),
+                    description: "test".to_string(),
+                    links: None,
+                    execute_msgs: None,
+                    commit_reveal: None,
+                    content_hash: None,
+                    referendum: None,
+                    stake_refund: None,
+                    reserved_poll_id: None,
+                    refund_address: None,
+                    option_labels: None,
+                    vote_reward: None,
+                    execution_bounty: None,
+                    on_reject_msgs: None,
+                    preset: Some(preset.to_string()),
+                })
+                .unwrap(),
+            ),
+        })
+    };
+
+    let env = mock_env_height(VOTING_TOKEN, &[], 0, 10000);
+    match handle(&mut deps, env.clone(), create_poll_msg("constitutional")) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Quorum preset does not exist"),
+        _ => panic!("Must return error"),
+    }
+    let _res = handle(&mut deps, env, create_poll_msg("expedited")).unwrap();
+
+    // the presets change without affecting the poll already created
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        vesting_contract: None,
+        reveal_period: None,
+        min_vote_amount: None,
+        quorum_excluded_addresses: None,
+        tally_checkpoint_interval: None,
+        poll_retention_period: None,
+        max_poll_extension: None,
+        narrow_pass_margin: None,
+        community_contract: None,
+        adaptive_quorum: None,
+        poll_sponsorship: None,
+        executor_whitelist: None,
+        partial_refund_ratio: None,
+        veto_threshold: None,
+        emergency_council: None,
+        max_active_votes: None,
+        reject_same_block_votes: None,
+        quorum_presets: Some(vec![QuorumPreset {
+            voting_period: 50,
+            ..expedited.clone()
+        }]),
+    };
+    let _res = handle(&mut deps, mock_env(TEST_CREATOR, &[]), msg).unwrap();
+    let config: ConfigResponse = from_binary(&query(&deps, QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.quorum_presets[0].voting_period, 50);
+
+    let poll: PollResponse =
+        from_binary(&query(&deps, QueryMsg::Poll { poll_id: 1 }).unwrap()).unwrap();
+    assert_eq!(poll.preset, Some("expedited".to_string()));
+    assert_eq!(poll.end_height, 100);
+
+    let poll = poll_read(&deps.storage).load(&1u64.to_be_bytes()).unwrap();
+    assert_eq!(poll.quorum, Decimal::percent(40));
+    assert_eq!(poll.threshold, Decimal::percent(60));
+}
@@ -1,41 +1,87 @@
 use cosmwasm_std::{
-    from_binary, log, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Decimal, Env, Extern,
-    HandleResponse, HandleResult, HumanAddr, InitResponse, MigrateResponse, MigrateResult, Querier,
-    StdError, StdResult, Storage, Uint128, WasmMsg,
+    from_binary, log, to_binary, Api, Binary, BlockInfo, CanonicalAddr, Coin, CosmosMsg, Decimal,
+    Env, Extern, HandleResponse, HandleResult, HumanAddr, InitResponse, MigrateResponse,
+    MigrateResult, Querier, QueryRequest, StdError, StdResult, Storage, Uint128, WasmMsg,
+    WasmQuery,
 };
 
+use anchor_token::common::Expiration;
+use anchor_token::gov::Cw20HookMsg as GovCw20HookMsg;
+use anchor_token::math::{decimal_ratio, mul_decimal, mul_ratio};
 use anchor_token::staking::{
-    ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, MigrateMsg, QueryMsg, StakerInfoResponse,
-    StateResponse,
+    BondHookMsg, ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, LockupPosition, MigrateMsg,
+    PoolStateResponse, QueryMsg, RemainingCapacityResponse, ScheduleChange,
+    ScheduleChangesResponse, StakerInfoResponse, StateResponse, UnderlyingAssetsResponse,
+    UnfundedResponse,
 };
 
 use crate::state::{
-    read_config, read_staker_info, read_state, remove_staker_info, store_config, store_staker_info,
-    store_state, Config, StakerInfo, State,
+    read_claimed_reward, read_config, read_schedule_changes, read_staker_info, read_state,
+    remove_staker_info, store_claimed_reward, store_config, store_schedule_changes,
+    store_staker_info, store_state, Config, StakerInfo, State,
 };
 
 use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::{
+    HandleMsg as TerraswapHandleMsg, PoolResponse, QueryMsg as TerraswapQueryMsg,
+};
+use terraswap::querier::query_token_balance;
+
+const SECONDS_PER_DAY: u64 = 86400;
 
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     msg: InitMsg,
 ) -> StdResult<InitResponse> {
+    for tier in msg.lockup_tiers.iter() {
+        if tier.reward_multiplier < Decimal::one() {
+            return Err(StdError::generic_err(
+                "Lockup reward multiplier must be at least 1",
+            ));
+        }
+
+        if tier.early_exit_penalty >= Decimal::one() {
+            return Err(StdError::generic_err(
+                "Early exit penalty must be less than 1",
+            ));
+        }
+    }
+
     store_config(
         &mut deps.storage,
         &Config {
+            owner: deps.api.canonical_address(&msg.owner)?,
             anchor_token: deps.api.canonical_address(&msg.anchor_token)?,
             staking_token: deps.api.canonical_address(&msg.staking_token)?,
+            pair_contract: deps.api.canonical_address(&msg.pair_contract)?,
             distribution_schedule: msg.distribution_schedule,
+            hook_contract: match msg.hook_contract {
+                Some(hook_contract) => Some(deps.api.canonical_address(&hook_contract)?),
+                None => None,
+            },
+            bond_cap: msg.bond_cap,
+            lockup_tiers: msg.lockup_tiers,
+            gov_contract: match msg.gov_contract {
+                Some(gov_contract) => Some(deps.api.canonical_address(&gov_contract)?),
+                None => None,
+            },
+            schedule_delay: msg.schedule_delay,
         },
     )?;
 
     store_state(
         &mut deps.storage,
         &State {
+            contract_addr: deps.api.canonical_address(&env.contract.address)?,
             last_distributed: env.block.height,
             total_bond_amount: Uint128::zero(),
             global_reward_index: Decimal::zero(),
+            paid_reward: Uint128::zero(),
+            total_reward_weight: Uint128::zero(),
+            global_penalty_index: Decimal::zero(),
+            distributed_reward: Uint128::zero(),
         },
     )?;
 
@@ -50,7 +96,35 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     match msg {
         HandleMsg::Receive(msg) => receive_cw20(deps, env, msg),
         HandleMsg::Unbond { amount } => unbond(deps, env, amount),
-        HandleMsg::Withdraw {} => withdraw(deps, env),
+        HandleMsg::Withdraw { recipient } => withdraw(deps, env, recipient),
+        HandleMsg::WithdrawToGov {} => withdraw_to_gov(deps, env),
+        HandleMsg::ReleaseLockups { staker } => release_lockups(deps, env, staker),
+        HandleMsg::ZapBond {
+            anc_amount,
+            max_spread,
+            slippage_tolerance,
+        } => zap_bond(deps, env, anc_amount, max_spread, slippage_tolerance),
+        HandleMsg::ZapBondProvide {
+            staker,
+            prev_anc_balance,
+            uusd_amount,
+            slippage_tolerance,
+        } => zap_bond_provide(
+            deps,
+            env,
+            staker,
+            prev_anc_balance,
+            uusd_amount,
+            slippage_tolerance,
+        ),
+        HandleMsg::ZapBondHook {
+            staker,
+            prev_staking_token_amount,
+        } => zap_bond_hook(deps, env, staker, prev_staking_token_amount),
+        HandleMsg::AddDistributionSchedule { schedule } => {
+            add_distribution_schedule(deps, env, schedule)
+        }
+        HandleMsg::UpdateBondCap { bond_cap } => update_bond_cap(deps, env, bond_cap),
     }
 }
 
@@ -63,13 +137,13 @@ pub fn receive_cw20<S: Storage, A: Api, Q: Querier>(
         let config: Config = read_config(&deps.storage)?;
 
         match from_binary(&msg)? {
-            Cw20HookMsg::Bond {} => {
+            Cw20HookMsg::Bond { lockup_days } => {
                 // only staking token contract can execute this message
                 if config.staking_token != deps.api.canonical_address(&env.message.sender)? {
                     return Err(StdError::unauthorized());
                 }
 
-                bond(deps, env, cw20_msg.sender, cw20_msg.amount)
+                bond(deps, env, cw20_msg.sender, cw20_msg.amount, lockup_days)
             }
         }
     } else {
@@ -82,6 +156,7 @@ pub fn bond<S: Storage, A: Api, Q: Querier>(
     env: Env,
     sender_addr: HumanAddr,
     amount: Uint128,
+    lockup_days: Option<u64>,
 ) -> HandleResult {
     let sender_addr_raw: CanonicalAddr = deps.api.canonical_address(&sender_addr)?;
 
@@ -90,23 +165,59 @@ pub fn bond<S: Storage, A: Api, Q: Querier>(
     let mut staker_info: StakerInfo = read_staker_info(&deps.storage, &sender_addr_raw)?;
 
     // Compute global reward & staker reward
-    compute_reward(&config, &mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height)?;
     compute_staker_reward(&state, &mut staker_info)?;
 
     // Increase bond_amount
     increase_bond_amount(&mut state, &mut staker_info, amount);
+    if let Some(bond_cap) = config.bond_cap {
+        if state.total_bond_amount > bond_cap {
+            return Err(StdError::generic_err(format!(
+                "Bond cap exceeded, only {} more tokens can be bonded",
+                remaining_capacity(bond_cap, (state.total_bond_amount - amount)?)
+            )));
+        }
+    }
+
+    release_expired_lockups(&mut staker_info, &env.block);
+    if let Some(lockup_days) = lockup_days {
+        let tier = match config.lockup_tiers.iter().find(|t| t.days == lockup_days) {
+            Some(tier) => tier,
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "Lockup of {} days is not available",
+                    lockup_days
+                )))
+            }
+        };
+
+        staker_info.lockups.push(LockupPosition {
+            amount,
+            unlock_time: env.block.time + lockup_days * SECONDS_PER_DAY,
+            reward_multiplier: tier.reward_multiplier,
+            early_exit_penalty: tier.early_exit_penalty,
+        });
+    }
+
+    update_reward_weight(&mut state, &mut staker_info)?;
 
     // Store updated state with staker's staker_info
     store_staker_info(&mut deps.storage, &sender_addr_raw, &staker_info)?;
     store_state(&mut deps.storage, &state)?;
 
+    let mut logs = vec![
+        log("action", "bond"),
+        log("owner", sender_addr.as_str()),
+        log("amount", amount.to_string()),
+    ];
+
+    if let Some(lockup_days) = lockup_days {
+        logs.push(log("lockup_days", lockup_days));
+    }
+
     Ok(HandleResponse {
-        messages: vec![],
-        log: vec![
-            log("action", "bond"),
-            log("owner", sender_addr),
-            log("amount", amount.to_string()),
-        ],
+        messages: bond_changed_msgs(deps, &config, &sender_addr, staker_info.bond_amount)?,
+        log: logs,
         data: None,
     })
 }
@@ -127,15 +238,22 @@ pub fn unbond<S: Storage, A: Api, Q: Querier>(
     }
 
     // Compute global reward & staker reward
-    compute_reward(&config, &mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height)?;
     compute_staker_reward(&state, &mut staker_info)?;
 
-    // Decrease bond_amount
+    // Decrease bond_amount, locked amounts are unbonded last
+    release_expired_lockups(&mut staker_info, &env.block);
+    let penalty = unlock_bond_amount(&mut staker_info, amount)?;
     decrease_bond_amount(&mut state, &mut staker_info, amount)?;
+    update_reward_weight(&mut state, &mut staker_info)?;
+    let penalty = distribute_penalty(&mut state, penalty)?;
 
     // Store or remove updated rewards info
     // depends on the left pending reward and bond amount
-    if staker_info.pending_reward.is_zero() && staker_info.bond_amount.is_zero() {
+    if staker_info.pending_reward.is_zero()
+        && staker_info.pending_penalty.is_zero()
+        && staker_info.bond_amount.is_zero()
+    {
         remove_staker_info(&mut deps.storage, &sender_addr_raw);
     } else {
         store_staker_info(&mut deps.storage, &sender_addr_raw, &staker_info)?;
@@ -144,41 +262,199 @@ pub fn unbond<S: Storage, A: Api, Q: Querier>(
     // Store updated state
     store_state(&mut deps.storage, &state)?;
 
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.human_address(&config.staking_token)?,
+        msg: to_binary(&Cw20HandleMsg::Transfer {
+            recipient: env.message.sender.clone(),
+            amount: (amount - penalty)?,
+        })?,
+        send: vec![],
+    })];
+    messages.extend(bond_changed_msgs(
+        deps,
+        &config,
+        &env.message.sender,
+        staker_info.bond_amount,
+    )?);
+
+    let mut logs = vec![
+        log("action", "unbond"),
+        log("owner", env.message.sender.as_str()),
+        log("amount", amount.to_string()),
+    ];
+
+    if !penalty.is_zero() {
+        logs.push(log("penalty", penalty.to_string()));
+    }
+
     Ok(HandleResponse {
-        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        messages,
+        log: logs,
+        data: None,
+    })
+}
+
+// withdraw rewards to executor
+pub fn withdraw<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    recipient: Option<HumanAddr>,
+) -> HandleResult {
+    let recipient = recipient.unwrap_or_else(|| env.message.sender.clone());
+    let config: Config = read_config(&deps.storage)?;
+    let (amount, penalty_amount) = claim_rewards(deps, &env)?;
+
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.human_address(&config.anchor_token)?,
+        msg: to_binary(&Cw20HandleMsg::Transfer {
+            recipient: recipient.clone(),
+            amount,
+        })?,
+        send: vec![],
+    })];
+    let mut logs = vec![
+        log("action", "withdraw"),
+        log("owner", env.message.sender.as_str()),
+        log("recipient", recipient.as_str()),
+        log("amount", amount.to_string()),
+    ];
+
+    // staking tokens forfeited by the early exits of other stakers
+    if !penalty_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps.api.human_address(&config.staking_token)?,
             msg: to_binary(&Cw20HandleMsg::Transfer {
-                recipient: env.message.sender.clone(),
-                amount,
+                recipient,
+                amount: penalty_amount,
             })?,
             send: vec![],
-        })],
-        log: vec![
-            log("action", "unbond"),
-            log("owner", env.message.sender),
-            log("amount", amount.to_string()),
-        ],
+        }));
+        logs.push(log("penalty_amount", penalty_amount.to_string()));
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
         data: None,
     })
 }
 
-// withdraw rewards to executor
-pub fn withdraw<S: Storage, A: Api, Q: Querier>(
+/// Sends the pending rewards to the gov contract, staked there on behalf
+/// of the sender; forfeited staking tokens are still transferred to the sender
+pub fn withdraw_to_gov<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
 ) -> HandleResult {
-    let sender_addr_raw = deps.api.canonical_address(&env.message.sender)?;
+    let config: Config = read_config(&deps.storage)?;
+    let gov_contract = match &config.gov_contract {
+        Some(gov_contract) => deps.api.human_address(gov_contract)?,
+        None => return Err(StdError::generic_err("Gov contract is not registered")),
+    };
+
+    let (amount, penalty_amount) = claim_rewards(deps, &env)?;
+    if amount.is_zero() {
+        return Err(StdError::generic_err("No rewards to stake"));
+    }
+
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.human_address(&config.anchor_token)?,
+        msg: to_binary(&Cw20HandleMsg::Send {
+            contract: gov_contract.clone(),
+            amount,
+            msg: Some(to_binary(&GovCw20HookMsg::StakeVotingTokensFor {
+                staker: env.message.sender.clone(),
+            })?),
+        })?,
+        send: vec![],
+    })];
+    let mut logs = vec![
+        log("action", "withdraw_to_gov"),
+        log("owner", env.message.sender.as_str()),
+        log("gov_contract", gov_contract.as_str()),
+        log("amount", amount.to_string()),
+    ];
+
+    if !penalty_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.staking_token)?,
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: env.message.sender,
+                amount: penalty_amount,
+            })?,
+            send: vec![],
+        }));
+        logs.push(log("penalty_amount", penalty_amount.to_string()));
+    }
+
+    Ok(HandleResponse {
+        messages,
+        log: logs,
+        data: None,
+    })
+}
+
+pub fn release_lockups<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    staker: HumanAddr,
+) -> HandleResult {
+    let staker_raw = deps.api.canonical_address(&staker)?;
+    let config: Config = read_config(&deps.storage)?;
+    let mut state: State = read_state(&deps.storage)?;
+    let mut staker_info: StakerInfo = read_staker_info(&deps.storage, &staker_raw)?;
+
+    let lockup_count = staker_info.lockups.len();
+    release_expired_lockups(&mut staker_info, &env.block);
+    if staker_info.lockups.len() == lockup_count {
+        return Err(StdError::generic_err("No expired lockups to release"));
+    }
+
+    // the rewards until now are computed with the boosted weight
+    compute_reward(&config, &mut state, env.block.height)?;
+    compute_staker_reward(&state, &mut staker_info)?;
+    update_reward_weight(&mut state, &mut staker_info)?;
+
+    store_staker_info(&mut deps.storage, &staker_raw, &staker_info)?;
+    store_state(&mut deps.storage, &state)?;
 
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "release_lockups"),
+            log("staker", staker.as_str()),
+            log("reward_weight", staker_info.reward_weight.to_string()),
+        ],
+        data: None,
+    })
+}
+
+// Settles the rewards of the sender, returning the pending
+// reward and forfeited staking tokens to pay out
+fn claim_rewards<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+) -> StdResult<(Uint128, Uint128)> {
+    let sender_addr_raw = deps.api.canonical_address(&env.message.sender)?;
     let config: Config = read_config(&deps.storage)?;
     let mut state: State = read_state(&deps.storage)?;
     let mut staker_info = read_staker_info(&deps.storage, &sender_addr_raw)?;
 
     // Compute global reward & staker reward
-    compute_reward(&config, &mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height)?;
     compute_staker_reward(&state, &mut staker_info)?;
 
     let amount = staker_info.pending_reward;
     staker_info.pending_reward = Uint128::zero();
+    state.paid_reward += amount;
+
+    let claimed_reward = read_claimed_reward(&deps.storage, &sender_addr_raw)? + amount;
+    store_claimed_reward(&mut deps.storage, &sender_addr_raw, claimed_reward)?;
+
+    let penalty_amount = staker_info.pending_penalty;
+    staker_info.pending_penalty = Uint128::zero();
+
+    release_expired_lockups(&mut staker_info, &env.block);
+    update_reward_weight(&mut state, &mut staker_info)?;
 
     // Store or remove updated rewards info
     // depends on the left pending reward and bond amount
@@ -190,24 +466,352 @@ pub fn withdraw<S: Storage, A: Api, Q: Querier>(
 
     // Store updated state
     store_state(&mut deps.storage, &state)?;
+
+    Ok((amount, penalty_amount))
+}
+
+pub fn add_distribution_schedule<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    schedule: (u64, u64, Uint128),
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+    if config.owner != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    if schedule.0 >= schedule.1 {
+        return Err(StdError::generic_err(
+            "Schedule end height must be greater than the start height",
+        ));
+    }
+
+    if schedule.0 < env.block.height {
+        return Err(StdError::generic_err("Schedule must start in the future"));
+    }
+
+    // announced ahead so the stakers can react before the schedule starts
+    if schedule.0 < env.block.height + config.schedule_delay {
+        return Err(StdError::generic_err(format!(
+            "Schedule must start at least {} blocks after it is added",
+            config.schedule_delay
+        )));
+    }
+
+    config.distribution_schedule.push(schedule);
+
+    let state: State = read_state(&deps.storage)?;
+    let unfunded = compute_unfunded(deps, &config, &state)?;
+    if !unfunded.shortfall.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Distribution schedule is not funded, {} more reward tokens are required",
+            unfunded.shortfall
+        )));
+    }
+
+    store_config(&mut deps.storage, &config)?;
+
+    let mut changes = read_schedule_changes(&deps.storage)?;
+    changes.push(ScheduleChange {
+        schedule,
+        announced_height: env.block.height,
+    });
+    store_schedule_changes(&mut deps.storage, &changes)?;
+
     Ok(HandleResponse {
-        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.human_address(&config.anchor_token)?,
-            msg: to_binary(&Cw20HandleMsg::Transfer {
-                recipient: env.message.sender.clone(),
-                amount,
-            })?,
-            send: vec![],
-        })],
+        messages: vec![],
         log: vec![
-            log("action", "withdraw"),
+            log("action", "add_distribution_schedule"),
+            log("start_height", schedule.0),
+            log("end_height", schedule.1),
+            log("amount", schedule.2.to_string()),
+            log("announced_height", env.block.height),
+        ],
+        data: None,
+    })
+}
+
+pub fn update_bond_cap<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    bond_cap: Option<Uint128>,
+) -> HandleResult {
+    let mut config: Config = read_config(&deps.storage)?;
+    if config.owner != deps.api.canonical_address(&env.message.sender)? {
+        return Err(StdError::unauthorized());
+    }
+
+    config.bond_cap = bond_cap;
+    store_config(&mut deps.storage, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "update_bond_cap"),
+            log(
+                "bond_cap",
+                bond_cap.map_or_else(|| "none".to_string(), |cap| cap.to_string()),
+            ),
+        ],
+        data: None,
+    })
+}
+
+fn remaining_capacity(bond_cap: Uint128, total_bond_amount: Uint128) -> Uint128 {
+    (bond_cap - total_bond_amount).unwrap_or_else(|_| Uint128::zero())
+}
+
+// compare the rewards not yet paid out, distributed or not,
+// with the reward token balance held by the contract
+fn compute_unfunded<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    state: &State,
+) -> StdResult<UnfundedResponse> {
+    let mut total_reward = Uint128::zero();
+    for s in config.distribution_schedule.iter() {
+        total_reward += s.2;
+    }
+
+    let reward_balance = query_token_balance(
+        deps,
+        &deps.api.human_address(&config.anchor_token)?,
+        &deps.api.human_address(&state.contract_addr)?,
+    )?;
+
+    let unpaid_reward = (total_reward - state.paid_reward)?;
+    Ok(UnfundedResponse {
+        total_reward,
+        paid_reward: state.paid_reward,
+        reward_balance,
+        shortfall: (unpaid_reward - reward_balance).unwrap_or_else(|_| Uint128::zero()),
+    })
+}
+
+/// ZapBond
+/// 1. Swap half of the sent uusd to ANC, or pull `anc_amount` ANC from the sender
+/// 2. Provide liquidity with the received ANC and the remaining uusd
+/// 3. Bond the minted LP token on behalf of the sender
+pub fn zap_bond<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    anc_amount: Option<Uint128>,
+    max_spread: Option<Decimal>,
+    slippage_tolerance: Option<Decimal>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    let anchor_token = deps.api.human_address(&config.anchor_token)?;
+
+    let amount = env
+        .message
+        .sent_funds
+        .iter()
+        .find(|c| c.denom == "uusd")
+        .map(|c| c.amount)
+        .unwrap_or_else(Uint128::zero);
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Must provide uusd"));
+    }
+
+    let prev_anc_balance = query_token_balance(deps, &anchor_token, &env.contract.address)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let uusd_amount = match anc_amount {
+        Some(anc_amount) => {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: anchor_token,
+                msg: to_binary(&Cw20HandleMsg::TransferFrom {
+                    owner: env.message.sender.clone(),
+                    recipient: env.contract.address.clone(),
+                    amount: anc_amount,
+                })?,
+                send: vec![],
+            }));
+
+            amount
+        }
+        None => {
+            let swap_amount = amount.multiply_ratio(1u128, 2u128);
+            let swap_asset = Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: swap_amount,
+            };
+
+            // deduct tax first
+            let swap_coin = swap_asset.deduct_tax(deps)?;
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.human_address(&config.pair_contract)?,
+                msg: to_binary(&TerraswapHandleMsg::Swap {
+                    offer_asset: Asset {
+                        amount: swap_coin.amount,
+                        ..swap_asset
+                    },
+                    max_spread,
+                    belief_price: None,
+                    to: None,
+                })?,
+                send: vec![swap_coin],
+            }));
+
+            (amount - swap_amount)?
+        }
+    };
+
+    // the tax of the provided uusd is deducted here, once
+    let uusd_coin: Coin = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: uusd_amount,
+    }
+    .deduct_tax(deps)?;
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address,
+        msg: to_binary(&HandleMsg::ZapBondProvide {
+            staker: env.message.sender.clone(),
+            prev_anc_balance,
+            uusd_amount: uusd_coin.amount,
+            slippage_tolerance,
+        })?,
+        send: vec![],
+    }));
+
+    Ok(HandleResponse {
+        messages,
+        log: vec![
+            log("action", "zap_bond"),
             log("owner", env.message.sender),
-            log("amount", amount.to_string()),
+            log("uusd_amount", amount.to_string()),
+        ],
+        data: None,
+    })
+}
+
+pub fn zap_bond_provide<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    staker: HumanAddr,
+    prev_anc_balance: Uint128,
+    uusd_amount: Uint128,
+    slippage_tolerance: Option<Decimal>,
+) -> HandleResult {
+    // only the contract itself can execute this message
+    if env.message.sender != env.contract.address {
+        return Err(StdError::unauthorized());
+    }
+
+    let config: Config = read_config(&deps.storage)?;
+    let anchor_token = deps.api.human_address(&config.anchor_token)?;
+    let staking_token = deps.api.human_address(&config.staking_token)?;
+    let pair_contract = deps.api.human_address(&config.pair_contract)?;
+
+    let anc_amount =
+        (query_token_balance(deps, &anchor_token, &env.contract.address)? - prev_anc_balance)?;
+    let prev_staking_token_amount =
+        query_token_balance(deps, &staking_token, &env.contract.address)?;
+
+    // the tax is already deducted from the uusd amount
+    let uusd_coin = Coin {
+        denom: "uusd".to_string(),
+        amount: uusd_amount,
+    };
+
+    Ok(HandleResponse {
+        messages: vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: anchor_token.clone(),
+                msg: to_binary(&Cw20HandleMsg::IncreaseAllowance {
+                    spender: pair_contract.clone(),
+                    amount: anc_amount,
+                    expires: None,
+                })?,
+                send: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_contract,
+                msg: to_binary(&TerraswapHandleMsg::ProvideLiquidity {
+                    assets: [
+                        Asset {
+                            info: AssetInfo::Token {
+                                contract_addr: anchor_token,
+                            },
+                            amount: anc_amount,
+                        },
+                        Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: "uusd".to_string(),
+                            },
+                            amount: uusd_coin.amount,
+                        },
+                    ],
+                    slippage_tolerance,
+                })?,
+                send: vec![uusd_coin.clone()],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address,
+                msg: to_binary(&HandleMsg::ZapBondHook {
+                    staker,
+                    prev_staking_token_amount,
+                })?,
+                send: vec![],
+            }),
+        ],
+        log: vec![
+            log("action", "zap_bond_provide"),
+            log("anc_amount", anc_amount.to_string()),
+            log("uusd_amount", uusd_coin.amount.to_string()),
         ],
         data: None,
     })
 }
 
+pub fn zap_bond_hook<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    staker: HumanAddr,
+    prev_staking_token_amount: Uint128,
+) -> HandleResult {
+    // only the contract itself can execute this message
+    if env.message.sender != env.contract.address {
+        return Err(StdError::unauthorized());
+    }
+
+    let config: Config = read_config(&deps.storage)?;
+    let amount = (query_token_balance(
+        deps,
+        &deps.api.human_address(&config.staking_token)?,
+        &env.contract.address,
+    )? - prev_staking_token_amount)?;
+
+    bond(deps, env, staker, amount, None)
+}
+
+// notify the hook contract of the staker's new bond amount
+fn bond_changed_msgs<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    config: &Config,
+    staker: &HumanAddr,
+    new_amount: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    let hook_contract = match &config.hook_contract {
+        Some(hook_contract) => deps.api.human_address(hook_contract)?,
+        None => return Ok(vec![]),
+    };
+
+    Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: hook_contract,
+        msg: to_binary(&BondHookMsg::BondChanged {
+            staker: staker.clone(),
+            new_amount,
+        })?,
+        send: vec![],
+    })])
+}
+
 fn increase_bond_amount(state: &mut State, staker_info: &mut StakerInfo, amount: Uint128) {
     state.total_bond_amount += amount;
     staker_info.bond_amount += amount;
@@ -223,40 +827,128 @@ fn decrease_bond_amount(
     Ok(())
 }
 
+// lockups past their unlock time no longer weigh in the rewards
+fn release_expired_lockups(staker_info: &mut StakerInfo, block: &BlockInfo) {
+    staker_info
+        .lockups
+        .retain(|lockup| !Expiration::AtTime(lockup.unlock_time).is_expired(block));
+}
+
+// Takes the unbonded amount from the unlocked bond first and then from the
+// lockups closest to their unlock time, returning the early exit penalty
+fn unlock_bond_amount(staker_info: &mut StakerInfo, amount: Uint128) -> StdResult<Uint128> {
+    let unlocked_amount = (staker_info.bond_amount - locked_amount(staker_info))?;
+    let mut remaining = (amount - unlocked_amount).unwrap_or_else(|_| Uint128::zero());
+    let mut penalty = Uint128::zero();
+
+    staker_info.lockups.sort_by_key(|lockup| lockup.unlock_time);
+    for lockup in staker_info.lockups.iter_mut() {
+        if remaining.is_zero() {
+            break;
+        }
+
+        let unlock_amount = std::cmp::min(remaining, lockup.amount);
+        lockup.amount = (lockup.amount - unlock_amount)?;
+        remaining = (remaining - unlock_amount)?;
+        penalty += unlock_amount * lockup.early_exit_penalty;
+    }
+
+    staker_info
+        .lockups
+        .retain(|lockup| !lockup.amount.is_zero());
+    Ok(penalty)
+}
+
+fn locked_amount(staker_info: &StakerInfo) -> Uint128 {
+    let mut locked_amount = Uint128::zero();
+    for lockup in staker_info.lockups.iter() {
+        locked_amount += lockup.amount;
+    }
+
+    locked_amount
+}
+
+// reward weight of the bond, the locked amounts count with their multiplier
+fn update_reward_weight(state: &mut State, staker_info: &mut StakerInfo) -> StdResult<()> {
+    let mut reward_weight = (staker_info.bond_amount - locked_amount(staker_info))?;
+    for lockup in staker_info.lockups.iter() {
+        reward_weight += lockup.amount * lockup.reward_multiplier;
+    }
+
+    state.total_reward_weight =
+        (state.total_reward_weight + reward_weight - staker_info.reward_weight)?;
+    staker_info.reward_weight = reward_weight;
+    Ok(())
+}
+
+// share the penalty among the remaining bonds by their weight;
+// nothing is forfeited when no bond remains to receive it
+fn distribute_penalty(state: &mut State, penalty: Uint128) -> StdResult<Uint128> {
+    if state.total_reward_weight.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    state.global_penalty_index =
+        state.global_penalty_index + decimal_ratio(penalty, state.total_reward_weight)?;
+    Ok(penalty)
+}
+
 // compute distributed rewards and update global reward index
-fn compute_reward(config: &Config, state: &mut State, block_height: u64) {
-    if state.total_bond_amount.is_zero() {
+fn compute_reward(config: &Config, state: &mut State, block_height: u64) -> StdResult<()> {
+    if state.total_reward_weight.is_zero() {
         state.last_distributed = block_height;
-        return;
+        return Ok(());
+    }
+
+    // rewards are never distributed twice for the same blocks
+    if block_height <= state.last_distributed {
+        return Ok(());
     }
 
+    // each schedule accrues the blocks of the range inside it, so a range
+    // straddling schedule boundaries is split pro rata between the schedules
     let mut distributed_amount: Uint128 = Uint128::zero();
     for s in config.distribution_schedule.iter() {
-        if s.0 > block_height || s.1 < state.last_distributed {
-            continue;
-        }
-
-        // min(s.1, block_height) - max(s.0, last_distributed)
-        let passed_blocks =
-            std::cmp::min(s.1, block_height) - std::cmp::max(s.0, state.last_distributed);
-
-        let num_blocks = s.1 - s.0;
-        let distribution_amount_per_block: Decimal = Decimal::from_ratio(s.2, num_blocks);
-        distributed_amount += distribution_amount_per_block * Uint128(passed_blocks as u128);
+        distributed_amount +=
+            (distributed_by(s, block_height)? - distributed_by(s, state.last_distributed)?)?;
     }
 
     state.last_distributed = block_height;
-    state.global_reward_index = state.global_reward_index
-        + Decimal::from_ratio(distributed_amount, state.total_bond_amount);
+    state.distributed_reward += distributed_amount;
+    state.global_reward_index =
+        state.global_reward_index + decimal_ratio(distributed_amount, state.total_reward_weight)?;
+    Ok(())
+}
+
+// amount of the schedule distributed by the end of the given height; rounding
+// down the cumulative amount instead of each accrual, the accruals add up to
+// the exact schedule amount however the blocks are split
+fn distributed_by(schedule: &(u64, u64, Uint128), height: u64) -> StdResult<Uint128> {
+    let (start, end, amount) = *schedule;
+    if height <= start {
+        Ok(Uint128::zero())
+    } else if height >= end {
+        Ok(amount)
+    } else {
+        mul_ratio(
+            amount,
+            Uint128((height - start) as u128),
+            Uint128((end - start) as u128),
+        )
+    }
 }
 
 // withdraw reward to pending reward
 fn compute_staker_reward(state: &State, staker_info: &mut StakerInfo) -> StdResult<()> {
-    let pending_reward = (staker_info.bond_amount * state.global_reward_index
-        - staker_info.bond_amount * staker_info.reward_index)?;
+    let pending_reward = (mul_decimal(staker_info.reward_weight, state.global_reward_index)?
+        - mul_decimal(staker_info.reward_weight, staker_info.reward_index)?)?;
+    let pending_penalty = (mul_decimal(staker_info.reward_weight, state.global_penalty_index)?
+        - mul_decimal(staker_info.reward_weight, staker_info.penalty_index)?)?;
 
     staker_info.reward_index = state.global_reward_index;
     staker_info.pending_reward += pending_reward;
+    staker_info.penalty_index = state.global_penalty_index;
+    staker_info.pending_penalty += pending_penalty;
     Ok(())
 }
 
@@ -271,6 +963,13 @@ pub fn query<S: Storage, A: Api, Q: Querier>(
             staker,
             block_height,
         } => to_binary(&query_staker_info(deps, staker, block_height)?),
+        QueryMsg::PoolState { block_height } => to_binary(&query_pool_state(deps, block_height)?),
+        QueryMsg::Unfunded {} => to_binary(&query_unfunded(deps)?),
+        QueryMsg::RemainingCapacity {} => to_binary(&query_remaining_capacity(deps)?),
+        QueryMsg::ScheduleChanges { block_height } => {
+            to_binary(&query_schedule_changes(deps, block_height)?)
+        }
+        QueryMsg::UnderlyingAssets { staker } => to_binary(&query_underlying_assets(deps, staker)?),
     }
 }
 
@@ -279,9 +978,22 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<ConfigResponse> {
     let state = read_config(&deps.storage)?;
     let resp = ConfigResponse {
+        owner: deps.api.human_address(&state.owner)?,
         anchor_token: deps.api.human_address(&state.anchor_token)?,
         staking_token: deps.api.human_address(&state.staking_token)?,
+        pair_contract: deps.api.human_address(&state.pair_contract)?,
         distribution_schedule: state.distribution_schedule,
+        hook_contract: match state.hook_contract {
+            Some(hook_contract) => Some(deps.api.human_address(&hook_contract)?),
+            None => None,
+        },
+        bond_cap: state.bond_cap,
+        lockup_tiers: state.lockup_tiers,
+        gov_contract: match state.gov_contract {
+            Some(gov_contract) => Some(deps.api.human_address(&gov_contract)?),
+            None => None,
+        },
+        schedule_delay: state.schedule_delay,
     };
 
     Ok(resp)
@@ -294,16 +1006,133 @@ pub fn query_state<S: Storage, A: Api, Q: Querier>(
     let mut state: State = read_state(&deps.storage)?;
     if let Some(block_height) = block_height {
         let config = read_config(&deps.storage)?;
-        compute_reward(&config, &mut state, block_height);
+        compute_reward(&config, &mut state, block_height)?;
     }
 
     Ok(StateResponse {
         last_distributed: state.last_distributed,
         total_bond_amount: state.total_bond_amount,
         global_reward_index: state.global_reward_index,
+        distributed_reward: state.distributed_reward,
+    })
+}
+
+pub fn query_pool_state<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    block_height: Option<u64>,
+) -> StdResult<PoolStateResponse> {
+    let config = read_config(&deps.storage)?;
+    let mut state: State = read_state(&deps.storage)?;
+    if let Some(block_height) = block_height {
+        compute_reward(&config, &mut state, block_height)?;
+    }
+
+    let current_schedule = config
+        .distribution_schedule
+        .iter()
+        .find(|s| s.0 <= state.last_distributed && state.last_distributed < s.1)
+        .cloned();
+
+    Ok(PoolStateResponse {
+        staking_token: deps.api.human_address(&config.staking_token)?,
+        total_bond_amount: state.total_bond_amount,
+        current_schedule,
+        global_reward_index: state.global_reward_index,
+        last_distributed: state.last_distributed,
+    })
+}
+
+pub fn query_unfunded<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<UnfundedResponse> {
+    let config = read_config(&deps.storage)?;
+    let state = read_state(&deps.storage)?;
+    compute_unfunded(deps, &config, &state)
+}
+
+pub fn query_remaining_capacity<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<RemainingCapacityResponse> {
+    let config = read_config(&deps.storage)?;
+    let state = read_state(&deps.storage)?;
+
+    Ok(RemainingCapacityResponse {
+        bond_cap: config.bond_cap,
+        total_bond_amount: state.total_bond_amount,
+        remaining_capacity: config
+            .bond_cap
+            .map(|bond_cap| remaining_capacity(bond_cap, state.total_bond_amount)),
+    })
+}
+
+pub fn query_schedule_changes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    block_height: Option<u64>,
+) -> StdResult<ScheduleChangesResponse> {
+    let changes = read_schedule_changes(&deps.storage)?
+        .into_iter()
+        .filter(|change| change.schedule.0 > block_height.unwrap_or(0))
+        .collect();
+
+    Ok(ScheduleChangesResponse { changes })
+}
+
+pub fn query_underlying_assets<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    staker: HumanAddr,
+) -> StdResult<UnderlyingAssetsResponse> {
+    let config = read_config(&deps.storage)?;
+    let state = read_state(&deps.storage)?;
+    let staker_info = read_staker_info(&deps.storage, &deps.api.canonical_address(&staker)?)?;
+
+    let pool: PoolResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: deps.api.human_address(&config.pair_contract)?,
+        msg: to_binary(&TerraswapQueryMsg::Pool {})?,
+    }))?;
+    let anchor_token = deps.api.human_address(&config.anchor_token)?;
+
+    let (anc_amount, uusd_amount) =
+        underlying_amounts(&pool, &anchor_token, staker_info.bond_amount);
+    let (total_anc_amount, total_uusd_amount) =
+        underlying_amounts(&pool, &anchor_token, state.total_bond_amount);
+
+    Ok(UnderlyingAssetsResponse {
+        staker,
+        bond_amount: staker_info.bond_amount,
+        anc_amount,
+        uusd_amount,
+        total_bond_amount: state.total_bond_amount,
+        total_anc_amount,
+        total_uusd_amount,
     })
 }
 
+// ANC and UST reserves of the pair backing the LP token amount
+fn underlying_amounts(
+    pool: &PoolResponse,
+    anchor_token: &HumanAddr,
+    lp_amount: Uint128,
+) -> (Uint128, Uint128) {
+    let mut anc_amount = Uint128::zero();
+    let mut uusd_amount = Uint128::zero();
+    if pool.total_share.is_zero() {
+        return (anc_amount, uusd_amount);
+    }
+
+    for asset in pool.assets.iter() {
+        let amount = asset.amount.multiply_ratio(lp_amount, pool.total_share);
+        match &asset.info {
+            AssetInfo::Token { contract_addr } if contract_addr == anchor_token => {
+                anc_amount = amount
+            }
+            AssetInfo::NativeToken { denom } if denom == "uusd" => uusd_amount = amount,
+            _ => {}
+        }
+    }
+
+    (anc_amount, uusd_amount)
+}
+
 pub fn query_staker_info<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     staker: HumanAddr,
@@ -316,7 +1145,7 @@ pub fn query_staker_info<S: Storage, A: Api, Q: Querier>(
         let config = read_config(&deps.storage)?;
         let mut state = read_state(&deps.storage)?;
 
-        compute_reward(&config, &mut state, block_height);
+        compute_reward(&config, &mut state, block_height)?;
         compute_staker_reward(&state, &mut staker_info)?;
     }
 
@@ -325,13 +1154,59 @@ pub fn query_staker_info<S: Storage, A: Api, Q: Querier>(
         reward_index: staker_info.reward_index,
         bond_amount: staker_info.bond_amount,
         pending_reward: staker_info.pending_reward,
+        reward_weight: staker_info.reward_weight,
+        pending_penalty: staker_info.pending_penalty,
+        lockups: staker_info.lockups,
+        claimed_reward: read_claimed_reward(&deps.storage, &staker_raw)?,
     })
 }
 
 pub fn migrate<S: Storage, A: Api, Q: Querier>(
-    _deps: &mut Extern<S, A, Q>,
-    _env: Env,
-    _msg: MigrateMsg,
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: MigrateMsg,
 ) -> MigrateResult {
+    let mut config: Config = read_config(&deps.storage)?;
+    let mut state: State = read_state(&deps.storage)?;
+
+    // contracts stored before the owner have not recorded
+    // the paid rewards either, both must be given
+    if config.owner.is_empty() {
+        let (owner, paid_reward) = match (msg.owner, msg.paid_reward) {
+            (Some(owner), Some(paid_reward)) => (owner, paid_reward),
+            _ => {
+                return Err(StdError::generic_err(
+                    "Owner and paid reward must be given to migrate this contract",
+                ))
+            }
+        };
+
+        config.owner = deps.api.canonical_address(&owner)?;
+        state.paid_reward = paid_reward;
+    }
+
+    if config.pair_contract.is_empty() {
+        let pair_contract = match msg.pair_contract {
+            Some(pair_contract) => pair_contract,
+            None => {
+                return Err(StdError::generic_err(
+                    "Pair contract must be given to migrate this contract",
+                ))
+            }
+        };
+
+        config.pair_contract = deps.api.canonical_address(&pair_contract)?;
+    }
+
+    store_config(&mut deps.storage, &config)?;
+
+    // states stored before the lockups weigh the bonds by their amount
+    if state.total_reward_weight.is_zero() {
+        state.total_reward_weight = state.total_bond_amount;
+    }
+
+    state.contract_addr = deps.api.canonical_address(&env.contract.address)?;
+    store_state(&mut deps.storage, &state)?;
+
     Ok(MigrateResponse::default())
 }
@@ -4,5 +4,8 @@ pub mod state;
 #[cfg(test)]
 mod testing;
 
+#[cfg(test)]
+mod mock_querier;
+
 #[cfg(target_arch = "wasm32")]
 cosmwasm_std::create_entry_points_with_migration!(contract);
@@ -1,19 +1,36 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use anchor_token::staking::{LockupPosition, LockupTier, ScheduleChange};
 use cosmwasm_std::{CanonicalAddr, Decimal, ReadonlyStorage, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_STATE: &[u8] = b"state";
+static KEY_SCHEDULE_CHANGES: &[u8] = b"schedule_changes";
 
 static PREFIX_REWARD: &[u8] = b"reward";
+static PREFIX_CLAIMED_REWARD: &[u8] = b"claimed_reward";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    // missing from the configs stored before it, set by migrate
+    #[serde(default)]
+    pub owner: CanonicalAddr,
     pub anchor_token: CanonicalAddr,
     pub staking_token: CanonicalAddr,
+    // missing from the configs stored before it, set by migrate
+    #[serde(default)]
+    pub pair_contract: CanonicalAddr,
     pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub hook_contract: Option<CanonicalAddr>,
+    pub bond_cap: Option<Uint128>,
+    #[serde(default)]
+    pub lockup_tiers: Vec<LockupTier>,
+    pub gov_contract: Option<CanonicalAddr>,
+    // no delay for the configs stored before it
+    #[serde(default)]
+    pub schedule_delay: u64,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
@@ -26,9 +43,25 @@ pub fn read_config<S: Storage>(storage: &S) -> StdResult<Config> {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    // missing from the states stored before them, set by migrate
+    #[serde(default)]
+    pub contract_addr: CanonicalAddr,
     pub last_distributed: u64,
     pub total_bond_amount: Uint128,
     pub global_reward_index: Decimal,
+    #[serde(default)]
+    pub paid_reward: Uint128,
+    // rewards are distributed by the weight of the bonds, not their amount;
+    // missing from the states stored before the lockups, set by migrate
+    #[serde(default)]
+    pub total_reward_weight: Uint128,
+    // staking tokens forfeited by early exits per reward weight
+    #[serde(default)]
+    pub global_penalty_index: Decimal,
+    // rewards credited to the stakers by the reward index,
+    // counted from the migration on the states stored before it
+    #[serde(default)]
+    pub distributed_reward: Uint128,
 }
 
 pub fn store_state<S: Storage>(storage: &mut S, state: &State) -> StdResult<()> {
@@ -39,11 +72,35 @@ pub fn read_state<S: Storage>(storage: &S) -> StdResult<State> {
     singleton_read(storage, KEY_STATE).load()
 }
 
+pub fn store_schedule_changes<S: Storage>(
+    storage: &mut S,
+    changes: &[ScheduleChange],
+) -> StdResult<()> {
+    singleton(storage, KEY_SCHEDULE_CHANGES).save(&changes.to_vec())
+}
+
+pub fn read_schedule_changes<S: Storage>(storage: &S) -> StdResult<Vec<ScheduleChange>> {
+    Ok(singleton_read(storage, KEY_SCHEDULE_CHANGES)
+        .may_load()?
+        .unwrap_or_default())
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StakerInfo {
     pub reward_index: Decimal,
     pub bond_amount: Uint128,
     pub pending_reward: Uint128,
+    // missing from the staker infos stored before the lockups,
+    // which are read as weighing their bond amount
+    #[serde(default)]
+    pub reward_weight: Uint128,
+    #[serde(default)]
+    pub penalty_index: Decimal,
+    #[serde(default)]
+    pub pending_penalty: Uint128,
+    // locked parts of the bond amount, the rest can be unbonded freely
+    #[serde(default)]
+    pub lockups: Vec<LockupPosition>,
 }
 
 /// returns return staker_info of the given owner
@@ -66,12 +123,44 @@ pub fn read_staker_info<S: ReadonlyStorage>(
     storage: &S,
     owner: &CanonicalAddr,
 ) -> StdResult<StakerInfo> {
-    match ReadonlyBucket::new(PREFIX_REWARD, storage).may_load(owner.as_slice())? {
-        Some(staker_info) => Ok(staker_info),
+    match ReadonlyBucket::<S, StakerInfo>::new(PREFIX_REWARD, storage).may_load(owner.as_slice())? {
+        // a bond always weighs at least its amount, only
+        // the staker infos stored before the lockups weigh nothing
+        Some(mut staker_info) => {
+            if staker_info.reward_weight.is_zero() {
+                staker_info.reward_weight = staker_info.bond_amount;
+            }
+
+            Ok(staker_info)
+        }
         None => Ok(StakerInfo {
             reward_index: Decimal::zero(),
             bond_amount: Uint128::zero(),
             pending_reward: Uint128::zero(),
+            reward_weight: Uint128::zero(),
+            penalty_index: Decimal::zero(),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
         }),
     }
 }
+
+/// Claimed rewards are kept apart from the staker info,
+/// which is removed once the staker unbonds everything
+pub fn store_claimed_reward<S: Storage>(
+    storage: &mut S,
+    owner: &CanonicalAddr,
+    amount: Uint128,
+) -> StdResult<()> {
+    Bucket::new(PREFIX_CLAIMED_REWARD, storage).save(owner.as_slice(), &amount)
+}
+
+/// Total rewards the owner has claimed
+pub fn read_claimed_reward<S: ReadonlyStorage>(
+    storage: &S,
+    owner: &CanonicalAddr,
+) -> StdResult<Uint128> {
+    Ok(ReadonlyBucket::new(PREFIX_CLAIMED_REWARD, storage)
+        .may_load(owner.as_slice())?
+        .unwrap_or_default())
+}
@@ -1,12 +1,22 @@
-use crate::contract::{handle, init, query};
+use crate::contract::{handle, init, migrate, query};
+use crate::mock_querier::mock_dependencies;
+use anchor_token::gov::Cw20HookMsg as GovCw20HookMsg;
 use anchor_token::staking::{
-    ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, QueryMsg, StakerInfoResponse, StateResponse,
+    BondHookMsg, ConfigResponse, Cw20HookMsg, HandleMsg, InitMsg, LockupPosition, LockupTier,
+    MigrateMsg, PoolStateResponse, QueryMsg, RemainingCapacityResponse, ScheduleChange,
+    ScheduleChangesResponse, StakerInfoResponse, StateResponse, UnderlyingAssetsResponse,
+    UnfundedResponse,
 };
-use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, to_binary, CosmosMsg, Decimal, HumanAddr, StdError, Uint128, WasmMsg,
+    from_binary, log, to_binary, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, HumanAddr, StdError,
+    Uint128, WasmMsg,
 };
+use cosmwasm_storage::{bucket, singleton};
 use cw20::{Cw20HandleMsg, Cw20ReceiveMsg};
+use serde::{Deserialize, Serialize};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::{HandleMsg as TerraswapHandleMsg, PoolResponse};
 
 #[test]
 fn proper_initialization() {
@@ -15,7 +25,14 @@ fn proper_initialization() {
     let msg = InitMsg {
         anchor_token: HumanAddr("reward0000".to_string()),
         staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
         distribution_schedule: vec![(100, 200, Uint128::from(1000000u128))],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -31,7 +48,14 @@ fn proper_initialization() {
         ConfigResponse {
             anchor_token: HumanAddr::from("reward0000"),
             staking_token: HumanAddr::from("staking0000"),
+            pair_contract: HumanAddr::from("pair0000"),
             distribution_schedule: vec![(100, 200, Uint128::from(1000000u128))],
+            owner: HumanAddr::from("owner0000"),
+            hook_contract: None,
+            bond_cap: None,
+            lockup_tiers: vec![],
+            gov_contract: None,
+            schedule_delay: 0,
         }
     );
 
@@ -43,6 +67,7 @@ fn proper_initialization() {
             last_distributed: 12345,
             total_bond_amount: Uint128::zero(),
             global_reward_index: Decimal::zero(),
+            distributed_reward: Uint128::zero(),
         }
     );
 }
@@ -54,10 +79,17 @@ fn test_bond_tokens() {
     let msg = InitMsg {
         anchor_token: HumanAddr("reward0000".to_string()),
         staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
         distribution_schedule: vec![
             (12345, 12345 + 100, Uint128::from(1000000u128)),
             (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
         ],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -66,7 +98,7 @@ fn test_bond_tokens() {
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from("addr0000"),
         amount: Uint128(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::Bond {}).unwrap()),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
     });
 
     let mut env = mock_env("staking0000", &[]);
@@ -89,6 +121,10 @@ fn test_bond_tokens() {
             reward_index: Decimal::zero(),
             pending_reward: Uint128::zero(),
             bond_amount: Uint128(100u128),
+            reward_weight: Uint128(100u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
         }
     );
 
@@ -101,6 +137,7 @@ fn test_bond_tokens() {
             total_bond_amount: Uint128(100u128),
             global_reward_index: Decimal::zero(),
             last_distributed: 12345,
+            distributed_reward: Uint128::zero(),
         }
     );
 
@@ -108,7 +145,7 @@ fn test_bond_tokens() {
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from("addr0000"),
         amount: Uint128(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::Bond {}).unwrap()),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
     });
     env.block.height += 10;
 
@@ -131,6 +168,10 @@ fn test_bond_tokens() {
             reward_index: Decimal::from_ratio(1000u128, 1u128),
             pending_reward: Uint128::from(100000u128),
             bond_amount: Uint128(200u128),
+            reward_weight: Uint128(200u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
         }
     );
 
@@ -143,6 +184,7 @@ fn test_bond_tokens() {
             total_bond_amount: Uint128(200u128),
             global_reward_index: Decimal::from_ratio(1000u128, 1u128),
             last_distributed: 12345 + 10,
+            distributed_reward: Uint128(100000u128),
         }
     );
 
@@ -150,7 +192,7 @@ fn test_bond_tokens() {
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from("addr0000"),
         amount: Uint128(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::Bond {}).unwrap()),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
     });
 
     let env = mock_env("staking0001", &[]);
@@ -162,185 +204,576 @@ fn test_bond_tokens() {
 }
 
 #[test]
-fn test_unbond() {
+fn test_bond_hook() {
     let mut deps = mock_dependencies(20, &[]);
 
     let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
         anchor_token: HumanAddr("reward0000".to_string()),
         staking_token: HumanAddr("staking0000".to_string()),
-        distribution_schedule: vec![
-            (12345, 12345 + 100, Uint128::from(1000000u128)),
-            (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
-        ],
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        hook_contract: Some(HumanAddr::from("hook0000")),
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
     };
 
     let env = mock_env("addr0000", &[]);
     let _res = init(&mut deps, env, msg).unwrap();
 
-    // bond 100 tokens
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from("addr0000"),
         amount: Uint128(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::Bond {}).unwrap()),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
     });
     let env = mock_env("staking0000", &[]);
-    let _res = handle(&mut deps, env, msg).unwrap();
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("hook0000"),
+            msg: to_binary(&BondHookMsg::BondChanged {
+                staker: HumanAddr::from("addr0000"),
+                new_amount: Uint128(100u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
 
-    // unbond 150 tokens; failed
     let msg = HandleMsg::Unbond {
-        amount: Uint128(150u128),
+        amount: Uint128(40u128),
+    };
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("staking0000"),
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("addr0000"),
+                    amount: Uint128(40u128),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("hook0000"),
+                msg: to_binary(&BondHookMsg::BondChanged {
+                    staker: HumanAddr::from("addr0000"),
+                    new_amount: Uint128(60u128),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_bond_cap() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        hook_contract: None,
+        bond_cap: Some(Uint128(100u128)),
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
     };
 
     let env = mock_env("addr0000", &[]);
-    let res = handle(&mut deps, env, msg).unwrap_err();
-    match res {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "Cannot unbond more than bond amount");
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(60u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let env = mock_env("staking0000", &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0001"),
+        amount: Uint128(50u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let env = mock_env("staking0000", &[]);
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Bond cap exceeded, only 40 more tokens can be bonded")
         }
-        _ => panic!("Must return generic error"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    assert_eq!(
+        from_binary::<RemainingCapacityResponse>(
+            &query(&deps, QueryMsg::RemainingCapacity {}).unwrap()
+        )
+        .unwrap(),
+        RemainingCapacityResponse {
+            bond_cap: Some(Uint128(100u128)),
+            total_bond_amount: Uint128(60u128),
+            remaining_capacity: Some(Uint128(40u128)),
+        }
+    );
+
+    // only the owner can lift the cap
+    let update_msg = HandleMsg::UpdateBondCap { bond_cap: None };
+    match handle(&mut deps, mock_env("addr0000", &[]), update_msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, mock_env("owner0000", &[]), update_msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "update_bond_cap"), log("bond_cap", "none")]
+    );
+
+    let _res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        from_binary::<RemainingCapacityResponse>(
+            &query(&deps, QueryMsg::RemainingCapacity {}).unwrap()
+        )
+        .unwrap(),
+        RemainingCapacityResponse {
+            bond_cap: None,
+            total_bond_amount: Uint128(110u128),
+            remaining_capacity: None,
+        }
+    );
+}
+
+#[test]
+fn test_bond_lockup() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![LockupTier {
+            days: 30,
+            reward_multiplier: Decimal::percent(200),
+            early_exit_penalty: Decimal::percent(10),
+        }],
+        gov_contract: None,
+        schedule_delay: 0,
     };
 
-    // normal unbond
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    // only the configured lockups can be selected
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0001"),
+        amount: Uint128(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::Bond {
+                lockup_days: Some(60),
+            })
+            .unwrap(),
+        ),
+    });
+    let mut env = mock_env("staking0000", &[]);
+    match handle(&mut deps, env.clone(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Lockup of 60 days is not available")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0001"),
+        amount: Uint128(100u128),
+        msg: Some(
+            to_binary(&Cw20HookMsg::Bond {
+                lockup_days: Some(30),
+            })
+            .unwrap(),
+        ),
+    });
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "bond"),
+            log("owner", "addr0001"),
+            log("amount", "100"),
+            log("lockup_days", "30"),
+        ]
+    );
+
+    // the locked bond earns twice the rewards
+    env.block.height += 10;
+    assert_eq!(
+        from_binary::<StakerInfoResponse>(
+            &query(
+                &deps,
+                QueryMsg::StakerInfo {
+                    staker: HumanAddr::from("addr0001"),
+                    block_height: Some(env.block.height),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        StakerInfoResponse {
+            staker: HumanAddr::from("addr0001"),
+            reward_index: Decimal::from_ratio(1000u128, 3u128),
+            pending_reward: Uint128(66666u128),
+            bond_amount: Uint128(100u128),
+            reward_weight: Uint128(200u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![LockupPosition {
+                amount: Uint128(100u128),
+                unlock_time: env.block.time + 30 * 86400,
+                reward_multiplier: Decimal::percent(200),
+                early_exit_penalty: Decimal::percent(10),
+            }],
+            claimed_reward: Uint128::zero(),
+        }
+    );
+
+    // unbonding before the unlock time forfeits the penalty
+    env.message.sender = HumanAddr::from("addr0001");
     let msg = HandleMsg::Unbond {
         amount: Uint128(100u128),
     };
-
-    let env = mock_env("addr0000", &[]);
-    let res = handle(&mut deps, env, msg).unwrap();
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
     assert_eq!(
         res.messages,
         vec![CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: HumanAddr::from("staking0000"),
             msg: to_binary(&Cw20HandleMsg::Transfer {
-                recipient: HumanAddr::from("addr0000"),
-                amount: Uint128(100u128),
+                recipient: HumanAddr::from("addr0001"),
+                amount: Uint128(90u128),
             })
             .unwrap(),
             send: vec![],
         })]
     );
+    assert_eq!(res.log[3], log("penalty", "10"));
+
+    // the penalty goes to the remaining stakers
+    env.message.sender = HumanAddr::from("addr0000");
+    let msg = HandleMsg::Withdraw { recipient: None };
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("reward0000"),
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("addr0000"),
+                    amount: Uint128(33333u128),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("staking0000"),
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("addr0000"),
+                    amount: Uint128(10u128),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+        ]
+    );
 }
 
 #[test]
-fn test_compute_reward() {
+fn test_release_lockups() {
     let mut deps = mock_dependencies(20, &[]);
 
     let msg = InitMsg {
         anchor_token: HumanAddr("reward0000".to_string()),
         staking_token: HumanAddr("staking0000".to_string()),
-        distribution_schedule: vec![
-            (12345, 12345 + 100, Uint128::from(1000000u128)),
-            (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
-        ],
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![LockupTier {
+            days: 30,
+            reward_multiplier: Decimal::percent(200),
+            early_exit_penalty: Decimal::percent(10),
+        }],
+        gov_contract: None,
+        schedule_delay: 0,
     };
 
     let env = mock_env("addr0000", &[]);
     let _res = init(&mut deps, env, msg).unwrap();
 
-    // bond 100 tokens
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from("addr0000"),
         amount: Uint128(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::Bond {}).unwrap()),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
     });
     let mut env = mock_env("staking0000", &[]);
     let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
-    // 100 blocks passed
-    // 1,000,000 rewards distributed
-    env.block.height += 100;
-
-    // bond 100 more tokens
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
-        sender: HumanAddr::from("addr0000"),
+        sender: HumanAddr::from("addr0001"),
         amount: Uint128(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::Bond {}).unwrap()),
+        msg: Some(
+            to_binary(&Cw20HookMsg::Bond {
+                lockup_days: Some(30),
+            })
+            .unwrap(),
+        ),
     });
     let _res = handle(&mut deps, env.clone(), msg).unwrap();
 
+    let msg = HandleMsg::ReleaseLockups {
+        staker: HumanAddr::from("addr0001"),
+    };
+    env.message.sender = HumanAddr::from("addr0002");
+    match handle(&mut deps, env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "No expired lockups to release")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // anyone releases the expired lockup of the idle staker
+    env.block.height += 10;
+    env.block.time += 30 * 86400;
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "release_lockups"),
+            log("staker", "addr0001"),
+            log("reward_weight", "100"),
+        ]
+    );
+
+    // the lockup weighs until it is released, the bond alone afterwards
+    env.block.height += 10;
     assert_eq!(
         from_binary::<StakerInfoResponse>(
             &query(
                 &deps,
                 QueryMsg::StakerInfo {
-                    staker: HumanAddr::from("addr0000"),
-                    block_height: None,
+                    staker: HumanAddr::from("addr0001"),
+                    block_height: Some(env.block.height),
                 },
             )
             .unwrap()
         )
         .unwrap(),
         StakerInfoResponse {
-            staker: HumanAddr::from("addr0000"),
-            reward_index: Decimal::from_ratio(10000u128, 1u128),
-            pending_reward: Uint128(1000000u128),
-            bond_amount: Uint128(200u128),
+            staker: HumanAddr::from("addr0001"),
+            reward_index: Decimal::from_ratio(5000u128, 6u128),
+            pending_reward: Uint128(116666u128),
+            bond_amount: Uint128(100u128),
+            reward_weight: Uint128(100u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
         }
     );
+}
 
-    // 100 blocks passed
-    // 1,000,000 rewards distributed
-    env.block.height += 10;
-    env.message.sender = HumanAddr::from("addr0000");
+#[test]
+fn test_query_pool_state() {
+    let mut deps = mock_dependencies(20, &[]);
 
-    // unbond
-    let msg = HandleMsg::Unbond {
-        amount: Uint128(100u128),
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![
+            (12345, 12345 + 100, Uint128::from(1000000u128)),
+            (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
+        ],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
     };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+
+    let env = mock_env("staking0000", &[]);
     let _res = handle(&mut deps, env, msg).unwrap();
+
     assert_eq!(
-        from_binary::<StakerInfoResponse>(
+        from_binary::<PoolStateResponse>(
+            &query(&deps, QueryMsg::PoolState { block_height: None }).unwrap()
+        )
+        .unwrap(),
+        PoolStateResponse {
+            staking_token: HumanAddr::from("staking0000"),
+            total_bond_amount: Uint128(100u128),
+            current_schedule: Some((12345, 12345 + 100, Uint128::from(1000000u128))),
+            global_reward_index: Decimal::zero(),
+            last_distributed: 12345,
+        }
+    );
+
+    assert_eq!(
+        from_binary::<PoolStateResponse>(
             &query(
                 &deps,
-                QueryMsg::StakerInfo {
-                    staker: HumanAddr::from("addr0000"),
-                    block_height: None,
-                },
+                QueryMsg::PoolState {
+                    block_height: Some(12345 + 110),
+                }
             )
             .unwrap()
         )
         .unwrap(),
-        StakerInfoResponse {
-            staker: HumanAddr::from("addr0000"),
-            reward_index: Decimal::from_ratio(15000u64, 1u64),
-            pending_reward: Uint128(2000000u128),
-            bond_amount: Uint128(100u128),
+        PoolStateResponse {
+            staking_token: HumanAddr::from("staking0000"),
+            total_bond_amount: Uint128(100u128),
+            current_schedule: Some((12345 + 100, 12345 + 200, Uint128::from(10000000u128))),
+            global_reward_index: Decimal::from_ratio(20000u128, 1u128),
+            last_distributed: 12345 + 110,
         }
     );
 
-    // query future block
+    // no distribution after the last schedule
     assert_eq!(
-        from_binary::<StakerInfoResponse>(
+        from_binary::<PoolStateResponse>(
             &query(
                 &deps,
-                QueryMsg::StakerInfo {
-                    staker: HumanAddr::from("addr0000"),
-                    block_height: Some(12345 + 120),
+                QueryMsg::PoolState {
+                    block_height: Some(12345 + 200),
+                }
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .current_schedule,
+        None
+    );
+}
+
+#[test]
+fn test_query_underlying_assets() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    for (staker, amount) in [("addr0000", 100u128), ("addr0001", 300u128)].iter() {
+        let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+            sender: HumanAddr::from(*staker),
+            amount: Uint128(*amount),
+            msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+        });
+        let env = mock_env("staking0000", &[]);
+        let _res = handle(&mut deps, env, msg).unwrap();
+    }
+
+    deps.querier.with_pool(
+        &HumanAddr::from("pair0000"),
+        PoolResponse {
+            assets: [
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: HumanAddr::from("reward0000"),
+                    },
+                    amount: Uint128(5000u128),
                 },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128(2000u128),
+                },
+            ],
+            total_share: Uint128(1000u128),
+        },
+    );
+
+    assert_eq!(
+        from_binary::<UnderlyingAssetsResponse>(
+            &query(
+                &deps,
+                QueryMsg::UnderlyingAssets {
+                    staker: HumanAddr::from("addr0000"),
+                }
             )
             .unwrap()
         )
         .unwrap(),
-        StakerInfoResponse {
+        UnderlyingAssetsResponse {
             staker: HumanAddr::from("addr0000"),
-            reward_index: Decimal::from_ratio(25000u64, 1u64),
-            pending_reward: Uint128(3000000u128),
             bond_amount: Uint128(100u128),
+            anc_amount: Uint128(500u128),
+            uusd_amount: Uint128(200u128),
+            total_bond_amount: Uint128(400u128),
+            total_anc_amount: Uint128(2000u128),
+            total_uusd_amount: Uint128(800u128),
         }
     );
 }
 
 #[test]
-fn test_withdraw() {
+fn test_unbond() {
     let mut deps = mock_dependencies(20, &[]);
 
     let msg = InitMsg {
         anchor_token: HumanAddr("reward0000".to_string()),
         staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
         distribution_schedule: vec![
             (12345, 12345 + 100, Uint128::from(1000000u128)),
             (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
         ],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
     };
 
     let env = mock_env("addr0000", &[]);
@@ -350,18 +783,278 @@ fn test_withdraw() {
     let msg = HandleMsg::Receive(Cw20ReceiveMsg {
         sender: HumanAddr::from("addr0000"),
         amount: Uint128(100u128),
-        msg: Some(to_binary(&Cw20HookMsg::Bond {}).unwrap()),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
     });
-    let mut env = mock_env("staking0000", &[]);
-    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+    let env = mock_env("staking0000", &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
 
-    // 100 blocks passed
-    // 1,000,000 rewards distributed
+    // unbond 150 tokens; failed
+    let msg = HandleMsg::Unbond {
+        amount: Uint128(150u128),
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, msg).unwrap_err();
+    match res {
+        StdError::GenericErr { msg, .. } => {
+            assert_eq!(msg, "Cannot unbond more than bond amount");
+        }
+        _ => panic!("Must return generic error"),
+    };
+
+    // normal unbond
+    let msg = HandleMsg::Unbond {
+        amount: Uint128(100u128),
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("staking0000"),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("addr0000"),
+                amount: Uint128(100u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+}
+
+#[test]
+fn test_compute_reward() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![
+            (12345, 12345 + 100, Uint128::from(1000000u128)),
+            (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
+        ],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    // bond 100 tokens
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let mut env = mock_env("staking0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    // 100 blocks passed
+    // 1,000,000 rewards distributed
     env.block.height += 100;
+
+    // bond 100 more tokens
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    assert_eq!(
+        from_binary::<StakerInfoResponse>(
+            &query(
+                &deps,
+                QueryMsg::StakerInfo {
+                    staker: HumanAddr::from("addr0000"),
+                    block_height: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        StakerInfoResponse {
+            staker: HumanAddr::from("addr0000"),
+            reward_index: Decimal::from_ratio(10000u128, 1u128),
+            pending_reward: Uint128(1000000u128),
+            bond_amount: Uint128(200u128),
+            reward_weight: Uint128(200u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
+        }
+    );
+
+    // 100 blocks passed
+    // 1,000,000 rewards distributed
+    env.block.height += 10;
     env.message.sender = HumanAddr::from("addr0000");
 
-    let msg = HandleMsg::Withdraw {};
-    let res = handle(&mut deps, env, msg).unwrap();
+    // unbond
+    let msg = HandleMsg::Unbond {
+        amount: Uint128(100u128),
+    };
+    let _res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        from_binary::<StakerInfoResponse>(
+            &query(
+                &deps,
+                QueryMsg::StakerInfo {
+                    staker: HumanAddr::from("addr0000"),
+                    block_height: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        StakerInfoResponse {
+            staker: HumanAddr::from("addr0000"),
+            reward_index: Decimal::from_ratio(15000u64, 1u64),
+            pending_reward: Uint128(2000000u128),
+            bond_amount: Uint128(100u128),
+            reward_weight: Uint128(100u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
+        }
+    );
+
+    // query future block
+    assert_eq!(
+        from_binary::<StakerInfoResponse>(
+            &query(
+                &deps,
+                QueryMsg::StakerInfo {
+                    staker: HumanAddr::from("addr0000"),
+                    block_height: Some(12345 + 120),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        StakerInfoResponse {
+            staker: HumanAddr::from("addr0000"),
+            reward_index: Decimal::from_ratio(25000u64, 1u64),
+            pending_reward: Uint128(3000000u128),
+            bond_amount: Uint128(100u128),
+            reward_weight: Uint128(100u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_compute_reward_schedule_boundaries() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![
+            (12345, 12345 + 7, Uint128::from(10u128)),
+            (12345 + 7, 12345 + 10, Uint128::from(10u128)),
+        ],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    // bond 1 token, so the rewards are not split between stakers
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(1u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let mut env = mock_env("staking0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+    env.message.sender = HumanAddr::from("addr0000");
+
+    // the claim from block 5 to block 8 straddles the schedule boundary,
+    // 2 blocks of the first schedule and 1 block of the second are paid
+    for (height, amount) in [(3u64, 4u128), (5, 3), (8, 6), (10, 7)].iter() {
+        env.block.height = 12345 + height;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            HandleMsg::Withdraw { recipient: None },
+        )
+        .unwrap();
+        assert_eq!(res.log[3], log("amount", amount));
+    }
+
+    // the claims add up to the scheduled amounts without rounding loss
+    let res = query(&deps, QueryMsg::State { block_height: None }).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.distributed_reward, Uint128(20u128));
+
+    // nothing more is distributed after the last schedule
+    let res = query(
+        &deps,
+        QueryMsg::StakerInfo {
+            staker: HumanAddr::from("addr0000"),
+            block_height: Some(12345 + 20),
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.pending_reward, Uint128::zero());
+    assert_eq!(staker_info.claimed_reward, Uint128(20u128));
+}
+
+#[test]
+fn test_withdraw() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![
+            (12345, 12345 + 100, Uint128::from(1000000u128)),
+            (12345 + 100, 12345 + 200, Uint128::from(10000000u128)),
+        ],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    // bond 100 tokens
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let mut env = mock_env("staking0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    // 100 blocks passed
+    // 1,000,000 rewards distributed
+    env.block.height += 100;
+    env.message.sender = HumanAddr::from("addr0000");
+
+    let msg = HandleMsg::Withdraw { recipient: None };
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
 
     assert_eq!(
         res.messages,
@@ -375,4 +1068,711 @@ fn test_withdraw() {
             send: vec![],
         })]
     );
+
+    // 100 blocks passed
+    // 10,000,000 rewards distributed to the given recipient
+    env.block.height += 100;
+
+    let msg = HandleMsg::Withdraw {
+        recipient: Some(HumanAddr::from("vault0000")),
+    };
+    let res = handle(&mut deps, env, msg).unwrap();
+
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("reward0000"),
+            msg: to_binary(&Cw20HandleMsg::Transfer {
+                recipient: HumanAddr::from("vault0000"),
+                amount: Uint128(10000000u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "withdraw"),
+            log("owner", "addr0000"),
+            log("recipient", "vault0000"),
+            log("amount", "10000000"),
+        ]
+    );
+
+    // the claimed rewards are kept after unbonding everything
+    let msg = HandleMsg::Unbond {
+        amount: Uint128(100u128),
+    };
+    let mut env = mock_env("addr0000", &[]);
+    env.block.height = 12345 + 200;
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let res = query(
+        &deps,
+        QueryMsg::StakerInfo {
+            staker: HumanAddr::from("addr0000"),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let staker_info: StakerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_info.bond_amount, Uint128::zero());
+    assert_eq!(staker_info.claimed_reward, Uint128(11000000u128));
+
+    let res = query(&deps, QueryMsg::State { block_height: None }).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.distributed_reward, Uint128(11000000u128));
+}
+
+#[test]
+fn test_withdraw_to_gov() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: Some(HumanAddr::from("gov0000")),
+        schedule_delay: 0,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    // bond 100 tokens
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let mut env = mock_env("staking0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    // nothing to stake yet
+    env.message.sender = HumanAddr::from("addr0000");
+    match handle(&mut deps, env.clone(), HandleMsg::WithdrawToGov {}) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No rewards to stake"),
+        _ => panic!("Must return error"),
+    }
+
+    // 100 blocks passed
+    // 1,000,000 rewards distributed
+    env.block.height += 100;
+    let res = handle(&mut deps, env, HandleMsg::WithdrawToGov {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("reward0000"),
+            msg: to_binary(&Cw20HandleMsg::Send {
+                contract: HumanAddr::from("gov0000"),
+                amount: Uint128(1000000u128),
+                msg: Some(
+                    to_binary(&GovCw20HookMsg::StakeVotingTokensFor {
+                        staker: HumanAddr::from("addr0000"),
+                    })
+                    .unwrap()
+                ),
+            })
+            .unwrap(),
+            send: vec![],
+        })]
+    );
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "withdraw_to_gov"),
+            log("owner", "addr0000"),
+            log("gov_contract", "gov0000"),
+            log("amount", "1000000"),
+        ]
+    );
+}
+
+#[test]
+fn test_add_distribution_schedule() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("reward0000"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(1500000u128))],
+    )]);
+
+    let msg = HandleMsg::AddDistributionSchedule {
+        schedule: (12345 + 100, 12345 + 200, Uint128::from(1000000u128)),
+    };
+    match handle(&mut deps, mock_env("addr0000", &[]), msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    match handle(&mut deps, mock_env("owner0000", &[]), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Distribution schedule is not funded, 500000 more reward tokens are required"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    let res = query(&deps, QueryMsg::Unfunded {}).unwrap();
+    assert_eq!(
+        from_binary::<UnfundedResponse>(&res).unwrap(),
+        UnfundedResponse {
+            total_reward: Uint128(1000000u128),
+            paid_reward: Uint128::zero(),
+            reward_balance: Uint128(1500000u128),
+            shortfall: Uint128::zero(),
+        }
+    );
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("reward0000"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(2000000u128))],
+    )]);
+
+    let res = handle(&mut deps, mock_env("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "add_distribution_schedule"),
+            log("start_height", 12445),
+            log("end_height", 12545),
+            log("amount", "1000000"),
+            log("announced_height", 12345),
+        ]
+    );
+
+    // bond 100 tokens and withdraw the first schedule
+    let msg = HandleMsg::Receive(Cw20ReceiveMsg {
+        sender: HumanAddr::from("addr0000"),
+        amount: Uint128(100u128),
+        msg: Some(to_binary(&Cw20HookMsg::Bond { lockup_days: None }).unwrap()),
+    });
+    let mut env = mock_env("staking0000", &[]);
+    let _res = handle(&mut deps, env.clone(), msg).unwrap();
+
+    env.block.height += 100;
+    env.message.sender = HumanAddr::from("addr0000");
+    let _res = handle(&mut deps, env, HandleMsg::Withdraw { recipient: None }).unwrap();
+
+    // the paid reward left the contract
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("reward0000"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(900000u128))],
+    )]);
+
+    let res = query(&deps, QueryMsg::Unfunded {}).unwrap();
+    assert_eq!(
+        from_binary::<UnfundedResponse>(&res).unwrap(),
+        UnfundedResponse {
+            total_reward: Uint128(2000000u128),
+            paid_reward: Uint128(1000000u128),
+            reward_balance: Uint128(900000u128),
+            shortfall: Uint128(100000u128),
+        }
+    );
+
+    // schedules cannot start in the past
+    let msg = HandleMsg::AddDistributionSchedule {
+        schedule: (12300, 12345 + 300, Uint128::zero()),
+    };
+    match handle(&mut deps, mock_env("owner0000", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Schedule must start in the future")
+        }
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn test_schedule_delay() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner0000"),
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 1000,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("reward0000"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(2000000u128))],
+    )]);
+
+    // a schedule starting within the delay is rejected
+    let msg = HandleMsg::AddDistributionSchedule {
+        schedule: (12345 + 999, 12345 + 1100, Uint128::from(1000000u128)),
+    };
+    match handle(&mut deps, mock_env("owner0000", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Schedule must start at least 1000 blocks after it is added"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = HandleMsg::AddDistributionSchedule {
+        schedule: (12345 + 1000, 12345 + 1100, Uint128::from(1000000u128)),
+    };
+    let _res = handle(&mut deps, mock_env("owner0000", &[]), msg).unwrap();
+
+    // the change is queryable until the schedule starts
+    let change = ScheduleChange {
+        schedule: (12345 + 1000, 12345 + 1100, Uint128::from(1000000u128)),
+        announced_height: 12345,
+    };
+    let res = query(
+        &deps,
+        QueryMsg::ScheduleChanges {
+            block_height: Some(12345 + 999),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<ScheduleChangesResponse>(&res).unwrap(),
+        ScheduleChangesResponse {
+            changes: vec![change.clone()],
+        }
+    );
+
+    let res = query(
+        &deps,
+        QueryMsg::ScheduleChanges {
+            block_height: Some(12345 + 1000),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<ScheduleChangesResponse>(&res).unwrap(),
+        ScheduleChangesResponse { changes: vec![] }
+    );
+
+    let res = query(&deps, QueryMsg::ScheduleChanges { block_height: None }).unwrap();
+    assert_eq!(
+        from_binary::<ScheduleChangesResponse>(&res).unwrap(),
+        ScheduleChangesResponse {
+            changes: vec![change],
+        }
+    );
+}
+
+#[test]
+fn test_zap_bond() {
+    let mut deps = mock_dependencies(20, &[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &HumanAddr::from("reward0000"),
+            &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+        (
+            &HumanAddr::from("staking0000"),
+            &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+    ]);
+
+    let msg = InitMsg {
+        anchor_token: HumanAddr("reward0000".to_string()),
+        staking_token: HumanAddr("staking0000".to_string()),
+        pair_contract: HumanAddr("pair0000".to_string()),
+        distribution_schedule: vec![(12345, 12345 + 100, Uint128::from(1000000u128))],
+        owner: HumanAddr::from("owner0000"),
+        hook_contract: None,
+        bond_cap: None,
+        lockup_tiers: vec![],
+        gov_contract: None,
+        schedule_delay: 0,
+    };
+
+    let env = mock_env("addr0000", &[]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::ZapBond {
+        anc_amount: None,
+        max_spread: Some(Decimal::percent(2)),
+        slippage_tolerance: Some(Decimal::percent(1)),
+    };
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Must provide uusd"),
+        _ => panic!("Must return generic error"),
+    };
+
+    // half of the uusd is swapped to ANC
+    let env = mock_env(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128(2000000u128),
+        }],
+    );
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("pair0000"),
+                msg: to_binary(&TerraswapHandleMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: Uint128(990099u128),
+                    },
+                    max_spread: Some(Decimal::percent(2)),
+                    belief_price: None,
+                    to: None,
+                })
+                .unwrap(),
+                send: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128(990099u128),
+                }],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                msg: to_binary(&HandleMsg::ZapBondProvide {
+                    staker: HumanAddr::from("addr0000"),
+                    prev_anc_balance: Uint128::zero(),
+                    uusd_amount: Uint128(990099u128),
+                    slippage_tolerance: Some(Decimal::percent(1)),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+        ]
+    );
+
+    // ANC is pulled from the sender instead of swapped
+    let msg = HandleMsg::ZapBond {
+        anc_amount: Some(Uint128(500u128)),
+        max_spread: None,
+        slippage_tolerance: None,
+    };
+    let env = mock_env(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128(1000000u128),
+        }],
+    );
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("reward0000"),
+            msg: to_binary(&Cw20HandleMsg::TransferFrom {
+                owner: HumanAddr::from("addr0000"),
+                recipient: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128(500u128),
+            })
+            .unwrap(),
+            send: vec![],
+        })
+    );
+
+    // 500 ANC received from the swap
+    deps.querier.with_token_balances(&[
+        (
+            &HumanAddr::from("reward0000"),
+            &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(500u128))],
+        ),
+        (
+            &HumanAddr::from("staking0000"),
+            &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+    ]);
+
+    let msg = HandleMsg::ZapBondProvide {
+        staker: HumanAddr::from("addr0000"),
+        prev_anc_balance: Uint128::zero(),
+        uusd_amount: Uint128(990099u128),
+        slippage_tolerance: Some(Decimal::percent(1)),
+    };
+    let env = mock_env("addr0000", &[]);
+    let res = handle(&mut deps, env, msg.clone());
+    match res {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("Must return unauthorized error"),
+    };
+
+    let env = mock_env(MOCK_CONTRACT_ADDR, &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("reward0000"),
+                msg: to_binary(&Cw20HandleMsg::IncreaseAllowance {
+                    spender: HumanAddr::from("pair0000"),
+                    amount: Uint128(500u128),
+                    expires: None,
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("pair0000"),
+                msg: to_binary(&TerraswapHandleMsg::ProvideLiquidity {
+                    assets: [
+                        Asset {
+                            info: AssetInfo::Token {
+                                contract_addr: HumanAddr::from("reward0000"),
+                            },
+                            amount: Uint128(500u128),
+                        },
+                        Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: "uusd".to_string(),
+                            },
+                            amount: Uint128(990099u128),
+                        },
+                    ],
+                    slippage_tolerance: Some(Decimal::percent(1)),
+                })
+                .unwrap(),
+                send: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128(990099u128),
+                }],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from(MOCK_CONTRACT_ADDR),
+                msg: to_binary(&HandleMsg::ZapBondHook {
+                    staker: HumanAddr::from("addr0000"),
+                    prev_staking_token_amount: Uint128::zero(),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+        ]
+    );
+
+    // 1000 LP token minted
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("staking0000"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(1000u128))],
+    )]);
+
+    let msg = HandleMsg::ZapBondHook {
+        staker: HumanAddr::from("addr0000"),
+        prev_staking_token_amount: Uint128::zero(),
+    };
+    let env = mock_env(MOCK_CONTRACT_ADDR, &[]);
+    let res = handle(&mut deps, env, msg).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "bond"),
+            log("owner", "addr0000"),
+            log("amount", "1000"),
+        ]
+    );
+
+    assert_eq!(
+        from_binary::<StakerInfoResponse>(
+            &query(
+                &deps,
+                QueryMsg::StakerInfo {
+                    staker: HumanAddr::from("addr0000"),
+                    block_height: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        StakerInfoResponse {
+            staker: HumanAddr::from("addr0000"),
+            reward_index: Decimal::zero(),
+            pending_reward: Uint128::zero(),
+            bond_amount: Uint128(1000u128),
+            reward_weight: Uint128(1000u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_migrate() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    // config, state and staker info as stored before the migration
+    #[derive(Serialize, Deserialize)]
+    struct LegacyConfig {
+        anchor_token: CanonicalAddr,
+        staking_token: CanonicalAddr,
+        distribution_schedule: Vec<(u64, u64, Uint128)>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LegacyState {
+        last_distributed: u64,
+        total_bond_amount: Uint128,
+        global_reward_index: Decimal,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LegacyStakerInfo {
+        reward_index: Decimal,
+        bond_amount: Uint128,
+        pending_reward: Uint128,
+    }
+
+    singleton(&mut deps.storage, b"config")
+        .save(&LegacyConfig {
+            anchor_token: deps
+                .api
+                .canonical_address(&HumanAddr::from("reward0000"))
+                .unwrap(),
+            staking_token: deps
+                .api
+                .canonical_address(&HumanAddr::from("staking0000"))
+                .unwrap(),
+            distribution_schedule: vec![(100, 200, Uint128::from(1000000u128))],
+        })
+        .unwrap();
+    singleton(&mut deps.storage, b"state")
+        .save(&LegacyState {
+            last_distributed: 150,
+            total_bond_amount: Uint128(100u128),
+            global_reward_index: Decimal::from_ratio(5000u128, 1u128),
+        })
+        .unwrap();
+    let staker_raw = deps
+        .api
+        .canonical_address(&HumanAddr::from("addr0000"))
+        .unwrap();
+    bucket(b"reward", &mut deps.storage)
+        .save(
+            staker_raw.as_slice(),
+            &LegacyStakerInfo {
+                reward_index: Decimal::from_ratio(5000u128, 1u128),
+                bond_amount: Uint128(100u128),
+                pending_reward: Uint128::zero(),
+            },
+        )
+        .unwrap();
+
+    let msg = MigrateMsg {
+        owner: None,
+        pair_contract: None,
+        paid_reward: None,
+    };
+    match migrate(&mut deps, mock_env("addr0000", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Owner and paid reward must be given to migrate this contract"
+        ),
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = MigrateMsg {
+        owner: Some(HumanAddr::from("owner0000")),
+        pair_contract: None,
+        paid_reward: Some(Uint128(200000u128)),
+    };
+    match migrate(&mut deps, mock_env("addr0000", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Pair contract must be given to migrate this contract")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = MigrateMsg {
+        owner: Some(HumanAddr::from("owner0000")),
+        pair_contract: Some(HumanAddr::from("pair0000")),
+        paid_reward: Some(Uint128(200000u128)),
+    };
+    let _res = migrate(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
+
+    let res = query(&deps, QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, HumanAddr::from("owner0000"));
+    assert_eq!(config.pair_contract, HumanAddr::from("pair0000"));
+
+    deps.querier.with_token_balances(&[(
+        &HumanAddr::from("reward0000"),
+        &[(&HumanAddr::from(MOCK_CONTRACT_ADDR), &Uint128(700000u128))],
+    )]);
+
+    let res = query(&deps, QueryMsg::Unfunded {}).unwrap();
+    assert_eq!(
+        from_binary::<UnfundedResponse>(&res).unwrap(),
+        UnfundedResponse {
+            total_reward: Uint128(1000000u128),
+            paid_reward: Uint128(200000u128),
+            reward_balance: Uint128(700000u128),
+            shortfall: Uint128(100000u128),
+        }
+    );
+
+    // the bond stored before the lockups weighs its amount
+    assert_eq!(
+        from_binary::<StakerInfoResponse>(
+            &query(
+                &deps,
+                QueryMsg::StakerInfo {
+                    staker: HumanAddr::from("addr0000"),
+                    block_height: Some(160),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        StakerInfoResponse {
+            staker: HumanAddr::from("addr0000"),
+            reward_index: Decimal::from_ratio(6000u128, 1u128),
+            pending_reward: Uint128(100000u128),
+            bond_amount: Uint128(100u128),
+            reward_weight: Uint128(100u128),
+            pending_penalty: Uint128::zero(),
+            lockups: vec![],
+            claimed_reward: Uint128::zero(),
+        }
+    );
+
+    // later migrations need no values
+    let msg = MigrateMsg {
+        owner: None,
+        pair_contract: None,
+        paid_reward: None,
+    };
+    let _res = migrate(&mut deps, mock_env("addr0000", &[]), msg).unwrap();
 }
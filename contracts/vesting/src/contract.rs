@@ -4,12 +4,15 @@ use cosmwasm_std::{
 };
 
 use crate::state::{
-    read_config, read_vesting_info, read_vesting_infos, store_config, store_vesting_info, Config,
+    read_claim_history, read_config, read_vesting_info, read_vesting_infos, store_claim_record,
+    store_config, store_vesting_info, Config, DEFAULT_LIMIT, MAX_LIMIT,
 };
-use anchor_token::common::OrderBy;
+use anchor_token::common::{Expiration, OrderBy};
+use anchor_token::gov::{Cw20HookMsg as GovCw20HookMsg, HandleMsg as GovHandleMsg};
 use anchor_token::vesting::{
-    ConfigResponse, HandleMsg, InitMsg, QueryMsg, VestingAccount, VestingAccountResponse,
-    VestingAccountsResponse, VestingInfo,
+    ClaimHistoryResponse, ClaimRecord, ConfigResponse, ExportVestingStateResponse, HandleMsg,
+    InitMsg, QueryMsg, VestingAccount, VestingAccountResponse, VestingAccountsResponse,
+    VestingInfo, VestingStateRecord,
 };
 use cw20::Cw20HandleMsg;
 
@@ -24,6 +27,12 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
             owner: deps.api.canonical_address(&msg.owner)?,
             anchor_token: deps.api.canonical_address(&msg.anchor_token)?,
             genesis_time: msg.genesis_time,
+            gov_contract: None,
+            guardian: match msg.guardian {
+                Some(guardian) => Some(deps.api.canonical_address(&guardian)?),
+                None => None,
+            },
+            claims_frozen: false,
         },
     )?;
 
@@ -37,6 +46,9 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
 ) -> StdResult<HandleResponse> {
     match msg.clone() {
         HandleMsg::Claim {} => claim(deps, env),
+        HandleMsg::StakeVested { amount } => stake_vested(deps, env, amount),
+        HandleMsg::FreezeClaims {} => set_claims_frozen(deps, env, true),
+        HandleMsg::UnfreezeClaims {} => set_claims_frozen(deps, env, false),
         _ => {
             assert_owner_privilege(deps, env.clone())?;
             match msg {
@@ -44,10 +56,22 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
                     owner,
                     anchor_token,
                     genesis_time,
-                } => update_config(deps, owner, anchor_token, genesis_time),
+                    gov_contract,
+                    guardian,
+                } => update_config(
+                    deps,
+                    owner,
+                    anchor_token,
+                    genesis_time,
+                    gov_contract,
+                    guardian,
+                ),
                 HandleMsg::RegisterVestingAccounts { vesting_accounts } => {
                     register_vesting_accounts(deps, vesting_accounts)
                 }
+                HandleMsg::ImportVestingState { vesting_accounts } => {
+                    import_vesting_state(deps, env, vesting_accounts)
+                }
                 _ => panic!("DO NOT ENTER HERE"),
             }
         }
@@ -70,6 +94,8 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     owner: Option<HumanAddr>,
     anchor_token: Option<HumanAddr>,
     genesis_time: Option<u64>,
+    gov_contract: Option<HumanAddr>,
+    guardian: Option<HumanAddr>,
 ) -> HandleResult {
     let mut config = read_config(&deps.storage)?;
     if let Some(owner) = owner {
@@ -84,6 +110,19 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
         config.genesis_time = genesis_time;
     }
 
+    if let Some(gov_contract) = gov_contract {
+        config.gov_contract = Some(deps.api.canonical_address(&gov_contract)?);
+    }
+
+    if let Some(guardian) = guardian {
+        // the owner cannot replace the guardian to lift a freeze
+        if config.claims_frozen {
+            return Err(StdError::generic_err("Claims are frozen"));
+        }
+
+        config.guardian = Some(deps.api.canonical_address(&guardian)?);
+    }
+
     store_config(&mut deps.storage, &config)?;
 
     Ok(HandleResponse {
@@ -93,6 +132,31 @@ pub fn update_config<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// FreezeClaims and UnfreezeClaims are the incident switch of the guardian
+/// for schedules which turn out to be wrong or registered by a compromised key
+pub fn set_claims_frozen<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    claims_frozen: bool,
+) -> HandleResult {
+    let mut config = read_config(&deps.storage)?;
+    if config.guardian != Some(deps.api.canonical_address(&env.message.sender)?) {
+        return Err(StdError::unauthorized());
+    }
+
+    config.claims_frozen = claims_frozen;
+    store_config(&mut deps.storage, &config)?;
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "set_claims_frozen"),
+            log("claims_frozen", claims_frozen),
+        ],
+        data: None,
+    })
+}
+
 fn assert_vesting_schedules(vesting_schedules: &Vec<(u64, u64, Uint128)>) -> StdResult<()> {
     for vesting_schedule in vesting_schedules.iter() {
         if vesting_schedule.0 >= vesting_schedule.1 {
@@ -120,6 +184,7 @@ pub fn register_vesting_accounts<S: Storage, A: Api, Q: Querier>(
             &VestingInfo {
                 last_claim_time: config.genesis_time,
                 schedules: vesting_account.schedules.clone(),
+                staked_amount: Uint128::zero(),
             },
         )?;
     }
@@ -131,53 +196,195 @@ pub fn register_vesting_accounts<S: Storage, A: Api, Q: Querier>(
     })
 }
 
+/// ImportVestingState restores the records of a previous deployment without
+/// recomputing them; it is closed once the vesting has started
+pub fn import_vesting_state<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    vesting_accounts: Vec<VestingStateRecord>,
+) -> HandleResult {
+    let config: Config = read_config(&deps.storage)?;
+    if Expiration::AtTime(config.genesis_time).is_expired(&env.block) {
+        return Err(StdError::generic_err(
+            "Vesting state can only be imported before the genesis time",
+        ));
+    }
+
+    for vesting_account in vesting_accounts.iter() {
+        assert_vesting_schedules(&vesting_account.info.schedules)?;
+
+        let vesting_address = deps.api.canonical_address(&vesting_account.address)?;
+        store_vesting_info(&mut deps.storage, &vesting_address, &vesting_account.info)?;
+    }
+
+    Ok(HandleResponse {
+        messages: vec![],
+        log: vec![
+            log("action", "import_vesting_state"),
+            log("imported", vesting_accounts.len()),
+        ],
+        data: None,
+    })
+}
+
 pub fn claim<S: Storage, A: Api, Q: Querier>(deps: &mut Extern<S, A, Q>, env: Env) -> HandleResult {
     let current_time = env.block.time;
     let address = env.message.sender;
     let address_raw = deps.api.canonical_address(&address)?;
 
     let config: Config = read_config(&deps.storage)?;
+    if config.claims_frozen {
+        return Err(StdError::generic_err("Claims are frozen"));
+    }
+
     let mut vesting_info: VestingInfo = read_vesting_info(&deps.storage, &address_raw)?;
 
     let claim_amount = compute_claim_amount(current_time, &vesting_info);
-    let messages: Vec<CosmosMsg> = if claim_amount.is_zero() {
-        vec![]
-    } else {
-        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+
+    // vested tokens which were staked in gov are released there
+    // instead of being transferred from this contract
+    let unlock_amount = std::cmp::min(claim_amount, vesting_info.staked_amount);
+    let transfer_amount = (claim_amount - unlock_amount)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !unlock_amount.is_zero() {
+        let gov_contract = match config.gov_contract {
+            Some(v) => v,
+            None => return Err(StdError::generic_err("Gov contract is not registered")),
+        };
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&gov_contract)?,
+            send: vec![],
+            msg: to_binary(&GovHandleMsg::UnlockVestingTokens {
+                staker: address.clone(),
+                amount: unlock_amount,
+            })?,
+        }));
+    }
+
+    if !transfer_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps.api.human_address(&config.anchor_token)?,
             send: vec![],
             msg: to_binary(&Cw20HandleMsg::Transfer {
                 recipient: address.clone(),
-                amount: claim_amount,
+                amount: transfer_amount,
             })?,
-        })]
-    };
+        }));
+    }
 
     vesting_info.last_claim_time = current_time;
+    vesting_info.staked_amount = (vesting_info.staked_amount - unlock_amount)?;
     store_vesting_info(&mut deps.storage, &address_raw, &vesting_info)?;
 
+    if !claim_amount.is_zero() {
+        store_claim_record(
+            &mut deps.storage,
+            &address_raw,
+            &ClaimRecord {
+                height: env.block.height,
+                time: current_time,
+                amount: claim_amount,
+            },
+        )?;
+    }
+
+    let mut logs = vec![
+        log("action", "claim"),
+        log("address", address),
+        log("claim_amount", claim_amount),
+        log("last_claim_time", current_time),
+    ];
+
+    if !unlock_amount.is_zero() {
+        logs.push(log("unlock_amount", unlock_amount));
+    }
+
     Ok(HandleResponse {
         messages,
+        log: logs,
+        data: None,
+    })
+}
+
+/// StakeVested
+/// Vesting account owner can stake the tokens which are not vested yet
+/// to the gov contract. The stake is locked in gov until it is vested
+/// and released by `claim`.
+pub fn stake_vested<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    amount: Uint128,
+) -> HandleResult {
+    let current_time = env.block.time;
+    let address = env.message.sender;
+    let address_raw = deps.api.canonical_address(&address)?;
+
+    let config: Config = read_config(&deps.storage)?;
+    let gov_contract = match config.gov_contract {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Gov contract is not registered")),
+    };
+
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Invalid zero amount"));
+    }
+
+    let mut vesting_info: VestingInfo = read_vesting_info(&deps.storage, &address_raw)?;
+    let locked_amount = compute_locked_amount(current_time, &vesting_info);
+    if (vesting_info.staked_amount + amount) > locked_amount {
+        return Err(StdError::generic_err(
+            "Cannot stake more than unvested amount",
+        ));
+    }
+
+    vesting_info.staked_amount += amount;
+    store_vesting_info(&mut deps.storage, &address_raw, &vesting_info)?;
+
+    Ok(HandleResponse {
+        messages: vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.human_address(&config.anchor_token)?,
+            send: vec![],
+            msg: to_binary(&Cw20HandleMsg::Send {
+                contract: deps.api.human_address(&gov_contract)?,
+                amount,
+                msg: Some(to_binary(&GovCw20HookMsg::StakeVestingTokens {
+                    staker: address.clone(),
+                })?),
+            })?,
+        })],
         log: vec![
-            log("action", "claim"),
+            log("action", "stake_vested"),
             log("address", address),
-            log("claim_amount", claim_amount),
-            log("last_claim_time", current_time),
+            log("amount", amount),
         ],
         data: None,
     })
 }
 
 fn compute_claim_amount(current_time: u64, vesting_info: &VestingInfo) -> Uint128 {
+    compute_release_amount(
+        vesting_info.last_claim_time,
+        current_time,
+        &vesting_info.schedules,
+    )
+}
+
+// compute the amount released between the given times
+fn compute_release_amount(
+    from_time: u64,
+    to_time: u64,
+    schedules: &[(u64, u64, Uint128)],
+) -> Uint128 {
     let mut claimable_amount: Uint128 = Uint128::zero();
-    for s in vesting_info.schedules.iter() {
-        if s.0 > current_time || s.1 < vesting_info.last_claim_time {
+    for s in schedules.iter() {
+        if s.0 > to_time || s.1 < from_time {
             continue;
         }
 
-        // min(s.1, current_time) - max(s.0, last_claim_time)
-        let passed_time =
-            std::cmp::min(s.1, current_time) - std::cmp::max(s.0, vesting_info.last_claim_time);
+        // min(s.1, to_time) - max(s.0, from_time)
+        let passed_time = std::cmp::min(s.1, to_time) - std::cmp::max(s.0, from_time);
 
         // prevent zero time_period case
         let time_period = s.1 - s.0;
@@ -189,24 +396,65 @@ fn compute_claim_amount(current_time: u64, vesting_info: &VestingInfo) -> Uint12
     return claimable_amount;
 }
 
+// compute the amount which is not vested yet at the given time
+fn compute_locked_amount(current_time: u64, vesting_info: &VestingInfo) -> Uint128 {
+    let mut locked_amount: Uint128 = Uint128::zero();
+    for s in vesting_info.schedules.iter() {
+        if s.1 <= current_time {
+            continue;
+        }
+
+        if s.0 >= current_time {
+            locked_amount += s.2;
+            continue;
+        }
+
+        let left_time = s.1 - current_time;
+        let time_period = s.1 - s.0;
+        locked_amount += s.2.multiply_ratio(left_time, time_period);
+    }
+
+    return locked_amount;
+}
+
 pub fn query<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     msg: QueryMsg,
 ) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
-        QueryMsg::VestingAccount { address } => {
-            Ok(to_binary(&query_vesting_account(deps, address)?)?)
-        }
+        QueryMsg::VestingAccount {
+            address,
+            block_time,
+        } => Ok(to_binary(&query_vesting_account(
+            deps, address, block_time,
+        )?)?),
         QueryMsg::VestingAccounts {
             start_after,
             limit,
             order_by,
+            only_active,
+            block_time,
         } => Ok(to_binary(&query_vesting_accounts(
             deps,
             start_after,
             limit,
             order_by,
+            only_active,
+            block_time,
+        )?)?),
+        QueryMsg::ExportVestingState { start_after, limit } => Ok(to_binary(
+            &query_export_vesting_state(deps, start_after, limit)?,
+        )?),
+        QueryMsg::ClaimHistory {
+            address,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_claim_history(
+            deps,
+            address,
+            start_after,
+            limit,
         )?)?),
     }
 }
@@ -219,6 +467,17 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
         owner: deps.api.human_address(&state.owner)?,
         anchor_token: deps.api.human_address(&state.anchor_token)?,
         genesis_time: state.genesis_time,
+        gov_contract: if let Some(gov_contract) = state.gov_contract {
+            Some(deps.api.human_address(&gov_contract)?)
+        } else {
+            None
+        },
+        guardian: if let Some(guardian) = state.guardian {
+            Some(deps.api.human_address(&guardian)?)
+        } else {
+            None
+        },
+        claims_frozen: state.claims_frozen,
     };
 
     Ok(resp)
@@ -227,11 +486,12 @@ pub fn query_config<S: Storage, A: Api, Q: Querier>(
 pub fn query_vesting_account<S: Storage, A: Api, Q: Querier>(
     deps: &Extern<S, A, Q>,
     address: HumanAddr,
+    block_time: Option<u64>,
 ) -> StdResult<VestingAccountResponse> {
+    let config: Config = read_config(&deps.storage)?;
     let info = read_vesting_info(&deps.storage, &deps.api.canonical_address(&address)?)?;
-    let resp = VestingAccountResponse { address, info };
 
-    Ok(resp)
+    Ok(vesting_account_response(&config, address, info, block_time))
 }
 
 pub fn query_vesting_accounts<S: Storage, A: Api, Q: Querier>(
@@ -239,31 +499,113 @@ pub fn query_vesting_accounts<S: Storage, A: Api, Q: Querier>(
     start_after: Option<HumanAddr>,
     limit: Option<u32>,
     order_by: Option<OrderBy>,
+    only_active: Option<bool>,
+    block_time: Option<u64>,
 ) -> StdResult<VestingAccountsResponse> {
-    let vesting_infos = if let Some(start_after) = start_after {
-        read_vesting_infos(
+    let config: Config = read_config(&deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let only_active = only_active.unwrap_or(false);
+
+    let mut start_after = if let Some(start_after) = start_after {
+        Some(deps.api.canonical_address(&start_after)?)
+    } else {
+        None
+    };
+
+    // inactive accounts are skipped, so keep reading
+    // until the page is filled or the accounts are exhausted
+    let mut vesting_accounts: Vec<VestingAccountResponse> = vec![];
+    while vesting_accounts.len() < limit {
+        let vesting_infos = read_vesting_infos(
             &deps.storage,
-            Some(deps.api.canonical_address(&start_after)?),
-            limit,
-            order_by,
-        )?
+            start_after,
+            Some(limit as u32),
+            order_by.clone(),
+        )?;
+
+        start_after = match vesting_infos.last() {
+            Some(vesting_info) => Some(vesting_info.0.clone()),
+            None => break,
+        };
+
+        for (address, info) in vesting_infos.into_iter() {
+            let vesting_account = vesting_account_response(
+                &config,
+                deps.api.human_address(&address)?,
+                info,
+                block_time,
+            );
+
+            if (!only_active || !vesting_account.remaining.is_zero())
+                && vesting_accounts.len() < limit
+            {
+                vesting_accounts.push(vesting_account);
+            }
+        }
+    }
+
+    Ok(VestingAccountsResponse { vesting_accounts })
+}
+
+pub fn query_export_vesting_state<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<ExportVestingStateResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.canonical_address(&start_after)?)
     } else {
-        read_vesting_infos(&deps.storage, None, limit, order_by)?
+        None
     };
 
-    let vesting_account_responses: StdResult<Vec<VestingAccountResponse>> = vesting_infos
-        .iter()
-        .map(|vesting_account| {
-            Ok(VestingAccountResponse {
-                address: deps.api.human_address(&vesting_account.0)?,
-                info: vesting_account.1.clone(),
+    let vesting_infos = read_vesting_infos(&deps.storage, start_after, limit, Some(OrderBy::Asc))?;
+    let vesting_accounts = vesting_infos
+        .into_iter()
+        .map(|(address, info)| {
+            Ok(VestingStateRecord {
+                address: deps.api.human_address(&address)?,
+                info,
             })
         })
-        .collect();
+        .collect::<StdResult<Vec<VestingStateRecord>>>()?;
 
-    Ok(VestingAccountsResponse {
-        vesting_accounts: vesting_account_responses?,
-    })
+    Ok(ExportVestingStateResponse { vesting_accounts })
+}
+
+pub fn query_claim_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    address: HumanAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ClaimHistoryResponse> {
+    let address_raw = deps.api.canonical_address(&address)?;
+    let claims = read_claim_history(&deps.storage, &address_raw, start_after, limit)?;
+
+    Ok(ClaimHistoryResponse { address, claims })
+}
+
+fn vesting_account_response(
+    config: &Config,
+    address: HumanAddr,
+    info: VestingInfo,
+    block_time: Option<u64>,
+) -> VestingAccountResponse {
+    let block_time = block_time.unwrap_or(info.last_claim_time);
+    let end_time = info.schedules.iter().map(|s| s.1).max().unwrap_or(0);
+
+    // tokens are released from the genesis time
+    let total = compute_release_amount(config.genesis_time, end_time, &info.schedules);
+    let vested = compute_release_amount(config.genesis_time, block_time, &info.schedules);
+    let claimed =
+        compute_release_amount(config.genesis_time, info.last_claim_time, &info.schedules);
+
+    VestingAccountResponse {
+        address,
+        vested,
+        claimed,
+        remaining: (total - claimed).unwrap_or_else(|_| Uint128::zero()),
+        info,
+    }
 }
 
 #[test]
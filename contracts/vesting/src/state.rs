@@ -2,18 +2,22 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use anchor_token::common::OrderBy;
-use anchor_token::vesting::VestingInfo;
+use anchor_token::vesting::{ClaimRecord, VestingInfo};
 use cosmwasm_std::{CanonicalAddr, ReadonlyStorage, StdResult, Storage};
-use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, ReadonlyBucket};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket};
 
 const KEY_CONFIG: &[u8] = b"config";
 const PREFIX_KEY_VESTING_INFO: &[u8] = b"vesting_info";
+const PREFIX_KEY_CLAIM_HISTORY: &[u8] = b"claim_history";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub anchor_token: CanonicalAddr,
     pub genesis_time: u64,
+    pub gov_contract: Option<CanonicalAddr>,
+    pub guardian: Option<CanonicalAddr>,
+    pub claims_frozen: bool,
 }
 
 pub fn store_config<S: Storage>(storage: &mut S, config: &Config) -> StdResult<()> {
@@ -40,8 +44,8 @@ pub fn store_vesting_info<S: Storage>(
         .save(address.as_slice(), vesting_info)?)
 }
 
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+pub const DEFAULT_LIMIT: u32 = 10;
 pub fn read_vesting_infos<'a, S: ReadonlyStorage>(
     storage: &'a S,
     start_after: Option<CanonicalAddr>,
@@ -67,6 +71,38 @@ pub fn read_vesting_infos<'a, S: ReadonlyStorage>(
         .collect()
 }
 
+/// Appends the claim to the history of the account; a claim with an amount
+/// always has a later time than the previous one, so the time is the key
+pub fn store_claim_record<S: Storage>(
+    storage: &mut S,
+    address: &CanonicalAddr,
+    claim: &ClaimRecord,
+) -> StdResult<()> {
+    Bucket::multilevel(&[PREFIX_KEY_CLAIM_HISTORY, address.as_slice()], storage)
+        .save(&claim.time.to_be_bytes(), claim)
+}
+
+pub fn read_claim_history<S: ReadonlyStorage>(
+    storage: &S,
+    address: &CanonicalAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ClaimRecord>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|time| (time + 1).to_be_bytes().to_vec());
+
+    let claims: ReadonlyBucket<S, ClaimRecord> =
+        ReadonlyBucket::multilevel(&[PREFIX_KEY_CLAIM_HISTORY, address.as_slice()], storage);
+    claims
+        .range(start.as_deref(), None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 fn calc_range_start_addr(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
     start_after.map(|addr| {
@@ -1,8 +1,10 @@
 use crate::contract::{handle, init, query};
 use anchor_token::common::OrderBy;
+use anchor_token::gov::{Cw20HookMsg as GovCw20HookMsg, HandleMsg as GovHandleMsg};
 use anchor_token::vesting::{
-    ConfigResponse, HandleMsg, InitMsg, QueryMsg, VestingAccount, VestingAccountResponse,
-    VestingAccountsResponse, VestingInfo,
+    ClaimHistoryResponse, ClaimRecord, ConfigResponse, ExportVestingStateResponse, HandleMsg,
+    InitMsg, QueryMsg, VestingAccount, VestingAccountResponse, VestingAccountsResponse,
+    VestingInfo, VestingStateRecord,
 };
 
 use cosmwasm_std::testing::{mock_dependencies, mock_env};
@@ -17,6 +19,7 @@ fn proper_initialization() {
         owner: HumanAddr::from("owner"),
         anchor_token: HumanAddr::from("anchor_token"),
         genesis_time: 12345u64,
+        guardian: None,
     };
 
     let env = mock_env("addr0000", &vec![]);
@@ -28,6 +31,9 @@ fn proper_initialization() {
             owner: HumanAddr::from("owner"),
             anchor_token: HumanAddr::from("anchor_token"),
             genesis_time: 12345u64,
+            gov_contract: None,
+            guardian: None,
+            claims_frozen: false,
         }
     );
 }
@@ -40,6 +46,7 @@ fn update_config() {
         owner: HumanAddr::from("owner"),
         anchor_token: HumanAddr::from("anchor_token"),
         genesis_time: 12345u64,
+        guardian: None,
     };
 
     let env = mock_env("addr0000", &vec![]);
@@ -49,6 +56,8 @@ fn update_config() {
         owner: Some(HumanAddr::from("owner2")),
         anchor_token: None,
         genesis_time: None,
+        gov_contract: None,
+        guardian: None,
     };
     let env = mock_env("owner", &vec![]);
     let _res = handle(&mut deps, env, msg).unwrap();
@@ -59,6 +68,9 @@ fn update_config() {
             owner: HumanAddr::from("owner2"),
             anchor_token: HumanAddr::from("anchor_token"),
             genesis_time: 12345u64,
+            gov_contract: None,
+            guardian: None,
+            claims_frozen: false,
         }
     );
 
@@ -66,6 +78,8 @@ fn update_config() {
         owner: Some(HumanAddr::from("owner")),
         anchor_token: None,
         genesis_time: None,
+        gov_contract: None,
+        guardian: None,
     };
     let env = mock_env("owner", &vec![]);
     let res = handle(&mut deps, env, msg);
@@ -78,6 +92,8 @@ fn update_config() {
         owner: None,
         anchor_token: Some(HumanAddr::from("anchor_token2")),
         genesis_time: Some(1u64),
+        gov_contract: None,
+        guardian: None,
     };
     let env = mock_env("owner2", &vec![]);
     let _res = handle(&mut deps, env, msg).unwrap();
@@ -88,6 +104,9 @@ fn update_config() {
             owner: HumanAddr::from("owner2"),
             anchor_token: HumanAddr::from("anchor_token2"),
             genesis_time: 1u64,
+            gov_contract: None,
+            guardian: None,
+            claims_frozen: false,
         }
     );
 }
@@ -100,6 +119,7 @@ fn register_vesting_accounts() {
         owner: HumanAddr::from("owner"),
         anchor_token: HumanAddr::from("anchor_token"),
         genesis_time: 100u64,
+        guardian: None,
     };
 
     let env = mock_env("addr0000", &vec![]);
@@ -140,6 +160,7 @@ fn register_vesting_accounts() {
                 &deps,
                 QueryMsg::VestingAccount {
                     address: HumanAddr::from("addr0000"),
+                    block_time: None,
                 }
             )
             .unwrap()
@@ -154,7 +175,11 @@ fn register_vesting_accounts() {
                     (100u64, 110u64, Uint128::from(100u128)),
                     (100u64, 200u64, Uint128::from(100u128)),
                 ],
-            }
+                staked_amount: Uint128::zero(),
+            },
+            vested: Uint128::zero(),
+            claimed: Uint128::zero(),
+            remaining: Uint128::from(300u128),
         }
     );
 
@@ -166,6 +191,8 @@ fn register_vesting_accounts() {
                     limit: None,
                     start_after: None,
                     order_by: Some(OrderBy::Asc),
+                    only_active: None,
+                    block_time: None,
                 }
             )
             .unwrap()
@@ -182,27 +209,146 @@ fn register_vesting_accounts() {
                             (100u64, 110u64, Uint128::from(100u128)),
                             (100u64, 200u64, Uint128::from(100u128)),
                         ],
-                    }
+                        staked_amount: Uint128::zero(),
+                    },
+                    vested: Uint128::zero(),
+                    claimed: Uint128::zero(),
+                    remaining: Uint128::from(300u128),
                 },
                 VestingAccountResponse {
                     address: HumanAddr::from("addr0001"),
                     info: VestingInfo {
                         last_claim_time: 100u64,
                         schedules: vec![(100u64, 110u64, Uint128::from(100u128))],
-                    }
+                        staked_amount: Uint128::zero(),
+                    },
+                    vested: Uint128::zero(),
+                    claimed: Uint128::zero(),
+                    remaining: Uint128::from(100u128),
                 },
                 VestingAccountResponse {
                     address: HumanAddr::from("addr0002"),
                     info: VestingInfo {
                         last_claim_time: 100u64,
                         schedules: vec![(100u64, 200u64, Uint128::from(100u128))],
-                    }
+                        staked_amount: Uint128::zero(),
+                    },
+                    vested: Uint128::zero(),
+                    claimed: Uint128::zero(),
+                    remaining: Uint128::from(100u128),
                 }
             ]
         }
     );
 }
 
+#[test]
+fn query_vesting_accounts_filters() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        anchor_token: HumanAddr::from("anchor_token"),
+        genesis_time: 100u64,
+        guardian: None,
+    };
+
+    let env = mock_env("addr0000", &vec![]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![
+            VestingAccount {
+                address: HumanAddr::from("addr0000"),
+                schedules: vec![(100u64, 200u64, Uint128::from(100u128))],
+            },
+            VestingAccount {
+                address: HumanAddr::from("addr0001"),
+                schedules: vec![(100u64, 110u64, Uint128::from(100u128))],
+            },
+            VestingAccount {
+                address: HumanAddr::from("addr0002"),
+                schedules: vec![(100u64, 200u64, Uint128::from(1000u128))],
+            },
+        ],
+    };
+    let env = mock_env("owner", &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // addr0001 claims the whole grant
+    let mut env = mock_env("addr0001", &[]);
+    env.block.time = 150;
+    let _res = handle(&mut deps, env, HandleMsg::Claim {}).unwrap();
+
+    let res = from_binary::<VestingAccountsResponse>(
+        &query(
+            &deps,
+            QueryMsg::VestingAccounts {
+                limit: None,
+                start_after: None,
+                order_by: Some(OrderBy::Asc),
+                only_active: Some(true),
+                block_time: Some(150u64),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.vesting_accounts
+            .iter()
+            .map(|a| (a.address.clone(), a.vested, a.claimed, a.remaining))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                HumanAddr::from("addr0000"),
+                Uint128::from(50u128),
+                Uint128::zero(),
+                Uint128::from(100u128),
+            ),
+            (
+                HumanAddr::from("addr0002"),
+                Uint128::from(500u128),
+                Uint128::zero(),
+                Uint128::from(1000u128),
+            ),
+        ]
+    );
+
+    // inactive accounts do not shrink the page
+    let res = from_binary::<VestingAccountsResponse>(
+        &query(
+            &deps,
+            QueryMsg::VestingAccounts {
+                limit: Some(1),
+                start_after: Some(HumanAddr::from("addr0000")),
+                order_by: Some(OrderBy::Asc),
+                only_active: Some(true),
+                block_time: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.vesting_accounts.len(), 1);
+    assert_eq!(res.vesting_accounts[0].address, HumanAddr::from("addr0002"));
+
+    let res = from_binary::<VestingAccountResponse>(
+        &query(
+            &deps,
+            QueryMsg::VestingAccount {
+                address: HumanAddr::from("addr0001"),
+                block_time: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.vested, Uint128::from(100u128));
+    assert_eq!(res.claimed, Uint128::from(100u128));
+    assert_eq!(res.remaining, Uint128::zero());
+}
+
 #[test]
 fn claim() {
     let mut deps = mock_dependencies(20, &[]);
@@ -211,6 +357,7 @@ fn claim() {
         owner: HumanAddr::from("owner"),
         anchor_token: HumanAddr::from("anchor_token"),
         genesis_time: 100u64,
+        guardian: None,
     };
 
     let env = mock_env("addr0000", &vec![]);
@@ -292,4 +439,373 @@ fn claim() {
             send: vec![],
         })],
     );
+
+    // the zero amount claim is not recorded
+    let msg = QueryMsg::ClaimHistory {
+        address: HumanAddr::from("addr0000"),
+        start_after: None,
+        limit: None,
+    };
+    assert_eq!(
+        from_binary::<ClaimHistoryResponse>(&query(&deps, msg).unwrap()).unwrap(),
+        ClaimHistoryResponse {
+            address: HumanAddr::from("addr0000"),
+            claims: vec![
+                ClaimRecord {
+                    height: env.block.height,
+                    time: 101,
+                    amount: Uint128::from(111u128),
+                },
+                ClaimRecord {
+                    height: env.block.height,
+                    time: 102,
+                    amount: Uint128::from(11u128),
+                },
+            ],
+        }
+    );
+
+    let msg = QueryMsg::ClaimHistory {
+        address: HumanAddr::from("addr0000"),
+        start_after: Some(101),
+        limit: Some(1),
+    };
+    assert_eq!(
+        from_binary::<ClaimHistoryResponse>(&query(&deps, msg).unwrap())
+            .unwrap()
+            .claims,
+        vec![ClaimRecord {
+            height: env.block.height,
+            time: 102,
+            amount: Uint128::from(11u128),
+        }]
+    );
+}
+
+#[test]
+fn freeze_claims() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        anchor_token: HumanAddr::from("anchor_token"),
+        genesis_time: 100u64,
+        guardian: Some(HumanAddr::from("guardian")),
+    };
+
+    let env = mock_env("addr0000", &vec![]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: HumanAddr::from("addr0000"),
+            schedules: vec![(100u64, 101u64, Uint128::from(100u128))],
+        }],
+    };
+    let env = mock_env("owner", &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    // only the guardian can freeze the claims
+    let env = mock_env("owner", &[]);
+    match handle(&mut deps, env, HandleMsg::FreezeClaims {}) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env("guardian", &[]);
+    let res = handle(&mut deps, env, HandleMsg::FreezeClaims {}).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "set_claims_frozen"),
+            log("claims_frozen", "true"),
+        ]
+    );
+
+    let mut env = mock_env("addr0000", &[]);
+    env.block.time = 101;
+    match handle(&mut deps, env.clone(), HandleMsg::Claim {}) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Claims are frozen"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the owner cannot swap the guardian while frozen
+    let msg = HandleMsg::UpdateConfig {
+        owner: None,
+        anchor_token: None,
+        genesis_time: None,
+        gov_contract: None,
+        guardian: Some(HumanAddr::from("owner")),
+    };
+    match handle(&mut deps, mock_env("owner", &[]), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Claims are frozen"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env_guardian = mock_env("guardian", &[]);
+    let _res = handle(&mut deps, env_guardian, HandleMsg::UnfreezeClaims {}).unwrap();
+
+    let res = handle(&mut deps, env, HandleMsg::Claim {}).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "claim"),
+            log("address", "addr0000"),
+            log("claim_amount", "100"),
+            log("last_claim_time", "101"),
+        ]
+    );
+}
+
+#[test]
+fn stake_vested() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        anchor_token: HumanAddr::from("anchor_token"),
+        genesis_time: 100u64,
+        guardian: None,
+    };
+
+    let env = mock_env("addr0000", &vec![]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![VestingAccount {
+            address: HumanAddr::from("addr0000"),
+            schedules: vec![(100u64, 200u64, Uint128::from(1000u128))],
+        }],
+    };
+    let env = mock_env("owner", &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let mut env = mock_env("addr0000", &[]);
+    env.block.time = 150;
+
+    // gov contract is not registered yet
+    let msg = HandleMsg::StakeVested {
+        amount: Uint128::from(400u128),
+    };
+    let res = handle(&mut deps, env.clone(), msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Gov contract is not registered"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let update_msg = HandleMsg::UpdateConfig {
+        owner: None,
+        anchor_token: None,
+        genesis_time: None,
+        gov_contract: Some(HumanAddr::from("gov")),
+        guardian: None,
+    };
+    let _res = handle(&mut deps, mock_env("owner", &[]), update_msg).unwrap();
+
+    // only 500 is not vested yet
+    let res = handle(
+        &mut deps,
+        env.clone(),
+        HandleMsg::StakeVested {
+            amount: Uint128::from(501u128),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot stake more than unvested amount")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = handle(&mut deps, env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: HumanAddr::from("anchor_token"),
+            msg: to_binary(&Cw20HandleMsg::Send {
+                contract: HumanAddr::from("gov"),
+                amount: Uint128::from(400u128),
+                msg: Some(
+                    to_binary(&GovCw20HookMsg::StakeVestingTokens {
+                        staker: HumanAddr::from("addr0000"),
+                    })
+                    .unwrap()
+                ),
+            })
+            .unwrap(),
+            send: vec![],
+        })],
+    );
+
+    // 600 vested; 400 is unlocked in gov and 200 is transferred
+    env.block.time = 160;
+    let res = handle(&mut deps, env.clone(), HandleMsg::Claim {}).unwrap();
+    assert_eq!(
+        res.log,
+        vec![
+            log("action", "claim"),
+            log("address", "addr0000"),
+            log("claim_amount", "600"),
+            log("last_claim_time", "160"),
+            log("unlock_amount", "400"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("gov"),
+                msg: to_binary(&GovHandleMsg::UnlockVestingTokens {
+                    staker: HumanAddr::from("addr0000"),
+                    amount: Uint128::from(400u128),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: HumanAddr::from("anchor_token"),
+                msg: to_binary(&Cw20HandleMsg::Transfer {
+                    recipient: HumanAddr::from("addr0000"),
+                    amount: Uint128::from(200u128),
+                })
+                .unwrap(),
+                send: vec![],
+            }),
+        ],
+    );
+
+    let res = from_binary::<VestingAccountResponse>(
+        &query(
+            &deps,
+            QueryMsg::VestingAccount {
+                address: HumanAddr::from("addr0000"),
+                block_time: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.info.staked_amount, Uint128::zero());
+}
+
+#[test]
+fn export_import_vesting_state() {
+    let mut deps = mock_dependencies(20, &[]);
+
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        anchor_token: HumanAddr::from("anchor_token"),
+        genesis_time: 100u64,
+        guardian: None,
+    };
+
+    let env = mock_env("addr0000", &vec![]);
+    let _res = init(&mut deps, env, msg).unwrap();
+
+    let msg = HandleMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![
+            VestingAccount {
+                address: HumanAddr::from("addr0000"),
+                schedules: vec![(100u64, 200u64, Uint128::from(100u128))],
+            },
+            VestingAccount {
+                address: HumanAddr::from("addr0001"),
+                schedules: vec![(100u64, 300u64, Uint128::from(200u128))],
+            },
+        ],
+    };
+    let env = mock_env("owner", &[]);
+    let _res = handle(&mut deps, env, msg).unwrap();
+
+    let mut env = mock_env("addr0000", &[]);
+    env.block.time = 150;
+    let _res = handle(&mut deps, env, HandleMsg::Claim {}).unwrap();
+
+    let res = from_binary::<ExportVestingStateResponse>(
+        &query(
+            &deps,
+            QueryMsg::ExportVestingState {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.vesting_accounts,
+        vec![
+            VestingStateRecord {
+                address: HumanAddr::from("addr0000"),
+                info: VestingInfo {
+                    schedules: vec![(100u64, 200u64, Uint128::from(100u128))],
+                    last_claim_time: 150u64,
+                    staked_amount: Uint128::zero(),
+                },
+            },
+            VestingStateRecord {
+                address: HumanAddr::from("addr0001"),
+                info: VestingInfo {
+                    schedules: vec![(100u64, 300u64, Uint128::from(200u128))],
+                    last_claim_time: 100u64,
+                    staked_amount: Uint128::zero(),
+                },
+            },
+        ]
+    );
+
+    // import into a new deployment which is not activated yet
+    let mut new_deps = mock_dependencies(20, &[]);
+    let msg = InitMsg {
+        owner: HumanAddr::from("owner"),
+        anchor_token: HumanAddr::from("anchor_token"),
+        genesis_time: 100u64,
+        guardian: None,
+    };
+
+    let env = mock_env("addr0000", &vec![]);
+    let _res = init(&mut new_deps, env, msg).unwrap();
+
+    let msg = HandleMsg::ImportVestingState {
+        vesting_accounts: res.vesting_accounts.clone(),
+    };
+    let mut env = mock_env("addr0000", &[]);
+    env.block.time = 50;
+    match handle(&mut new_deps, env, msg.clone()) {
+        Err(StdError::Unauthorized { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env("owner", &[]);
+    env.block.time = 50;
+    let res = handle(&mut new_deps, env, msg.clone()).unwrap();
+    assert_eq!(
+        res.log,
+        vec![log("action", "import_vesting_state"), log("imported", "2"),]
+    );
+
+    let res = from_binary::<VestingAccountResponse>(
+        &query(
+            &new_deps,
+            QueryMsg::VestingAccount {
+                address: HumanAddr::from("addr0000"),
+                block_time: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.info.last_claim_time, 150u64);
+    assert_eq!(res.claimed, Uint128::from(50u128));
+
+    // the import is closed after the genesis time
+    let mut env = mock_env("owner", &[]);
+    env.block.time = 100;
+    match handle(&mut new_deps, env, msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Vesting state can only be imported before the genesis time"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
@@ -1,12 +1,20 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Uint128};
+use cosmwasm_std::{Decimal, HumanAddr, Uint128};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
     pub owner: HumanAddr,
     pub anchor_token: HumanAddr,
+    /// Maximum amount a single address can claim across all stages
+    pub claim_cap: Option<Uint128>,
+    /// When set, claims are paid by the distributor on each claim instead of
+    /// the airdrop balance; stages must then register their total amount
+    pub distributor: Option<HumanAddr>,
+    /// When set, the unclaimed tokens of expired stages are reclaimed to the
+    /// gov contract as a staking reward instead of to the owner
+    pub reclaim_gov_contract: Option<HumanAddr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -14,14 +22,47 @@ pub struct InitMsg {
 pub enum HandleMsg {
     UpdateConfig {
         owner: Option<HumanAddr>,
+        reclaim_gov_contract: Option<HumanAddr>,
     },
     RegisterMerkleRoot {
         merkle_root: String,
+        /// Total amount claimable in the stage, the outstanding stage totals
+        /// must stay within the ANC balance unless claims are paid by the distributor
+        total_amount: Uint128,
+        /// Block height after which the stage can no longer be claimed
+        expiry: Option<u64>,
+        /// Restricts the stage to claimants staking at least the minimum in gov
+        staking_requirement: Option<StakingRequirement>,
     },
     Claim {
         stage: u8,
         amount: Uint128,
         proof: Vec<String>,
+        /// Routes the claimed amount to the recipients by ratio, the ratios sum to 1
+        splits: Option<Vec<(HumanAddr, Decimal)>>,
+    },
+    /// Closes an expired stage and registers a new stage whose merkle root
+    /// covers the unclaimed remainder of the closed one
+    RolloverUnclaimed {
+        from_stage: u8,
+        to_stage_root: String,
+        expiry: Option<u64>,
+    },
+    /// Registers a stage distributing `total_amount` pro rata to the gov
+    /// stakers by their share at `height`, without a merkle root
+    RegisterStakingSnapshot {
+        gov_contract: HumanAddr,
+        height: u64,
+        total_amount: Uint128,
+        expiry: Option<u64>,
+    },
+    ClaimStakingSnapshot {
+        stage: u8,
+    },
+    /// Closes an expired stage and transfers its unclaimed remainder to the
+    /// reclaim gov contract, raising the value of the staked shares, or to the owner
+    ReclaimUnclaimed {
+        stage: u8,
     },
 }
 
@@ -36,6 +77,7 @@ pub enum QueryMsg {
     MerkleRoot { stage: u8 },
     LatestStage {},
     IsClaimed { stage: u8, address: HumanAddr },
+    StageInfo { stage: u8 },
 }
 
 // We define a custom struct for each query response
@@ -43,6 +85,9 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: HumanAddr,
     pub anchor_token: HumanAddr,
+    pub claim_cap: Option<Uint128>,
+    pub distributor: Option<HumanAddr>,
+    pub reclaim_gov_contract: Option<HumanAddr>,
 }
 
 // We define a custom struct for each query response
@@ -63,3 +108,29 @@ pub struct LatestStageResponse {
 pub struct IsClaimedResponse {
     pub is_claimed: bool,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageInfoResponse {
+    pub stage: u8,
+    pub total_amount: Option<Uint128>,
+    pub claimed_amount: Uint128,
+    pub expiry: Option<u64>,
+    pub rolled_over_to: Option<u8>,
+    pub reclaimed: bool,
+    pub staking_snapshot: Option<StakingSnapshot>,
+    pub staking_requirement: Option<StakingRequirement>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingSnapshot {
+    pub gov_contract: HumanAddr,
+    pub height: u64,
+}
+
+/// Minimum gov staked balance the claimant must hold at claim time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingRequirement {
+    pub gov_contract: HumanAddr,
+    pub min_staked: Uint128,
+}
@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Decimal, HumanAddr};
+use cosmwasm_std::{Decimal, HumanAddr, Uint128};
+use terraswap::asset::AssetInfo;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
@@ -10,20 +11,79 @@ pub struct InitMsg {
     pub anchor_token: HumanAddr,
     pub distributor_contract: HumanAddr,
     pub reward_factor: Decimal,
+    pub sweep_windows: Vec<SweepWindow>,
+    pub router: Option<Router>,
+}
+
+/// Block heights at which sweeps are allowed;
+/// with no window configured sweeps are always allowed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepWindow {
+    /// Open for the first `duration` blocks of every `period` blocks
+    Periodic { period: u64, duration: u64 },
+    /// Open from `start` to `end` height, inclusive
+    Range { start: u64, end: u64 },
+}
+
+impl SweepWindow {
+    pub fn is_open(&self, height: u64) -> bool {
+        match self {
+            SweepWindow::Periodic { period, duration } => height % period < *duration,
+            SweepWindow::Range { start, end } => *start <= height && height <= *end,
+        }
+    }
+}
+
+/// DEX router interfaces the collector can swap through
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RouterType {
+    Terraswap,
+    Astroport,
+}
+
+/// When a router is configured, all sweeps are routed through it
+/// instead of the terraswap pairs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Router {
+    pub router_type: RouterType,
+    pub contract_addr: HumanAddr,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
     /// Update config interface
-    /// to enable reward_factor update
+    /// to enable reward_factor, sweep_windows and router update
     UpdateConfig {
         reward_factor: Option<Decimal>,
+        sweep_windows: Option<Vec<SweepWindow>>,
+        router: Option<Router>,
+    },
+    /// Register a fee asset accepted by the collector,
+    /// only gov contract can execute this message.
+    /// When pair_contract is not given, the ANC pair is
+    /// looked up from the terraswap factory on sweep.
+    /// Balances below min_sweep_amount are not swept
+    RegisterAsset {
+        asset_info: AssetInfo,
+        decimals: u8,
+        pair_contract: Option<HumanAddr>,
+        min_sweep_amount: Option<Uint128>,
     },
+    /// Remove a fee asset from the registry
+    DeregisterAsset { asset_info: AssetInfo },
     /// Public Message
     /// Sweep all given denom balance to ANC token
-    /// and execute Distribute message
+    /// and execute Distribute message, the denom
+    /// must be registered
     Sweep { denom: String },
+    /// Public Message
+    /// Sweep the balances of all registered assets to ANC token,
+    /// skipping the ones below their minimum sweep amount,
+    /// and execute Distribute message
+    SweepAll {},
 
     /// Internal Message
     /// Distribute all ANC token to gov_contract
@@ -34,6 +94,12 @@ pub enum HandleMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    CollectableAssets {},
+    /// Balances of the registered assets with their estimated ANC return,
+    /// and whether a sweep at the given height would swap them
+    Sweepable {
+        block_height: u64,
+    },
 }
 
 // We define a custom struct for each query response
@@ -44,6 +110,40 @@ pub struct ConfigResponse {
     pub anchor_token: HumanAddr,
     pub distributor_contract: HumanAddr,
     pub reward_factor: Decimal,
+    pub sweep_windows: Vec<SweepWindow>,
+    pub router: Option<Router>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectableAssetResponse {
+    pub asset_info: AssetInfo,
+    pub decimals: u8,
+    pub pair_contract: Option<HumanAddr>,
+    pub min_sweep_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectableAssetsResponse {
+    pub assets: Vec<CollectableAssetResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SweepableAssetResponse {
+    pub asset_info: AssetInfo,
+    pub balance: Uint128,
+    /// balance swapped by a sweep, net of tax
+    pub sweep_amount: Uint128,
+    /// sweep amount below which the asset is not swept
+    pub min_sweep_amount: Uint128,
+    /// ANC token returned by the pair or router for the sweep amount
+    pub estimated_return: Uint128,
+    pub sweepable: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SweepableResponse {
+    pub sweep_window_open: bool,
+    pub assets: Vec<SweepableAssetResponse>,
 }
 
 /// We currently take no arguments for migrations
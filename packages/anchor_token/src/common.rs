@@ -1,7 +1,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
 
-use cosmwasm_std::Order;
+use cosmwasm_std::{BlockInfo, Order};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -19,3 +21,55 @@ impl Into<Order> for OrderBy {
         }
     }
 }
+
+/// Block height or time at which a period ends
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Expiration {
+    /// expires once the block height reaches the given height
+    AtHeight(u64),
+    /// expires once the block time reaches the given time in seconds
+    AtTime(u64),
+    /// never expires
+    Never {},
+}
+
+impl Expiration {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        match self {
+            Expiration::AtHeight(height) => block.height >= *height,
+            Expiration::AtTime(time) => block.time >= *time,
+            Expiration::Never {} => false,
+        }
+    }
+}
+
+impl Default for Expiration {
+    fn default() -> Self {
+        Expiration::Never {}
+    }
+}
+
+/// Expirations are ordered by when they expire; a height and a time can't be compared
+impl PartialOrd for Expiration {
+    fn partial_cmp(&self, other: &Expiration) -> Option<Ordering> {
+        match (self, other) {
+            (Expiration::AtHeight(a), Expiration::AtHeight(b)) => Some(a.cmp(b)),
+            (Expiration::AtTime(a), Expiration::AtTime(b)) => Some(a.cmp(b)),
+            (Expiration::Never {}, Expiration::Never {}) => Some(Ordering::Equal),
+            (Expiration::Never {}, _) => Some(Ordering::Greater),
+            (_, Expiration::Never {}) => Some(Ordering::Less),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Expiration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expiration::AtHeight(height) => write!(f, "expiration height: {}", height),
+            Expiration::AtTime(time) => write!(f, "expiration time: {}", time),
+            Expiration::Never {} => write!(f, "expiration: never"),
+        }
+    }
+}
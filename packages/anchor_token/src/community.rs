@@ -1,7 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Uint128};
+use cosmwasm_std::{Decimal, HumanAddr, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+use crate::common::Expiration;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
@@ -13,6 +16,7 @@ pub struct InitMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
+    Receive(Cw20ReceiveMsg),
     UpdateConfig {
         spend_limit: Option<Uint128>,
     },
@@ -20,6 +24,41 @@ pub enum HandleMsg {
         recipient: HumanAddr,
         amount: Uint128,
     },
+    /// Starts, replaces or (with `None`) stops the donation matching program
+    UpdateMatchingProgram {
+        program: Option<MatchingProgram>,
+    },
+    /// Sets aside up to `cap` tokens which `manager` can spend without a poll
+    /// until `expiry`; the unspent rest stays in the fund
+    CreateBudget {
+        name: String,
+        manager: HumanAddr,
+        cap: Uint128,
+        expiry: Expiration,
+    },
+    /// Spends from a budget, only executable by its manager
+    SpendBudget {
+        name: String,
+        recipient: HumanAddr,
+        amount: Uint128,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Donates the sent tokens; matched from the fund while a program runs
+    Donate {},
+}
+
+/// Donations are forwarded to `recipient` together with `ratio` times the
+/// donated amount from the fund, up to `epoch_cap` per `epoch_period` seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MatchingProgram {
+    pub recipient: HumanAddr,
+    pub ratio: Decimal,
+    pub epoch_cap: Uint128,
+    pub epoch_period: u64,
 }
 
 /// We currently take no arguments for migrations
@@ -30,6 +69,19 @@ pub struct MigrateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    MatchingProgram {},
+    Donor {
+        address: HumanAddr,
+    },
+    Budget {
+        name: String,
+    },
+    /// Spends of a budget from the oldest on
+    BudgetSpends {
+        name: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -39,3 +91,43 @@ pub struct ConfigResponse {
     pub anchor_token: HumanAddr,
     pub spend_limit: Uint128,
 }
+
+/// Epoch figures are as of the last donation or program update
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MatchingProgramResponse {
+    pub program: Option<MatchingProgram>,
+    pub epoch: u64,
+    pub epoch_matched: Uint128,
+    pub epoch_remaining: Uint128,
+    pub total_donated: Uint128,
+    pub total_matched: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DonorResponse {
+    pub address: HumanAddr,
+    pub donated: Uint128,
+    pub matched: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BudgetResponse {
+    pub name: String,
+    pub manager: HumanAddr,
+    pub cap: Uint128,
+    pub spent: Uint128,
+    pub expiry: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BudgetSpend {
+    pub id: u64,
+    pub recipient: HumanAddr,
+    pub amount: Uint128,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BudgetSpendsResponse {
+    pub spends: Vec<BudgetSpend>,
+}
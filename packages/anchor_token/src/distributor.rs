@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Uint128};
+use cosmwasm_std::{Binary, HumanAddr, Uint128};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
@@ -27,6 +27,32 @@ pub enum HandleMsg {
     RemoveDistributor {
         distributor: HumanAddr,
     },
+    /// Starts, replaces or (with `None`) stops the epoch emissions;
+    /// a new schedule starts its first epoch at the current block and
+    /// must not pay more than the spend limit per epoch; the ended epochs of
+    /// the replaced schedule are paid first, within the spend limit
+    UpdateEmissions {
+        schedule: Option<EmissionSchedule>,
+    },
+    /// Pays the emissions of the epochs ended since the last distribution,
+    /// at most the spend limit per call, executable by anyone
+    Distribute {},
+}
+
+/// Amounts paid to each recipient once every `epoch_period` seconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionSchedule {
+    pub epoch_period: u64,
+    pub emissions: Vec<Emission>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Emission {
+    pub recipient: HumanAddr,
+    pub amount: Uint128,
+    /// when given, the amount is sent to the recipient contract with this
+    /// Receive hook message instead of being transferred
+    pub hook_msg: Option<Binary>,
 }
 
 /// We currently take no arguments for migrations
@@ -37,6 +63,7 @@ pub struct MigrateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    Emissions {},
 }
 
 // We define a custom struct for each query response
@@ -47,3 +74,12 @@ pub struct ConfigResponse {
     pub whitelist: Vec<HumanAddr>,
     pub spend_limit: Uint128,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionsResponse {
+    pub schedule: Option<EmissionSchedule>,
+    /// epochs paid since the schedule started
+    pub distributed_epochs: u64,
+    /// time from which the next epoch can be distributed
+    pub next_distribution_time: Option<u64>,
+}
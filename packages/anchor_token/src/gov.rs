@@ -1,10 +1,10 @@
-use cosmwasm_std::{Binary, Decimal, HumanAddr, Uint128};
+use cosmwasm_std::{Binary, CosmosMsg, Decimal, HumanAddr, Uint128};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-use crate::common::OrderBy;
+use crate::common::{Expiration, OrderBy};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
@@ -14,7 +14,33 @@ pub struct InitMsg {
     pub timelock_period: u64,
     pub expiration_period: u64,
     pub proposal_deposit: Uint128,
-    pub snapshot_period:u64,
+    pub snapshot_period: u64,
+    pub reveal_period: u64,
+    pub min_vote_amount: Uint128,
+    pub quorum_excluded_addresses: Vec<HumanAddr>,
+    pub tally_checkpoint_interval: u64,
+    pub poll_retention_period: u64,
+    pub max_poll_extension: u64,
+    pub narrow_pass_margin: Decimal,
+    pub adaptive_quorum: Option<AdaptiveQuorum>,
+    pub poll_sponsorship: Option<PollSponsorship>,
+    /// Addresses allowed to execute passed polls, anyone when empty
+    pub executor_whitelist: Vec<HumanAddr>,
+    /// Part of the token deposit of a poll failing the quorum claimable back by
+    /// the depositor, zero slashes the whole deposit
+    pub partial_refund_ratio: Decimal,
+    /// Share of the tallied votes voting NoWithVeto above which a poll is vetoed
+    /// and its deposit slashed, zero disables the veto
+    pub veto_threshold: Decimal,
+    /// Members jointly executing the emergency actions, none disables them
+    pub emergency_council: Option<EmergencyCouncil>,
+    /// Most polls in progress a staker can have votes locked in at once, zero for no limit
+    pub max_active_votes: u64,
+    /// Rejects votes cast in the same block as the voter's last stake,
+    /// StakeAndVote included
+    pub reject_same_block_votes: bool,
+    /// Named parameters a poll can be created with instead of the config ones
+    pub quorum_presets: Vec<QuorumPreset>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -33,11 +59,88 @@ pub enum HandleMsg {
         expiration_period: Option<u64>,
         proposal_deposit: Option<Uint128>,
         snapshot_period: Option<u64>,
+        vesting_contract: Option<HumanAddr>,
+        reveal_period: Option<u64>,
+        min_vote_amount: Option<Uint128>,
+        quorum_excluded_addresses: Option<Vec<HumanAddr>>,
+        tally_checkpoint_interval: Option<u64>,
+        poll_retention_period: Option<u64>,
+        max_poll_extension: Option<u64>,
+        narrow_pass_margin: Option<Decimal>,
+        community_contract: Option<HumanAddr>,
+        /// a zero poll window disables the adaptive quorum
+        adaptive_quorum: Option<AdaptiveQuorum>,
+        /// zero required sponsors disables the poll sponsorship
+        poll_sponsorship: Option<PollSponsorship>,
+        executor_whitelist: Option<Vec<HumanAddr>>,
+        partial_refund_ratio: Option<Decimal>,
+        veto_threshold: Option<Decimal>,
+        /// a zero threshold disables the emergency council
+        emergency_council: Option<EmergencyCouncil>,
+        max_active_votes: Option<u64>,
+        reject_same_block_votes: Option<bool>,
+        /// replaces the presets, polls already created keep their parameters
+        quorum_presets: Option<Vec<QuorumPreset>>,
+    },
+    /// Applies the changes from the activation height on instead of immediately,
+    /// replacing any update still pending; only executable by the owner
+    ScheduleConfigUpdate {
+        changes: ConfigUpdate,
+        activation_height: u64,
     },
+    /// ExtendPoll prolongs the voting period of a poll in progress before its end
+    /// height, once per poll and by at most `max_poll_extension` blocks; only
+    /// executable by the owner or the gov contract itself through a passed poll
+    ExtendPoll {
+        poll_id: u64,
+        extra_blocks: u64,
+    },
+    /// SponsorPoll co-signs a poll pending sponsorship with the staked balance of
+    /// the sender, locking the minimum stake until the poll ends; the voting
+    /// period starts once the poll has its required sponsors
+    SponsorPoll {
+        poll_id: u64,
+    },
+    /// CastVote votes with the given amount, or with the entire staked balance if not given
     CastVote {
+        poll_id: u64,
+        vote: VoteOption,
+        amount: Option<Uint128>,
+        /// staker voting through the sender as its operator, the sender if not given
+        voter: Option<HumanAddr>,
+    },
+    /// ApproveOperator lets the operator cast votes with the stake of the sender
+    /// until `expires`; an operator can never withdraw or stake
+    ApproveOperator {
+        operator: HumanAddr,
+        expires: Expiration,
+    },
+    RevokeOperator {
+        operator: HumanAddr,
+    },
+    /// CommitVote stores a hidden vote on a commit-reveal poll during the voting period;
+    /// `hash` is the hex encoded keccak256 of voter + vote + amount + salt, where
+    /// the strings are prefixed by their 4 bytes big endian length and the amount
+    /// is 16 bytes big endian
+    CommitVote {
+        poll_id: u64,
+        hash: String,
+    },
+    /// RevealVote opens a committed vote during the reveal period;
+    /// unrevealed commitments are not counted
+    RevealVote {
         poll_id: u64,
         vote: VoteOption,
         amount: Uint128,
+        salt: String,
+    },
+    /// CastReferendumVote votes on a referendum poll with the sender's balance
+    /// in the poll's merkle snapshot, proven like an airdrop claim
+    CastReferendumVote {
+        poll_id: u64,
+        vote: VoteOption,
+        balance: Uint128,
+        proof: Vec<String>,
     },
     WithdrawVotingTokens {
         amount: Option<Uint128>,
@@ -51,24 +154,233 @@ pub enum HandleMsg {
     ExpirePoll {
         poll_id: u64,
     },
+    /// ExecuteRejectedPoll executes the on_reject_msgs of a poll voted down
+    /// with the quorum reached, once its timelock period has passed
+    ExecuteRejectedPoll {
+        poll_id: u64,
+    },
     SnapshotPoll {
         poll_id: u64,
+    },
+    /// PrunePolls removes the stored data of polls below `before_poll_id` which are
+    /// finished and ended more than `poll_retention_period` blocks ago;
+    /// `limit` bounds the number of removed storage entries
+    PrunePolls {
+        before_poll_id: u64,
+        limit: Option<u32>,
+    },
+    /// CreatePollWithStakedDeposit creates a poll by locking the proposal deposit
+    /// from the sender's staked shares instead of receiving a token deposit
+    CreatePollWithStakedDeposit {
+        title: String,
+        description: String,
+        links: Option<Vec<PollLink>>,
+        execute_msgs: Option<Vec<ExecuteMsg>>,
+        commit_reveal: Option<bool>,
+        content_hash: Option<Binary>,
+        referendum: Option<Referendum>,
+        reserved_poll_id: Option<u64>,
+        option_labels: Option<Vec<String>>,
+    },
+    /// ReservePollId allocates the next poll id to the sender, so coordinated
+    /// proposals can reference each other's ids before they are created
+    ReservePollId {},
+    /// MigrateStake is the last-resort upgrade path executed by governance;
+    /// cancels a page of the in progress polls per call and, once none is left,
+    /// transfers the staked balance to the successor and freezes the stakers'
+    /// claims for the successor to import; partial refunds, vote rewards and
+    /// execution bounties stay claimable in this contract
+    MigrateStake {
+        new_gov_contract: HumanAddr,
+        limit: Option<u32>,
+    },
+    /// ImportStake credits the next page of stakers from the claim snapshot
+    /// of a predecessor which migrated its stake to this contract
+    ImportStake {
+        old_gov_contract: HumanAddr,
+        limit: Option<u32>,
+    },
+    /// UnlockVestingTokens is called by the vesting contract to release
+    /// the vested portion of a staker's locked stake
+    UnlockVestingTokens {
+        staker: HumanAddr,
+        amount: Uint128,
+    },
+    /// SetParam stores a governance parameter for other contracts to read;
+    /// only executable by the gov contract itself through a passed poll.
+    /// The param is removed when no value is given
+    SetParam {
+        key: String,
+        value: Option<ParamValue>,
+    },
+    /// SetMigrationInProgress freezes staking, withdrawals and votes while a state
+    /// migration is prepared, ending and executing polls stays open; only set by the
+    /// gov contract itself through a passed poll, the owner can also clear it
+    SetMigrationInProgress {
+        migration_in_progress: bool,
+    },
+    /// ClaimDepositRefund pays out the partial refund of a deposit slashed
+    /// for failing the quorum to its refund recipient
+    ClaimDepositRefund {
+        poll_id: u64,
+    },
+    /// ClaimVoteReward pays out the share of the poll vote reward
+    /// of the sender, once the poll has ended
+    ClaimVoteReward {
+        poll_id: u64,
+    },
+    /// RefreshTokenInfo caches the symbol and decimals of the ANC token in the
+    /// config; only executable by the owner
+    RefreshTokenInfo {},
+    /// ReconcileTotalDeposit resets the total deposit to the deposits locked by
+    /// the polls; only executable by the owner
+    ReconcileTotalDeposit {},
+    /// SubmitEmergencyAction proposes an emergency action and approves it for the
+    /// sender; only executable by a member of the emergency council
+    SubmitEmergencyAction {
+        action: EmergencyAction,
+    },
+    /// ApproveEmergencyAction co-signs a pending emergency action, which is
+    /// executed once the approvals reach the council threshold; pending actions
+    /// expire after a fixed number of blocks
+    ApproveEmergencyAction {
+        action_id: u64,
+    },
+}
+
+/// Break-glass actions of the emergency council; anything else goes through a poll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmergencyAction {
+    /// Stops poll creation and votes until unpaused, passed polls stay executable
+    /// and are vetoed instead
+    PauseGov {},
+    UnpauseGov {},
+    /// Sends the pause message registered for the contract in the council config
+    PauseContract {
+        contract_addr: HumanAddr,
+    },
+    /// Rejects a passed poll before it is executed
+    VetoPoll {
+        poll_id: u64,
+    },
+}
+
+impl fmt::Display for EmergencyAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EmergencyAction::PauseGov {} => write!(f, "pause_gov"),
+            EmergencyAction::UnpauseGov {} => write!(f, "unpause_gov"),
+            EmergencyAction::PauseContract { contract_addr } => {
+                write!(f, "pause_contract/{}", contract_addr)
+            }
+            EmergencyAction::VetoPoll { poll_id } => write!(f, "veto_poll/{}", poll_id),
+        }
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+pub enum ParamValue {
+    Uint128(Uint128),
+    Decimal(Decimal),
+    Bool(bool),
+    String(String),
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamValue::Uint128(v) => write!(f, "{}", v),
+            ParamValue::Decimal(v) => write!(f, "{}", v),
+            ParamValue::Bool(v) => write!(f, "{}", v),
+            ParamValue::String(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum Cw20HookMsg {
     /// StakeVotingTokens a user can stake their mirror token to receive rewards
     /// or do vote on polls
     StakeVotingTokens {},
+    /// StakeVestingTokens is sent by the vesting contract to stake not yet vested
+    /// tokens on behalf of the staker; the stake can't be withdrawn until it is vested
+    StakeVestingTokens { staker: HumanAddr },
+    /// StakeVotingTokensFor stakes the sent tokens on behalf of the staker,
+    /// e.g. rewards claimed from another contract
+    StakeVotingTokensFor { staker: HumanAddr },
     /// CreatePoll need to receive deposit from a proposer
     CreatePoll {
         title: String,
         description: String,
-        link: Option<String>,
+        /// documents referenced by the poll, at most 4
+        links: Option<Vec<PollLink>>,
         execute_msgs: Option<Vec<ExecuteMsg>>,
+        /// votes are committed as hashes and revealed after the voting period
+        commit_reveal: Option<bool>,
+        /// 32 bytes hash of the off-chain proposal document
+        content_hash: Option<Binary>,
+        /// voting power is taken from a snapshot of token balances instead of the stake,
+        /// such a poll cannot have execute_msgs
+        referendum: Option<Referendum>,
+        /// a refunded deposit is staked for the creator instead of transferred
+        stake_refund: Option<bool>,
+        /// poll id previously reserved by the proposer
+        reserved_poll_id: Option<u64>,
+        /// receives the refunded deposit instead of the proposer
+        refund_address: Option<HumanAddr>,
+        /// custom options of a text poll, voted with `VoteOption::Label` instead of yes/no
+        option_labels: Option<Vec<String>>,
+        /// part of the sent amount shared by the voters pro rata to their votes
+        /// once the poll ends, the rest being the deposit
+        vote_reward: Option<Uint128>,
+        /// part of the deposit held back when the poll passes and paid
+        /// to the sender of its ExecutePoll
+        execution_bounty: Option<Uint128>,
+        /// executed with ExecuteRejectedPoll after the timelock when the poll
+        /// is voted down with the quorum reached, never when vetoed, limited to
+        /// a single cw20 transfer returning the poll escrow to the proposer or
+        /// refund address; the transferred token and amount are the escrow the
+        /// proposer funds with FundPollEscrow
+        on_reject_msgs: Option<Vec<ExecuteMsg>>,
+        /// name of the quorum preset the poll is judged by, the config parameters when none
+        preset: Option<String>,
     },
+    /// StakeAndVote stakes the sent tokens and votes the staked balance
+    /// of the sender on the poll; rejected while same block votes are rejected
+    StakeAndVote { poll_id: u64, vote: VoteOption },
+    /// FundPollEscrow is sent by the escrow token of a poll in progress, the
+    /// proposer sending exactly the amount its on_reject_msgs return
+    FundPollEscrow { poll_id: u64 },
+}
+
+/// Merkle snapshot of the token balances which vote on a referendum poll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Referendum {
+    /// hex encoded root of the (address + balance) leaves
+    pub merkle_root: String,
+    /// sum of the snapshot balances, used as the quorum denominator
+    pub total_balance: Uint128,
+}
+
+/// Document referenced by a poll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollLink {
+    pub link_type: LinkType,
+    /// http(s) url of the document
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkType {
+    Forum,
+    CodeDiff,
+    Audit,
+    Other,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -76,6 +388,8 @@ pub enum Cw20HookMsg {
 pub struct ExecuteMsg {
     pub order: u64,
     pub contract: HumanAddr,
+    /// `${poll_id}` and `${contract_balance}` (ANC balance of `contract`)
+    /// are substituted when the poll is executed
     pub msg: Binary,
 }
 
@@ -86,6 +400,13 @@ pub enum QueryMsg {
     State {},
     Staker {
         address: HumanAddr,
+        /// token balance of the gov contract already queried in the same block,
+        /// used instead of querying it again
+        token_balance: Option<Uint128>,
+    },
+    /// Share and amount withdrawn by WithdrawVotingTokens without an amount
+    Withdrawable {
+        address: HumanAddr,
     },
     Poll {
         poll_id: u64,
@@ -96,15 +417,116 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    PollsByIds {
+        poll_ids: Vec<u64>,
+    },
     Voters {
         poll_id: u64,
         start_after: Option<HumanAddr>,
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    ConfigHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    MigrationSnapshot {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    /// Config, state totals and the latest poll in one response
+    Overview {},
+    Param {
+        key: String,
+    },
+    Params {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Running tallies recorded every `tally_checkpoint_interval` votes
+    TallyCheckpoints {
+        poll_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Lifecycle events of the poll with the heights they happened at
+    PollEvents {
+        poll_id: u64,
+    },
+    /// Deposit locked by the poll and how it was settled
+    DepositLedger {
+        poll_id: u64,
+    },
+    /// Whether the poll deposit was refunded or slashed, where it went and when
+    PollDeposit {
+        poll_id: u64,
+    },
+    /// Messages ExecutePoll would emit for the poll, in execution order
+    SimulateExecution {
+        poll_id: u64,
+    },
+    /// Vote and poll outcome counters since the contract was instantiated
+    Stats {},
+    /// Final tally of an ended poll with the merkle root over its voter records,
+    /// so the outcome can be relayed and verified on other chains
+    PollTally {
+        poll_id: u64,
+    },
+    /// Share of the staker and the total share as of the end of the given height
+    StakingSnapshot {
+        address: HumanAddr,
+        height: u64,
+    },
+    /// Sums the shares of a page of stakers, so the pages can be added up
+    /// and checked against the total share
+    ShareAccounting {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    /// Stored bytes of the poll record, so migration tooling can
+    /// check the storage layout against the schema
+    RawPoll {
+        poll_id: u64,
+    },
+    /// Stored bytes of the staker record
+    RawBank {
+        address: HumanAddr,
+    },
+    /// Config update waiting for its activation height
+    ScheduledConfigUpdate {},
+    EmergencyAction {
+        action_id: u64,
+    },
+    EmergencyActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    /// Approval of the operator to vote for the staker
+    Operator {
+        staker: HumanAddr,
+        operator: HumanAddr,
+    },
+    /// Stored staker records in storage order, so the whole bank can be
+    /// snapshotted page by page
+    ExportBanks {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    /// Stored poll records in poll id order
+    ExportPolls {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Stored voter records of the poll in storage order
+    ExportVoters {
+        poll_id: u64,
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: HumanAddr,
     pub anchor_token: HumanAddr,
@@ -115,13 +537,76 @@ pub struct ConfigResponse {
     pub expiration_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub vesting_contract: Option<HumanAddr>,
+    pub reveal_period: u64,
+    pub min_vote_amount: Uint128,
+    pub quorum_excluded_addresses: Vec<HumanAddr>,
+    pub tally_checkpoint_interval: u64,
+    pub poll_retention_period: u64,
+    pub max_poll_extension: u64,
+    pub narrow_pass_margin: Decimal,
+    pub community_contract: Option<HumanAddr>,
+    pub adaptive_quorum: Option<AdaptiveQuorum>,
+    pub poll_sponsorship: Option<PollSponsorship>,
+    pub executor_whitelist: Vec<HumanAddr>,
+    pub partial_refund_ratio: Decimal,
+    pub veto_threshold: Decimal,
+    pub emergency_council: Option<EmergencyCouncil>,
+    pub max_active_votes: u64,
+    pub reject_same_block_votes: bool,
+    pub quorum_presets: Vec<QuorumPreset>,
+    pub token_info: Option<TokenInfoCache>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+/// Metadata of the ANC token as of the last refresh, so amounts can be
+/// rendered without querying the token contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenInfoCache {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub refreshed_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StateResponse {
     pub poll_count: u64,
     pub total_share: Uint128,
     pub total_deposit: Uint128,
+    /// average turnout of the polls ended with the adaptive quorum enabled
+    pub turnout_ema: Option<Decimal>,
+    /// quorum required of the polls ending now
+    pub quorum: Decimal,
+    pub migration_in_progress: bool,
+    pub unclaimed_refund: Uint128,
+    pub emergency_paused: bool,
+    pub vote_reward_pool: Uint128,
+    pub execution_bounty_pool: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollTallyResponse {
+    pub poll_id: u64,
+    pub voters_root: Option<String>,
+    /// fixed width big endian encoding of the tally and the voters root
+    pub tally: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub total_votes: u64,
+    pub unique_voters: u64,
+    pub polls_passed: u64,
+    pub polls_rejected: u64,
+    pub polls_executed: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OverviewResponse {
+    pub config: ConfigResponse,
+    pub state: StateResponse,
+    pub latest_poll_id: Option<u64>,
+    pub latest_poll_status: Option<PollStatus>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -132,18 +617,50 @@ pub struct PollResponse {
     pub end_height: u64,
     pub title: String,
     pub description: String,
-    pub link: Option<String>,
+    pub links: Vec<PollLink>,
     pub deposit_amount: Uint128,
+    pub deposit_share: Option<Uint128>,
     pub execute_data: Option<Vec<ExecuteMsg>>,
     pub yes_votes: Uint128, // balance
     pub no_votes: Uint128,  // balance
+    pub yes_shares: Uint128,
+    pub no_shares: Uint128,
+    /// part of the no votes voting NoWithVeto
+    pub veto_votes: Uint128,
     pub staked_amount: Option<Uint128>,
+    pub staked_share: Option<Uint128>,
     pub total_balance_at_end_poll: Option<Uint128>,
+    pub reveal_end_height: Option<u64>,
+    pub rejected_reason: Option<RejectedReason>,
+    pub content_hash: Option<Binary>,
+    pub referendum: Option<Referendum>,
+    pub stake_refund: bool,
+    pub refund_address: Option<HumanAddr>,
+    pub narrow_pass: bool,
+    pub option_labels: Vec<String>,
+    pub option_votes: Vec<Uint128>,
+    pub required_sponsors: u64,
+    pub sponsors: Vec<HumanAddr>,
+    pub vote_reward: Uint128,
+    pub vote_reward_claimed: Uint128,
+    pub execution_bounty: Uint128,
+    pub on_reject_msgs: Option<Vec<ExecuteMsg>>,
+    pub on_reject_executed: bool,
+    pub escrow_funded: bool,
+    pub preset: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct PollsResponse {
     pub polls: Vec<PollResponse>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PollsByIdsResponse {
+    pub polls: Vec<PollResponse>,
+    pub missing_poll_ids: Vec<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -156,6 +673,14 @@ pub struct StakerResponse {
     pub balance: Uint128,
     pub share: Uint128,
     pub locked_balance: Vec<(u64, VoterInfo)>,
+    pub vesting_locked_balance: Uint128,
+    pub deposit_locked_share: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct WithdrawableResponse {
+    pub share: Uint128,
+    pub amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -163,23 +688,348 @@ pub struct VotersResponseItem {
     pub voter: HumanAddr,
     pub vote: VoteOption,
     pub balance: Uint128,
+    pub share: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct VotersResponse {
     pub voters: Vec<VotersResponseItem>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ConfigHistoryResponseItem {
+    pub id: u64,
+    pub height: u64,
+    pub sender: HumanAddr,
+    pub changes: Vec<ConfigChange>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ConfigHistoryResponse {
+    pub records: Vec<ConfigHistoryResponseItem>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct EmergencyActionResponse {
+    pub id: u64,
+    pub action: EmergencyAction,
+    pub proposer: HumanAddr,
+    pub approvals: Vec<HumanAddr>,
+    pub submitted_height: u64,
+    /// last height the action can be approved at while pending
+    pub expiry_height: u64,
+    pub executed_height: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct EmergencyActionsResponse {
+    pub actions: Vec<EmergencyActionResponse>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakerClaim {
+    pub staker: HumanAddr,
+    pub share: Uint128,
+    pub balance: Uint128,
+    pub vesting_locked_balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct MigrationSnapshotResponse {
+    pub new_gov_contract: HumanAddr,
+    pub total_balance: Uint128,
+    pub total_share: Uint128,
+    pub claims: Vec<StakerClaim>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ParamResponse {
+    pub key: String,
+    pub value: ParamValue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ParamsResponse {
+    pub params: Vec<ParamResponse>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct TallyCheckpointsResponse {
+    pub checkpoints: Vec<TallyCheckpoint>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PollEventsResponse {
+    pub poll_id: u64,
+    /// in the order the events happened
+    pub events: Vec<PollEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakingSnapshotResponse {
+    pub share: Uint128,
+    pub total_share: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ShareAccountingResponse {
+    pub total_share: Uint128,
+    pub page_share: Uint128,
+    pub page_deposit_locked_share: Uint128,
+    pub stakers: u32,
+    /// None once the last page has been read
+    pub last_staker: Option<HumanAddr>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduledConfigUpdateResponse {
+    pub changes: ConfigUpdate,
+    pub activation_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RawStorageResponse {
+    /// hex encoded storage key, including the namespace
+    pub key: String,
+    /// None if nothing is stored under the key
+    pub value: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorResponse {
+    pub staker: HumanAddr,
+    pub operator: HumanAddr,
+    /// None if the operator is not approved
+    pub expires: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportResponse {
+    pub records: Vec<ExportRecord>,
+    /// page size applied to the query
+    pub limit: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportRecord {
+    /// staker or voter address, or poll id; the `start_after` of the next page
+    pub key: String,
+    /// JSON record as stored by the contract
+    pub value: Binary,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DepositLedgerResponse {
+    pub poll_id: u64,
+    pub depositor: HumanAddr,
+    pub amount: Uint128,
+    pub share: Option<Uint128>,
+    pub status: DepositStatus,
+    pub settled_height: Option<u64>,
+    /// partial refund of a slashed deposit not claimed yet
+    pub claimable_refund: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PollDepositResponse {
+    pub poll_id: u64,
+    pub amount: Uint128,
+    pub status: DepositStatus,
+    /// refund recipient of a refunded deposit, or the community contract
+    /// receiving a slashed one; None while locked or when the slashed
+    /// deposit is shared by the stakers
+    pub destination: Option<HumanAddr>,
+    /// height the deposit was refunded or slashed at
+    pub settled_height: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct SimulateExecutionResponse {
+    pub poll_id: u64,
+    pub status: PollStatus,
+    /// height from which the poll can be executed once passed
+    pub executable_height: u64,
+    pub messages: Vec<CosmosMsg>,
+}
+
+/// Parameter changes of a scheduled config update, same as the UpdateConfig fields
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ConfigUpdate {
+    pub owner: Option<HumanAddr>,
+    pub quorum: Option<Decimal>,
+    pub threshold: Option<Decimal>,
+    pub voting_period: Option<u64>,
+    pub timelock_period: Option<u64>,
+    pub expiration_period: Option<u64>,
+    pub proposal_deposit: Option<Uint128>,
+    pub snapshot_period: Option<u64>,
+    pub vesting_contract: Option<HumanAddr>,
+    pub reveal_period: Option<u64>,
+    pub min_vote_amount: Option<Uint128>,
+    pub quorum_excluded_addresses: Option<Vec<HumanAddr>>,
+    pub tally_checkpoint_interval: Option<u64>,
+    pub poll_retention_period: Option<u64>,
+    pub max_poll_extension: Option<u64>,
+    pub narrow_pass_margin: Option<Decimal>,
+    pub community_contract: Option<HumanAddr>,
+    pub adaptive_quorum: Option<AdaptiveQuorum>,
+    pub poll_sponsorship: Option<PollSponsorship>,
+    pub executor_whitelist: Option<Vec<HumanAddr>>,
+    pub partial_refund_ratio: Option<Decimal>,
+    pub veto_threshold: Option<Decimal>,
+    pub emergency_council: Option<EmergencyCouncil>,
+    pub max_active_votes: Option<u64>,
+    pub reject_same_block_votes: Option<bool>,
+    pub quorum_presets: Option<Vec<QuorumPreset>>,
+}
+
+/// Quorum following the turnout of the recently ended polls: the exponential
+/// moving average of their turnout, bounded to `min_quorum` and `max_quorum`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdaptiveQuorum {
+    pub min_quorum: Decimal,
+    pub max_quorum: Decimal,
+    /// number of recent polls the average spans
+    pub poll_window: u64,
+}
+
+/// Parameters of the polls created with the named preset, such as
+/// "standard", "expedited" or "constitutional"
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuorumPreset {
+    pub name: String,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+}
+
+/// Co-sponsors a new poll needs before its voting period starts; a poll left
+/// without them until the end of the voting period is rejected and its deposit slashed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollSponsorship {
+    pub required_sponsors: u64,
+    /// staked balance a sponsor must hold
+    pub min_stake: Uint128,
+}
+
+/// Members executing an emergency action once `threshold` of them approved it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmergencyCouncil {
+    pub members: Vec<HumanAddr>,
+    pub threshold: u64,
+    /// contracts the council can pause, each with its pause message
+    pub pausable_contracts: Vec<PausableContract>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PausableContract {
+    pub contract_addr: HumanAddr,
+    pub pause_msg: Binary,
+}
+
+impl fmt::Display for EmergencyCouncil {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let members: Vec<&str> = self.members.iter().map(|addr| addr.as_str()).collect();
+        let contracts: Vec<&str> = self
+            .pausable_contracts
+            .iter()
+            .map(|contract| contract.contract_addr.as_str())
+            .collect();
+        write!(
+            f,
+            "{}/{}/{}",
+            self.threshold,
+            members.join(","),
+            contracts.join(",")
+        )
+    }
+}
+
+impl fmt::Display for PollSponsorship {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.required_sponsors, self.min_stake)
+    }
+}
+
+impl fmt::Display for QuorumPreset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}/{}/{}",
+            self.name, self.quorum, self.threshold, self.voting_period
+        )
+    }
 }
 
+impl fmt::Display for AdaptiveQuorum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}/{}",
+            self.min_quorum, self.max_quorum, self.poll_window
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigChange {
+    pub field: String,
+    pub old_value: String,
+    pub new_value: String,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct VoterInfo {
     pub vote: VoteOption,
     pub balance: Uint128,
+    /// share weight of the voted balance at the vote time
+    pub share: Uint128,
+}
+
+/// Running tally of a poll after `vote_count` votes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TallyCheckpoint {
+    pub vote_count: u64,
+    pub height: u64,
+    pub yes_votes: Uint128,
+    pub no_votes: Uint128,
+}
+
+/// Lifecycle event of a poll, recorded by the handler moving the poll on
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollEvent {
+    pub kind: PollEventKind,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollEventKind {
+    Created,
+    Snapshot,
+    Ended,
+    Executed,
+    Expired,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PollStatus {
+    PendingSponsorship,
     InProgress,
     Passed,
     Rejected,
@@ -193,19 +1043,55 @@ impl fmt::Display for PollStatus {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectedReason {
+    QuorumNotReached,
+    ThresholdNotReached,
+    SponsorshipNotReached,
+    Vetoed,
+    /// vetoed by the emergency council after passing
+    EmergencyVetoed,
+}
+
+impl fmt::Display for RejectedReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RejectedReason::QuorumNotReached => write!(f, "quorum_not_reached"),
+            RejectedReason::ThresholdNotReached => write!(f, "threshold_not_reached"),
+            RejectedReason::SponsorshipNotReached => write!(f, "sponsorship_not_reached"),
+            RejectedReason::Vetoed => write!(f, "vetoed"),
+            RejectedReason::EmergencyVetoed => write!(f, "emergency_vetoed"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositStatus {
+    Locked,
+    Refunded,
+    Slashed,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum VoteOption {
     Yes,
     No,
+    /// counted as a no vote, also vetoing the poll above the veto threshold
+    NoWithVeto,
+    /// index of the option label of the poll
+    Label(u8),
 }
 
 impl fmt::Display for VoteOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if *self == VoteOption::Yes {
-            write!(f, "yes")
-        } else {
-            write!(f, "no")
+        match self {
+            VoteOption::Yes => write!(f, "yes"),
+            VoteOption::No => write!(f, "no"),
+            VoteOption::NoWithVeto => write!(f, "no_with_veto"),
+            VoteOption::Label(index) => write!(f, "label_{}", index),
         }
     }
 }
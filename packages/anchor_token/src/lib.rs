@@ -4,6 +4,7 @@ pub mod common;
 pub mod community;
 pub mod distributor;
 pub mod gov;
+pub mod math;
 pub mod querier;
 pub mod staking;
 pub mod vesting;
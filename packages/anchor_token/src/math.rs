@@ -0,0 +1,79 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Decimal, StdError, StdResult, Uint128};
+
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000u128;
+
+/// Returns value * numerator / denominator rounded down; the product is
+/// computed over 256 bits, so it can't overflow for any Uint128 operands
+pub fn mul_ratio(value: Uint128, numerator: Uint128, denominator: Uint128) -> StdResult<Uint128> {
+    div_floor(
+        Uint256::from(value) * Uint256::from(numerator),
+        Uint256::from(denominator),
+    )
+}
+
+/// Returns value * numerator / denominator rounded up
+pub fn mul_ratio_ceil(
+    value: Uint128,
+    numerator: Uint128,
+    denominator: Uint128,
+) -> StdResult<Uint128> {
+    div_ceil(
+        Uint256::from(value) * Uint256::from(numerator),
+        Uint256::from(denominator),
+    )
+}
+
+/// Returns numerator / denominator rounded down,
+/// failing when the quotient doesn't fit in a Uint128
+pub fn div_floor(numerator: Uint256, denominator: Uint256) -> StdResult<Uint128> {
+    if denominator.is_zero() {
+        return Err(StdError::generic_err("Division by zero"));
+    }
+
+    to_uint128(Uint256(numerator.0 / denominator.0))
+}
+
+/// Returns numerator / denominator rounded up,
+/// failing when the quotient doesn't fit in a Uint128
+pub fn div_ceil(numerator: Uint256, denominator: Uint256) -> StdResult<Uint128> {
+    if denominator.is_zero() {
+        return Err(StdError::generic_err("Division by zero"));
+    }
+
+    let mut quotient = Uint256(numerator.0 / denominator.0);
+    if !(numerator.0 % denominator.0).is_zero() {
+        quotient += Uint256::one();
+    }
+
+    to_uint128(quotient)
+}
+
+/// Returns value * decimal rounded down
+pub fn mul_decimal(value: Uint128, decimal: Decimal) -> StdResult<Uint128> {
+    div_floor(
+        Uint256::from(value) * Uint256(Decimal256::from(decimal).0),
+        Uint256::from(DECIMAL_FRACTIONAL),
+    )
+}
+
+/// Returns numerator / denominator as a decimal rounded down
+pub fn decimal_ratio(numerator: Uint128, denominator: Uint128) -> StdResult<Decimal> {
+    let atomics = div_floor(
+        Uint256::from(numerator) * Uint256::from(DECIMAL_FRACTIONAL),
+        Uint256::from(denominator),
+    )?
+    .u128();
+
+    // split the atomics, as Decimal::from_ratio scales its numerator up by 10^18
+    Ok(Decimal::from_ratio(atomics / DECIMAL_FRACTIONAL, 1u128)
+        + Decimal::from_ratio(atomics % DECIMAL_FRACTIONAL, DECIMAL_FRACTIONAL))
+}
+
+fn to_uint128(value: Uint256) -> StdResult<Uint128> {
+    if value > Uint256::from(u128::MAX) {
+        return Err(StdError::generic_err("Uint128 overflow"));
+    }
+
+    Ok(value.into())
+}
@@ -6,9 +6,40 @@ use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
+    pub owner: HumanAddr,
     pub anchor_token: HumanAddr,
     pub staking_token: HumanAddr, // lp token of ANC-UST pair contract
+    pub pair_contract: HumanAddr, // ANC-UST pair contract
     pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    /// Contract notified with BondChanged whenever a bonded amount changes
+    pub hook_contract: Option<HumanAddr>,
+    /// Most staking tokens which can be bonded in total, unlimited if not given
+    pub bond_cap: Option<Uint128>,
+    /// Lockups which can be selected at bond time
+    pub lockup_tiers: Vec<LockupTier>,
+    /// Gov contract staking the rewards claimed with WithdrawToGov
+    pub gov_contract: Option<HumanAddr>,
+    /// Blocks an added distribution schedule is announced ahead of its start
+    pub schedule_delay: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockupTier {
+    pub days: u64,
+    /// Weight of the locked amount in the reward distribution
+    pub reward_multiplier: Decimal,
+    /// Ratio of the locked amount forfeited to the remaining stakers
+    /// when it is unbonded before the unlock time
+    pub early_exit_penalty: Decimal,
+}
+
+/// Bonded amount locked with the tier values in effect at bond time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockupPosition {
+    pub amount: Uint128,
+    pub unlock_time: u64,
+    pub reward_multiplier: Decimal,
+    pub early_exit_penalty: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -18,19 +49,76 @@ pub enum HandleMsg {
     Unbond {
         amount: Uint128,
     },
-    /// Withdraw pending rewards
-    Withdraw {},
+    /// Withdraw pending rewards, to the sender unless a recipient is given
+    Withdraw {
+        recipient: Option<HumanAddr>,
+    },
+    /// Withdraw pending rewards and stake them in gov on behalf of the sender
+    WithdrawToGov {},
+    /// Releases the expired lockups of the staker, which otherwise keep their
+    /// reward multiplier until the staker acts; executable by anyone
+    ReleaseLockups {
+        staker: HumanAddr,
+    },
+    /// ZapBond provides liquidity with the sent uusd and bonds the minted LP token;
+    /// half of the uusd is swapped to ANC unless `anc_amount` is pulled from the sender,
+    /// `max_spread` bounds the swap and `slippage_tolerance` the provided liquidity
+    ZapBond {
+        anc_amount: Option<Uint128>,
+        max_spread: Option<Decimal>,
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Internal callback of ZapBond, provides liquidity with the received ANC
+    /// and `uusd_amount`, the uusd left after the tax
+    ZapBondProvide {
+        staker: HumanAddr,
+        prev_anc_balance: Uint128,
+        uusd_amount: Uint128,
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Internal callback of ZapBond, bonds the minted LP token
+    ZapBondHook {
+        staker: HumanAddr,
+        prev_staking_token_amount: Uint128,
+    },
+    /// Adds a future reward schedule, only accepted when the ANC balance
+    /// of the contract covers every reward not yet paid out and the
+    /// schedule starts at least `schedule_delay` blocks after this one
+    AddDistributionSchedule {
+        schedule: (u64, u64, Uint128),
+    },
+    /// Raises, lowers or lifts the bond cap, only executable by the owner
+    UpdateBondCap {
+        bond_cap: Option<Uint128>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    Bond {},
+    /// Bonds the sent tokens, locked for `lockup_days` if given
+    Bond { lockup_days: Option<u64> },
+}
+
+/// Callback sent to the hook contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BondHookMsg {
+    BondChanged {
+        staker: HumanAddr,
+        new_amount: Uint128,
+    },
 }
 
-/// We currently take no arguments for migrations
+/// Values missing from the contracts stored before they were added,
+/// required to migrate such a contract and ignored otherwise
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    pub owner: Option<HumanAddr>,
+    pub pair_contract: Option<HumanAddr>,
+    /// Rewards withdrawn before the migration, as they were not recorded
+    pub paid_reward: Option<Uint128>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -43,14 +131,40 @@ pub enum QueryMsg {
         staker: HumanAddr,
         block_height: Option<u64>,
     },
+    /// Bonded amount, reward index and the active distribution schedule
+    /// at the block height, or at the last distributed height if not given
+    PoolState {
+        block_height: Option<u64>,
+    },
+    /// Shortfall between the rewards not yet paid out and the ANC balance
+    Unfunded {},
+    /// Amount which can still be bonded under the bond cap
+    RemainingCapacity {},
+    /// Added schedules which have not started at the block height,
+    /// every added schedule if not given
+    ScheduleChanges {
+        block_height: Option<u64>,
+    },
+    /// Bonded LP tokens of the staker and of the whole pool, valued at
+    /// their share of the ANC and UST reserves of the pair contract
+    UnderlyingAssets {
+        staker: HumanAddr,
+    },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
+    pub owner: HumanAddr,
     pub anchor_token: HumanAddr,
     pub staking_token: HumanAddr,
+    pub pair_contract: HumanAddr,
     pub distribution_schedule: Vec<(u64, u64, Uint128)>,
+    pub hook_contract: Option<HumanAddr>,
+    pub bond_cap: Option<Uint128>,
+    pub lockup_tiers: Vec<LockupTier>,
+    pub gov_contract: Option<HumanAddr>,
+    pub schedule_delay: u64,
 }
 
 // We define a custom struct for each query response
@@ -59,6 +173,8 @@ pub struct StateResponse {
     pub last_distributed: u64,
     pub total_bond_amount: Uint128,
     pub global_reward_index: Decimal,
+    /// Rewards credited to the stakers since the instantiation
+    pub distributed_reward: Uint128,
 }
 
 // We define a custom struct for each query response
@@ -68,4 +184,64 @@ pub struct StakerInfoResponse {
     pub reward_index: Decimal,
     pub bond_amount: Uint128,
     pub pending_reward: Uint128,
+    /// Bond amount weighted by the lockup multipliers
+    pub reward_weight: Uint128,
+    /// Staking tokens forfeited by early exits, paid out on withdraw
+    pub pending_penalty: Uint128,
+    pub lockups: Vec<LockupPosition>,
+    /// Rewards withdrawn by the staker so far
+    pub claimed_reward: Uint128,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolStateResponse {
+    pub staking_token: HumanAddr,
+    pub total_bond_amount: Uint128,
+    pub current_schedule: Option<(u64, u64, Uint128)>,
+    pub global_reward_index: Decimal,
+    pub last_distributed: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnfundedResponse {
+    pub total_reward: Uint128,
+    pub paid_reward: Uint128,
+    pub reward_balance: Uint128,
+    pub shortfall: Uint128,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemainingCapacityResponse {
+    pub bond_cap: Option<Uint128>,
+    pub total_bond_amount: Uint128,
+    /// None when there is no bond cap
+    pub remaining_capacity: Option<Uint128>,
+}
+
+/// Distribution schedule added after the instantiation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduleChange {
+    pub schedule: (u64, u64, Uint128),
+    pub announced_height: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduleChangesResponse {
+    pub changes: Vec<ScheduleChange>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnderlyingAssetsResponse {
+    pub staker: HumanAddr,
+    pub bond_amount: Uint128,
+    pub anc_amount: Uint128,
+    pub uusd_amount: Uint128,
+    pub total_bond_amount: Uint128,
+    pub total_anc_amount: Uint128,
+    pub total_uusd_amount: Uint128,
 }
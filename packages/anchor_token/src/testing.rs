@@ -1,8 +1,12 @@
+use crate::common::Expiration;
+use crate::math::{decimal_ratio, div_ceil, div_floor, mul_decimal, mul_ratio, mul_ratio_ceil};
 use crate::mock_querier::mock_dependencies;
 use crate::querier::{compute_tax, deduct_tax, query_tax_rate};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Coin, Decimal, Uint128};
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{Coin, Decimal, StdError, Uint128};
+use std::str::FromStr;
 
 #[test]
 fn tax_rate_querier() {
@@ -61,3 +65,87 @@ fn test_deduct_tax() {
         }
     );
 }
+
+#[test]
+fn mul_ratio_large_balances() {
+    let value = Uint128(u128::MAX / 3);
+
+    // the product overflows 128 bits
+    assert_eq!(
+        mul_ratio(value, Uint128(6), Uint128(4)).unwrap(),
+        Uint128(u128::MAX / 2)
+    );
+    assert_eq!(
+        mul_ratio(Uint128(10), Uint128(1), Uint128(3)).unwrap(),
+        Uint128(3)
+    );
+    assert_eq!(
+        mul_ratio_ceil(Uint128(10), Uint128(1), Uint128(3)).unwrap(),
+        Uint128(4)
+    );
+    assert_eq!(
+        mul_ratio_ceil(Uint128(9), Uint128(1), Uint128(3)).unwrap(),
+        Uint128(3)
+    );
+
+    match mul_ratio(value, Uint128(4), Uint128(1)) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Uint128 overflow"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    match mul_ratio(value, Uint128(1), Uint128::zero()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Division by zero"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    assert_eq!(
+        div_floor(Uint256::from(7u64), Uint256::from(2u64)).unwrap(),
+        Uint128(3)
+    );
+    assert_eq!(
+        div_ceil(Uint256::from(7u64), Uint256::from(2u64)).unwrap(),
+        Uint128(4)
+    );
+}
+
+#[test]
+fn decimal_math_large_balances() {
+    let large = Uint128(1_000_000_000_000_000_000_000_000u128);
+
+    assert_eq!(
+        decimal_ratio(large, Uint128(4_000_000u128)).unwrap(),
+        Decimal::from_str("250000000000000000").unwrap()
+    );
+    assert_eq!(
+        decimal_ratio(Uint128(1), Uint128(3)).unwrap(),
+        Decimal::from_str("0.333333333333333333").unwrap()
+    );
+    assert_eq!(
+        mul_decimal(large, Decimal::from_str("12345.5").unwrap()).unwrap(),
+        Uint128(12_345_500_000_000_000_000_000_000_000u128)
+    );
+    assert_eq!(
+        mul_decimal(Uint128(10), Decimal::percent(15)).unwrap(),
+        Uint128(1)
+    );
+}
+
+#[test]
+fn expiration() {
+    let mut env = mock_env("addr0000", &[]);
+    env.block.height = 100;
+    env.block.time = 1000;
+
+    assert!(Expiration::AtHeight(100).is_expired(&env.block));
+    assert!(!Expiration::AtHeight(101).is_expired(&env.block));
+    assert!(Expiration::AtTime(1000).is_expired(&env.block));
+    assert!(!Expiration::AtTime(1001).is_expired(&env.block));
+    assert!(!Expiration::Never {}.is_expired(&env.block));
+
+    assert!(Expiration::AtHeight(100) < Expiration::AtHeight(101));
+    assert!(Expiration::AtTime(1000) < Expiration::Never {});
+    assert_eq!(
+        Expiration::AtHeight(100).partial_cmp(&Expiration::AtTime(1000)),
+        None
+    );
+    assert_eq!(Expiration::default(), Expiration::Never {});
+}
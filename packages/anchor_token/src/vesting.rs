@@ -9,6 +9,8 @@ pub struct InitMsg {
     pub owner: HumanAddr,
     pub anchor_token: HumanAddr,
     pub genesis_time: u64,
+    /// Address allowed to freeze and unfreeze the claims
+    pub guardian: Option<HumanAddr>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -18,11 +20,26 @@ pub enum HandleMsg {
         owner: Option<HumanAddr>,
         anchor_token: Option<HumanAddr>,
         genesis_time: Option<u64>,
+        gov_contract: Option<HumanAddr>,
+        guardian: Option<HumanAddr>,
     },
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
     },
     Claim {},
+    /// Stake not-yet-vested tokens into the gov contract on behalf of
+    /// the sender. Staked tokens stay locked in gov until they vest.
+    StakeVested {
+        amount: Uint128,
+    },
+    /// Stops all claims until unfrozen, only executable by the guardian
+    FreezeClaims {},
+    UnfreezeClaims {},
+    /// Stores the records exported from a previous deployment as they are,
+    /// only executable by the owner before the genesis time
+    ImportVestingState {
+        vesting_accounts: Vec<VestingStateRecord>,
+    },
 }
 
 /// CONTRACT: end_time > start_time 
@@ -36,6 +53,15 @@ pub struct VestingAccount {
 pub struct VestingInfo {
     pub schedules: Vec<(u64, u64, Uint128)>,
     pub last_claim_time: u64,
+    /// unvested amount currently staked in the gov contract
+    pub staked_amount: Uint128,
+}
+
+/// Raw vesting record used to move the state to a new deployment
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingStateRecord {
+    pub address: HumanAddr,
+    pub info: VestingInfo,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -44,11 +70,28 @@ pub enum QueryMsg {
     Config {},
     VestingAccount {
         address: HumanAddr,
+        block_time: Option<u64>,
     },
     VestingAccounts {
         start_after: Option<HumanAddr>,
         limit: Option<u32>,
         order_by: Option<OrderBy>,
+        /// only return the accounts which still have tokens to claim
+        only_active: Option<bool>,
+        block_time: Option<u64>,
+    },
+    /// Raw vesting records in ascending address order, to be imported
+    /// by a new deployment with `ImportVestingState`
+    ExportVestingState {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    /// Claims of the account in ascending claim time order
+    ClaimHistory {
+        address: HumanAddr,
+        /// claim time of the last record of the previous page
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
 }
 
@@ -58,6 +101,9 @@ pub struct ConfigResponse {
     pub owner: HumanAddr,
     pub anchor_token: HumanAddr,
     pub genesis_time: u64,
+    pub gov_contract: Option<HumanAddr>,
+    pub guardian: Option<HumanAddr>,
+    pub claims_frozen: bool,
 }
 
 // We define a custom struct for each query response
@@ -65,6 +111,12 @@ pub struct ConfigResponse {
 pub struct VestingAccountResponse {
     pub address: HumanAddr,
     pub info: VestingInfo,
+    /// amount vested until the block time (last claim time if not given)
+    pub vested: Uint128,
+    /// amount already claimed
+    pub claimed: Uint128,
+    /// amount not claimed yet, including the unvested amount
+    pub remaining: Uint128,
 }
 
 // We define a custom struct for each query response
@@ -72,3 +124,24 @@ pub struct VestingAccountResponse {
 pub struct VestingAccountsResponse {
     pub vesting_accounts: Vec<VestingAccountResponse>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportVestingStateResponse {
+    pub vesting_accounts: Vec<VestingStateRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecord {
+    pub height: u64,
+    pub time: u64,
+    /// claimed amount, including the part unlocked in the gov contract
+    pub amount: Uint128,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimHistoryResponse {
+    pub address: HumanAddr,
+    pub claims: Vec<ClaimRecord>,
+}